@@ -21,11 +21,22 @@ use crate::model::PrepSpec;
 use crate::util;
 use moor_var::{Var, v_str};
 
+/// The command prefixes LambdaMOO expands into verbs: `"` for `say`, `:` for `emote` and `;` for
+/// `eval`, as (prefix, verb) pairs.
+pub fn default_command_aliases() -> Vec<(String, String)> {
+    [("\"", "say"), (":", "emote"), (";", "eval")]
+        .into_iter()
+        .map(|(prefix, verb)| (prefix.to_string(), verb.to_string()))
+        .collect()
+}
+
 /// The default command parser implementation based on the one in LambdaMOO 1.8.
 pub struct DefaultParseCommand<M>
 where
     M: ObjectNameMatcher,
 {
+    /// Prefixes which, starting a command, stand for a verb, as (prefix, verb) pairs.
+    aliases: Vec<(String, String)>,
     phantom_data: PhantomData<M>,
 }
 
@@ -43,10 +54,30 @@ where
     M: ObjectNameMatcher,
 {
     pub fn new() -> Self {
+        Self::with_aliases(default_command_aliases())
+    }
+
+    /// A parser expanding the given command aliases instead of LambdaMOO's.
+    pub fn with_aliases(aliases: Vec<(String, String)>) -> Self {
         DefaultParseCommand {
+            aliases,
             phantom_data: PhantomData,
         }
     }
+
+    /// Expand an alias at the start of `command` into its verb. Aliases needn't be followed by a
+    /// space (`'hello` is `say hello`), so the longest matching one wins.
+    fn expand_alias(&self, command: &str) -> String {
+        let alias = self
+            .aliases
+            .iter()
+            .filter(|(prefix, _)| !prefix.is_empty() && command.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len());
+        match alias {
+            Some((prefix, verb)) => format!("{verb} {}", &command[prefix.len()..]),
+            None => command.to_string(),
+        }
+    }
 }
 
 impl<M> CommandParser<M> for DefaultParseCommand<M>
@@ -58,15 +89,9 @@ where
         input: &str,
         command_environment: &M,
     ) -> Result<ParsedCommand, ParseCommandError> {
-        // Replace initial command characters with say/emote/eval
-        let mut command = input.trim_start().to_string();
-        let first_char = command.chars().next().unwrap_or(' ');
-        match first_char {
-            '"' => command.replace_range(..1, "say "),
-            ':' => command.replace_range(..1, "emote "),
-            ';' => command.replace_range(..1, "eval "),
-            _ => {}
-        };
+        // Replace initial command characters with say/emote/eval, or whatever they're configured
+        // to stand for.
+        let command = self.expand_alias(input.trim_start());
 
         // Get word list
         let words = util::parse_into_words(&command);
@@ -277,6 +302,27 @@ mod tests {
         assert_eq!(parsed.iobj, None);
     }
 
+    #[test]
+    fn test_parse_configured_alias() {
+        let pc = DefaultParseCommand::with_aliases(vec![
+            ("'".to_string(), "say".to_string()),
+            ("''".to_string(), "think".to_string()),
+        ]);
+        let parsed = pc.parse_command("'hi", &SimpleParseMatcher {}).unwrap();
+        assert_eq!(parsed.verb.as_str(), "say");
+        assert_eq!(parsed.args, vec![v_str("hi")]);
+        assert_eq!(parsed.argstr, "hi");
+
+        // The longest alias wins.
+        let parsed = pc.parse_command("''hmm", &SimpleParseMatcher {}).unwrap();
+        assert_eq!(parsed.verb.as_str(), "think");
+        assert_eq!(parsed.argstr, "hmm");
+
+        // The configured aliases replace LambdaMOO's.
+        let parsed = pc.parse_command(":waves", &SimpleParseMatcher {}).unwrap();
+        assert_eq!(parsed.verb.as_str(), ":waves");
+    }
+
     #[test]
     fn test_parse_emote_command() {
         // Test emote command
@@ -20,6 +20,9 @@ use moor_var::{AMBIGUOUS, FAILED_MATCH, NOTHING};
 const ME: &str = "me";
 const HERE: &str = "here";
 
+/// The most suggestions we'll offer for a failed match.
+const MAX_SUGGESTIONS: usize = 3;
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 struct MatchData {
     exact: Obj,
@@ -91,6 +94,57 @@ fn match_contents<M: MatchEnvironment>(
     }
 }
 
+/// Levenshtein distance between two strings, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Find names of objects in the player's surroundings within a small edit distance of
+/// `object_name`. Short names get less slack, so that e.g. "t5" doesn't suggest everything.
+fn suggest_contents<M: MatchEnvironment>(
+    env: &M,
+    player: &Obj,
+    object_name: &str,
+) -> Result<Vec<String>, WorldStateError> {
+    let match_name = object_name.to_lowercase();
+    let max_distance = if match_name.chars().count() <= 4 {
+        1
+    } else {
+        2
+    };
+
+    let mut candidates: Vec<(usize, String)> = vec![];
+    let search = env.get_surroundings(player)?;
+    for oid in search.iter() {
+        if !env.obj_valid(&oid)? {
+            continue;
+        }
+        for name in env.get_names(&oid)? {
+            let distance = edit_distance(&match_name, &name.to_lowercase());
+            if distance == 0 || distance > max_distance {
+                continue;
+            }
+            if !candidates.iter().any(|(_, n)| *n == name) {
+                candidates.push((distance, name));
+            }
+        }
+    }
+    candidates.sort();
+    candidates.truncate(MAX_SUGGESTIONS);
+    Ok(candidates.into_iter().map(|(_, name)| name).collect())
+}
+
 pub struct DefaultObjectNameMatcher<M: MatchEnvironment> {
     pub env: M,
     pub player: Obj,
@@ -129,12 +183,23 @@ impl<M: MatchEnvironment> ObjectNameMatcher for DefaultObjectNameMatcher<M> {
 
         match_contents(&self.env, &self.player, object_name)
     }
+
+    fn suggest_names(&self, object_name: &str) -> Result<Vec<String>, WorldStateError> {
+        if object_name.is_empty() || object_name.starts_with('#') {
+            return Ok(vec![]);
+        }
+        if !self.env.obj_valid(&self.player)? {
+            return Ok(vec![]);
+        }
+        suggest_contents(&self.env, &self.player, object_name)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::matching::match_env::{
         DefaultObjectNameMatcher, MatchData, ObjectNameMatcher, do_match_object_names,
+        edit_distance,
     };
     use crate::matching::mock_matching_env::{
         MOCK_PLAYER, MOCK_ROOM1, MOCK_THING1, MOCK_THING2, setup_mock_environment,
@@ -363,4 +428,44 @@ mod tests {
         let result = menv.match_object("thing1");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("lamp", "lamp"), 0);
+        assert_eq!(edit_distance("lamp", "lammp"), 1);
+        assert_eq!(edit_distance("thnig", "thing"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_suggest_names_for_typo() {
+        let env = setup_mock_environment();
+        let menv = DefaultObjectNameMatcher {
+            env,
+            player: MOCK_PLAYER,
+        };
+        assert_eq!(menv.match_object("porcupne").unwrap(), Some(FAILED_MATCH));
+        assert_eq!(
+            menv.suggest_names("porcupne").unwrap(),
+            vec!["porcupine".to_string()]
+        );
+        // Nearest first.
+        assert_eq!(
+            menv.suggest_names("thinng1").unwrap(),
+            vec!["thing1".to_string(), "thing2".to_string()]
+        );
+        // Objects out of sight aren't suggested, nor is anything for an unrelated name.
+        assert!(
+            menv.suggest_names("room3")
+                .unwrap()
+                .contains(&"room1".to_string())
+        );
+        assert!(
+            !menv
+                .suggest_names("room3")
+                .unwrap()
+                .contains(&"room2".to_string())
+        );
+        assert!(menv.suggest_names("aardvark").unwrap().is_empty());
+    }
 }
@@ -22,7 +22,7 @@ pub mod mock_matching_env;
 mod prepositions;
 pub mod ws_match_env;
 
-pub use command_parse::DefaultParseCommand;
+pub use command_parse::{DefaultParseCommand, default_command_aliases};
 pub use match_env::DefaultObjectNameMatcher;
 pub use ws_match_env::WsMatchEnv;
 
@@ -84,4 +84,10 @@ pub enum ParseCommandError {
 /// objects in the world state that match the entities given in the command.
 pub trait ObjectNameMatcher {
     fn match_object(&self, name: &str) -> Result<Option<Obj>, WorldStateError>;
+
+    /// Names of nearby objects which are close to (but not a match for) `name`, nearest first,
+    /// for "Did you mean...?" style feedback when matching fails.
+    fn suggest_names(&self, _name: &str) -> Result<Vec<String>, WorldStateError> {
+        Ok(vec![])
+    }
 }
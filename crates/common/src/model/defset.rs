@@ -30,6 +30,8 @@ pub trait Named {
 
 /// A container for verb or property defs.
 /// Immutable, and can be iterated over in sequence, or searched by name.
+/// Items are kept in definition order: additions go on the end, and removals and updates leave
+/// the position of everything else alone, so an index is stable until the defs are changed.
 #[derive(Eq, PartialEq, Clone, Encode, Decode)]
 pub struct Defs<T: AsByteBuffer + Clone + Sized + HasUuid + Named + 'static> {
     contents: Vec<T>,
@@ -89,11 +91,11 @@ impl<T: AsByteBuffer + Clone + HasUuid + Named> ValSet<T> for Defs<T> {
     }
     // Provides the number of items in the buffer.
     fn len(&self) -> usize {
-        self.iter().count()
+        self.contents.len()
     }
 
     fn is_empty(&self) -> bool {
-        self.iter().next().is_none()
+        self.contents.is_empty()
     }
 }
 
@@ -123,6 +125,16 @@ impl<T: AsByteBuffer + Clone + HasUuid + Named> Defs<T> {
     pub fn find(&self, uuid: &Uuid) -> Option<T> {
         self.iter().find(|p| &p.uuid() == uuid)
     }
+    /// The item at `index` in definition order.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.contents.get(index).cloned()
+    }
+    /// The position in definition order of the item with the given uuid.
+    #[must_use]
+    pub fn index_of(&self, uuid: Uuid) -> Option<usize> {
+        self.contents.iter().position(|p| p.uuid() == uuid)
+    }
     #[must_use]
     pub fn find_named(&self, name: Symbol) -> Vec<T> {
         self.iter().filter(|p| p.matches_name(name)).collect()
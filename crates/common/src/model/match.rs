@@ -146,6 +146,18 @@ impl VerbArgsSpec {
         }
     }
 
+    /// The specificity of a spec with no `any` slots at all.
+    pub const MAX_SPECIFICITY: u8 = 3;
+
+    /// How many of the three slots are something other than `any`. When more than one verb could
+    /// match a command, the one with the most specific argument spec is preferred.
+    #[must_use]
+    pub fn specificity(&self) -> u8 {
+        u8::from(self.dobj != ArgSpec::Any)
+            + u8::from(self.prep != PrepSpec::Any)
+            + u8::from(self.iobj != ArgSpec::Any)
+    }
+
     #[must_use]
     pub fn matches(&self, v: &Self) -> bool {
         (self.dobj == ArgSpec::Any || self.dobj == v.dobj)
@@ -196,4 +208,22 @@ mod tests {
         let spec2 = VerbArgsSpec::try_read(v).unwrap();
         assert_eq!(spec, spec2);
     }
+
+    #[test]
+    fn verbargs_spec_specificity() {
+        use super::{ArgSpec, PrepSpec, VerbArgsSpec};
+        let any = VerbArgsSpec {
+            dobj: ArgSpec::Any,
+            prep: PrepSpec::Any,
+            iobj: ArgSpec::Any,
+        };
+        let this_any_any = VerbArgsSpec {
+            dobj: ArgSpec::This,
+            ..any
+        };
+        assert_eq!(any.specificity(), 0);
+        assert_eq!(this_any_any.specificity(), 1);
+        assert_eq!(VerbArgsSpec::this_none_this().specificity(), 3);
+        assert_eq!(VerbArgsSpec::none_none_none().specificity(), 3);
+    }
 }
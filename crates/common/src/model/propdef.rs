@@ -55,7 +55,7 @@ impl PropDef {
 
 impl Named for PropDef {
     fn matches_name(&self, name: Symbol) -> bool {
-        self.name().to_lowercase() == name.as_str()
+        self.name.eq_case_insensitive(&name)
     }
 
     fn names(&self) -> Vec<&str> {
@@ -88,6 +88,11 @@ mod tests {
         let pds = PropDefs::empty().with_all_added(&[test_pd1.clone(), test_pd2.clone()]);
         let pd1 = pds.find_first_named(Symbol::mk("test")).unwrap();
         assert_eq!(pd1.uuid(), test_pd1.uuid());
+
+        // Property names match regardless of case.
+        let pd2 = pds.find_first_named(Symbol::mk("TEST2")).unwrap();
+        assert_eq!(pd2.uuid(), test_pd2.uuid());
+        assert!(pds.find_first_named(Symbol::mk("test3")).is_none());
     }
 
     #[test]
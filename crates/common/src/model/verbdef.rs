@@ -92,9 +92,14 @@ impl VerbDef {
 
 impl Named for VerbDef {
     fn matches_name(&self, name: Symbol) -> bool {
-        self.names()
-            .iter()
-            .any(|verb| verbname_cmp(verb.to_lowercase().as_str(), name.as_str()))
+        // Verb names are matched case-insensitively, whichever way `name` was constructed. Only
+        // names with a `*` wildcard need comparing as strings; the rest match by identity.
+        let name = name.fold();
+        self.names.iter().any(|verb| {
+            let verb = verb.fold();
+            verb == name
+                || (verb.as_str().contains('*') && verbname_cmp(verb.as_str(), name.as_str()))
+        })
     }
 
     fn names(&self) -> Vec<&str> {
@@ -114,8 +119,13 @@ pub type VerbDefs = Defs<VerbDef>;
 
 #[cfg(test)]
 mod tests {
+    use crate::model::defset::Named;
+    use crate::model::r#match::VerbArgsSpec;
+    use crate::model::verbdef::VerbDef;
     use crate::model::verbs::VerbFlag;
-    use crate::util::BitEnum;
+    use crate::util::{BitEnum, verbname_cmp};
+    use moor_var::{Obj, Symbol};
+    use uuid::Uuid;
 
     #[test]
     fn test_bitflags() {
@@ -134,4 +144,36 @@ mod tests {
             BitEnum::new() | VerbFlag::Read | VerbFlag::Write | VerbFlag::Exec | VerbFlag::Debug
         );
     }
+
+    /// Matching by folded symbol agrees with comparing the lowercased names as strings.
+    #[test]
+    fn test_interned_lookup_matches_string_lookup() {
+        let names = [
+            "look", "l*ook", "Get TAKE", "pu*t", "*", "eXamine", "exam*ine",
+        ];
+        let candidates = [
+            "look", "LOOK", "l", "lo", "looker", "get", "Take", "p", "pu", "PUT", "putter", "",
+            "examine", "EXAM", "exa", "x",
+        ];
+        for verb_names in names {
+            let verbdef = VerbDef::new(
+                Uuid::new_v4(),
+                Obj::mk_id(1),
+                Obj::mk_id(2),
+                &verb_names.split(' ').collect::<Vec<_>>(),
+                VerbFlag::rxd(),
+                VerbArgsSpec::this_none_this(),
+            );
+            for candidate in candidates {
+                let by_string = verb_names
+                    .split(' ')
+                    .any(|verb| verbname_cmp(&verb.to_lowercase(), &candidate.to_lowercase()));
+                assert_eq!(
+                    verbdef.matches_name(Symbol::mk(candidate)),
+                    by_string,
+                    "{verb_names} vs {candidate}"
+                );
+            }
+        }
+    }
 }
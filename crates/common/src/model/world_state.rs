@@ -27,7 +27,9 @@ use crate::model::{ObjAttr, Vid};
 use crate::program::ProgramType;
 use crate::util::{BitEnum, PerfCounter};
 use moor_var::Var;
-use moor_var::{E_INVARG, E_INVIND, E_PERM, E_PROPNF, E_RECMOVE, E_TYPE, E_VERBNF, Symbol};
+use moor_var::{
+    E_INVARG, E_INVIND, E_PERM, E_PROPNF, E_QUOTA, E_RECMOVE, E_TYPE, E_VERBNF, Symbol,
+};
 use moor_var::{Error, Obj};
 
 /// Errors related to the world state and operations on it.
@@ -41,6 +43,8 @@ pub enum WorldStateError {
     ObjectAttributeError(ObjAttr, Obj),
     #[error("Recursive move detected: {0} -> {1}")]
     RecursiveMove(Obj, Obj),
+    #[error("Ownership quota exhausted for {0}")]
+    QuotaExceeded(Obj),
 
     #[error("Object permission denied")]
     ObjectPermissionDenied,
@@ -93,18 +97,29 @@ impl WorldStateError {
             | Self::VerbPermissionDenied
             | Self::PropertyPermissionDenied => E_PERM,
             Self::RecursiveMove(_, _) => E_RECMOVE,
+            Self::QuotaExceeded(_) => E_QUOTA,
             Self::VerbNotFound(_, _) | Self::InvalidVerb(_) => E_VERBNF,
             Self::DuplicateVerb(_, _)
             | Self::DuplicatePropertyDefinition(_, _)
             | Self::ChparentPropertyNameConflict(_, _, _) => E_INVARG,
             Self::PropertyNotFound(_, _) | Self::PropertyDefinitionNotFound(_, _) => E_PROPNF,
             Self::PropertyTypeMismatch => E_TYPE,
+            Self::DatabaseError(_) => E_INVARG,
             _ => panic!("Unhandled error code: {:?}", self),
         };
 
         err_code.msg(self.to_string())
     }
 
+    /// As `to_error`, but with the internal detail of a database error withheld, for callers who
+    /// shouldn't see it.
+    pub fn to_redacted_error(&self) -> Error {
+        match self {
+            Self::DatabaseError(_) => E_INVARG.msg("Database error"),
+            _ => self.to_error(),
+        }
+    }
+
     pub fn database_error_msg(&self) -> Option<&str> {
         if let Self::DatabaseError(msg) = self {
             Some(msg)
@@ -120,6 +135,39 @@ impl From<WorldStateError> for Error {
     }
 }
 
+/// The core attributes of an object, as fetched in one go by `WorldState::object_summary`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectSummary {
+    pub name: String,
+    pub owner: Obj,
+    pub parent: Obj,
+    pub location: Obj,
+    pub flags: BitEnum<ObjFlag>,
+    /// The verbs defined on the object, or None if the caller isn't allowed to read them.
+    pub verbs: Option<VerbDefs>,
+    /// The properties defined on the object, or None if the caller isn't allowed to read them.
+    pub properties: Option<PropDefs>,
+}
+
+/// A change to the shape of the world, reported once the transaction making it has committed, for
+/// the benefit of external services (e.g. search indexers) which keep their own view of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode)]
+pub struct LifecycleEvent {
+    pub obj: Obj,
+    pub kind: LifecycleKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, Encode)]
+pub enum LifecycleKind {
+    /// The object was created, or brought back with `undelete`.
+    ObjectCreated,
+    ObjectRecycled,
+    VerbAdded,
+    VerbRemoved,
+    PropertyAdded,
+    PropertyRemoved,
+}
+
 /// A "world state" is anything which represents the shared, mutable, state of the user's
 /// environment during verb execution. This includes the location of objects, their contents,
 /// their properties, their verbs, etc.
@@ -155,12 +203,25 @@ pub trait WorldState: Send {
         flags: BitEnum<ObjFlag>,
     ) -> Result<(), WorldStateError>;
 
+    /// Get the name, owner, parent, location and flags of an object, along with its verbs and
+    /// properties if `perms` may read it, in one call.
+    fn object_summary(&self, perms: &Obj, obj: &Obj) -> Result<ObjectSummary, WorldStateError>;
+
     /// Get the location of the given object.
     fn location_of(&self, perms: &Obj, obj: &Obj) -> Result<Obj, WorldStateError>;
 
     /// Return the number of bytes used by the given object and all its attributes.
     fn object_bytes(&self, perms: &Obj, obj: &Obj) -> Result<usize, WorldStateError>;
 
+    /// Transfer ownership of every verb and property owned by `from` to `to`, across all objects.
+    /// Returns the number of verbs and properties transferred.
+    fn chown_all(
+        &mut self,
+        perms: &Obj,
+        from: &Obj,
+        to: &Obj,
+    ) -> Result<(usize, usize), WorldStateError>;
+
     /// Create a new object, assigning it a new unique object id.
     /// If owner is #-1, the object's is set to itself.
     /// Note it is the caller's responsibility to execute :initialize).
@@ -178,6 +239,10 @@ pub trait WorldState: Send {
     /// (It is the caller's (bf_recycle) responsibility to execute :exitfunc for those objects).
     fn recycle_object(&mut self, perms: &Obj, obj: &Obj) -> Result<(), WorldStateError>;
 
+    /// Bring back an object recycled while the database was keeping tombstones, if its retention
+    /// window hasn't passed. Wizard only.
+    fn undelete_object(&mut self, perms: &Obj, obj: &Obj) -> Result<(), WorldStateError>;
+
     /// Return the highest used object # in the system.
     fn max_object(&self, perms: &Obj) -> Result<Obj, WorldStateError>;
 
@@ -196,6 +261,18 @@ pub trait WorldState: Send {
     /// inherited from its parent.
     fn properties(&self, perms: &Obj, obj: &Obj) -> Result<PropDefs, WorldStateError>;
 
+    /// The number of verbs defined directly on the given object. Cheaper than `verbs(..).len()`.
+    fn count_verbs(&self, perms: &Obj, obj: &Obj) -> Result<usize, WorldStateError>;
+
+    /// The number of properties defined directly on the given object. Cheaper than
+    /// `properties(..).len()`.
+    fn count_properties(&self, perms: &Obj, obj: &Obj) -> Result<usize, WorldStateError>;
+
+    /// How many times the given object's verbs or properties have been changed, counted by
+    /// committed transaction. Anything cached from those can be kept for as long as this doesn't
+    /// move.
+    fn object_generation(&self, obj: &Obj) -> Result<i64, WorldStateError>;
+
     /// Retrieve a property from the given object, walking transitively up its inheritance chain.
     fn retrieve_property(
         &self,
@@ -231,6 +308,18 @@ pub trait WorldState: Send {
         value: &Var,
     ) -> Result<(), WorldStateError>;
 
+    /// Add `delta` to the numeric value of a property, which must be of the same type, and
+    /// return the sum. Unlike reading and then updating the property, concurrent additions to
+    /// the same value don't conflict with each other at commit, and none are lost.
+    /// Returns None if the sum would overflow.
+    fn add_to_property(
+        &mut self,
+        perms: &Obj,
+        obj: &Obj,
+        pname: Symbol,
+        delta: &Var,
+    ) -> Result<Option<Var>, WorldStateError>;
+
     /// Check if a property is 'clear' (value is purely inherited)
     fn is_property_clear(
         &self,
@@ -332,6 +421,8 @@ pub trait WorldState: Send {
     ) -> Result<(ProgramType, VerbDef), WorldStateError>;
 
     /// Retrieve a verb/method from the given object (or its parents).
+    /// Only verbs with the `x` (exec) flag are callable as methods; as in LambdaMOO, a verb
+    /// without it is reported as not found.
     fn find_method_verb_on(
         &self,
         perms: &Obj,
@@ -340,6 +431,8 @@ pub trait WorldState: Send {
     ) -> Result<(ProgramType, VerbDef), WorldStateError>;
 
     /// Seek the verb referenced by the given command on the given object.
+    /// Unlike `find_method_verb_on`, the `x` flag is not consulted here: as in LambdaMOO, command
+    /// matching considers every verb whose name and argument spec match.
     fn find_command_verb_on(
         &self,
         perms: &Obj,
@@ -365,6 +458,9 @@ pub trait WorldState: Send {
     /// Get the children of the given object.
     fn children_of(&self, perms: &Obj, obj: &Obj) -> Result<ObjSet, WorldStateError>;
 
+    /// The number of children of the given object. Cheaper than `children_of(..).len()`.
+    fn count_children(&self, perms: &Obj, obj: &Obj) -> Result<usize, WorldStateError>;
+
     /// Get the full descendant tree of the given object.
     fn descendants_of(
         &self,
@@ -390,9 +486,41 @@ pub trait WorldState: Send {
     /// Get the name & aliases of an object.
     fn names_of(&self, perms: &Obj, obj: &Obj) -> Result<(String, Vec<String>), WorldStateError>;
 
+    /// Every verb on any object with a name for which `matches` holds, as (object, verbdef) pairs
+    /// ordered by object. Wizard only.
+    fn find_verbs(
+        &self,
+        perms: &Obj,
+        matches: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<(Obj, VerbDef)>, WorldStateError>;
+
+    /// Every property defined on any object with a name for which `matches` holds, as
+    /// (definer, propdef) pairs ordered by definer. Wizard only.
+    fn find_properties(
+        &self,
+        perms: &Obj,
+        matches: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<(Obj, PropDef)>, WorldStateError>;
+
+    /// Increment the named sequence, creating it on first use, and return its new value.
+    /// Sequences are not transactional: a value handed out stays used even on rollback.
+    fn next_sequence(&self, name: &str) -> Result<i64, WorldStateError>;
+
+    /// The current value of the named sequence, 0 if it has never been incremented.
+    fn current_sequence(&self, name: &str) -> Result<i64, WorldStateError>;
+
+    /// Write everything committed to the database to the backup file `name` in the configured
+    /// backup directory, from which it can later be restored into a fresh database. `name` must
+    /// be a plain file name. Returns the number of tuples written.
+    fn backup(&self, name: &str) -> Result<usize, WorldStateError>;
+
     /// Returns the (rough) total number of bytes used by database storage subsystem.
     fn db_usage(&self) -> Result<usize, WorldStateError>;
 
+    /// Compact the database's storage, reclaiming the space held by overwritten and deleted
+    /// values. Returns the `db_usage` before and after.
+    fn compact(&self) -> Result<(usize, usize), WorldStateError>;
+
     /// Commit all modifications made to the state of this world since the start of its transaction.
     fn commit(self: Box<Self>) -> Result<CommitResult, WorldStateError>;
 
@@ -417,18 +545,25 @@ pub struct WorldStatePerf {
     pub flags_of: PerfCounter,
     pub set_flags_of: PerfCounter,
     pub location_of: PerfCounter,
+    pub object_summary: PerfCounter,
     pub object_bytes: PerfCounter,
+    pub chown_all: PerfCounter,
     pub create_object: PerfCounter,
     pub recycle_object: PerfCounter,
+    pub undelete_object: PerfCounter,
     pub max_object: PerfCounter,
     pub move_object: PerfCounter,
     pub contents_of: PerfCounter,
     pub verbs: PerfCounter,
     pub properties: PerfCounter,
+    pub count_verbs: PerfCounter,
+    pub count_properties: PerfCounter,
+    pub object_generation: PerfCounter,
     pub retrieve_property: PerfCounter,
     pub get_property_info: PerfCounter,
     pub set_property_info: PerfCounter,
     pub update_property: PerfCounter,
+    pub add_to_property: PerfCounter,
     pub is_property_clear: PerfCounter,
     pub clear_property: PerfCounter,
     pub define_property: PerfCounter,
@@ -446,12 +581,19 @@ pub struct WorldStatePerf {
     pub parent_of: PerfCounter,
     pub change_parent: PerfCounter,
     pub children_of: PerfCounter,
+    pub count_children: PerfCounter,
     pub descendants_of: PerfCounter,
     pub ancestors_of: PerfCounter,
     pub valid: PerfCounter,
     pub name_of: PerfCounter,
     pub names_of: PerfCounter,
+    pub find_verbs: PerfCounter,
+    pub find_properties: PerfCounter,
+    pub next_sequence: PerfCounter,
+    pub current_sequence: PerfCounter,
+    pub backup: PerfCounter,
     pub db_usage: PerfCounter,
+    pub compact: PerfCounter,
     pub commit: PerfCounter,
     pub rollback: PerfCounter,
 
@@ -479,18 +621,25 @@ impl WorldStatePerf {
             flags_of: PerfCounter::new("flags_of"),
             set_flags_of: PerfCounter::new("set_flags_of"),
             location_of: PerfCounter::new("location_of"),
+            object_summary: PerfCounter::new("object_summary"),
             object_bytes: PerfCounter::new("object_bytes"),
+            chown_all: PerfCounter::new("chown_all"),
             create_object: PerfCounter::new("create_object"),
             recycle_object: PerfCounter::new("recycle_object"),
+            undelete_object: PerfCounter::new("undelete_object"),
             max_object: PerfCounter::new("max_object"),
             move_object: PerfCounter::new("move_object"),
             contents_of: PerfCounter::new("contents_of"),
             verbs: PerfCounter::new("verbs"),
             properties: PerfCounter::new("properties"),
+            count_verbs: PerfCounter::new("count_verbs"),
+            count_properties: PerfCounter::new("count_properties"),
+            object_generation: PerfCounter::new("object_generation"),
             retrieve_property: PerfCounter::new("retrieve_property"),
             get_property_info: PerfCounter::new("get_property_info"),
             set_property_info: PerfCounter::new("set_property_info"),
             update_property: PerfCounter::new("update_property"),
+            add_to_property: PerfCounter::new("add_to_property"),
             is_property_clear: PerfCounter::new("is_property_clear"),
             clear_property: PerfCounter::new("clear_property"),
             define_property: PerfCounter::new("define_property"),
@@ -508,12 +657,19 @@ impl WorldStatePerf {
             parent_of: PerfCounter::new("parent_of"),
             change_parent: PerfCounter::new("change_parent"),
             children_of: PerfCounter::new("children_of"),
+            count_children: PerfCounter::new("count_children"),
             descendants_of: PerfCounter::new("descendants_of"),
             ancestors_of: PerfCounter::new("ancestors_of"),
             valid: PerfCounter::new("valid"),
             name_of: PerfCounter::new("name_of"),
             names_of: PerfCounter::new("names_of"),
+            find_verbs: PerfCounter::new("find_verbs"),
+            find_properties: PerfCounter::new("find_properties"),
+            next_sequence: PerfCounter::new("next_sequence"),
+            current_sequence: PerfCounter::new("current_sequence"),
+            backup: PerfCounter::new("backup"),
             db_usage: PerfCounter::new("db_usage"),
+            compact: PerfCounter::new("compact"),
             commit: PerfCounter::new("commit"),
             rollback: PerfCounter::new("rollback"),
             commit_check_phase: PerfCounter::new("commit_check_phase"),
@@ -533,18 +689,25 @@ impl WorldStatePerf {
             &self.flags_of,
             &self.set_flags_of,
             &self.location_of,
+            &self.object_summary,
             &self.object_bytes,
+            &self.chown_all,
             &self.create_object,
             &self.recycle_object,
+            &self.undelete_object,
             &self.max_object,
             &self.move_object,
             &self.contents_of,
             &self.verbs,
             &self.properties,
+            &self.count_verbs,
+            &self.count_properties,
+            &self.object_generation,
             &self.retrieve_property,
             &self.get_property_info,
             &self.set_property_info,
             &self.update_property,
+            &self.add_to_property,
             &self.is_property_clear,
             &self.clear_property,
             &self.define_property,
@@ -562,12 +725,19 @@ impl WorldStatePerf {
             &self.parent_of,
             &self.change_parent,
             &self.children_of,
+            &self.count_children,
             &self.descendants_of,
             &self.ancestors_of,
             &self.valid,
             &self.name_of,
             &self.names_of,
+            &self.find_verbs,
+            &self.find_properties,
+            &self.next_sequence,
+            &self.current_sequence,
+            &self.backup,
             &self.db_usage,
+            &self.compact,
             &self.commit,
             &self.rollback,
             &self.commit_check_phase,
@@ -150,7 +150,7 @@ fn mk_builtin_table() -> Vec<Builtin> {
             min_args: Q(1),
             max_args: Q(2),
             types: vec![Typed(TYPE_INT), Any],
-            implemented: false,
+            implemented: true,
         },
         Builtin {
             name: Symbol::mk("function_info"),
@@ -183,16 +183,16 @@ fn mk_builtin_table() -> Vec<Builtin> {
         Builtin {
             name: Symbol::mk("string_hash"),
             min_args: Q(1),
-            max_args: Q(1),
-            types: vec![Typed(TYPE_STR)],
-            implemented: false,
+            max_args: Q(3),
+            types: vec![Typed(TYPE_STR), Typed(TYPE_STR), Typed(TYPE_INT)],
+            implemented: true,
         },
         Builtin {
             name: Symbol::mk("binary_hash"),
             min_args: Q(1),
-            max_args: Q(1),
-            types: vec![Typed(TYPE_STR)],
-            implemented: false,
+            max_args: Q(3),
+            types: vec![Any, Typed(TYPE_STR), Typed(TYPE_INT)],
+            implemented: true,
         },
         Builtin {
             name: Symbol::mk("decode_binary"),
@@ -745,7 +745,7 @@ fn mk_builtin_table() -> Vec<Builtin> {
             min_args: Q(0),
             max_args: Q(0),
             types: vec![],
-            implemented: false,
+            implemented: true,
         },
         Builtin {
             name: Symbol::mk("db_disk_size"),
@@ -808,21 +808,21 @@ fn mk_builtin_table() -> Vec<Builtin> {
             min_args: Q(3),
             max_args: Q(3),
             types: vec![Typed(TYPE_OBJ), Typed(TYPE_STR), Any],
-            implemented: false,
+            implemented: true,
         },
         Builtin {
             name: Symbol::mk("connection_option"),
             min_args: Q(2),
             max_args: Q(2),
             types: vec![Typed(TYPE_OBJ), Typed(TYPE_STR)],
-            implemented: false,
+            implemented: true,
         },
         Builtin {
             name: Symbol::mk("connection_options"),
             min_args: Q(1),
             max_args: Q(1),
             types: vec![Typed(TYPE_OBJ)],
-            implemented: false,
+            implemented: true,
         },
         Builtin {
             name: Symbol::mk("listen"),
@@ -974,8 +974,8 @@ fn mk_builtin_table() -> Vec<Builtin> {
         Builtin {
             name: Symbol::mk("eval"),
             min_args: Q(1),
-            max_args: Q(1),
-            types: vec![Typed(TYPE_STR)],
+            max_args: Q(3),
+            types: vec![Typed(TYPE_STR), Typed(TYPE_INT), AnyNum],
             implemented: true,
         },
         Builtin {
@@ -1262,6 +1262,263 @@ fn mk_builtin_table() -> Vec<Builtin> {
             ],
             implemented: true,
         },
+        Builtin {
+            name: Symbol::mk("locals"),
+            min_args: Q(0),
+            max_args: Q(0),
+            types: vec![],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("set_verb_trace"),
+            min_args: Q(3),
+            max_args: Q(3),
+            types: vec![Typed(TYPE_OBJ), Any, Any],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("player_info"),
+            min_args: Q(0),
+            max_args: Q(1),
+            types: vec![Any],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("lock_object"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("unlock_object"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("next_sequence"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("current_sequence"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("backup"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("object_summary"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("chown_all"),
+            min_args: Q(2),
+            max_args: Q(2),
+            types: vec![Typed(TYPE_OBJ), Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("set_aliases"),
+            min_args: Q(2),
+            max_args: Q(2),
+            types: vec![Typed(TYPE_OBJ), Typed(TYPE_LIST)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("add_alias"),
+            min_args: Q(2),
+            max_args: Q(2),
+            types: vec![Typed(TYPE_OBJ), Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("remove_alias"),
+            min_args: Q(2),
+            max_args: Q(2),
+            types: vec![Typed(TYPE_OBJ), Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("undelete"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("profile"),
+            min_args: Q(1),
+            max_args: Q(3),
+            types: vec![Any, Typed(TYPE_STR), Typed(TYPE_LIST)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("format"),
+            min_args: Q(1),
+            max_args: U,
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("check_args"),
+            min_args: Q(2),
+            max_args: Q(3),
+            types: vec![Typed(TYPE_LIST), Typed(TYPE_LIST), Typed(TYPE_INT)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("compact_database"),
+            min_args: Q(0),
+            max_args: Q(0),
+            types: vec![],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("find_verbs"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("find_properties"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("object_to_json"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("property_add"),
+            min_args: Q(3),
+            max_args: Q(3),
+            types: vec![Typed(TYPE_OBJ), Typed(TYPE_STR), AnyNum],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("move_all"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_LIST)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("copy_verb"),
+            min_args: Q(3),
+            max_args: Q(4),
+            types: vec![Typed(TYPE_OBJ), Any, Typed(TYPE_OBJ), Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("copy_property"),
+            min_args: Q(3),
+            max_args: Q(4),
+            types: vec![
+                Typed(TYPE_OBJ),
+                Typed(TYPE_STR),
+                Typed(TYPE_OBJ),
+                Typed(TYPE_OBJ),
+            ],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("set_task_name"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("notify_confirmed"),
+            min_args: Q(2),
+            max_args: Q(3),
+            types: vec![Typed(TYPE_OBJ), Typed(TYPE_STR), Any],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("object_generation"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("create_player"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("relation_stats"),
+            min_args: Q(0),
+            max_args: Q(0),
+            types: vec![],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("reseed_random"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_INT)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("send_binary"),
+            min_args: Q(3),
+            max_args: Q(3),
+            types: vec![Typed(TYPE_OBJ), Any, Any],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("property_definer"),
+            min_args: Q(2),
+            max_args: Q(2),
+            types: vec![Typed(TYPE_OBJ), Typed(TYPE_STR)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("verb_count"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("property_count"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
+        Builtin {
+            name: Symbol::mk("child_count"),
+            min_args: Q(1),
+            max_args: Q(1),
+            types: vec![Typed(TYPE_OBJ)],
+            implemented: true,
+        },
     ]
 }
 
@@ -12,9 +12,11 @@
 //
 
 use crate::program::names::Variable;
-use crate::program::program::Program;
+use crate::program::program::{PrgInnerV0, Program};
 use bincode::{Decode, Encode};
-use moor_var::BincodeAsByteBufferExt;
+use byteview::ByteView;
+use moor_var::{AsByteBuffer, BINCODE_CONFIG, CountingWriter, DecodingError, EncodingError};
+use std::sync::Arc;
 
 pub mod builtins;
 pub mod labels;
@@ -27,14 +29,117 @@ pub mod program;
 #[derive(Debug, Clone, PartialEq, Encode, Decode)]
 pub enum ProgramType {
     MooR(Program),
+    /// A MOO program stored along with the exact source text (lines) it was compiled from, so
+    /// that it can be handed back verbatim, comments and formatting included, instead of being
+    /// decompiled.
+    MooRWithSource(Program, Vec<String>),
 }
 
-impl BincodeAsByteBufferExt for ProgramType {}
+/// Stored programs start with this marker, followed by the format version as a little-endian
+/// u16, followed by the bincode encoded `ProgramType`. Programs stored before the format was
+/// versioned have no header; they start with a bincode enum discriminant, which is never 0xFF.
+const PROGRAM_FORMAT_MARKER: [u8; 2] = [0xFF, b'P'];
+const PROGRAM_HEADER_LEN: usize = PROGRAM_FORMAT_MARKER.len() + size_of::<u16>();
+
+/// The version of the stored program format written by this server. Bump this whenever a change
+/// to the opcode set or to `PrgInner` alters how stored programs decode, and teach
+/// `ProgramType::decode_versioned` how to read (or explicitly reject) the previous version.
+///
+/// Version history:
+///   0: unversioned; no line number spans for fork vectors.
+///   1: adds `fork_line_number_spans`.
+pub const PROGRAM_FORMAT_VERSION: u16 = 1;
+
+/// `ProgramType` as stored in format version 0.
+#[derive(Encode, Decode)]
+enum ProgramTypeV0 {
+    MooR(PrgInnerV0),
+    MooRWithSource(PrgInnerV0, Vec<String>),
+}
+
+impl From<ProgramTypeV0> for ProgramType {
+    fn from(v0: ProgramTypeV0) -> Self {
+        match v0 {
+            ProgramTypeV0::MooR(inner) => ProgramType::MooR(Program(Arc::new(inner.into()))),
+            ProgramTypeV0::MooRWithSource(inner, source) => {
+                ProgramType::MooRWithSource(Program(Arc::new(inner.into())), source)
+            }
+        }
+    }
+}
+
+impl ProgramType {
+    /// Decode a stored program, migrating it forward if it was written by an older version of the
+    /// format, and refusing it (rather than mis-decoding it) if it was written by a newer one.
+    fn decode_versioned(bytes: &[u8]) -> Result<Self, DecodingError> {
+        let could_not_decode =
+            |e: bincode::error::DecodeError| DecodingError::CouldNotDecode(e.to_string());
+        let Some(rest) = bytes.strip_prefix(&PROGRAM_FORMAT_MARKER[..]) else {
+            let (v0, _): (ProgramTypeV0, _) =
+                bincode::decode_from_slice(bytes, *BINCODE_CONFIG).map_err(could_not_decode)?;
+            return Ok(v0.into());
+        };
+        let Some((version, body)) = rest.split_first_chunk::<2>() else {
+            return Err(DecodingError::CouldNotDecode(
+                "truncated program format header".to_string(),
+            ));
+        };
+        match u16::from_le_bytes(*version) {
+            PROGRAM_FORMAT_VERSION => Ok(bincode::decode_from_slice(body, *BINCODE_CONFIG)
+                .map_err(could_not_decode)?
+                .0),
+            version => Err(DecodingError::UnsupportedProgramVersion(
+                version,
+                PROGRAM_FORMAT_VERSION,
+            )),
+        }
+    }
+}
+
+impl AsByteBuffer for ProgramType {
+    fn size_bytes(&self) -> usize {
+        let mut cw = CountingWriter { count: 0 };
+        bincode::encode_into_writer(self, &mut cw, *BINCODE_CONFIG)
+            .expect("bincode to bytes for counting size");
+        PROGRAM_HEADER_LEN + cw.count
+    }
+
+    fn with_byte_buffer<R, F: FnMut(&[u8]) -> R>(&self, mut f: F) -> Result<R, EncodingError> {
+        let v = self.make_copy_as_vec()?;
+        Ok(f(&v[..]))
+    }
+
+    fn make_copy_as_vec(&self) -> Result<Vec<u8>, EncodingError> {
+        let body = bincode::encode_to_vec(self, *BINCODE_CONFIG)
+            .map_err(|e| EncodingError::CouldNotEncode(e.to_string()))?;
+        let mut v = Vec::with_capacity(PROGRAM_HEADER_LEN + body.len());
+        v.extend_from_slice(&PROGRAM_FORMAT_MARKER);
+        v.extend_from_slice(&PROGRAM_FORMAT_VERSION.to_le_bytes());
+        v.extend_from_slice(&body);
+        Ok(v)
+    }
+
+    fn from_bytes(bytes: ByteView) -> Result<Self, DecodingError> {
+        Self::decode_versioned(bytes.as_ref())
+    }
+
+    fn as_bytes(&self) -> Result<ByteView, EncodingError> {
+        Ok(ByteView::from(self.make_copy_as_vec()?))
+    }
+}
 
 impl ProgramType {
     pub fn is_empty(&self) -> bool {
         match self {
-            ProgramType::MooR(p) => p.main_vector().is_empty(),
+            ProgramType::MooR(p) | ProgramType::MooRWithSource(p, _) => p.main_vector().is_empty(),
+        }
+    }
+
+    /// The original source text of the program, if it was stored with it.
+    pub fn source(&self) -> Option<&[String]> {
+        match self {
+            ProgramType::MooR(_) => None,
+            ProgramType::MooRWithSource(_, source) => Some(source),
         }
     }
 }
@@ -66,3 +171,86 @@ pub struct Decl {
     /// This is used to determine the scope of the variable when binding (or rebinding at decompile)
     pub scope_id: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::labels::Offset;
+    use crate::program::names::Names;
+    use crate::program::opcode::Op;
+    use crate::program::program::PrgInner;
+    use moor_var::v_int;
+
+    fn v0_inner() -> PrgInnerV0 {
+        PrgInnerV0 {
+            literals: vec![v_int(5)],
+            jump_labels: vec![],
+            var_names: Names::new(0),
+            scatter_tables: vec![],
+            for_sequence_operands: vec![],
+            range_comprehensions: vec![],
+            list_comprehensions: vec![],
+            error_operands: vec![],
+            main_vector: vec![
+                Op::ImmInt(5),
+                Op::Fork {
+                    fv_offset: Offset(0),
+                    id: None,
+                },
+                Op::Done,
+            ],
+            fork_vectors: vec![vec![Op::ImmInt(1), Op::Return]],
+            line_number_spans: vec![(0, 1)],
+        }
+    }
+
+    #[test]
+    fn test_round_trip_current_version() {
+        let program = ProgramType::MooRWithSource(
+            Program(Arc::new(PrgInner::from(v0_inner()))),
+            vec!["return 5;".to_string()],
+        );
+        let bytes = program.as_bytes().unwrap();
+        assert!(bytes.as_ref().starts_with(&PROGRAM_FORMAT_MARKER));
+        assert_eq!(bytes.len(), program.size_bytes());
+        assert_eq!(ProgramType::from_bytes(bytes).unwrap(), program);
+    }
+
+    /// A program stored before the format was versioned still loads, with empty fork spans.
+    #[test]
+    fn test_load_unversioned_program() {
+        let bytes =
+            bincode::encode_to_vec(ProgramTypeV0::MooR(v0_inner()), *BINCODE_CONFIG).unwrap();
+        let ProgramType::MooR(program) = ProgramType::from_bytes(ByteView::from(bytes)).unwrap()
+        else {
+            panic!("expected MooR program");
+        };
+        assert_eq!(program.main_vector().len(), 3);
+        assert_eq!(program.0.fork_vectors.len(), 1);
+        assert_eq!(program.0.fork_line_number_spans, vec![vec![]]);
+        assert_eq!(program.0.line_number_spans, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_reject_newer_version() {
+        let program = ProgramType::MooR(Program::new());
+        let mut bytes = program.make_copy_as_vec().unwrap();
+        bytes[PROGRAM_FORMAT_MARKER.len()..PROGRAM_HEADER_LEN]
+            .copy_from_slice(&(PROGRAM_FORMAT_VERSION + 1).to_le_bytes());
+        let result = ProgramType::from_bytes(ByteView::from(bytes));
+        assert!(matches!(
+            result,
+            Err(DecodingError::UnsupportedProgramVersion(v, PROGRAM_FORMAT_VERSION))
+                if v == PROGRAM_FORMAT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_reject_truncated_header() {
+        let bytes = PROGRAM_FORMAT_MARKER.to_vec();
+        assert!(matches!(
+            ProgramType::from_bytes(ByteView::from(bytes)),
+            Err(DecodingError::CouldNotDecode(_))
+        ));
+    }
+}
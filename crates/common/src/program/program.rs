@@ -56,9 +56,49 @@ pub struct PrgInner {
     pub fork_vectors: Vec<Vec<Op>>,
     /// As each statement is pushed, the line number is recorded, along with its offset in the main
     /// vector.
-    /// TODO: fork vector offsets... Have to think about that one.
     pub line_number_spans: Vec<(usize, usize)>,
+    /// The same, for each fork vector, with offsets relative to the start of that fork vector.
+    pub fork_line_number_spans: Vec<Vec<(usize, usize)>>,
 }
+
+/// `PrgInner` as stored in program format version 0, before fork vectors carried their own line
+/// number spans. See `PROGRAM_FORMAT_VERSION`.
+#[derive(Encode, Decode)]
+pub(crate) struct PrgInnerV0 {
+    pub literals: Vec<Var>,
+    pub jump_labels: Vec<JumpLabel>,
+    pub var_names: Names,
+    pub scatter_tables: Vec<ScatterArgs>,
+    pub for_sequence_operands: Vec<ForSequenceOperand>,
+    pub range_comprehensions: Vec<RangeComprehend>,
+    pub list_comprehensions: Vec<ListComprehend>,
+    pub error_operands: Vec<ErrorCode>,
+    pub main_vector: Vec<Op>,
+    pub fork_vectors: Vec<Vec<Op>>,
+    pub line_number_spans: Vec<(usize, usize)>,
+}
+
+impl From<PrgInnerV0> for PrgInner {
+    /// Fork line numbers weren't recorded, so tracebacks from within the forks of a migrated
+    /// program report line 1 until the verb is recompiled.
+    fn from(v0: PrgInnerV0) -> Self {
+        PrgInner {
+            fork_line_number_spans: vec![vec![]; v0.fork_vectors.len()],
+            literals: v0.literals,
+            jump_labels: v0.jump_labels,
+            var_names: v0.var_names,
+            scatter_tables: v0.scatter_tables,
+            for_sequence_operands: v0.for_sequence_operands,
+            range_comprehensions: v0.range_comprehensions,
+            list_comprehensions: v0.list_comprehensions,
+            error_operands: v0.error_operands,
+            main_vector: v0.main_vector,
+            fork_vectors: v0.fork_vectors,
+            line_number_spans: v0.line_number_spans,
+        }
+    }
+}
+
 impl Program {
     pub fn new() -> Self {
         Program(Arc::new(PrgInner {
@@ -72,6 +112,7 @@ impl Program {
             main_vector: vec![],
             fork_vectors: vec![],
             line_number_spans: vec![],
+            fork_line_number_spans: vec![],
             error_operands: vec![],
         }))
     }
@@ -141,6 +182,10 @@ impl Program {
         &self.0.fork_vectors[offset.0 as usize]
     }
 
+    pub fn fork_vectors(&self) -> &[Vec<Op>] {
+        &self.0.fork_vectors
+    }
+
     pub fn main_vector(&self) -> &Vec<Op> {
         &self.0.main_vector
     }
@@ -149,16 +194,30 @@ impl Program {
         self.0.jump_labels.iter().find(|j| &j.id == label).cloned()
     }
 
+    /// The source line for the opcode just before `position` in the main vector. Called with the
+    /// program counter, which has already moved past the opcode being executed.
     pub fn line_num_for_position(&self, position: usize) -> usize {
-        let mut last_line_num = 1;
-        for (offset, line_no) in &self.0.line_number_spans {
-            if *offset >= position {
-                return last_line_num;
-            }
-            last_line_num = *line_no
+        line_num_in_spans(&self.0.line_number_spans, position)
+    }
+
+    /// As `line_num_for_position`, but for a position within the given fork vector.
+    pub fn fork_line_num_for_position(&self, fork_vector: Offset, position: usize) -> usize {
+        match self.0.fork_line_number_spans.get(fork_vector.0 as usize) {
+            Some(spans) => line_num_in_spans(spans, position),
+            None => 1,
+        }
+    }
+}
+
+fn line_num_in_spans(spans: &[(usize, usize)], position: usize) -> usize {
+    let mut last_line_num = 1;
+    for (offset, line_no) in spans {
+        if *offset >= position {
+            return last_line_num;
         }
-        last_line_num
+        last_line_num = *line_no
     }
+    last_line_num
 }
 
 impl Default for Program {
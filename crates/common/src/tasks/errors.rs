@@ -34,6 +34,8 @@ pub enum AbortLimitReason {
     Ticks(usize),
     /// This task hit its allotted time limit.
     Time(Duration),
+    /// A builtin function called by this task took longer than the builtin duration limit.
+    BuiltinDuration(Symbol),
 }
 
 #[derive(Debug, Error, Clone, Decode, Encode, PartialEq)]
@@ -91,6 +93,8 @@ pub enum CommandError {
     CouldNotParseCommand,
     #[error("Could not find object match for command")]
     NoObjectMatch,
+    #[error("Could not find object match for {0:?}, did you mean one of {1:?}?")]
+    NoObjectMatchSuggestions(String, Vec<String>),
     #[error("Could not find verb match for command")]
     NoCommandMatch,
     #[error("Could not start transaction due to database error")]
@@ -43,6 +43,9 @@ pub enum Event {
     Unpresent(String),
     /// Present a backtrace to the user.
     Traceback(Exception),
+    /// Binary content, e.g. an image, for clients which can take it out of band. Content-Type &
+    /// bytes.
+    Binary(Symbol, Vec<u8>),
     // TODO: Other Event types on Session stream
     //   other events that might happen here would be things like (local) "object moved" or "object
     //   created."
@@ -78,6 +81,15 @@ impl NarrativeEvent {
         }
     }
 
+    #[must_use]
+    pub fn binary(author: Var, content_type: Symbol, bytes: Vec<u8>) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            author,
+            event: Event::Binary(content_type, bytes),
+        }
+    }
+
     #[must_use]
     pub fn timestamp(&self) -> SystemTime {
         self.timestamp
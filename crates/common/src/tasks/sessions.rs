@@ -11,13 +11,14 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
+use bincode::{Decode, Encode};
 use std::sync::{Arc, RwLock};
 
 use thiserror::Error;
 use uuid::Uuid;
 
 use crate::tasks::NarrativeEvent;
-use moor_var::{Error, Obj, SYSTEM_OBJECT};
+use moor_var::{Error, Obj, SYSTEM_OBJECT, Symbol, Var};
 
 /// The interface for managing the user I/O connection side of state, exposed by the scheduler to
 /// the VM during execution and by the host server to the scheduler.
@@ -72,6 +73,17 @@ pub trait Session: Send + Sync {
     /// rollback.
     fn send_event(&self, player: Obj, event: Box<NarrativeEvent>) -> Result<(), SessionError>;
 
+    /// Send output to the given player's connections right away, without spooling it, and wait
+    /// for their host(s) to report whether it was written out.
+    /// Returns whether any connection took it; false if the player isn't connected, or every
+    /// write failed, or no host answered in time. Since it isn't spooled, the output is not
+    /// thrown out if the task later rolls back.
+    fn send_event_confirmed(
+        &self,
+        player: Obj,
+        event: Box<NarrativeEvent>,
+    ) -> Result<bool, SessionError>;
+
     /// Send non-spooled output to the given player's connection
     /// Examples of the kinds of messages that would be sent here are state-independent messages
     /// like login/logout messages, system error messages ("task aborted") or messages that are not
@@ -90,6 +102,15 @@ pub trait Session: Send + Sync {
     /// Disconnect the given player's connection.
     fn disconnect(&self, player: Obj) -> Result<(), SessionError>;
 
+    /// Set a connection option (e.g. "echo") on the given player's connections.
+    /// Like `send_system_msg` this is not spooled; it takes effect immediately.
+    fn set_connection_option(
+        &self,
+        player: Obj,
+        option: Symbol,
+        value: Var,
+    ) -> Result<(), SessionError>;
+
     /// Return the list of other currently-connected players.
     fn connected_players(&self) -> Result<Vec<Obj>, SessionError>;
 
@@ -98,22 +119,35 @@ pub trait Session: Send + Sync {
 
     /// Return how many seconds the given player has been idle (no tasks submitted).
     fn idle_seconds(&self, player: Obj) -> Result<f64, SessionError>;
+
+    /// Return the options of the player's most recent connection: both those set with
+    /// `set_connection_option`, and those reported by its host (e.g. the client's window size).
+    fn connection_options(&self, player: Obj) -> Result<Vec<(Symbol, Var)>, SessionError>;
+}
+
+/// The certificate chain and private key (PEM files, at paths as seen by the host) a listener
+/// terminates TLS with.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
+pub struct ListenerTls {
+    pub certificate: String,
+    pub key: String,
 }
 
 /// A handle back to the controlling process (e.g. RpcServer) for handling system level events,
 /// such as shutdown, listen(), etc.
-///
 pub trait SystemControl: Send + Sync {
     /// Process a (wizard) request for system shutdown, with an optional shutdown message.
     fn shutdown(&self, msg: Option<String>) -> Result<(), Error>;
 
-    /// Ask hosts of `host_type` to listen on the given port, with the given handler object.
+    /// Ask hosts of `host_type` to listen on the given port, with the given handler object, and
+    /// to terminate TLS on it if `tls` is given.
     fn listen(
         &self,
         handler_object: Obj,
         host_type: &str,
         port: u16,
         print_messages: bool,
+        tls: Option<ListenerTls>,
     ) -> Result<(), Error>;
 
     /// Ask hosts of `host_type` to stop listening on the given port.
@@ -181,6 +215,14 @@ impl Session for NoopClientSession {
         Ok(())
     }
 
+    fn send_event_confirmed(
+        &self,
+        _player: Obj,
+        _msg: Box<NarrativeEvent>,
+    ) -> Result<bool, SessionError> {
+        Ok(false)
+    }
+
     fn send_system_msg(&self, _player: Obj, _msg: &str) -> Result<(), SessionError> {
         Ok(())
     }
@@ -195,6 +237,14 @@ impl Session for NoopClientSession {
     fn disconnect(&self, _player: Obj) -> Result<(), SessionError> {
         Ok(())
     }
+    fn set_connection_option(
+        &self,
+        _player: Obj,
+        _option: Symbol,
+        _value: Var,
+    ) -> Result<(), SessionError> {
+        Ok(())
+    }
     fn connected_players(&self) -> Result<Vec<Obj>, SessionError> {
         Ok(vec![])
     }
@@ -206,6 +256,10 @@ impl Session for NoopClientSession {
     fn idle_seconds(&self, _player: Obj) -> Result<f64, SessionError> {
         Ok(0.0)
     }
+
+    fn connection_options(&self, _player: Obj) -> Result<Vec<(Symbol, Var)>, SessionError> {
+        Ok(vec![])
+    }
 }
 
 #[derive(Default)]
@@ -222,6 +276,7 @@ impl SystemControl for NoopSystemControl {
         _host_type: &str,
         _port: u16,
         _print_messages: bool,
+        _tls: Option<ListenerTls>,
     ) -> Result<(), Error> {
         Ok(())
     }
@@ -235,16 +290,22 @@ impl SystemControl for NoopSystemControl {
     }
 }
 /// A 'mock' client connection which collects output in a vector of strings that tests can use to
-/// verify output.
-/// For now that's all it does, but facilities for pretending players are connected, mocking
-/// hostnames, etc. can be added later.
+/// verify output, and which can pretend players are connected.
 struct Inner {
     received: Vec<NarrativeEvent>,
     committed: Vec<NarrativeEvent>,
 }
+/// A pretend player connection, see `MockClientSession::connect`.
+#[derive(Clone, Copy)]
+struct MockConnection {
+    player: Obj,
+    connected_seconds: f64,
+    idle_seconds: f64,
+}
 pub struct MockClientSession {
     inner: RwLock<Inner>,
     system: Arc<RwLock<Vec<String>>>,
+    connections: Arc<RwLock<Vec<MockConnection>>>,
 }
 impl MockClientSession {
     pub fn new() -> Self {
@@ -254,8 +315,25 @@ impl MockClientSession {
                 committed: vec![],
             }),
             system: Arc::new(Default::default()),
+            connections: Arc::new(Default::default()),
         }
     }
+    /// Pretend `player` is connected, with the given connection times.
+    pub fn connect(&self, player: Obj, connected_seconds: f64, idle_seconds: f64) {
+        self.connections.write().unwrap().push(MockConnection {
+            player,
+            connected_seconds,
+            idle_seconds,
+        });
+    }
+    fn connection(&self, player: Obj) -> Option<MockConnection> {
+        self.connections
+            .read()
+            .unwrap()
+            .iter()
+            .find(|c| c.player == player)
+            .copied()
+    }
     pub fn received(&self) -> Vec<NarrativeEvent> {
         let inner = self.inner.read().unwrap();
         inner.received.clone()
@@ -294,6 +372,7 @@ impl Session for MockClientSession {
                 committed: vec![],
             }),
             system: self.system.clone(),
+            connections: self.connections.clone(),
         }))
     }
 
@@ -309,6 +388,18 @@ impl Session for MockClientSession {
         Ok(())
     }
 
+    fn send_event_confirmed(
+        &self,
+        player: Obj,
+        msg: Box<NarrativeEvent>,
+    ) -> Result<bool, SessionError> {
+        if self.connection(player).is_none() {
+            return Ok(false);
+        }
+        self.inner.write().unwrap().committed.push(*msg);
+        Ok(true)
+    }
+
     fn send_system_msg(&self, player: Obj, msg: &str) -> Result<(), SessionError> {
         self.system
             .write()
@@ -337,16 +428,45 @@ impl Session for MockClientSession {
         Ok(())
     }
 
+    fn set_connection_option(
+        &self,
+        player: Obj,
+        option: Symbol,
+        value: Var,
+    ) -> Result<(), SessionError> {
+        self.system
+            .write()
+            .unwrap()
+            .push(format!("{}: set {} = {:?}", player, option, value));
+        Ok(())
+    }
+
     fn connected_players(&self) -> Result<Vec<Obj>, SessionError> {
-        Ok(vec![])
+        Ok(self
+            .connections
+            .read()
+            .unwrap()
+            .iter()
+            .map(|c| c.player)
+            .collect())
     }
 
-    fn connected_seconds(&self, _player: Obj) -> Result<f64, SessionError> {
-        Ok(0.0)
+    fn connected_seconds(&self, player: Obj) -> Result<f64, SessionError> {
+        Ok(self
+            .connection(player)
+            .map(|c| c.connected_seconds)
+            .unwrap_or(0.0))
     }
 
-    fn idle_seconds(&self, _player: Obj) -> Result<f64, SessionError> {
-        Ok(0.0)
+    fn idle_seconds(&self, player: Obj) -> Result<f64, SessionError> {
+        Ok(self
+            .connection(player)
+            .map(|c| c.idle_seconds)
+            .unwrap_or(0.0))
+    }
+
+    fn connection_options(&self, _player: Obj) -> Result<Vec<(Symbol, Var)>, SessionError> {
+        Ok(vec![])
     }
 }
 
@@ -363,6 +483,7 @@ impl SystemControl for MockClientSession {
         _host_type: &str,
         _port: u16,
         _print_messages: bool,
+        _tls: Option<ListenerTls>,
     ) -> Result<(), Error> {
         let mut system = self.system.write().unwrap();
         system.push(String::from("listen"));
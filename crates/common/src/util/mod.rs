@@ -23,6 +23,11 @@ pub use perf_counter::{PerfCounter, PerfTimerGuard};
 
 /// Check `names` for matches with wildcard prefixes.
 /// e.g. "dname*c" will match for any of 'dname', 'dnamec'
+///
+/// As in LambdaMOO: a star inside a name (`g*et`, or `*get`) makes the name match any prefix of
+/// itself at least as long as the part before the star; a trailing star (`get*`) matches anything
+/// that begins with the part before it; and a bare `*` matches anything at all. Both arguments
+/// are expected to already be in the same case.
 #[must_use]
 pub fn verbname_cmp(vname: &str, candidate: &str) -> bool {
     let mut v_iter = vname.chars().peekable();
@@ -169,5 +174,27 @@ mod tests {
 
         // Regression for 'do_object' matching 'do'
         assert!(!verbname_cmp("do", "do_object"));
+
+        // A leading star behaves like any other inner star: any prefix of the rest, down to the
+        // empty part before the star.
+        let starfoo = "*foo";
+        assert!(verbname_cmp(starfoo, "f"));
+        assert!(verbname_cmp(starfoo, "foo"));
+        assert!(!verbname_cmp(starfoo, "food"));
+        assert!(!verbname_cmp(starfoo, "xfoo"));
+
+        // Single-character parts either side of the star.
+        assert!(verbname_cmp("f*o", "f"));
+        assert!(verbname_cmp("f*o", "fo"));
+        assert!(!verbname_cmp("f*o", "fox"));
+        assert!(!verbname_cmp("f*o", "o"));
+
+        // Once past a trailing star anything goes, even after an inner star.
+        assert!(verbname_cmp("f*o*", "fooooo"));
+        assert!(!verbname_cmp("f*o*", "fx"));
+
+        // The bare star matches anything, whatever its length.
+        assert!(verbname_cmp("*", "g"));
+        assert!(verbname_cmp("*", "give_me_everything"));
     }
 }
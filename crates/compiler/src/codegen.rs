@@ -66,6 +66,7 @@ pub struct CodegenState {
     pub(crate) max_stack: usize,
     pub(crate) fork_vectors: Vec<Vec<Op>>,
     pub(crate) line_number_spans: Vec<(usize, usize)>,
+    pub(crate) fork_line_number_spans: Vec<Vec<(usize, usize)>>,
     pub(crate) current_line_col: (usize, usize),
     pub(crate) compile_options: CompileOptions,
 }
@@ -91,6 +92,7 @@ impl CodegenState {
             for_sequence_operands: vec![],
             range_comprehensions: vec![],
             line_number_spans: vec![],
+            fork_line_number_spans: vec![],
             current_line_col: (0, 0),
             compile_options,
             list_comprehensions: vec![],
@@ -205,9 +207,14 @@ impl CodegenState {
         self.saved_stack = old
     }
 
-    fn add_fork_vector(&mut self, opcodes: Vec<Op>) -> Offset {
+    fn add_fork_vector(
+        &mut self,
+        opcodes: Vec<Op>,
+        line_number_spans: Vec<(usize, usize)>,
+    ) -> Offset {
         let fv = self.fork_vectors.len();
         self.fork_vectors.push(opcodes);
+        self.fork_line_number_spans.push(line_number_spans);
         Offset(fv as u16)
     }
 
@@ -887,14 +894,18 @@ impl CodegenState {
                 self.generate_expr(time)?;
                 // Stash all of main vector in a temporary buffer, then begin compilation of the forked code.
                 // Once compiled, we can create a fork vector from the new buffer, and then restore the main vector.
+                // Line number spans are per-vector, so they get the same treatment.
                 let stashed_ops = std::mem::take(&mut self.ops);
+                let stashed_spans = std::mem::take(&mut self.line_number_spans);
                 for stmt in body {
                     self.generate_stmt(stmt)?;
                 }
                 self.emit(Op::Done);
                 let forked_ops = std::mem::take(&mut self.ops);
-                let fv_id = self.add_fork_vector(forked_ops);
+                let forked_spans = std::mem::take(&mut self.line_number_spans);
+                let fv_id = self.add_fork_vector(forked_ops, forked_spans);
                 self.ops = stashed_ops;
+                self.line_number_spans = stashed_spans;
                 self.emit(Op::Fork {
                     id: id.as_ref().map(|id| self.binding_mappings[id]),
                     fv_offset: fv_id,
@@ -1078,6 +1089,7 @@ fn do_compile(parse: Parse, compile_options: CompileOptions) -> Result<Program,
         main_vector: cg_state.ops,
         fork_vectors: cg_state.fork_vectors,
         line_number_spans: cg_state.line_number_spans,
+        fork_line_number_spans: cg_state.fork_line_number_spans,
         error_operands: cg_state.error_operands,
     });
     let program = Program(program);
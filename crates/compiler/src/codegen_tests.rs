@@ -286,6 +286,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fork_line_number_spans() {
+        let program = "fork (5)\n  a = 1;\n  b = 2;\nendfork\nc = 3;";
+        let binary = compile(program, CompileOptions::default()).unwrap();
+
+        // Main vector spans cover only the main vector's statements: the fork on line 1 and the
+        // assignment to `c` on line 5, the latter starting right after the Fork opcode.
+        assert_eq!(binary.line_number_spans(), &[(0, 1), (2, 5)]);
+        assert_eq!(binary.0.fork_line_number_spans, vec![vec![(0, 2), (3, 3)]]);
+
+        // Positions are program counters, i.e. one past the opcode in question.
+        assert_eq!(binary.line_num_for_position(2), 1);
+        assert_eq!(binary.line_num_for_position(4), 5);
+        assert_eq!(binary.fork_line_num_for_position(Offset(0), 1), 2);
+        assert_eq!(binary.fork_line_num_for_position(Offset(0), 4), 3);
+    }
+
     #[test]
     fn test_and_or() {
         let program = "a = (1 && 2 || 3);";
@@ -148,6 +148,25 @@ impl Decompile {
         self.decompile_statements_sub_offset(label, 0)
     }
 
+    /// Decompile the opcodes up to (but not including) `end_position`, which must produce exactly
+    /// one expression and no statements, and return that expression.
+    fn decompile_expr_until(&mut self, end_position: usize) -> Result<Expr, DecompileError> {
+        let old_stack_len = self.expr_stack.len();
+        let old_statements_len = self.statements.len();
+        while self.position < end_position {
+            self.decompile()?;
+        }
+        if self.position != end_position
+            || self.expr_stack.len() != old_stack_len + 1
+            || self.statements.len() != old_statements_len
+        {
+            return Err(MalformedProgram(format!(
+                "expected a single expression ending at {end_position}"
+            )));
+        }
+        self.pop_expr()
+    }
+
     fn decompile_until_branch_end(
         &mut self,
         label: &Label,
@@ -175,7 +194,13 @@ impl Decompile {
     fn decompile(&mut self) -> Result<(), DecompileError> {
         let opcode = self.next()?;
 
-        let line_num = (self.program.line_num_for_position(self.position), 0);
+        let line_num = match self.fork_vector {
+            Some(fv) => self
+                .program
+                .fork_line_num_for_position(Offset(fv as u16), self.position),
+            None => self.program.line_num_for_position(self.position),
+        };
+        let line_num = (line_num, 0);
         match opcode {
             Op::If(otherwise_label, environment_width) => {
                 let cond = self.pop_expr()?;
@@ -373,8 +398,13 @@ impl Decompile {
                 let jump_label = self.find_jump(&label)?;
                 // Whether it's a break or a continue depends on whether the jump is forward or
                 // backward from the current position.
-                let jump_label_name =
-                    self.decompile_name(&jump_label.name.expect("jump label must have name"))?;
+                let Some(name) = jump_label.name else {
+                    return Err(MalformedProgram(format!(
+                        "exit to unnamed label {:?}",
+                        label
+                    )));
+                };
+                let jump_label_name = self.decompile_name(&name)?;
                 let s = if jump_label.position.0 < self.position as u16 {
                     StmtNode::Continue {
                         exit: Some(jump_label_name),
@@ -391,6 +421,13 @@ impl Decompile {
                 // Delay time should be on stack.
                 let delay_time = self.pop_expr()?;
 
+                if fv_offset.0 as usize >= self.program.fork_vectors().len() {
+                    return Err(MalformedProgram(format!(
+                        "fork vector {} does not exist",
+                        fv_offset.0
+                    )));
+                }
+
                 // Grab the fork vector at `fv_offset` and start decompilation from there, using
                 // a brand new decompiler
                 let mut fork_decompile = Decompile {
@@ -405,7 +442,7 @@ impl Decompile {
                 while fork_decompile.position < fv_len {
                     fork_decompile.decompile()?;
                 }
-                let id = id.map(|x| self.decompile_name(&x).unwrap());
+                let id = id.map(|x| self.decompile_name(&x)).transpose()?;
                 self.statements.push(Stmt::new(
                     StmtNode::Fork {
                         id,
@@ -692,25 +729,30 @@ impl Decompile {
                             // next label after it (if any), or done.
                             let next_label = opt_jump_labels[label_pos + 1];
                             label_pos += 1;
-                            let _ = self.decompile_statements_up_to(next_label)?;
-                            let assign_expr = self.pop_expr()?;
-                            let Expr::Assign { left: _, right } = assign_expr else {
+                            // Each default is `<expr>, Put(id), Pop`, and the defaults are laid out
+                            // in the same order as the scatter items, so a default can refer to
+                            // any earlier item. Decompile up to (not including) the Pop, which
+                            // sits just before the next label.
+                            let next_position = self.find_jump(next_label)?.position.0 as usize;
+                            let assign_expr =
+                                self.decompile_expr_until(next_position.saturating_sub(1))?;
+                            let Op::Pop = self.next()? else {
                                 return Err(MalformedProgram(
-                                    format!(
-                                        "expected assign for optional scatter assignment; got {:?}",
-                                        assign_expr
-                                    )
-                                    .to_string(),
+                                    "expected Pop after optional scatter assignment".to_string(),
                                 ));
                             };
-                            // We need to eat the 'pop' after us that is present in the program
-                            // stream.
-                            // It's not clear to me why we have to do this vs the way LambdaMOO
-                            // is decompiling this, but this is what works, otherwise we get
-                            // a hanging pop.
-                            let _ = self.next()?;
-
                             let id = self.decompile_name(id)?;
+                            let Expr::Assign { left, right } = assign_expr else {
+                                return Err(MalformedProgram(format!(
+                                    "expected assign for optional scatter assignment; got {:?}",
+                                    assign_expr
+                                )));
+                            };
+                            if !matches!(left.as_ref(), Expr::Id(target) if *target == id) {
+                                return Err(MalformedProgram(format!(
+                                    "optional scatter default assigns to {left:?}, not {id:?}"
+                                )));
+                            }
                             ScatterItem {
                                 kind: ScatterKind::Optional,
                                 id,
@@ -737,7 +779,7 @@ impl Decompile {
             Op::TryExcept {
                 num_excepts,
                 environment_width,
-                ..
+                end_label,
             } => {
                 let mut except_arms = Vec::with_capacity(num_excepts as usize);
                 for _ in 0..num_excepts {
@@ -757,14 +799,12 @@ impl Decompile {
                         statements: vec![],
                     });
                 }
-                // Decompile the body.
-                // Means decompiling until we hit EndExcept, so scan forward for that.
-                // TODO: make sure that this doesn't fail with nested try/excepts?
-                let (body, end_except) =
-                    self.decompile_statements_until_match(|_, o| matches!(o, Op::EndExcept(_)))?;
-                let Op::EndExcept(end_label) = end_except else {
-                    return Err(MalformedProgram("expected EndExcept".to_string()));
-                };
+                // Decompile the body, up to our own EndExcept. Nested try/excepts in the body are
+                // consumed whole by the recursive decompile, but we also match on the end label so
+                // that a stray EndExcept belonging to something else can't terminate us early.
+                let (body, _) = self.decompile_statements_until_match(
+                    |_, o| matches!(o, Op::EndExcept(l) if *l == end_label),
+                )?;
 
                 // Order of except arms is reversed in the program, so reverse it back before we
                 // decompile the except arm statements.
@@ -785,25 +825,25 @@ impl Decompile {
                         return Err(MalformedProgram("expected Pop".to_string()));
                     };
 
-                    // Scan forward until the jump, decompiling as we go.
-                    let end_label_position = self.find_jump(&end_label)?.position.0;
-                    let (statements, _) =
-                        self.decompile_statements_until_match(|position, o| {
-                            if position == (end_label_position as usize) {
-                                return true;
-                            }
-                            if let Op::Jump { label } = o {
-                                label == &end_label
-                            } else {
-                                false
-                            }
-                        })?;
-                    arm.statements = statements;
+                    // Every arm but the last ends in a jump to the end label; the last one just
+                    // runs up to it. Either way, don't consume anything past the end of the
+                    // construct, since it belongs to whatever follows.
+                    let end_label_position = self.find_jump(&end_label)?.position.0 as usize;
+                    let old_len = self.statements.len();
+                    while self.position < end_label_position {
+                        let at_end_jump = matches!(
+                            self.opcode_vector().get(self.position),
+                            Some(Op::Jump { label }) if *label == end_label
+                        );
+                        if at_end_jump {
+                            self.position += 1;
+                            break;
+                        }
+                        self.decompile()?;
+                    }
+                    arm.statements = self.statements.split_off(old_len);
                 }
 
-                // We need to rewind the position by one opcode, it seems.
-                // TODO this is not the most elegant. we're being too greedy above
-                self.position -= 1;
                 self.statements.push(Stmt::new(
                     StmtNode::TryExcept {
                         body,
@@ -889,21 +929,21 @@ impl Decompile {
                 self.push_expr(Expr::Length);
             }
             Op::IfQues(label) => {
-                let condition = self.pop_expr();
-                // Read up to the jump, decompiling as we go.
-                self.decompile_statements_up_to(&label)?;
-                // We should be findin' a jump now.
-                let Op::Jump { label: jump_label } = self.next()? else {
+                // The condition has already been evaluated onto the stack.
+                let condition = self.pop_expr()?;
+                // The consequent runs up to the jump just before the else label...
+                let else_position = self.find_jump(&label)?.position.0 as usize;
+                let consequence = self.decompile_expr_until(else_position.saturating_sub(1))?;
+                let Op::Jump { label: end_label } = self.next()? else {
                     return Err(MalformedProgram("expected Jump".to_string()));
                 };
-                let consequent = self.pop_expr();
-                // Now decompile up to and including jump_label's offset
-                self.decompile_statements_until(&jump_label)?;
-                let alternate = self.pop_expr();
+                // ... and the alternate from there up to the end label.
+                let end_position = self.find_jump(&end_label)?.position.0 as usize;
+                let alternative = self.decompile_expr_until(end_position)?;
                 let e = Expr::Cond {
-                    condition: Box::new(condition?),
-                    consequence: Box::new(consequent?),
-                    alternative: Box::new(alternate?),
+                    condition: Box::new(condition),
+                    consequence: Box::new(consequence),
+                    alternative: Box::new(alternative),
                 };
                 self.push_expr(e);
             }
@@ -929,10 +969,11 @@ impl Decompile {
                 // unreachable!("should have been handled other decompilation branches")
             }
             Op::EndCatch(_) | Op::FinallyContinue | Op::EndExcept(_) | Op::EndFinally => {
-                // Early exit; main logic is in TRY_FINALLY or CATCH etc case, above
-                // TODO: MOO has "return ptr - 2;"  -- doing something with the iteration, that
-                //   I may not be able to do with the current structure. See if I need to
-                unreachable!("should have been handled other decompilation branches")
+                // These are consumed by the TRY_FINALLY or CATCH etc cases, above, so reaching one
+                // here means the program's structure isn't what the compiler would produce.
+                return Err(MalformedProgram(format!(
+                    "unexpected {opcode:?} outside of its enclosing construct"
+                )));
             }
             Op::ImmNone => {
                 self.push_expr(Expr::Value(v_none()));
@@ -1155,6 +1196,30 @@ mod tests {
     #[test_case("try return 1; except a (E_INVARG) return 2; endtry"; "try_except")]
     #[test_case("try return 1; except a (E_INVARG) return 2; except b (E_PROPNF) return 3; endtry"; "try_except_2")]
     #[test_case("try return 1; finally return 2; endtry"; "try_finally")]
+    #[test_case(
+        "try try return 1; except (E_RANGE) return 2; endtry except a (E_INVARG) return 3; endtry";
+        "try_except_in_try_body"
+    )]
+    #[test_case(
+        "try return 1; except a (E_INVARG) try return 2; except b (E_RANGE) return 3; endtry endtry";
+        "try_except_in_last_except_arm"
+    )]
+    #[test_case(
+        "try return 1; except a (E_INVARG) try x = 2; except (ANY) x = 3; endtry return x; except b (E_PROPNF) return 4; endtry";
+        "try_except_in_first_except_arm"
+    )]
+    #[test_case(
+        "try try return 1; finally x = 2; endtry except a (ANY) return 3; endtry";
+        "try_finally_in_try_body"
+    )]
+    #[test_case(
+        "try try return 1; except (ANY) return 2; endtry finally try x = 1; finally x = 2; endtry endtry";
+        "try_except_in_try_finally"
+    )]
+    #[test_case(
+        "fork (0) try return 1; except (ANY) try return 2; except (ANY) return 3; endtry endtry endfork";
+        "nested_try_except_ending_fork"
+    )]
     #[test_case("return setadd({1,2}, 3);"; "builtin")]
     #[test_case("return {1,2,3};"; "list")]
     #[test_case("return {1,2,3,@{1,2,3}};"; "list_splice")]
@@ -1187,15 +1252,38 @@ mod tests {
     #[test_case("a[1..2] = {3,4};"; "range_set")]
     #[test_case("a[1] = {3,4};"; "index_set")]
     #[test_case("1 ? 2 | 3;"; "ternary")]
+    #[test_case("return a ? b ? 1 | 2 | c ? 3 | 4;"; "nested_ternary")]
+    #[test_case("x = (a ? 1 | 2) + (b ? 3 | 4);"; "ternaries_as_operands")]
+    #[test_case("a ? (x = 1) | (y = 2);"; "ternary_arm_assignments")]
+    #[test_case("x = (y = a) ? (y = b ? 1 | 2) | this:foo(y = 3);"; "ternary_assignment_everywhere")]
+    #[test_case("return {a ? 1 | 2, b ? `x ! ANY => 3' | 4};"; "ternary_in_list_with_catch")]
     #[test_case("x.y = 1;"; "prop_assign")]
     #[test_case("try return x; except (E_VARNF) endtry; if (x) return 1; endif"; "if_after_try")]
     #[test_case("2 ? 0 | caller_perms();"; "regression_builtin_after_ternary")]
     #[test_case(r#"options="test"; return #0.(options);"#; "sysprop expr")]
     #[test_case(r#"{?package = 5} = args;"#; "scatter optional assignment")]
     #[test_case(r#"{?package = $nothing} = args;"#; "scatter optional assignment from property")]
+    #[test_case("{a, ?b = a} = args;"; "scatter_default_from_earlier")]
+    #[test_case(
+        "{a, ?b = a, ?c = b + a, @rest} = args; return {a, b, c, rest};";
+        "scatter_chained_defaults"
+    )]
+    #[test_case("{?a = 1, b, ?c = a * b, ?d} = args;"; "scatter_mixed_defaults")]
+    #[test_case("{a, ?b = (a = 2), ?c = {a, b}} = args;"; "scatter_default_with_assignment")]
     #[test_case(r#"5; fork (5) 1; endfork 2;"#; "unlabelled fork decompile")]
     #[test_case(r#"5; fork tst (5) 1; endfork 2;"#; "labelled fork decompile")]
+    #[test_case(r#"fork (5) player:tell("hi"); endfork"#; "fork with verb call body")]
+    #[test_case(r#"fork f (0) player:tell(f); endfork return f;"#; "labelled fork with zero delay")]
+    #[test_case(
+        r#"for i in [1..3] fork (i) player:tell(i); endfork endfor"#;
+        "fork inside for loop"
+    )]
+    #[test_case(
+        r#"while (x) fork t (1) fork (2) x = t; endfork endfork x = x - 1; endwhile"#;
+        "nested forks inside while loop"
+    )]
     #[test_case(r#"[ 1 -> 2, 3 -> 4 ];"#; "map")]
+    #[test_case(r#"for v, k in ([ "b" -> 2, "a" -> 1 ]) x = k; endfor"#; "for value and key in map")]
     fn test_case_decompile_matches(prg: &str) {
         let (parse, decompiled) = parse_decompile(prg);
         assert_trees_match_recursive(&parse.stmts, &decompiled.stmts);
@@ -1340,6 +1428,13 @@ return 0 && "Automatically Added Return";
         assert_trees_match_recursive(&parse.stmts, &decompiled.stmts);
     }
 
+    #[test]
+    fn test_nested_map_index_decompile() {
+        let program = r#"x = [1 -> "a", "k" -> [#1 -> {1, 2}]]; x["k"][#1] = 5; return x[1];"#;
+        let (parse, decompiled) = parse_decompile(program);
+        assert_trees_match_recursive(&parse.stmts, &decompiled.stmts);
+    }
+
     #[test]
     fn test_for_range_comprehension() {
         let program = r#"return { x * 2 for x in [1..3] };"#;
@@ -0,0 +1,238 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Randomized robustness checks for the decompiler: generate random (but valid) MOO programs,
+//! compile them, and make sure they decompile back to the same tree without panicking. Also
+//! regression tests for malformed programs that used to panic the decompiler.
+
+#[cfg(test)]
+mod tests {
+    use crate::CompileOptions;
+    use crate::ast::assert_trees_match_recursive;
+    use crate::codegen::compile;
+    use crate::decompile::{DecompileError, program_to_tree};
+    use crate::parse::parse_program;
+    use crate::unparse::{annotate_line_numbers, unparse};
+    use moor_common::program::labels::{Label, Offset};
+    use moor_common::program::names::Name;
+    use moor_common::program::opcode::Op;
+    use moor_common::program::program::Program;
+    use std::sync::Arc;
+
+    const SEEDS: u64 = 500;
+    const MAX_DEPTH: usize = 3;
+    const VARS: &[&str] = &["a", "b", "c", "d"];
+    const STRINGS: &[&str] = &["\"\"", "\"hello\"", "\"a b c\"", "\"x\\\"y\""];
+    const BINOPS: &[&str] = &["+", "-", "*", "==", "!=", "<", ">=", "&&", "||", "in"];
+
+    /// Small xorshift generator, so failures reproduce from the seed alone.
+    struct Rng(u64);
+
+    impl Rng {
+        fn new(seed: u64) -> Self {
+            Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+        }
+
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+            choices[self.below(choices.len())]
+        }
+    }
+
+    struct Generator {
+        rng: Rng,
+        /// How many loops enclose the statement being generated; `break`/`continue` are only
+        /// produced when this is non-zero.
+        loop_depth: usize,
+    }
+
+    impl Generator {
+        fn expr(&mut self, depth: usize) -> String {
+            let leaf = depth >= MAX_DEPTH || self.rng.below(3) == 0;
+            if leaf {
+                return match self.rng.below(3) {
+                    0 => self.rng.below(1000).to_string(),
+                    1 => self.rng.pick(STRINGS).to_string(),
+                    _ => self.rng.pick(VARS).to_string(),
+                };
+            }
+            match self.rng.below(8) {
+                0 | 1 => {
+                    let op = self.rng.pick(BINOPS);
+                    format!("({} {} {})", self.expr(depth + 1), op, self.expr(depth + 1))
+                }
+                2 => {
+                    let elements: Vec<_> = (0..self.rng.below(3))
+                        .map(|_| self.expr(depth + 1))
+                        .collect();
+                    format!("{{{}}}", elements.join(", "))
+                }
+                3 => format!("length({})", self.expr(depth + 1)),
+                4 => format!("{}[{}]", self.rng.pick(VARS), self.expr(depth + 1)),
+                5 => format!("{}.name", self.rng.pick(VARS)),
+                6 => format!("{}:tell({})", self.rng.pick(VARS), self.expr(depth + 1)),
+                _ => format!("`{} ! ANY => 0'", self.expr(depth + 1)),
+            }
+        }
+
+        fn block(&mut self, depth: usize) -> String {
+            let stmts: Vec<_> = (0..1 + self.rng.below(3))
+                .map(|_| self.stmt(depth))
+                .collect();
+            stmts.join(" ")
+        }
+
+        fn stmt(&mut self, depth: usize) -> String {
+            let simple = depth >= MAX_DEPTH || self.rng.below(2) == 0;
+            if simple {
+                let choice = self.rng.below(if self.loop_depth > 0 { 5 } else { 3 });
+                return match choice {
+                    0 => format!("{} = {};", self.rng.pick(VARS), self.expr(0)),
+                    1 => format!("{};", self.expr(0)),
+                    2 => format!("return {};", self.expr(0)),
+                    3 => "break;".to_string(),
+                    _ => "continue;".to_string(),
+                };
+            }
+            // try is only generated at the outermost level; nesting is covered separately.
+            let choice = self.rng.below(if depth == 0 { 6 } else { 4 });
+            match choice {
+                0 => {
+                    let mut s = format!("if ({}) {}", self.expr(0), self.block(depth + 1));
+                    for _ in 0..self.rng.below(2) {
+                        s += &format!(" elseif ({}) {}", self.expr(0), self.block(depth + 1));
+                    }
+                    if self.rng.below(2) == 0 {
+                        s += &format!(" else {}", self.block(depth + 1));
+                    }
+                    s + " endif"
+                }
+                1 => {
+                    let cond = self.expr(0);
+                    let body = self.loop_body(depth);
+                    format!("while ({cond}) {body} endwhile")
+                }
+                2 => {
+                    let var = format!("i{depth}");
+                    let seq = self.expr(0);
+                    let body = self.loop_body(depth);
+                    format!("for {var} in ({seq}) {body} endfor")
+                }
+                3 => {
+                    let var = format!("i{depth}");
+                    let (from, to) = (self.expr(0), self.expr(0));
+                    let body = self.loop_body(depth);
+                    format!("for {var} in [{from}..{to}] {body} endfor")
+                }
+                4 => format!(
+                    "try {} except e (ANY) {} endtry",
+                    self.block(depth + 1),
+                    self.block(depth + 1)
+                ),
+                _ => format!(
+                    "try {} finally {} endtry",
+                    self.block(depth + 1),
+                    self.block(depth + 1)
+                ),
+            }
+        }
+
+        fn loop_body(&mut self, depth: usize) -> String {
+            self.loop_depth += 1;
+            let body = self.block(depth + 1);
+            self.loop_depth -= 1;
+            body
+        }
+    }
+
+    fn random_program(seed: u64) -> String {
+        let mut generator = Generator {
+            rng: Rng::new(seed),
+            loop_depth: 0,
+        };
+        generator.block(0)
+    }
+
+    #[test]
+    fn test_random_programs_round_trip() {
+        for seed in 0..SEEDS {
+            let program_text = random_program(seed);
+            let parsed = parse_program(&program_text, CompileOptions::default())
+                .unwrap_or_else(|e| panic!("seed {seed}: {program_text}: parse failed: {e:?}"));
+            let binary = compile(&program_text, CompileOptions::default())
+                .unwrap_or_else(|e| panic!("seed {seed}: {program_text}: compile failed: {e:?}"));
+            let mut decompiled = program_to_tree(&binary)
+                .unwrap_or_else(|e| panic!("seed {seed}: {program_text}: decompile failed: {e}"));
+            annotate_line_numbers(1, &mut decompiled.stmts);
+            assert_trees_match_recursive(&parsed.stmts, &decompiled.stmts);
+            unparse(&decompiled)
+                .unwrap_or_else(|e| panic!("seed {seed}: {program_text}: unparse failed: {e}"));
+        }
+    }
+
+    /// Compile `program_text`, then let `mutate` corrupt the result before decompiling it.
+    fn decompile_mutated(
+        program_text: &str,
+        mutate: impl FnOnce(&mut Vec<Op>),
+    ) -> Result<(), DecompileError> {
+        let binary = compile(program_text, CompileOptions::default()).unwrap();
+        let mut inner = (*binary.0).clone();
+        mutate(&mut inner.main_vector);
+        program_to_tree(&Program(Arc::new(inner))).map(|_| ())
+    }
+
+    #[test]
+    fn test_stray_end_opcodes_are_errors() {
+        for op in [Op::EndFinally, Op::FinallyContinue, Op::EndCatch(Label(0))] {
+            let result = decompile_mutated("return 1;", |main| main.insert(0, op.clone()));
+            assert!(
+                matches!(result, Err(DecompileError::MalformedProgram(_))),
+                "{op:?}: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fork_with_missing_vector_is_error() {
+        let result = decompile_mutated("fork (5) return 1; endfork", |main| {
+            for op in main.iter_mut() {
+                if let Op::Fork { fv_offset, .. } = op {
+                    *fv_offset = Offset(42);
+                }
+            }
+        });
+        assert!(matches!(result, Err(DecompileError::MalformedProgram(_))));
+    }
+
+    #[test]
+    fn test_fork_with_unknown_task_id_is_error() {
+        let result = decompile_mutated("fork tid (5) return 1; endfork", |main| {
+            for op in main.iter_mut() {
+                if let Op::Fork { id, .. } = op {
+                    *id = Some(Name(999, 0));
+                }
+            }
+        });
+        assert!(matches!(result, Err(DecompileError::NameNotFound(_))));
+    }
+}
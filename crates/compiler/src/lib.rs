@@ -22,6 +22,7 @@ mod parse;
 mod unparse;
 
 mod codegen_tests;
+mod decompile_fuzz_tests;
 mod objdef;
 mod var_scope;
 
@@ -1076,6 +1076,9 @@ mod tests {
     #[test_case(r#"verb[1..5 - 1];"#; "range precedence")]
     #[test_case(r#"1 && ((a = 5) && 3);"#; "and/or precedence")]
     #[test_case(r#"n + 10 in a;"#; "in precedence")]
+    #[test_case(r#"x = [1 -> "a", "k" -> [#1 -> {1, 2}]];
+           x["k"][#1] = 5;
+           return x[1];"#; "nested map literal and indexing")]
     pub fn compare_parse_roundtrip(original: &str) {
         let stripped = unindent(original);
         let result = parse_and_unparse(&stripped).unwrap();
@@ -125,6 +125,15 @@ pub struct Args {
     )]
     pub num_io_threads: i32,
 
+    #[arg(
+        long,
+        help = "Publish the database's lifecycle events (objects created and recycled, verbs and \
+          properties added and removed) on the events socket's `lifecycle` topic, for external \
+          indexers",
+        default_value = "false"
+    )]
+    pub publish_lifecycle_events: bool,
+
     #[arg(long, help = "Enable debug logging", default_value = "false")]
     pub debug: bool,
 }
@@ -205,6 +214,30 @@ pub struct FeatureArgs {
                 Note that this is the default behaviour in LambdaMOO."
     )]
     pub persistent_tasks: Option<bool>,
+
+    #[arg(
+        long,
+        help = "When a command names an object that can't be found, suggest the names of nearby objects it may have been a typo for"
+    )]
+    pub fuzzy_match_suggestions: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Log every set_task_perms() call, with caller, new perms, verb and line, under the moor::audit::task_perms tracing target"
+    )]
+    pub audit_task_perms: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Store the original source text of verbs alongside their compiled form, so verb_code() returns it verbatim, comments included"
+    )]
+    pub preserve_verb_source: Option<bool>,
+
+    #[arg(
+        long,
+        help = "Abort a task when one of its builtin function calls takes longer than this many milliseconds (default 5000). Blocking I/O is cut off at the limit; 0 disables it"
+    )]
+    pub builtin_duration_limit_ms: Option<u64>,
 }
 
 impl FeatureArgs {
@@ -245,6 +278,18 @@ impl FeatureArgs {
         if let Some(args) = self.list_comprehensions {
             config.list_comprehensions = args;
         }
+        if let Some(args) = self.fuzzy_match_suggestions {
+            config.fuzzy_match_suggestions = args;
+        }
+        if let Some(args) = self.audit_task_perms {
+            config.audit_task_perms = args;
+        }
+        if let Some(args) = self.preserve_verb_source {
+            config.preserve_verb_source = args;
+        }
+        if let Some(args) = self.builtin_duration_limit_ms {
+            config.builtin_duration_limit_ms = args;
+        }
     }
 }
 
@@ -369,6 +414,31 @@ pub struct DatabaseArgs {
           If they are still there, untouched, by the next eviction cycle, they will be removed."
     )]
     pub default_eviction_threshold: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "tombstone-retention-seconds",
+        help = "Keep recycled objects as tombstones for this many seconds, during which a wizard can \
+          bring them back with undelete(). By default recycled objects are destroyed at once."
+    )]
+    pub tombstone_retention: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "backup-directory",
+        help = "Directory that the backup() builtin writes into. Without it, backup() is refused.",
+        value_hint = ValueHint::DirPath
+    )]
+    pub backup_directory: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "restore",
+        help = "Path to a backup (made with the backup() builtin) to load into the database. \
+          Only used when the database is being created.",
+        value_hint = ValueHint::FilePath
+    )]
+    pub restore: Option<PathBuf>,
     // TODO: per table options
 }
 
@@ -381,6 +451,12 @@ impl DatabaseArgs {
         if let Some(args) = self.default_eviction_threshold {
             config.default_eviction_threshold = Some(args);
         }
+        if let Some(args) = self.tombstone_retention {
+            config.tombstone_retention = Some(Duration::from_secs(args));
+        }
+        if let Some(args) = self.backup_directory.as_ref() {
+            config.backup_directory = Some(args.clone());
+        }
     }
 }
 
@@ -16,7 +16,7 @@ use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 
 use moor_common::tasks::SessionError;
-use moor_var::Obj;
+use moor_var::{Obj, Symbol, Var};
 use rpc_common::RpcMessageError;
 
 pub const CONNECTION_TIMEOUT_DURATION: Duration = Duration::from_secs(30);
@@ -55,6 +55,16 @@ pub trait ConnectionsDB {
 
     fn client_ids_for(&self, player: Obj) -> Result<Vec<Uuid>, SessionError>;
 
+    /// Record attributes of the given client's connection, as reported by its host.
+    fn set_client_attributes(
+        &self,
+        client_id: Uuid,
+        attributes: Vec<(Symbol, Var)>,
+    ) -> Result<(), eyre::Error>;
+
+    /// Return the attributes recorded for the player's most recent connection.
+    fn client_attributes_for(&self, player: Obj) -> Result<Vec<(Symbol, Var)>, SessionError>;
+
     /// Return all connection objects (player or not)
     fn connections(&self) -> Vec<Obj>;
 
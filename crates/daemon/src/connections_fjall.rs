@@ -17,7 +17,7 @@ use byteview::ByteView;
 use eyre::{Error, bail};
 use fjall::{Config, Keyspace, PartitionCreateOptions, PartitionHandle};
 use moor_common::tasks::SessionError;
-use moor_var::{AsByteBuffer, BINCODE_CONFIG, Obj};
+use moor_var::{AsByteBuffer, BINCODE_CONFIG, Obj, Symbol, Var};
 use rpc_common::RpcMessageError;
 use std::collections::HashMap;
 use std::path::Path;
@@ -56,6 +56,9 @@ struct Inner {
 
     client_players: HashMap<Uuid, Obj>,
     player_clients: HashMap<Obj, ConnectionsRecords>,
+    /// Attributes reported by each client's host, e.g. its window size. These only mean anything
+    /// while the host is connected, so they aren't persisted.
+    client_attributes: HashMap<Uuid, Vec<(Symbol, Var)>>,
 }
 
 impl ConnectionsFjall {
@@ -118,6 +121,7 @@ impl ConnectionsFjall {
                 connection_id_sequence_table: sequences_partition,
                 client_players,
                 player_clients,
+                client_attributes: HashMap::new(),
             })),
         }
     }
@@ -304,6 +308,7 @@ impl ConnectionsDB for ConnectionsFjall {
         }
 
         for (player_id, client_id) in to_remove {
+            inner.client_attributes.remove(&Uuid::from_u128(client_id));
             let oid_bytes = player_id.as_bytes().unwrap();
             let mut connections_record = inner.player_clients.get(&player_id).unwrap().clone();
             connections_record
@@ -385,6 +390,44 @@ impl ConnectionsDB for ConnectionsFjall {
         Ok(client_ids)
     }
 
+    fn set_client_attributes(
+        &self,
+        client_id: Uuid,
+        attributes: Vec<(Symbol, Var)>,
+    ) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.client_players.contains_key(&client_id) {
+            bail!("No connection found for client {:?}", client_id);
+        }
+        let recorded = inner.client_attributes.entry(client_id).or_default();
+        for (key, value) in attributes {
+            match recorded.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, v)) => *v = value,
+                None => recorded.push((key, value)),
+            }
+        }
+        Ok(())
+    }
+
+    fn client_attributes_for(&self, player: Obj) -> Result<Vec<(Symbol, Var)>, SessionError> {
+        let inner = self.inner.lock().unwrap();
+        let Some(connections_record) = inner.player_clients.get(&player) else {
+            return Err(SessionError::NoConnectionForPlayer(player));
+        };
+        let Some(most_recent) = connections_record
+            .connections
+            .iter()
+            .max_by_key(|cr| cr.connected_time)
+        else {
+            return Err(SessionError::NoConnectionForPlayer(player));
+        };
+        Ok(inner
+            .client_attributes
+            .get(&Uuid::from_u128(most_recent.client_id))
+            .cloned()
+            .unwrap_or_default())
+    }
+
     fn connections(&self) -> Vec<Obj> {
         let inner = self.inner.lock().unwrap();
         inner
@@ -405,6 +448,7 @@ impl ConnectionsDB for ConnectionsFjall {
         let Some(player_id) = inner.client_players.remove(&client_id) else {
             bail!("No connection to prune found for {:?}", client_id);
         };
+        inner.client_attributes.remove(&client_id);
         if inner
             .client_player_table
             .remove(client_id.as_u128().to_le_bytes())
@@ -442,7 +486,7 @@ impl ConnectionsDB for ConnectionsFjall {
 mod tests {
     use std::sync::Arc;
 
-    use moor_var::Obj;
+    use moor_var::{Obj, Symbol, v_int};
 
     use crate::connections::ConnectionsDB;
     use crate::connections_fjall::ConnectionsFjall;
@@ -577,4 +621,40 @@ mod tests {
         db.remove_client_connection(client_id1).unwrap();
         assert_eq!(db.connections(), vec![]);
     }
+
+    #[test]
+    fn client_attributes() {
+        let db = Arc::new(ConnectionsFjall::open(None));
+        let client_id = uuid::Uuid::new_v4();
+        let ob = db
+            .new_connection(client_id, "localhost".to_string(), None)
+            .unwrap();
+        assert_eq!(db.client_attributes_for(ob).unwrap(), vec![]);
+
+        let width = Symbol::mk("window-width");
+        let height = Symbol::mk("window-height");
+        db.set_client_attributes(client_id, vec![(width, v_int(80)), (height, v_int(24))])
+            .unwrap();
+        // A resize replaces what was there.
+        db.set_client_attributes(client_id, vec![(width, v_int(132)), (height, v_int(50))])
+            .unwrap();
+        assert_eq!(
+            db.client_attributes_for(ob).unwrap(),
+            vec![(width, v_int(132)), (height, v_int(50))]
+        );
+
+        // They follow the client through login, and go with it.
+        let player = Obj::mk_id(1);
+        db.update_client_connection(ob, player).unwrap();
+        assert_eq!(
+            db.client_attributes_for(player).unwrap(),
+            vec![(width, v_int(132)), (height, v_int(50))]
+        );
+        db.remove_client_connection(client_id).unwrap();
+        assert!(db.client_attributes_for(player).is_err());
+        assert!(
+            db.set_client_attributes(client_id, vec![(width, v_int(80))])
+                .is_err()
+        );
+    }
 }
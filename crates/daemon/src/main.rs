@@ -13,6 +13,8 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 
 use crate::args::Args;
 use crate::rpc_server::RpcServer;
@@ -192,6 +194,21 @@ fn main() -> Result<(), Report> {
     let database = Box::new(database);
     info!(path = ?args.db_args.db, "Opened database");
 
+    if let Some(backup_path) = args.db_args.restore.as_ref() {
+        if !freshly_made {
+            info!("Database already exists, skipping restore");
+        } else {
+            info!("Restoring backup from {:?}", backup_path);
+            match database.restore(backup_path) {
+                Ok(tuples) => info!(tuples, "Restored backup"),
+                Err(e) => {
+                    error!(?e, "Unable to restore backup");
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
     if let Some(import_path) = config.import_export_config.input_path.as_ref() {
         // If the database already existed, do not try to import the textdump...
         if !freshly_made {
@@ -234,6 +251,11 @@ fn main() -> Result<(), Report> {
         }
     }
 
+    // Followed only from here, so that a fresh import isn't published object by object.
+    let lifecycle_events = args
+        .publish_lifecycle_events
+        .then(|| database.lifecycle_events());
+
     let tasks_db: Box<dyn TasksDb> = if config.features_config.persistent_tasks {
         Box::new(tasks_fjall::FjallTasksDB::open(&args.tasks_db).0)
     } else {
@@ -326,6 +348,37 @@ fn main() -> Result<(), Report> {
         info!("Checkpointing disabled.");
     }
 
+    // SIGHUP reloads the server options (tick & seconds limits, etc.) without disturbing
+    // connections or running tasks.
+    let reload_requested = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGHUP, reload_requested.clone())?;
+    let reload_kill_switch = kill_switch.clone();
+    let reload_scheduler_client = scheduler_client.clone();
+    std::thread::Builder::new()
+        .name("moor-reload".to_string())
+        .spawn(move || {
+            loop {
+                if reload_kill_switch.load(std::sync::atomic::Ordering::Relaxed) {
+                    break;
+                }
+                if reload_requested.swap(false, std::sync::atomic::Ordering::Relaxed) {
+                    info!("SIGHUP received; reloading server options");
+                    if let Err(e) = reload_scheduler_client.reload_server_options() {
+                        error!(?e, "Unable to reload server options");
+                    }
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        })?;
+
+    if let Some(lifecycle_events) = lifecycle_events {
+        info!("Publishing lifecycle events");
+        let lifecycle_rpc_server = rpc_server.clone();
+        std::thread::Builder::new()
+            .name("moor-lifecycle".to_string())
+            .spawn(move || lifecycle_rpc_server.lifecycle_events_loop(lifecycle_events))?;
+    }
+
     let rpc_loop_scheduler_client = scheduler_client.clone();
     let rpc_listen = args.rpc_listen.clone();
     let rpc_loop_thread = std::thread::Builder::new()
@@ -19,9 +19,9 @@ use uuid::Uuid;
 
 use moor_common::tasks::NarrativeEvent;
 use moor_common::tasks::{Session, SessionError, SessionFactory};
-use moor_var::Obj;
+use moor_var::{Obj, Symbol, Var};
 
-use crate::rpc_server::RpcServer;
+use crate::rpc_server::{DELIVERY_REPORT_TIMEOUT, RpcServer};
 
 /// A "session" that runs over the RPC system.
 pub struct RpcSession {
@@ -37,13 +37,25 @@ pub struct RpcSession {
 
 pub(crate) enum SessionActions {
     PublishNarrativeEvents(Vec<(Obj, Box<NarrativeEvent>)>),
+    PublishConfirmedNarrativeEvent(
+        Uuid,
+        Obj,
+        Box<NarrativeEvent>,
+        oneshot::Sender<Result<bool, SessionError>>,
+    ),
     RequestClientInput(Uuid, Obj, Uuid),
     SendSystemMessage(Uuid, Obj, String),
     RequestConnectionName(Uuid, Obj, oneshot::Sender<Result<String, SessionError>>),
     Disconnect(Uuid, Obj),
+    SetConnectionOption(Uuid, Obj, Symbol, Var),
     RequestConnectedPlayers(Uuid, oneshot::Sender<Result<Vec<Obj>, SessionError>>),
     RequestConnectedSeconds(Uuid, Obj, oneshot::Sender<Result<f64, SessionError>>),
     RequestIdleSeconds(Uuid, Obj, oneshot::Sender<Result<f64, SessionError>>),
+    RequestConnectionOptions(
+        Uuid,
+        Obj,
+        oneshot::Sender<Result<Vec<(Symbol, Var)>, SessionError>>,
+    ),
 }
 
 impl RpcSession {
@@ -100,6 +112,28 @@ impl Session for RpcSession {
         Ok(())
     }
 
+    fn send_event_confirmed(
+        &self,
+        player: Obj,
+        event: Box<NarrativeEvent>,
+    ) -> Result<bool, SessionError> {
+        let (tx, rx) = oneshot::channel();
+        self.send
+            .send(SessionActions::PublishConfirmedNarrativeEvent(
+                self.client_id,
+                player,
+                event,
+                tx,
+            ))
+            .map_err(|_e| SessionError::DeliveryError)?;
+        match rx.recv_timeout(DELIVERY_REPORT_TIMEOUT) {
+            Ok(result) => result,
+            // Nobody answered in time, so as far as we can tell it didn't get there.
+            Err(oneshot::RecvTimeoutError::Timeout) => Ok(false),
+            Err(oneshot::RecvTimeoutError::Disconnected) => Err(SessionError::DeliveryError),
+        }
+    }
+
     fn send_system_msg(&self, player: Obj, msg: &str) -> Result<(), SessionError> {
         self.send
             .send(SessionActions::SendSystemMessage(
@@ -144,6 +178,23 @@ impl Session for RpcSession {
         Ok(())
     }
 
+    fn set_connection_option(
+        &self,
+        player: Obj,
+        option: Symbol,
+        value: Var,
+    ) -> Result<(), SessionError> {
+        self.send
+            .send(SessionActions::SetConnectionOption(
+                self.client_id,
+                player,
+                option,
+                value,
+            ))
+            .map_err(|_e| SessionError::DeliveryError)?;
+        Ok(())
+    }
+
     fn connected_players(&self) -> Result<Vec<Obj>, SessionError> {
         let (tx, rx) = oneshot::channel();
         self.send
@@ -175,6 +226,18 @@ impl Session for RpcSession {
             .map_err(|_e| SessionError::DeliveryError)?;
         rx.recv().map_err(|_e| SessionError::DeliveryError)?
     }
+
+    fn connection_options(&self, player: Obj) -> Result<Vec<(Symbol, Var)>, SessionError> {
+        let (tx, rx) = oneshot::channel();
+        self.send
+            .send(SessionActions::RequestConnectionOptions(
+                self.client_id,
+                player,
+                tx,
+            ))
+            .map_err(|_e| SessionError::DeliveryError)?;
+        rx.recv().map_err(|_e| SessionError::DeliveryError)?
+    }
 }
 
 impl SessionFactory for RpcServer {
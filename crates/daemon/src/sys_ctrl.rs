@@ -13,7 +13,7 @@
 
 use crate::rpc_server::RpcServer;
 use moor_common::tasks::SessionError::DeliveryError;
-use moor_common::tasks::SystemControl;
+use moor_common::tasks::{ListenerTls, SystemControl};
 use moor_var::Obj;
 use rpc_common::{HOST_BROADCAST_TOPIC, HostBroadcastEvent, HostType};
 use std::sync::atomic::Ordering;
@@ -32,6 +32,7 @@ impl SystemControl for RpcServer {
         host_type: &str,
         port: u16,
         print_messages: bool,
+        tls: Option<ListenerTls>,
     ) -> Result<(), moor_var::Error> {
         let host_type = match host_type {
             "tcp" => HostType::TCP,
@@ -47,6 +48,7 @@ impl SystemControl for RpcServer {
             host_type,
             port,
             print_messages,
+            tls,
         };
 
         let event_bytes = bincode::encode_to_vec(event, bincode::config::standard()).unwrap();
@@ -111,7 +111,7 @@ mod tests {
     use moor_common::tasks::NoopClientSession;
     use moor_kernel::tasks::{ServerOptions, TaskStart, TasksDb};
     use moor_kernel::{SuspendedTask, Task, WakeCondition};
-    use moor_var::SYSTEM_OBJECT;
+    use moor_var::{SYSTEM_OBJECT, Symbol};
     use std::sync::Arc;
     use std::sync::atomic::AtomicBool;
 
@@ -144,6 +144,12 @@ mod tests {
             fg_seconds: 0,
             fg_ticks: 0,
             max_stack_depth: 0,
+            mssp: vec![],
+            idle_threshold: None,
+            idle_hook: Symbol::mk("idle_notification"),
+            command_aliases: vec![],
+            player_prototype: None,
+            player_starter_properties: vec![],
         };
 
         /*
@@ -202,6 +208,12 @@ mod tests {
                 fg_seconds: 0,
                 fg_ticks: 0,
                 max_stack_depth: 0,
+                mssp: vec![],
+                idle_threshold: None,
+                idle_hook: Symbol::mk("idle_notification"),
+                command_aliases: vec![],
+                player_prototype: None,
+                player_starter_properties: vec![],
             };
 
             let task = Task::new(
@@ -259,6 +271,12 @@ mod tests {
                 fg_seconds: 0,
                 fg_ticks: 0,
                 max_stack_depth: 0,
+                mssp: vec![],
+                idle_threshold: None,
+                idle_hook: Symbol::mk("idle_notification"),
+                command_aliases: vec![],
+                player_prototype: None,
+                player_starter_properties: vec![],
             };
 
             let task = Task::new(
@@ -14,11 +14,14 @@
 #![recursion_limit = "256"]
 
 use criterion::{Criterion, Throughput, criterion_group, criterion_main};
-use moor_common::model::{CommitResult, PropFlag, WorldStateSource};
+use moor_common::model::{CommitResult, PropFlag, VerbArgsSpec, VerbFlag, WorldStateSource};
+use moor_common::program::ProgramType;
+use moor_common::program::program::Program;
 use moor_common::util::BitEnum;
 use moor_db::{DatabaseConfig, TxDB};
 use moor_var::{NOTHING, SYSTEM_OBJECT, Symbol, v_int, v_list_iter};
 use rand::prelude::SliceRandom;
+use std::hint::black_box;
 use std::time::Duration;
 
 fn create_db() -> TxDB {
@@ -98,5 +101,120 @@ fn commit_latency(c: &mut Criterion) {
         })
     });
 }
-criterion_group!(benches, commit_latency);
+/// The attribute fetches an `@examine` does, one call each versus a single `object_summary`.
+fn examine_fetches(c: &mut Criterion) {
+    let db = create_db();
+    let mut tx = db.new_world_state().unwrap();
+    let obj = tx
+        .create_object(
+            &SYSTEM_OBJECT,
+            &SYSTEM_OBJECT,
+            &SYSTEM_OBJECT,
+            BitEnum::all(),
+        )
+        .unwrap();
+    for i in 0..10 {
+        tx.define_property(
+            &SYSTEM_OBJECT,
+            &obj,
+            &obj,
+            Symbol::mk(&format!("prop{i}")),
+            &SYSTEM_OBJECT,
+            PropFlag::rw(),
+            Some(v_int(i)),
+        )
+        .unwrap();
+    }
+    assert_eq!(tx.commit().unwrap(), CommitResult::Success);
+
+    let mut group = c.benchmark_group("examine_fetches");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("individual", |b| {
+        let tx = db.new_world_state().unwrap();
+        b.iter(|| {
+            black_box(tx.name_of(&SYSTEM_OBJECT, &obj).unwrap());
+            black_box(tx.owner_of(&obj).unwrap());
+            black_box(tx.parent_of(&SYSTEM_OBJECT, &obj).unwrap());
+            black_box(tx.location_of(&SYSTEM_OBJECT, &obj).unwrap());
+            black_box(tx.flags_of(&obj).unwrap());
+            black_box(tx.verbs(&SYSTEM_OBJECT, &obj).unwrap());
+            black_box(tx.properties(&SYSTEM_OBJECT, &obj).unwrap());
+        });
+    });
+
+    group.bench_function("summary", |b| {
+        let tx = db.new_world_state().unwrap();
+        b.iter(|| black_box(tx.object_summary(&SYSTEM_OBJECT, &obj).unwrap()));
+    });
+}
+
+/// Name resolution against an object with many verbs and properties, looked up with names in
+/// varying case, as command matching and property access do.
+fn name_lookups(c: &mut Criterion) {
+    let db = create_db();
+    let mut tx = db.new_world_state().unwrap();
+    let obj = tx
+        .create_object(
+            &SYSTEM_OBJECT,
+            &SYSTEM_OBJECT,
+            &SYSTEM_OBJECT,
+            BitEnum::all(),
+        )
+        .unwrap();
+    for i in 0..50 {
+        tx.define_property(
+            &SYSTEM_OBJECT,
+            &obj,
+            &obj,
+            Symbol::mk(&format!("Prop{i}")),
+            &SYSTEM_OBJECT,
+            PropFlag::rw(),
+            Some(v_int(i)),
+        )
+        .unwrap();
+        tx.add_verb(
+            &SYSTEM_OBJECT,
+            &obj,
+            vec![
+                Symbol::mk(&format!("verb{i}")),
+                Symbol::mk(&format!("v{i}*erb")),
+            ],
+            &SYSTEM_OBJECT,
+            VerbFlag::rxd(),
+            VerbArgsSpec::this_none_this(),
+            ProgramType::MooR(Program::new()),
+        )
+        .unwrap();
+    }
+    assert_eq!(tx.commit().unwrap(), CommitResult::Success);
+
+    let prop_names: Vec<_> = (0..50).map(|i| Symbol::mk(&format!("PROP{i}"))).collect();
+    let verb_names: Vec<_> = (0..50).map(|i| Symbol::mk(&format!("V{i}e"))).collect();
+
+    let mut group = c.benchmark_group("name_lookups");
+    group.throughput(Throughput::Elements(50));
+
+    group.bench_function("properties", |b| {
+        let tx = db.new_world_state().unwrap();
+        let propdefs = tx.properties(&SYSTEM_OBJECT, &obj).unwrap();
+        b.iter(|| {
+            for name in &prop_names {
+                black_box(propdefs.find_first_named(*name).unwrap());
+            }
+        });
+    });
+
+    group.bench_function("verbs", |b| {
+        let tx = db.new_world_state().unwrap();
+        let verbdefs = tx.verbs(&SYSTEM_OBJECT, &obj).unwrap();
+        b.iter(|| {
+            for name in &verb_names {
+                black_box(verbdefs.find_first_named(*name).unwrap());
+            }
+        });
+    });
+}
+
+criterion_group!(benches, commit_latency, examine_fetches, name_lookups);
 criterion_main!(benches);
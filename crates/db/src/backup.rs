@@ -0,0 +1,158 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Point-in-time backups.
+//! A backup is every tuple committed as of the moment it is requested, along with the named
+//! sequences, so restoring one is a replay of its tuples into a fresh database as a single change
+//! log entry. Unlike a textdump, it is an exact copy of what is stored. The commit thread only
+//! queues a request for a view of each relation; the views are read and written out by whoever
+//! asked for the backup, so commits don't wait on it.
+//!
+//! The file is the magic bytes, followed by each tuple as a relation byte, then the domain and
+//! codomain, each prefixed by its length as a little-endian u32. The named sequences follow, each
+//! as the `NAMED_SEQUENCE` byte, then the name and the value as little-endian i64 bytes, prefixed
+//! the same way.
+
+use crate::change_log::{Change, CommitLog, LoggedRelation};
+use crate::tx_management::{Error, TupleSnapshot};
+use std::io::{self, Read, Write};
+
+const BACKUP_MAGIC: &[u8; 8] = b"MOORBAK1";
+
+const RELATIONS: [LoggedRelation; 15] = [
+    LoggedRelation::ObjectLocation,
+    LoggedRelation::ObjectContents,
+    LoggedRelation::ObjectFlags,
+    LoggedRelation::ObjectParent,
+    LoggedRelation::ObjectChildren,
+    LoggedRelation::ObjectOwner,
+    LoggedRelation::ObjectName,
+    LoggedRelation::ObjectVerbdefs,
+    LoggedRelation::ObjectVerbs,
+    LoggedRelation::ObjectPropdefs,
+    LoggedRelation::ObjectPropvalues,
+    LoggedRelation::ObjectPropflags,
+    LoggedRelation::ObjectFreelist,
+    LoggedRelation::ObjectTombstones,
+    LoggedRelation::ObjectGenerations,
+];
+
+/// Marks a named sequence, in place of a relation byte.
+const NAMED_SEQUENCE: u8 = 0xff;
+
+/// A backup waiting to be written out: a view of each relation as of the same commit, and the
+/// values of the named sequences at that point.
+pub(crate) struct Snapshot {
+    pub(crate) relations: Vec<(LoggedRelation, oneshot::Receiver<Box<dyn TupleSnapshot>>)>,
+    pub(crate) named_sequences: Vec<(String, i64)>,
+}
+
+fn write_bytes<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "tuple too large to back up"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+fn write_tuple<W: Write>(
+    writer: &mut W,
+    relation: u8,
+    domain: &[u8],
+    codomain: &[u8],
+) -> io::Result<()> {
+    writer.write_all(&[relation])?;
+    write_bytes(writer, domain)?;
+    write_bytes(writer, codomain)
+}
+
+fn read_bytes<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Write out a snapshot, as returned by `WorldStateTransaction::snapshot`, reading each relation's
+/// view as it goes. Returns the number of tuples written.
+pub(crate) fn write_backup<W: Write>(writer: &mut W, snapshot: Snapshot) -> io::Result<usize> {
+    writer.write_all(BACKUP_MAGIC)?;
+    let mut count = 0;
+    for (logged, view) in snapshot.relations {
+        let relation = RELATIONS
+            .iter()
+            .position(|r| *r == logged)
+            .expect("Unknown relation");
+        let view = view.recv().map_err(io::Error::other)?;
+        view.for_each(&mut |domain, codomain| {
+            write_tuple(writer, relation as u8, domain, codomain)
+                .map_err(|e| Error::StorageFailure(e.to_string()))?;
+            count += 1;
+            Ok(())
+        })
+        .map_err(io::Error::other)?;
+    }
+    for (name, value) in &snapshot.named_sequences {
+        writer.write_all(&[NAMED_SEQUENCE])?;
+        write_bytes(writer, name.as_bytes())?;
+        write_bytes(writer, &value.to_le_bytes())?;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Read a backup back in, as a change log entry which will recreate its tuples, and the values of
+/// its named sequences.
+pub(crate) fn read_backup<R: Read>(reader: &mut R) -> io::Result<(CommitLog, Vec<(String, i64)>)> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != BACKUP_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a moor backup file",
+        ));
+    }
+    let mut log = CommitLog::default();
+    let mut named_sequences = vec![];
+    loop {
+        let mut relation = [0; 1];
+        match reader.read_exact(&mut relation) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        if relation[0] == NAMED_SEQUENCE {
+            let name = String::from_utf8(read_bytes(reader)?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let value = read_bytes(reader)?.try_into().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "bad named sequence value")
+            })?;
+            named_sequences.push((name, i64::from_le_bytes(value)));
+            continue;
+        }
+        let Some(relation) = RELATIONS.get(relation[0] as usize) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown relation {} in backup", relation[0]),
+            ));
+        };
+        let domain = read_bytes(reader)?;
+        let codomain = read_bytes(reader)?;
+        log.changes.push(Change {
+            relation: *relation,
+            domain,
+            codomain: Some(codomain),
+        });
+    }
+    Ok((log, named_sequences))
+}
@@ -0,0 +1,94 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! A log of the tuples changed by each committed transaction, for replication.
+//! When a change log is subscribed to, the commit thread sends one `CommitLog` per successful
+//! write commit, in commit order, holding the working set it applied. A secondary database can
+//! `replay` these, in the same order, to reproduce the primary's state.
+//! Named sequences are not logged; the maximum object number is recomputed on replay.
+
+use crate::tx_management::{OpType, WorkingSet};
+use moor_var::AsByteBuffer;
+use serde::{Deserialize, Serialize};
+
+/// The relations of the world state, as named in a change log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LoggedRelation {
+    ObjectLocation,
+    ObjectContents,
+    ObjectFlags,
+    ObjectParent,
+    ObjectChildren,
+    ObjectOwner,
+    ObjectName,
+    ObjectVerbdefs,
+    ObjectVerbs,
+    ObjectPropdefs,
+    ObjectPropvalues,
+    ObjectPropflags,
+    ObjectFreelist,
+    ObjectTombstones,
+    ObjectGenerations,
+}
+
+/// A single tuple's new state, in its stored encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    pub relation: LoggedRelation,
+    pub domain: Vec<u8>,
+    /// The new value, or None if the tuple was deleted.
+    pub codomain: Option<Vec<u8>>,
+}
+
+/// Everything written by one committed transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitLog {
+    pub changes: Vec<Change>,
+}
+
+impl CommitLog {
+    /// Add the contents of a relation's working set to the log.
+    pub(crate) fn record<Domain, Codomain>(
+        &mut self,
+        relation: LoggedRelation,
+        working_set: &WorkingSet<Domain, Codomain>,
+    ) where
+        Domain: AsByteBuffer,
+        Codomain: AsByteBuffer + Clone + PartialEq,
+    {
+        for (domain, op, entry) in working_set {
+            let codomain = match op.operation {
+                OpType::Delete => None,
+                // Merges are resolved to the merged value during the check phase, before the
+                // log is taken.
+                OpType::Insert | OpType::Update | OpType::Merge => entry.as_ref().map(|entry| {
+                    entry
+                        .value
+                        .make_copy_as_vec()
+                        .expect("Unable to encode logged codomain")
+                }),
+            };
+            self.changes.push(Change {
+                relation,
+                domain: domain
+                    .make_copy_as_vec()
+                    .expect("Unable to encode logged domain"),
+                codomain,
+            });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
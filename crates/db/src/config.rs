@@ -13,6 +13,7 @@
 
 use fjall::PartitionCreateOptions;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::time::Duration;
 
 pub const DEFAULT_EVICTION_INTERVAL: Duration = Duration::from_secs(60);
@@ -27,6 +28,12 @@ pub struct DatabaseConfig {
     /// and if it exceeds this threshold, random entries will be put onto the eviction queue.
     /// If they are still there, untouched, by the next eviction cycle, they will be removed.
     pub default_eviction_threshold: Option<usize>,
+    /// If set, recycled objects are kept as tombstones for at least this long, during which they
+    /// can be brought back with `undelete`. If not, recycling destroys them outright.
+    pub tombstone_retention: Option<Duration>,
+    /// The directory that `backup` writes into. Backups go nowhere else; if it isn't set, they
+    /// are refused.
+    pub backup_directory: Option<PathBuf>,
 
     /// Per-table configurations
     pub object_location: Option<TableConfig>,
@@ -41,6 +48,9 @@ pub struct DatabaseConfig {
     pub object_propdefs: Option<TableConfig>,
     pub object_propvalues: Option<TableConfig>,
     pub object_propflags: Option<TableConfig>,
+    pub object_freelist: Option<TableConfig>,
+    pub object_tombstones: Option<TableConfig>,
+    pub object_generations: Option<TableConfig>,
 }
 
 impl Default for DatabaseConfig {
@@ -49,6 +59,8 @@ impl Default for DatabaseConfig {
             cache_eviction_interval: Some(DEFAULT_EVICTION_INTERVAL),
             // 64MB.
             default_eviction_threshold: Some(1 << 26),
+            tombstone_retention: None,
+            backup_directory: None,
             object_location: None,
             object_contents: None,
             object_flags: None,
@@ -61,6 +73,9 @@ impl Default for DatabaseConfig {
             object_propdefs: None,
             object_propvalues: None,
             object_propflags: None,
+            object_freelist: None,
+            object_tombstones: None,
+            object_generations: None,
         }
     }
 }
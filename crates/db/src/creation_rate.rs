@@ -0,0 +1,115 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Limiting how fast each owner can create objects ($server_options.max_creates_per_minute), so
+//! that a verb calling `create()` in a loop can't fill the disk before anyone notices.
+
+use minstant::Instant;
+use moor_var::Obj;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// The span over which creations are counted.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// When each owner created objects within the last minute. Shared between transactions. A
+/// creation is counted when it is made, not when it commits, so one that is rolled back, or
+/// redone when its transaction retries after a conflict, still counts; erring that way keeps
+/// concurrent creators from getting past the limit together.
+#[derive(Default)]
+pub(crate) struct CreationRateLimiter {
+    creations: HashMap<Obj, VecDeque<Instant>>,
+}
+
+impl CreationRateLimiter {
+    /// Count a creation by `owner` at `now`, if they have created fewer than `limit` in the
+    /// minute before it. Returns false, counting nothing, if they haven't room for it.
+    pub(crate) fn try_record(&mut self, owner: Obj, limit: usize, now: Instant) -> bool {
+        self.prune(owner, now);
+        let times = self.creations.entry(owner).or_default();
+        if times.len() >= limit {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+
+    /// Forget `owner`'s creations from before the window, and `owner` altogether if that leaves
+    /// none, so the map doesn't grow forever.
+    fn prune(&mut self, owner: Obj, now: Instant) {
+        let Some(times) = self.creations.get_mut(&owner) else {
+            return;
+        };
+        while times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= WINDOW)
+        {
+            times.pop_front();
+        }
+        if times.is_empty() {
+            self.creations.remove(&owner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_trips_and_recovers() {
+        let (spammer, other) = (Obj::mk_id(1), Obj::mk_id(2));
+        let mut limiter = CreationRateLimiter::default();
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // Creating rapidly, the fourth in a minute is refused...
+        for i in 0..3 {
+            assert!(limiter.try_record(spammer, 3, at(i)));
+        }
+        assert!(!limiter.try_record(spammer, 3, at(3)));
+        // ...and so are retries, which don't count against them.
+        assert!(!limiter.try_record(spammer, 3, at(59)));
+        // Other owners are counted separately.
+        assert!(limiter.try_record(other, 3, at(3)));
+
+        // As the earliest creations fall out of the window, there's room again.
+        assert!(limiter.try_record(spammer, 3, at(60)));
+        assert!(!limiter.try_record(spammer, 3, at(60)));
+        assert!(limiter.try_record(spammer, 3, at(62)));
+    }
+
+    #[test]
+    fn test_refusals_count_nothing() {
+        let owner = Obj::mk_id(1);
+        let mut limiter = CreationRateLimiter::default();
+        let start = Instant::now();
+
+        assert!(limiter.try_record(owner, 1, start));
+        for _ in 0..10 {
+            assert!(!limiter.try_record(owner, 1, start));
+        }
+        assert_eq!(limiter.creations[&owner].len(), 1);
+    }
+
+    #[test]
+    fn test_idle_owners_are_forgotten() {
+        let owner = Obj::mk_id(1);
+        let mut limiter = CreationRateLimiter::default();
+        let start = Instant::now();
+
+        assert!(limiter.try_record(owner, 2, start));
+        limiter.prune(owner, start + Duration::from_secs(61));
+        assert!(limiter.creations.is_empty());
+    }
+}
@@ -13,16 +13,20 @@
 
 use ahash::HashSet;
 use lazy_static::lazy_static;
+use std::fs::File;
+use std::io::BufWriter;
 use uuid::Uuid;
 
+use crate::backup::write_backup;
 use crate::ws_transaction::WorldStateTransaction;
+use minstant::Instant;
 use moor_common::model::Perms;
 use moor_common::model::WorldState;
 use moor_common::model::WorldStateError;
 use moor_common::model::{ArgSpec, PrepSpec, VerbArgsSpec};
 use moor_common::model::{CommitResult, PropPerms, ValSet};
 use moor_common::model::{HasUuid, ObjectRef};
-use moor_common::model::{ObjAttrs, ObjFlag};
+use moor_common::model::{ObjAttrs, ObjFlag, ObjectSummary};
 use moor_common::model::{ObjSet, WorldStatePerf};
 use moor_common::model::{PropAttrs, PropFlag};
 use moor_common::model::{PropDef, PropDefs};
@@ -31,8 +35,9 @@ use moor_common::model::{VerbDef, VerbDefs};
 use moor_common::program::ProgramType;
 use moor_common::util::{BitEnum, PerfTimerGuard};
 use moor_var::NOTHING;
+use moor_var::SYSTEM_OBJECT;
 use moor_var::Variant;
-use moor_var::{Obj, v_bool_int};
+use moor_var::{Obj, v_bool_int, v_int};
 use moor_var::{Symbol, v_list};
 use moor_var::{Var, v_obj};
 
@@ -49,6 +54,9 @@ lazy_static! {
     static ref W_SYM: Symbol = Symbol::mk("w");
     static ref F_SYM: Symbol = Symbol::mk("f");
     static ref ALIASES_SYM: Symbol = Symbol::mk("aliases");
+    static ref OWNERSHIP_QUOTA_SYM: Symbol = Symbol::mk("ownership_quota");
+    static ref SERVER_OPTIONS_SYM: Symbol = Symbol::mk("server_options");
+    static ref MAX_CREATES_PER_MINUTE_SYM: Symbol = Symbol::mk("max_creates_per_minute");
     static ref WORLD_STATE_PERF: WorldStatePerf = WorldStatePerf::new();
 }
 
@@ -68,6 +76,55 @@ impl DbWorldState {
     pub(crate) fn get_tx_mut(&mut self) -> &mut WorldStateTransaction {
         &mut self.tx
     }
+    /// The integer value of `who`'s `ownership_quota` property, if it has one.
+    fn ownership_quota(&self, who: &Obj) -> Result<Option<i64>, WorldStateError> {
+        if !self.valid(who)? {
+            return Ok(None);
+        }
+        match self.get_tx().resolve_property(who, *OWNERSHIP_QUOTA_SYM) {
+            Ok((_, quota, _, _)) => Ok(quota.as_integer()),
+            Err(WorldStateError::PropertyNotFound(_, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// $server_options.max_creates_per_minute, if it's a positive integer.
+    fn max_creates_per_minute(&self) -> Result<Option<usize>, WorldStateError> {
+        let server_options = match self
+            .get_tx()
+            .resolve_property(&SYSTEM_OBJECT, *SERVER_OPTIONS_SYM)
+        {
+            Ok((_, server_options, _, _)) => server_options,
+            Err(WorldStateError::PropertyNotFound(_, _) | WorldStateError::ObjectNotFound(_)) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        let Some(server_options) = server_options.as_object() else {
+            return Ok(None);
+        };
+        if !self.valid(&server_options)? {
+            return Ok(None);
+        }
+        match self
+            .get_tx()
+            .resolve_property(&server_options, *MAX_CREATES_PER_MINUTE_SYM)
+        {
+            Ok((_, limit, _, _)) => Ok(limit
+                .as_integer()
+                .filter(|limit| *limit > 0)
+                .map(|limit| limit as usize)),
+            Err(WorldStateError::PropertyNotFound(_, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn set_ownership_quota(&mut self, who: &Obj, quota: i64) -> Result<(), WorldStateError> {
+        let (pdef, _, _, _) = self.get_tx().resolve_property(who, *OWNERSHIP_QUOTA_SYM)?;
+        self.get_tx_mut()
+            .set_property(who, pdef.uuid(), v_int(quota))
+    }
+
     fn perms(&self, who: &Obj) -> Result<Perms, WorldStateError> {
         let flags = self.flags_of(who)?;
         Ok(Perms { who: *who, flags })
@@ -119,6 +176,7 @@ impl DbWorldState {
     ) -> Result<(), WorldStateError> {
         // If object or one of its descendants defines a property with the same name as one defined
         // either on new-parent or on one of its ancestors, then E_INVARG is raised.
+        // Property names are case-insensitive, so the comparison is too.
         let obj_or_descendant_props = self
             .descendants_of(perms, obj, true)?
             .iter()
@@ -133,10 +191,12 @@ impl DbWorldState {
             .collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .flatten()
-            .map(|prop| prop.name().to_string())
+            .map(|prop| prop.name().to_lowercase())
             .collect();
         for obj_or_descendant_prop in obj_or_descendant_props {
-            if new_parent_or_ancestors_property_names.contains(obj_or_descendant_prop.name()) {
+            if new_parent_or_ancestors_property_names
+                .contains(&obj_or_descendant_prop.name().to_lowercase())
+            {
                 return Err(WorldStateError::ChparentPropertyNameConflict(
                     *obj,
                     *new_parent,
@@ -195,6 +255,20 @@ impl WorldState for DbWorldState {
         self.get_tx_mut().set_object_flags(obj, new_flags)
     }
 
+    fn object_summary(&self, perms: &Obj, obj: &Obj) -> Result<ObjectSummary, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.object_summary);
+        let mut summary = self.get_tx().get_object_summary(obj)?;
+        let readable = self
+            .perms(perms)?
+            .check_object_allows(&summary.owner, summary.flags, ObjFlag::Read.into())
+            .is_ok();
+        if !readable {
+            summary.verbs = None;
+            summary.properties = None;
+        }
+        Ok(summary)
+    }
+
     fn location_of(&self, _perms: &Obj, obj: &Obj) -> Result<Obj, WorldStateError> {
         let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.location_of);
         // MOO permits location query even if the object is unreadable!
@@ -207,6 +281,22 @@ impl WorldState for DbWorldState {
         self.get_tx().get_object_size_bytes(obj)
     }
 
+    fn chown_all(
+        &mut self,
+        perms: &Obj,
+        from: &Obj,
+        to: &Obj,
+    ) -> Result<(usize, usize), WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.chown_all);
+        self.perms(perms)?.check_wizard()?;
+        // `from` may well be gone already, its leftovers being what's tidied up, but `to` must
+        // exist.
+        if !self.get_tx().object_valid(to)? {
+            return Err(WorldStateError::ObjectNotFound(ObjectRef::Id(*to)));
+        }
+        self.get_tx_mut().transfer_ownership(from, to)
+    }
+
     fn create_object(
         &mut self,
         perms: &Obj,
@@ -226,10 +316,29 @@ impl WorldState for DbWorldState {
 
         self.check_parent(perms, parent, owner)?;
 
-        // TODO: ownership_quota support
-        //    If the intended owner of the new object has a property named `ownership_quota' and the value of that property is an integer, then `create()' treats that value
-        //    as a "quota".  If the quota is less than or equal to zero, then the quota is considered to be exhausted and `create()' raises `E_QUOTA' instead of creating an
-        //    object.  Otherwise, the quota is decremented and stored back into the `ownership_quota' property as a part of the creation of the new object.
+        // If the intended owner has an integer `ownership_quota', it's exhausted at zero, and
+        // otherwise is decremented as part of this creation. Anything else means no quota.
+        if let Some(quota) = self.ownership_quota(owner)? {
+            if quota <= 0 {
+                return Err(WorldStateError::QuotaExceeded(*owner));
+            }
+            self.set_ownership_quota(owner, quota - 1)?;
+        }
+
+        // Wizards aside, owners may only create so many objects a minute. The creation is counted
+        // now, under the same lock as the check, so it counts even if this transaction later rolls
+        // back or retries.
+        let rate_limit = match is_wizard {
+            true => None,
+            false => self.max_creates_per_minute()?,
+        };
+        if let Some(limit) = rate_limit {
+            let mut creation_rate = self.get_tx().creation_rate.lock().unwrap();
+            if !creation_rate.try_record(*owner, limit, Instant::now()) {
+                return Err(WorldStateError::QuotaExceeded(*owner));
+            }
+        }
+
         let attrs = ObjAttrs::new(*owner, *parent, NOTHING, flags, "");
         self.get_tx_mut().create_object(None, attrs)
     }
@@ -240,9 +349,20 @@ impl WorldState for DbWorldState {
         self.perms(perms)?
             .check_object_allows(&owner, flags, ObjFlag::Write.into())?;
 
+        // Recycling gives back the owner's quota which creating it took.
+        if let Some(quota) = self.ownership_quota(&owner)? {
+            self.set_ownership_quota(&owner, quota + 1)?;
+        }
+
         self.get_tx_mut().recycle_object(obj)
     }
 
+    fn undelete_object(&mut self, perms: &Obj, obj: &Obj) -> Result<(), WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.undelete_object);
+        self.perms(perms)?.check_wizard()?;
+        self.get_tx_mut().undelete_object(obj)
+    }
+
     fn max_object(&self, _perms: &Obj) -> Result<Obj, WorldStateError> {
         let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.max_object);
         self.get_tx().get_max_object()
@@ -288,6 +408,32 @@ impl WorldState for DbWorldState {
         Ok(properties)
     }
 
+    fn count_verbs(&self, perms: &Obj, obj: &Obj) -> Result<usize, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.count_verbs);
+        let (flags, owner) = (self.flags_of(obj)?, self.owner_of(obj)?);
+        self.perms(perms)?
+            .check_object_allows(&owner, flags, ObjFlag::Read.into())?;
+
+        self.get_tx().count_verbs(obj)
+    }
+
+    fn count_properties(&self, perms: &Obj, obj: &Obj) -> Result<usize, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.count_properties);
+        let (flags, owner) = (self.flags_of(obj)?, self.owner_of(obj)?);
+        self.perms(perms)?
+            .check_object_allows(&owner, flags, ObjFlag::Read.into())?;
+
+        self.get_tx().count_properties(obj)
+    }
+
+    fn object_generation(&self, obj: &Obj) -> Result<i64, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.object_generation);
+        if !self.valid(obj)? {
+            return Err(WorldStateError::ObjectNotFound(ObjectRef::Id(*obj)));
+        }
+        self.get_tx().get_object_generation(obj)
+    }
+
     #[allow(clippy::obfuscated_if_else)]
     fn retrieve_property(
         &self,
@@ -506,6 +652,38 @@ impl WorldState for DbWorldState {
         Ok(())
     }
 
+    fn add_to_property(
+        &mut self,
+        perms: &Obj,
+        obj: &Obj,
+        pname: Symbol,
+        delta: &Var,
+    ) -> Result<Option<Var>, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.add_to_property);
+        let (pdef, value, propperms, clear) = self.get_tx().resolve_property(obj, pname)?;
+        self.perms(perms)?
+            .check_property_allows(&propperms, PropFlag::Write)?;
+
+        match (value.variant(), delta.variant()) {
+            (Variant::Int(_), Variant::Int(_)) | (Variant::Float(_), Variant::Float(_)) => {}
+            _ => return Err(WorldStateError::PropertyTypeMismatch),
+        }
+
+        // A clear property has no value of its own to merge into, so the sum is set as its value
+        // like any other update.
+        if clear {
+            let Ok(sum) = value.add(delta) else {
+                return Ok(None);
+            };
+            self.get_tx_mut()
+                .set_property(obj, pdef.uuid(), sum.clone())?;
+            return Ok(Some(sum));
+        }
+
+        self.get_tx_mut()
+            .add_to_property(obj, pdef.uuid(), delta.clone())
+    }
+
     fn is_property_clear(
         &self,
         perms: &Obj,
@@ -811,6 +989,11 @@ impl WorldState for DbWorldState {
         self.get_tx().get_object_children(obj)
     }
 
+    fn count_children(&self, _perms: &Obj, obj: &Obj) -> Result<usize, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.count_children);
+        self.get_tx().count_children(obj)
+    }
+
     fn descendants_of(
         &self,
         _perms: &Obj,
@@ -869,11 +1052,58 @@ impl WorldState for DbWorldState {
         Ok((name, aliases))
     }
 
+    fn find_verbs(
+        &self,
+        perms: &Obj,
+        matches: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<(Obj, VerbDef)>, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.find_verbs);
+        self.perms(perms)?.check_wizard()?;
+        self.get_tx().find_verbs(matches)
+    }
+
+    fn find_properties(
+        &self,
+        perms: &Obj,
+        matches: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<(Obj, PropDef)>, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.find_properties);
+        self.perms(perms)?.check_wizard()?;
+        self.get_tx().find_properties(matches)
+    }
+
+    fn next_sequence(&self, name: &str) -> Result<i64, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.next_sequence);
+        Ok(self.get_tx().next_named_sequence(name))
+    }
+
+    fn current_sequence(&self, name: &str) -> Result<i64, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.current_sequence);
+        Ok(self.get_tx().get_named_sequence(name))
+    }
+
+    fn backup(&self, name: &str) -> Result<usize, WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.backup);
+        let path = self.get_tx().backup_path(name)?;
+        let snapshot = self.get_tx().snapshot()?;
+        let file = File::create(&path).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Unable to create backup {:?}: {}", path, e))
+        })?;
+        write_backup(&mut BufWriter::new(file), snapshot).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Unable to write backup {:?}: {}", path, e))
+        })
+    }
+
     fn db_usage(&self) -> Result<usize, WorldStateError> {
         let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.db_usage);
         self.get_tx().db_usage()
     }
 
+    fn compact(&self) -> Result<(usize, usize), WorldStateError> {
+        let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.compact);
+        self.get_tx().compact()
+    }
+
     fn commit(self: Box<Self>) -> Result<CommitResult, WorldStateError> {
         let _t = PerfTimerGuard::new(&WORLD_STATE_PERF.commit);
         self.tx.commit()
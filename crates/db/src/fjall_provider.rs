@@ -11,7 +11,7 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
-use crate::tx_management::{Error, Provider, Timestamp};
+use crate::tx_management::{Error, Provider, Timestamp, TupleSnapshot};
 use byteview::ByteView;
 use crossbeam_channel::Sender;
 use fjall::UserValue;
@@ -30,6 +30,22 @@ enum WriteOp<
 > {
     Insert(Timestamp, Domain, Codomain),
     Delete(Domain),
+    /// Answered with a view of the partition once every op queued before it has been written.
+    Snapshot(oneshot::Sender<Box<dyn TupleSnapshot>>),
+}
+
+/// A point-in-time view of a partition, taken by its writer thread.
+struct FjallSnapshot(fjall::Snapshot);
+
+impl TupleSnapshot for FjallSnapshot {
+    fn for_each(&self, f: &mut dyn FnMut(&[u8], &[u8]) -> Result<(), Error>) -> Result<(), Error> {
+        for entry in self.0.iter() {
+            let (key, value) = entry.map_err(|e| Error::RetrievalFailure(e.to_string()))?;
+            // Skip the timestamp the value is stored with.
+            f(&key, &value[8..])?;
+        }
+        Ok(())
+    }
 }
 
 /// A backing persistence provider that fills the DB cache from a Fjall partition.
@@ -52,7 +68,10 @@ where
 {
     let result: ByteView = user_value.into();
     let ts = Timestamp(u64::from_le_bytes(result[0..8].try_into().unwrap()));
-    let codomain = Codomain::from_bytes(result.slice(8..)).map_err(|_| Error::EncodingFailure)?;
+    let codomain = Codomain::from_bytes(result.slice(8..)).map_err(|e| {
+        error!("Unable to decode stored value: {e}");
+        Error::EncodingFailure
+    })?;
     Ok((ts, codomain))
 }
 
@@ -118,6 +137,11 @@ where
                                 })
                                 .ok();
                         }
+                        Ok(WriteOp::Snapshot(reply)) => {
+                            reply
+                                .send(Box::new(FjallSnapshot(fjall_partition.snapshot())))
+                                .ok();
+                        }
                         Err(_e) => {
                             continue;
                         }
@@ -196,6 +220,17 @@ where
         Ok(result)
     }
 
+    fn snapshot(&self) -> Result<oneshot::Receiver<Box<dyn TupleSnapshot>>, Error> {
+        let (send, recv) = oneshot::channel();
+        if let Err(e) = self.ops.send(WriteOp::Snapshot(send)) {
+            return Err(Error::StorageFailure(format!(
+                "failed to snapshot database: {}",
+                e
+            )));
+        }
+        Ok(recv)
+    }
+
     fn stop(&self) -> Result<(), Error> {
         self.kill_switch
             .store(true, std::sync::atomic::Ordering::SeqCst);
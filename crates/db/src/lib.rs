@@ -14,7 +14,8 @@
 //
 
 use byteview::ByteView;
-use moor_common::model::{CommitResult, WorldStateSource};
+use crossbeam_channel::Receiver;
+use moor_common::model::{CommitResult, LifecycleEvent, ObjFlag, WorldStateSource};
 use moor_common::model::{WorldState, WorldStateError};
 use moor_var::{AsByteBuffer, DecodingError, EncodingError, Obj};
 use std::cmp::Ordering;
@@ -23,7 +24,11 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use moor_common::model::loader::LoaderInterface;
+use moor_common::util::BitEnum;
 
+mod backup;
+mod change_log;
+mod creation_rate;
 mod db_loader_client;
 pub mod db_worldstate;
 mod fjall_provider;
@@ -31,8 +36,10 @@ pub(crate) mod moor_db;
 mod moor_db_tests;
 mod ws_transaction;
 
+use crate::backup::Snapshot;
 use crate::db_worldstate::DbWorldState;
 use crate::moor_db::{MoorDB, WorkingSets};
+pub use change_log::{Change, CommitLog, LoggedRelation};
 pub use config::{DatabaseConfig, TableConfig};
 mod config;
 mod prop_cache;
@@ -43,7 +50,9 @@ use crate::prop_cache::PropResolutionCache;
 use crate::verb_cache::{AncestryCache, VerbResolutionCache};
 pub use db_worldstate::db_counters;
 pub use tx_management::Provider;
-pub use tx_management::{Error, Relation, RelationTransaction, Timestamp, Tx, WorkingSet};
+pub use tx_management::{
+    Error, Relation, RelationStats, RelationTransaction, Timestamp, Tx, WorkingSet, relation_stats,
+};
 
 pub trait Database: Send + WorldStateSource {
     fn loader_client(&self) -> Result<Box<dyn LoaderInterface>, WorldStateError>;
@@ -59,6 +68,31 @@ impl TxDB {
         let (storage, fresh) = MoorDB::open(path, database_config);
         (Self { storage }, fresh)
     }
+
+    /// Follow the changes made by each commit from now on, e.g. to feed a standby database.
+    /// Only one follower is supported; a new call replaces the previous one.
+    pub fn change_log(&self) -> Receiver<CommitLog> {
+        self.storage.follow_changes()
+    }
+
+    /// Follow the lifecycle events (objects created and recycled, verbs and properties added and
+    /// removed) of each commit from now on, e.g. to keep an external search index up to date.
+    /// Only one follower is supported; a new call replaces the previous one.
+    pub fn lifecycle_events(&self) -> Receiver<LifecycleEvent> {
+        self.storage.follow_lifecycle_events()
+    }
+
+    /// Apply a commit from another database's change log. Logs must be replayed in the order
+    /// they were received.
+    pub fn replay(&self, log: &CommitLog) -> Result<CommitResult, WorldStateError> {
+        self.storage.replay(log)
+    }
+
+    /// Load a backup made with the `backup()` builtin into a freshly created database. Returns
+    /// the number of tuples restored.
+    pub fn restore(&self, path: &Path) -> Result<usize, WorldStateError> {
+        self.storage.restore(path)
+    }
 }
 impl WorldStateSource for TxDB {
     fn new_world_state(&self) -> Result<Box<dyn WorldState>, WorldStateError> {
@@ -278,6 +312,64 @@ impl AsByteBuffer for ObjAndUUIDHolder {
     }
 }
 
+/// What's kept of a recycled object while it can still be undeleted. The rest of its data stays
+/// where it was, under its own number; it's only its flags, the mark of a valid object, which
+/// are moved here.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Tombstone {
+    pub(crate) flags: BitEnum<ObjFlag>,
+    /// When it was recycled, in seconds since the epoch.
+    pub(crate) recycled_at: u64,
+    /// The properties it inherited when it was recycled, so that any its ancestors have removed
+    /// since can be dropped from it if it is undeleted.
+    pub(crate) inherited: Vec<Uuid>,
+}
+
+impl AsByteBuffer for Tombstone {
+    fn size_bytes(&self) -> usize {
+        10 + self.inherited.len() * 16
+    }
+
+    fn with_byte_buffer<R, F: FnMut(&[u8]) -> R>(&self, mut f: F) -> Result<R, EncodingError> {
+        Ok(f(&self.make_copy_as_vec()?))
+    }
+
+    fn make_copy_as_vec(&self) -> Result<Vec<u8>, EncodingError> {
+        let mut bytes = Vec::with_capacity(self.size_bytes());
+        bytes.extend_from_slice(&self.recycled_at.to_le_bytes());
+        bytes.extend_from_slice(&self.flags.to_u16().to_le_bytes());
+        for uuid in &self.inherited {
+            bytes.extend_from_slice(uuid.as_bytes());
+        }
+        Ok(bytes)
+    }
+
+    fn from_bytes(bytes: ByteView) -> Result<Self, DecodingError> {
+        let bytes = bytes.as_ref();
+        if bytes.len() < 10 || (bytes.len() - 10) % 16 != 0 {
+            return Err(DecodingError::CouldNotDecode(format!(
+                "Expected 10 bytes and a list of uuids for tombstone, got {}",
+                bytes.len()
+            )));
+        }
+        let recycled_at = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let flags = BitEnum::from_bytes(ByteView::from(bytes[8..10].to_vec()))?;
+        let inherited = bytes[10..]
+            .chunks_exact(16)
+            .map(|uuid| Uuid::from_bytes(uuid.try_into().unwrap()))
+            .collect();
+        Ok(Self {
+            flags,
+            recycled_at,
+            inherited,
+        })
+    }
+
+    fn as_bytes(&self) -> Result<ByteView, EncodingError> {
+        Ok(ByteView::from(self.make_copy_as_vec()?))
+    }
+}
+
 enum CommitSet {
     /// Commit the working sets of a transaction.
     CommitWrites(Box<WorkingSets>, oneshot::Sender<CommitResult>),
@@ -288,11 +380,15 @@ enum CommitSet {
         Box<PropResolutionCache>,
         Box<AncestryCache>,
     ),
+    /// Request views of everything committed for a backup, between commits.
+    Snapshot(oneshot::Sender<Result<Snapshot, WorldStateError>>),
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ObjAndUUIDHolder;
+    use crate::{ObjAndUUIDHolder, Tombstone};
+    use moor_common::model::ObjFlag;
+    use moor_common::util::BitEnum;
     use moor_var::{AsByteBuffer, SYSTEM_OBJECT};
     use std::collections::BTreeSet;
     use std::hash::{Hash, Hasher};
@@ -309,6 +405,18 @@ mod tests {
         assert_eq!(oh.obj, oh2.obj);
     }
 
+    #[test]
+    fn test_reconstitute_tombstone() {
+        let tombstone = Tombstone {
+            flags: BitEnum::new_with(ObjFlag::Read),
+            recycled_at: 12345,
+            inherited: vec![Uuid::new_v4(), Uuid::new_v4()],
+        };
+        let bytes = tombstone.as_bytes().unwrap();
+        assert_eq!(bytes.len(), tombstone.size_bytes());
+        assert_eq!(Tombstone::from_bytes(bytes).unwrap(), tombstone);
+    }
+
     #[test]
     fn test_hash_obj_uuid_holder() {
         let u = Uuid::new_v4();
@@ -11,24 +11,32 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
+use crate::backup::{Snapshot, read_backup};
+use crate::change_log::{CommitLog, LoggedRelation};
 use crate::config::{DEFAULT_EVICTION_INTERVAL, DatabaseConfig};
+use crate::creation_rate::CreationRateLimiter;
 use crate::db_worldstate::db_counters;
 use crate::fjall_provider::FjallProvider;
 use crate::prop_cache::PropResolutionCache;
 use crate::tx_management::{Relation, SizedCache, Timestamp, Tx, WorkingSet};
-use crate::verb_cache::{AncestryCache, VerbResolutionCache};
+use crate::verb_cache::{AncestryCache, ForkedCache, VerbResolutionCache};
 use crate::ws_transaction::WorldStateTransaction;
-use crate::{CommitSet, ObjAndUUIDHolder, StringHolder};
+use crate::{CommitSet, ObjAndUUIDHolder, StringHolder, Tombstone};
 use arc_swap::ArcSwap;
-use crossbeam_channel::Sender;
+use crossbeam_channel::{Receiver, Sender};
 use crossbeam_utils::CachePadded;
 use fjall::{Config, PartitionCreateOptions, PartitionHandle, PersistMode};
 use gdt_cpus::{ThreadPriority, set_thread_priority};
 use minstant::Instant;
-use moor_common::model::{CommitResult, ObjFlag, ObjSet, PropDefs, PropPerms, VerbDefs};
+use moor_common::model::{
+    CommitResult, LifecycleEvent, ObjFlag, ObjSet, PropDefs, PropPerms, VerbDefs, WorldStateError,
+};
 use moor_common::program::ProgramType;
 use moor_common::util::{BitEnum, PerfTimerGuard};
-use moor_var::{Obj, Symbol, Var};
+use moor_var::{Obj, Symbol, Var, Variant};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64};
 use std::sync::{Arc, Mutex};
@@ -56,8 +64,31 @@ pub struct MoorDB {
     object_propvalues: R<ObjAndUUIDHolder, Var>,
     object_propflags: R<ObjAndUUIDHolder, PropPerms>,
 
+    /// Recycled object numbers available for reuse, as a single tuple keyed on #-1.
+    object_freelist: R<Obj, ObjSet>,
+
+    /// Recycled objects kept for undeleting, when tombstones are enabled.
+    object_tombstones: R<Obj, Tombstone>,
+    tombstone_retention: Option<Duration>,
+
+    /// Where backups are written, if anywhere.
+    backup_directory: Option<Arc<Path>>,
+
+    /// Recent object creations by each owner, for $server_options.max_creates_per_minute.
+    creation_rate: Arc<Mutex<CreationRateLimiter>>,
+
+    /// How many committed transactions have changed each object's verbs or properties, so that
+    /// clients caching them can cheaply tell when to refetch. Maintained at commit.
+    object_generations: R<Obj, Var>,
+
     sequences: [Arc<CachePadded<AtomicI64>>; 16],
     sequences_partition: PartitionHandle,
+    named_sequences: Arc<NamedSequences>,
+
+    /// Where to send the changes made by each commit, if anyone is following them.
+    change_log: Mutex<Option<Sender<CommitLog>>>,
+    /// Where to send the lifecycle events of each commit, if anyone is following them.
+    lifecycle_events: Mutex<Option<Sender<LifecycleEvent>>>,
 
     kill_switch: Arc<AtomicBool>,
     commit_channel: Sender<CommitSet>,
@@ -72,6 +103,75 @@ pub struct MoorDB {
 
 type R<Domain, Codomain> = Relation<Domain, Codomain, FjallProvider<Domain, Codomain>>;
 
+/// Key prefix for named sequences in the sequences partition. The fixed sequences are keyed by
+/// their index, so can't collide with these.
+const NAMED_SEQUENCE_PREFIX: &[u8] = b"seq:";
+
+/// Counters created by name on first use, for cores that want their own (e.g. for unique IDs).
+/// Like the fixed sequences they are not transactional: a value once handed out stays used even if
+/// the transaction that took it rolls back. Each new value is written through to the sequences
+/// partition before it is returned, so it survives a restart.
+pub(crate) struct NamedSequences {
+    partition: PartitionHandle,
+    values: Mutex<HashMap<String, i64>>,
+}
+
+impl NamedSequences {
+    fn load(partition: PartitionHandle) -> Self {
+        let mut values = HashMap::new();
+        for entry in partition.prefix(NAMED_SEQUENCE_PREFIX) {
+            let (key, value) = entry.expect("Unable to read named sequences");
+            let name = String::from_utf8_lossy(&key[NAMED_SEQUENCE_PREFIX.len()..]).to_string();
+            values.insert(name, i64::from_le_bytes(value[0..8].try_into().unwrap()));
+        }
+        Self {
+            partition,
+            values: Mutex::new(values),
+        }
+    }
+
+    /// Increment the named sequence, creating it if need be, and return the new value. A new
+    /// sequence starts at 0, so the first value handed out is 1.
+    pub(crate) fn next(&self, name: &str) -> i64 {
+        // The lock is held across the write so that values reach the partition in order.
+        let mut values = self.values.lock().unwrap();
+        let value = values.entry(name.to_string()).or_insert(0);
+        *value += 1;
+        self.persist(name, *value);
+        *value
+    }
+
+    /// Set the named sequence's value outright, as when restoring a backup.
+    pub(crate) fn set(&self, name: &str, value: i64) {
+        let mut values = self.values.lock().unwrap();
+        values.insert(name.to_string(), value);
+        self.persist(name, value);
+    }
+
+    /// Every named sequence that has been used, with its last value.
+    pub(crate) fn values(&self) -> Vec<(String, i64)> {
+        let values = self.values.lock().unwrap();
+        values
+            .iter()
+            .map(|(name, value)| (name.clone(), *value))
+            .collect()
+    }
+
+    fn persist(&self, name: &str, value: i64) {
+        let key = [NAMED_SEQUENCE_PREFIX, name.as_bytes()].concat();
+        self.partition
+            .insert(key, value.to_le_bytes())
+            .unwrap_or_else(|e| {
+                error!("Failed to persist sequence {}: {}", name, e);
+            });
+    }
+
+    /// The last value handed out by the named sequence, or 0 if it hasn't been used.
+    pub(crate) fn current(&self, name: &str) -> i64 {
+        self.values.lock().unwrap().get(name).copied().unwrap_or(0)
+    }
+}
+
 pub(crate) struct WorkingSets {
     #[allow(dead_code)]
     pub(crate) tx: Tx,
@@ -87,12 +187,37 @@ pub(crate) struct WorkingSets {
     pub(crate) object_propdefs: WorkingSet<Obj, PropDefs>,
     pub(crate) object_propvalues: WorkingSet<ObjAndUUIDHolder, Var>,
     pub(crate) object_propflags: WorkingSet<ObjAndUUIDHolder, PropPerms>,
+    pub(crate) object_freelist: WorkingSet<Obj, ObjSet>,
+    pub(crate) object_tombstones: WorkingSet<Obj, Tombstone>,
+    pub(crate) object_generations: WorkingSet<Obj, Var>,
     pub(crate) verb_resolution_cache: Box<VerbResolutionCache>,
     pub(crate) prop_resolution_cache: Box<PropResolutionCache>,
     pub(crate) ancestry_cache: Box<AncestryCache>,
+    pub(crate) lifecycle_events: Vec<LifecycleEvent>,
 }
 
 impl WorkingSets {
+    /// The log entry describing everything in these working sets.
+    pub(crate) fn commit_log(&self) -> CommitLog {
+        let mut log = CommitLog::default();
+        log.record(LoggedRelation::ObjectLocation, &self.object_location);
+        log.record(LoggedRelation::ObjectContents, &self.object_contents);
+        log.record(LoggedRelation::ObjectFlags, &self.object_flags);
+        log.record(LoggedRelation::ObjectParent, &self.object_parent);
+        log.record(LoggedRelation::ObjectChildren, &self.object_children);
+        log.record(LoggedRelation::ObjectOwner, &self.object_owner);
+        log.record(LoggedRelation::ObjectName, &self.object_name);
+        log.record(LoggedRelation::ObjectVerbdefs, &self.object_verbdefs);
+        log.record(LoggedRelation::ObjectVerbs, &self.object_verbs);
+        log.record(LoggedRelation::ObjectPropdefs, &self.object_propdefs);
+        log.record(LoggedRelation::ObjectPropvalues, &self.object_propvalues);
+        log.record(LoggedRelation::ObjectPropflags, &self.object_propflags);
+        log.record(LoggedRelation::ObjectFreelist, &self.object_freelist);
+        log.record(LoggedRelation::ObjectTombstones, &self.object_tombstones);
+        log.record(LoggedRelation::ObjectGenerations, &self.object_generations);
+        log
+    }
+
     pub fn total_tuples(&self) -> usize {
         self.object_location.len()
             + self.object_contents.len()
@@ -106,6 +231,9 @@ impl WorkingSets {
             + self.object_propdefs.len()
             + self.object_propvalues.len()
             + self.object_propflags.len()
+            + self.object_freelist.len()
+            + self.object_tombstones.len()
+            + self.object_generations.len()
     }
 }
 
@@ -125,6 +253,7 @@ impl MoorDB {
             .unwrap();
 
         let sequences = [(); 16].map(|_| Arc::new(CachePadded::new(AtomicI64::new(-1))));
+        let named_sequences = Arc::new(NamedSequences::load(sequences_partition.clone()));
 
         let mut fresh = false;
         if !keyspace.partition_exists("object_location") {
@@ -271,6 +400,37 @@ impl MoorDB {
             )
             .unwrap();
 
+        let object_freelist = keyspace
+            .open_partition(
+                "object_freelist",
+                config
+                    .object_freelist
+                    .clone()
+                    .unwrap_or_default()
+                    .partition_options(),
+            )
+            .unwrap();
+        let object_tombstones = keyspace
+            .open_partition(
+                "object_tombstones",
+                config
+                    .object_tombstones
+                    .clone()
+                    .unwrap_or_default()
+                    .partition_options(),
+            )
+            .unwrap();
+        let object_generations = keyspace
+            .open_partition(
+                "object_generations",
+                config
+                    .object_generations
+                    .clone()
+                    .unwrap_or_default()
+                    .partition_options(),
+            )
+            .unwrap();
+
         let object_location = FjallProvider::new("oloc", object_location);
         let object_contents = FjallProvider::new("ocont", object_contents);
         let object_flags = FjallProvider::new("oflags", object_flags);
@@ -283,6 +443,9 @@ impl MoorDB {
         let object_propdefs = FjallProvider::new("opdefs", object_propdefs);
         let object_propvalues = FjallProvider::new("opvals", object_propvalues);
         let object_propflags = FjallProvider::new("opflags", object_propflags);
+        let object_freelist = FjallProvider::new("ofree", object_freelist);
+        let object_tombstones = FjallProvider::new("otomb", object_tombstones);
+        let object_generations = FjallProvider::new("ogen", object_generations);
 
         let object_location =
             Relation::new(Symbol::mk("object_location"), Arc::new(object_location));
@@ -300,9 +463,19 @@ impl MoorDB {
         let object_propdefs =
             Relation::new(Symbol::mk("object_propdefs"), Arc::new(object_propdefs));
         let object_propvalues =
-            Relation::new(Symbol::mk("object_propvalues"), Arc::new(object_propvalues));
+            Relation::new(Symbol::mk("object_propvalues"), Arc::new(object_propvalues))
+                .with_merge(add_property_values);
         let object_propflags =
             Relation::new(Symbol::mk("object_propflags"), Arc::new(object_propflags));
+        let object_freelist =
+            Relation::new(Symbol::mk("object_freelist"), Arc::new(object_freelist));
+        let object_tombstones =
+            Relation::new(Symbol::mk("object_tombstones"), Arc::new(object_tombstones));
+        let object_generations = Relation::new(
+            Symbol::mk("object_generations"),
+            Arc::new(object_generations),
+        )
+        .with_merge(add_generations);
 
         let (commit_channel, commit_receiver) = crossbeam_channel::unbounded();
         let (usage_send, usage_recv) = crossbeam_channel::unbounded();
@@ -324,8 +497,17 @@ impl MoorDB {
             object_propdefs,
             object_propvalues,
             object_propflags,
+            object_freelist,
+            object_tombstones,
+            tombstone_retention: config.tombstone_retention,
+            backup_directory: config.backup_directory.as_deref().map(Arc::from),
+            creation_rate: Default::default(),
+            object_generations,
             sequences,
             sequences_partition,
+            named_sequences,
+            change_log: Mutex::new(None),
+            lifecycle_events: Mutex::new(None),
             commit_channel,
             usage_send,
             kill_switch: kill_switch.clone(),
@@ -342,6 +524,117 @@ impl MoorDB {
         (s, fresh)
     }
 
+    /// Start sending the changes made by each subsequent commit to the returned receiver,
+    /// replacing any previous follower.
+    pub(crate) fn follow_changes(&self) -> Receiver<CommitLog> {
+        let (send, receive) = crossbeam_channel::unbounded();
+        *self.change_log.lock().unwrap() = Some(send);
+        receive
+    }
+
+    /// Start sending the lifecycle events (objects created and recycled, verbs and properties
+    /// added and removed) of each subsequent commit to the returned receiver, replacing any
+    /// previous follower. Transactions only record these while they're being followed.
+    pub(crate) fn follow_lifecycle_events(&self) -> Receiver<LifecycleEvent> {
+        let (send, receive) = crossbeam_channel::unbounded();
+        *self.lifecycle_events.lock().unwrap() = Some(send);
+        receive
+    }
+
+    /// Apply a commit captured from another database's change log, as a single transaction.
+    pub(crate) fn replay(&self, log: &CommitLog) -> Result<CommitResult, WorldStateError> {
+        let mut tx = self.start_transaction();
+        tx.replay(log)?;
+        tx.commit()
+    }
+
+    /// Load a backup made with `WorldState::backup` into this (fresh) database. Returns the number
+    /// of tuples restored.
+    pub(crate) fn restore(&self, path: &Path) -> Result<usize, WorldStateError> {
+        let file = File::open(path).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Unable to open backup {:?}: {}", path, e))
+        })?;
+        let (log, named_sequences) = read_backup(&mut BufReader::new(file)).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Unable to read backup {:?}: {}", path, e))
+        })?;
+        match self.replay(&log)? {
+            CommitResult::Success => {
+                for (name, value) in &named_sequences {
+                    self.named_sequences.set(name, *value);
+                }
+                Ok(log.changes.len())
+            }
+            CommitResult::ConflictRetry => Err(WorldStateError::DatabaseError(
+                "Conflict while restoring backup".to_string(),
+            )),
+        }
+    }
+
+    /// Start a backup of everything as of the last commit. Nothing is read here: each relation's
+    /// writer thread takes a view of its partition once it has written out the commits queued
+    /// before the request. Must only be called from the commit processing thread, so that the
+    /// views all stop at the same commit.
+    fn snapshot(&self) -> Result<Snapshot, WorldStateError> {
+        let requests = [
+            (
+                LoggedRelation::ObjectLocation,
+                self.object_location.snapshot(),
+            ),
+            (
+                LoggedRelation::ObjectContents,
+                self.object_contents.snapshot(),
+            ),
+            (LoggedRelation::ObjectFlags, self.object_flags.snapshot()),
+            (LoggedRelation::ObjectParent, self.object_parent.snapshot()),
+            (
+                LoggedRelation::ObjectChildren,
+                self.object_children.snapshot(),
+            ),
+            (LoggedRelation::ObjectOwner, self.object_owner.snapshot()),
+            (LoggedRelation::ObjectName, self.object_name.snapshot()),
+            (
+                LoggedRelation::ObjectVerbdefs,
+                self.object_verbdefs.snapshot(),
+            ),
+            (LoggedRelation::ObjectVerbs, self.object_verbs.snapshot()),
+            (
+                LoggedRelation::ObjectPropdefs,
+                self.object_propdefs.snapshot(),
+            ),
+            (
+                LoggedRelation::ObjectPropvalues,
+                self.object_propvalues.snapshot(),
+            ),
+            (
+                LoggedRelation::ObjectPropflags,
+                self.object_propflags.snapshot(),
+            ),
+            (
+                LoggedRelation::ObjectFreelist,
+                self.object_freelist.snapshot(),
+            ),
+            (
+                LoggedRelation::ObjectTombstones,
+                self.object_tombstones.snapshot(),
+            ),
+            (
+                LoggedRelation::ObjectGenerations,
+                self.object_generations.snapshot(),
+            ),
+        ];
+        let mut relations = Vec::with_capacity(requests.len());
+        for (logged, request) in requests {
+            let view = request.map_err(|e| {
+                WorldStateError::DatabaseError(format!("Unable to snapshot relation: {:?}", e))
+            })?;
+            relations.push((logged, view));
+        }
+        Ok(Snapshot {
+            relations,
+            named_sequences: self.named_sequences.values(),
+        })
+    }
+
     pub(crate) fn start_transaction(&self) -> WorldStateTransaction {
         let tx = Tx {
             ts: Timestamp(
@@ -362,6 +655,7 @@ impl MoorDB {
             tx,
             commit_channel: self.commit_channel.clone(),
             usage_channel: self.usage_send.clone(),
+            keyspace: self.keyspace.clone(),
             object_location: self.object_location.start(&tx),
             object_contents: self.object_contents.start(&tx),
             object_flags: self.object_flags.start(&tx),
@@ -374,11 +668,24 @@ impl MoorDB {
             object_propdefs: self.object_propdefs.start(&tx),
             object_propvalues: self.object_propvalues.start(&tx),
             object_propflags: self.object_propflags.start(&tx),
+            object_freelist: self.object_freelist.start(&tx),
+            object_tombstones: self.object_tombstones.start(&tx),
+            object_generations: self.object_generations.start(&tx),
+            tombstone_retention: self.tombstone_retention,
+            backup_directory: self.backup_directory.clone(),
+            creation_rate: self.creation_rate.clone(),
             sequences: self.sequences.clone(),
+            named_sequences: self.named_sequences.clone(),
             verb_resolution_cache,
             prop_resolution_cache,
             ancestry_cache,
             has_mutations: false,
+            lifecycle_events: self
+                .lifecycle_events
+                .lock()
+                .unwrap()
+                .is_some()
+                .then(Vec::new),
         }
     }
 
@@ -396,6 +703,9 @@ impl MoorDB {
             &self.object_propdefs,
             &self.object_propvalues,
             &self.object_propflags,
+            &self.object_freelist,
+            &self.object_tombstones,
+            &self.object_generations,
         ]
     }
 
@@ -444,6 +754,9 @@ impl MoorDB {
         self.object_propdefs.stop_provider().unwrap();
         self.object_propvalues.stop_provider().unwrap();
         self.object_propflags.stop_provider().unwrap();
+        self.object_freelist.stop_provider().unwrap();
+        self.object_tombstones.stop_provider().unwrap();
+        self.object_generations.stop_provider().unwrap();
         if let Err(e) = self.keyspace.persist(PersistMode::SyncAll) {
             error!("Failed to persist keyspace: {}", e);
         }
@@ -499,20 +812,20 @@ impl MoorDB {
                     }
 
                     let msg = receiver.recv_timeout(Duration::from_millis(100));
-                    let (ws, reply) = match msg {
+                    let (mut ws, reply) = match msg {
                         Ok(CommitSet::CommitWrites(ws, reply)) => {
                             (ws, reply)
                         }
                         Ok(CommitSet::CommitReadOnly(vc, pc, ac)) => {
-                            if vc.has_changed() {
-                                this.verb_resolution_cache.store(Arc::new(*vc));
-                            }
-                            if pc.has_changed() {
-                                this.prop_resolution_cache.store(Arc::new(*pc));
-                            }
-                            if ac.has_changed() {
-                                this.ancestry_cache.store(Arc::new(*ac));
-                            }
+                            install_forked_cache(&this.verb_resolution_cache, vc);
+                            install_forked_cache(&this.prop_resolution_cache, pc);
+                            install_forked_cache(&this.ancestry_cache, ac);
+                            continue;
+                        }
+                        Ok(CommitSet::Snapshot(reply)) => {
+                            // Commits are only applied on this thread, so the views requested
+                            // here all stop at the same one. Reading them is left to the caller.
+                            reply.send(this.snapshot()).ok();
                             continue;
                         }
                         Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
@@ -539,6 +852,9 @@ impl MoorDB {
                     let mut object_propdefs = this.object_propdefs.begin_check();
                     let mut object_propvalues = this.object_propvalues.begin_check();
                     let mut object_propflags = this.object_propflags.begin_check();
+                    let mut object_freelist = this.object_freelist.begin_check();
+                    let mut object_tombstones = this.object_tombstones.begin_check();
+                    let mut object_generations = this.object_generations.begin_check();
 
 
                     let num_tuples = ws.object_flags.len()
@@ -552,25 +868,31 @@ impl MoorDB {
                         + ws.object_verbs.len()
                         + ws.object_propdefs.len()
                         + ws.object_propvalues.len()
-                        + ws.object_propflags.len();
+                        + ws.object_propflags.len()
+                        + ws.object_freelist.len()
+                        + ws.object_tombstones.len()
+                        + ws.object_generations.len();
 
                     if num_tuples > 10_000 {
                         warn!("Potential large batch @ commit... Checking {num_tuples} total tuples from the working set...");
                     }
 
                     {
-                        if object_flags.check(&ws.object_flags).is_err()
-                            || object_parent.check(&ws.object_parent).is_err()
-                            || object_children.check(&ws.object_children).is_err()
-                            || object_owner.check(&ws.object_owner).is_err()
-                            || object_location.check(&ws.object_location).is_err()
-                            || object_contents.check(&ws.object_contents).is_err()
-                            || object_name.check(&ws.object_name).is_err()
-                            || object_verbdefs.check(&ws.object_verbdefs).is_err()
-                            || object_verbs.check(&ws.object_verbs).is_err()
-                            || object_propdefs.check(&ws.object_propdefs).is_err()
-                            || object_propvalues.check(&ws.object_propvalues).is_err()
-                            || object_propflags.check(&ws.object_propflags).is_err() {
+                        if object_flags.check(&mut ws.object_flags).is_err()
+                            || object_parent.check(&mut ws.object_parent).is_err()
+                            || object_children.check(&mut ws.object_children).is_err()
+                            || object_owner.check(&mut ws.object_owner).is_err()
+                            || object_location.check(&mut ws.object_location).is_err()
+                            || object_contents.check(&mut ws.object_contents).is_err()
+                            || object_name.check(&mut ws.object_name).is_err()
+                            || object_verbdefs.check(&mut ws.object_verbdefs).is_err()
+                            || object_verbs.check(&mut ws.object_verbs).is_err()
+                            || object_propdefs.check(&mut ws.object_propdefs).is_err()
+                            || object_propvalues.check(&mut ws.object_propvalues).is_err()
+                            || object_propflags.check(&mut ws.object_propflags).is_err()
+                            || object_freelist.check(&mut ws.object_freelist).is_err()
+                            || object_tombstones.check(&mut ws.object_tombstones).is_err()
+                            || object_generations.check(&mut ws.object_generations).is_err() {
                             reply.send(CommitResult::ConflictRetry).ok();
                             continue;
                         }
@@ -588,20 +910,17 @@ impl MoorDB {
                             && !object_verbs.dirty()
                             && !object_propdefs.dirty()
                             && !object_propvalues.dirty()
-                            && !object_propflags.dirty();
+                            && !object_propflags.dirty()
+                            && !object_freelist.dirty()
+                            && !object_tombstones.dirty()
+                            && !object_generations.dirty();
 
                         if all_clean {
                             reply.send(CommitResult::Success).ok();
 
-                            if ws.verb_resolution_cache.has_changed() {
-                                this.verb_resolution_cache.store(Arc::new(*ws.verb_resolution_cache));
-                            }
-                            if ws.prop_resolution_cache.has_changed() {
-                                this.prop_resolution_cache.store(Arc::new(*ws.prop_resolution_cache));
-                            }
-                            if ws.ancestry_cache.has_changed() {
-                                this.ancestry_cache.store(Arc::new(*ws.ancestry_cache));
-                            }
+                            install_forked_cache(&this.verb_resolution_cache, ws.verb_resolution_cache);
+                            install_forked_cache(&this.prop_resolution_cache, ws.prop_resolution_cache);
+                            install_forked_cache(&this.ancestry_cache, ws.ancestry_cache);
                             continue;
                         }
 
@@ -616,6 +935,10 @@ impl MoorDB {
 
                         let _t = PerfTimerGuard::new(&counters.commit_apply_phase);
 
+                        // Capture the changes before the working sets are consumed by the apply.
+                        let change_log = this.change_log.lock().unwrap().clone();
+                        let commit_log = change_log.as_ref().map(|_| ws.commit_log());
+
                         if object_flags.apply(ws.object_flags).is_err()
                             || object_parent.apply(ws.object_parent).is_err()
                             || object_children.apply(ws.object_children).is_err()
@@ -627,7 +950,10 @@ impl MoorDB {
                             || object_verbs.apply(ws.object_verbs).is_err()
                             || object_propdefs.apply(ws.object_propdefs).is_err()
                             || object_propvalues.apply(ws.object_propvalues).is_err()
-                            || object_propflags.apply(ws.object_propflags).is_err() {
+                            || object_propflags.apply(ws.object_propflags).is_err()
+                            || object_freelist.apply(ws.object_freelist).is_err()
+                            || object_tombstones.apply(ws.object_tombstones).is_err()
+                            || object_generations.apply(ws.object_generations).is_err() {
                             reply.send(CommitResult::ConflictRetry).ok();
                             continue;
                         }
@@ -672,10 +998,35 @@ impl MoorDB {
 
                             let object_propflags_lock = object_propflags.dirty().then(|| this.object_propflags.write_lock());
                             object_propflags.commit(object_propflags_lock);
+
+                            let object_freelist_lock = object_freelist.dirty().then(|| this.object_freelist.write_lock());
+                            object_freelist.commit(object_freelist_lock);
+
+                            let object_tombstones_lock = object_tombstones.dirty().then(|| this.object_tombstones.write_lock());
+                            object_tombstones.commit(object_tombstones_lock);
+
+                            let object_generations_lock = object_generations.dirty().then(|| this.object_generations.write_lock());
+                            object_generations.commit(object_generations_lock);
                         }
                         // No need to block the caller while we're doing the final write to disk.
                         reply.send(CommitResult::Success).ok();
 
+                        if let (Some(change_log), Some(commit_log)) = (change_log, commit_log) {
+                            if change_log.send(commit_log).is_err() {
+                                // The follower went away.
+                                this.change_log.lock().unwrap().take();
+                            }
+                        }
+                        if !ws.lifecycle_events.is_empty() {
+                            let mut follower = this.lifecycle_events.lock().unwrap();
+                            let gone = follower.as_ref().is_some_and(|send| {
+                                ws.lifecycle_events.into_iter().any(|e| send.send(e).is_err())
+                            });
+                            if gone {
+                                follower.take();
+                            }
+                        }
+
                         // And if the commit took a long time, warn before the write to disk is begun.
                         if start_time.elapsed() > Duration::from_secs(5) {
                             warn!(
@@ -695,15 +1046,9 @@ impl MoorDB {
 
                     // Swap the commit set's cache with the main cache.
                     {
-                        if ws.verb_resolution_cache.has_changed() {
-                            this.verb_resolution_cache.store(Arc::new(*ws.verb_resolution_cache));
-                        }
-                        if ws.prop_resolution_cache.has_changed() {
-                            this.prop_resolution_cache.store(Arc::new(*ws.prop_resolution_cache));
-                        }
-                        if ws.ancestry_cache.has_changed() {
-                            this.ancestry_cache.store(Arc::new(*ws.ancestry_cache));
-                        }
+                        install_forked_cache(&this.verb_resolution_cache, ws.verb_resolution_cache);
+                        install_forked_cache(&this.prop_resolution_cache, ws.prop_resolution_cache);
+                        install_forked_cache(&this.ancestry_cache, ws.ancestry_cache);
                     }
 
                     let _t = PerfTimerGuard::new(&counters.commit_write_phase);
@@ -738,3 +1083,44 @@ impl Drop for MoorDB {
         self.stop();
     }
 }
+
+/// Offer a transaction's (possibly modified) copy of a resolution cache back as the new global
+/// copy.
+///
+/// The transaction's entries were filled against its own snapshot, so they can only be trusted if
+/// no other cache was installed since it forked. If one was, and the transaction flushed (e.g.
+/// because it changed inheritance with `chparent`), then the global entries are stale with respect
+/// to its writes, and are dropped wholesale. Otherwise the transaction's entries are discarded.
+fn install_forked_cache<C: ForkedCache>(global: &ArcSwap<C>, forked: Box<C>) {
+    if !forked.has_changed() {
+        return;
+    }
+    let current = global.load();
+    if forked.orig_version() == current.version() {
+        global.store(Arc::new(*forked));
+    } else if forked.was_flushed() {
+        let flushed = current.fork();
+        flushed.flush();
+        global.store(Arc::new(*flushed));
+    }
+}
+
+/// The merge function for object generations: the bumps of concurrent transactions add up, rather
+/// than conflicting.
+fn add_generations(generation: &Var, bump: &Var) -> Option<Var> {
+    match (generation.variant(), bump.variant()) {
+        (Variant::Int(_), Variant::Int(_)) => generation.add(bump).ok(),
+        _ => None,
+    }
+}
+
+/// The merge function for property values: adds a delta from `add_to_property` to the value.
+/// Only like numeric types add, so the sum is always the same size as the value it replaces.
+fn add_property_values(value: &Var, delta: &Var) -> Option<Var> {
+    match (value.variant(), delta.variant()) {
+        (Variant::Int(_), Variant::Int(_)) | (Variant::Float(_), Variant::Float(_)) => {
+            value.add(delta).ok()
+        }
+        _ => None,
+    }
+}
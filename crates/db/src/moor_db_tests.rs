@@ -14,13 +14,16 @@
 #[cfg(test)]
 mod tests {
     use crate::DatabaseConfig;
+    use crate::backup::write_backup;
     use crate::moor_db::MoorDB;
-    use moor_common::model::{CommitResult, WorldStateError};
+    use crate::tx_management::relation_stats;
+    use crate::ws_transaction::{WorldStateTransaction, upsert};
+    use moor_common::model::{ArgSpec, PrepSpec, VerbArgsSpec, VerbFlag};
+    use moor_common::model::{CommitResult, LifecycleEvent, LifecycleKind, WorldStateError};
     use moor_common::model::{HasUuid, Named};
     use moor_common::model::{ObjAttrs, PropFlag, ValSet};
     use moor_common::model::{ObjFlag, VerbAttrs};
     use moor_common::model::{ObjSet, ObjectRef};
-    use moor_common::model::{VerbArgsSpec, VerbFlag};
     use moor_common::program::ProgramType;
     use moor_common::program::program::Program;
     use moor_common::util::BitEnum;
@@ -28,7 +31,10 @@ mod tests {
     use moor_var::Symbol;
     use moor_var::{NOTHING, SYSTEM_OBJECT};
     use moor_var::{v_int, v_str};
+    use std::fs::File;
     use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::TempDir;
 
     fn test_db() -> Arc<MoorDB> {
         MoorDB::open(None, DatabaseConfig::default()).0
@@ -222,6 +228,56 @@ mod tests {
         assert_eq!(tx.commit(), Ok(CommitResult::Success));
     }
 
+    #[test]
+    fn test_inheritance_cycle_detected() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), "b"),
+            )
+            .unwrap();
+        let c = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, b, NOTHING, BitEnum::new(), "c"),
+            )
+            .unwrap();
+
+        // Corrupt the graph underneath the usual checks, making a the child of c.
+        upsert(&mut tx.object_parent, a, c).unwrap();
+        upsert(&mut tx.object_children, c, ObjSet::from_items(&[a])).unwrap();
+        tx.ancestry_cache.flush();
+
+        // Each walk has to give up rather than go round forever.
+        assert!(matches!(
+            tx.ancestors(&c, false),
+            Err(WorldStateError::DatabaseError(_))
+        ));
+        assert!(matches!(
+            tx.descendants(&a, false),
+            Err(WorldStateError::DatabaseError(_))
+        ));
+        let d = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "d"),
+            )
+            .unwrap();
+        assert!(matches!(
+            tx.set_object_parent(&d, &b),
+            Err(WorldStateError::DatabaseError(_))
+        ));
+    }
+
     #[test]
     pub fn test_location_contents() {
         let db = test_db();
@@ -642,6 +698,78 @@ mod tests {
         assert_eq!(tx.commit(), Ok(CommitResult::Success));
     }
 
+    #[test]
+    fn test_inherited_property_follows_definer() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), "b"),
+            )
+            .unwrap();
+        let c = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, b, NOTHING, BitEnum::new(), "c"),
+            )
+            .unwrap();
+        let uuid = tx
+            .define_property(
+                &a,
+                &a,
+                Symbol::mk_case_insensitive("test"),
+                &NOTHING,
+                BitEnum::new(),
+                Some(v_int(1)),
+            )
+            .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // Descendants hold no copy of the definer's value, so see it change, from storage as
+        // well as within the transaction that changed it.
+        let mut tx = db.start_transaction();
+        tx.set_property(&a, uuid, v_int(2)).unwrap();
+        for o in [b, c] {
+            let (_, v, _, is_clear) = tx
+                .resolve_property(&o, Symbol::mk_case_insensitive("test"))
+                .unwrap();
+            assert_eq!(v, v_int(2));
+            assert!(is_clear);
+        }
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        let mut tx = db.start_transaction();
+        let (_, v, _, _) = tx
+            .resolve_property(&c, Symbol::mk_case_insensitive("test"))
+            .unwrap();
+        assert_eq!(v, v_int(2));
+
+        // A value of the child's own hides the definer's, until cleared, when it's the definer's
+        // current value which shows through, not the one the child last saw.
+        tx.set_property(&b, uuid, v_int(10)).unwrap();
+        tx.set_property(&a, uuid, v_int(3)).unwrap();
+        let (_, v, _, _) = tx
+            .resolve_property(&c, Symbol::mk_case_insensitive("test"))
+            .unwrap();
+        assert_eq!(v, v_int(10));
+        tx.clear_property(&b, uuid).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        let tx = db.start_transaction();
+        for o in [b, c] {
+            let (_, v, _, is_clear) = tx
+                .resolve_property(&o, Symbol::mk_case_insensitive("test"))
+                .unwrap();
+            assert_eq!(v, v_int(3));
+            assert!(is_clear);
+        }
+    }
+
     #[test]
     pub fn test_rename_property() {
         let db = test_db();
@@ -691,6 +819,191 @@ mod tests {
         )
     }
 
+    /// Deleting a verb shifts the verbs after it down, so index-based lookups land on the verb
+    /// that is now in that position.
+    #[test]
+    fn test_verb_index_after_delete() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let oid = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap();
+        for name in ["first", "second", "third"] {
+            tx.add_object_verb(
+                &oid,
+                &oid,
+                vec![Symbol::mk_case_insensitive(name)],
+                ProgramType::MooR(Program::new()),
+                BitEnum::new_with(VerbFlag::Exec),
+                VerbArgsSpec::this_none_this(),
+            )
+            .unwrap();
+        }
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let mut tx = db.start_transaction();
+        let second = tx.get_verb_by_index(&oid, 1).unwrap();
+        assert_eq!(second.names(), vec!["second"]);
+        tx.delete_verb(&oid, second.uuid()).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        assert_eq!(
+            tx.get_verb_by_index(&oid, 0).unwrap().names(),
+            vec!["first"]
+        );
+        assert_eq!(
+            tx.get_verb_by_index(&oid, 1).unwrap().names(),
+            vec!["third"]
+        );
+        assert!(matches!(
+            tx.get_verb_by_index(&oid, 2),
+            Err(WorldStateError::VerbNotFound(_, _))
+        ));
+    }
+
+    /// Properties come back in the order they were defined, across commits, and adding, renaming
+    /// or deleting properties leaves the relative order of the rest alone.
+    #[test]
+    pub fn test_property_definition_order() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let oid = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap();
+        let mut uuids = vec![];
+        for name in ["zebra", "apple", "mango", "kiwi"] {
+            uuids.push(
+                tx.define_property(
+                    &oid,
+                    &oid,
+                    Symbol::mk_case_insensitive(name),
+                    &NOTHING,
+                    BitEnum::new(),
+                    None,
+                )
+                .unwrap(),
+            );
+        }
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let names = |tx: &WorldStateTransaction| {
+            tx.get_properties(&oid)
+                .unwrap()
+                .iter()
+                .map(|p| p.name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut tx = db.start_transaction();
+        assert_eq!(names(&tx), vec!["zebra", "apple", "mango", "kiwi"]);
+        let props = tx.get_properties(&oid).unwrap();
+        assert_eq!(props.get(2).unwrap().name(), "mango");
+        assert_eq!(props.index_of(uuids[3]), Some(3));
+        assert!(props.get(4).is_none());
+
+        tx.delete_property(&oid, uuids[1]).unwrap();
+        tx.update_property_info(&oid, uuids[2], None, None, Some("melon".to_string()))
+            .unwrap();
+        tx.define_property(
+            &oid,
+            &oid,
+            Symbol::mk_case_insensitive("banana"),
+            &NOTHING,
+            BitEnum::new(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        assert_eq!(names(&tx), vec!["zebra", "melon", "kiwi", "banana"]);
+        let props = tx.get_properties(&oid).unwrap();
+        assert_eq!(props.get(1).unwrap().uuid(), uuids[2]);
+        assert_eq!(props.index_of(uuids[1]), None);
+    }
+
+    /// The flattened view of an object's properties agrees with resolving each one by name.
+    #[test]
+    fn test_get_all_properties_matches_resolve_property() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), "b"),
+            )
+            .unwrap();
+        let c = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, b, NOTHING, BitEnum::new(), "c"),
+            )
+            .unwrap();
+
+        let define = |tx: &mut WorldStateTransaction, definer: &Obj, name: &str, value| {
+            tx.define_property(
+                definer,
+                definer,
+                Symbol::mk_case_insensitive(name),
+                &NOTHING,
+                BitEnum::new_with(PropFlag::Read),
+                value,
+            )
+            .unwrap()
+        };
+        let x = define(&mut tx, &a, "x", Some(v_int(1)));
+        let y = define(&mut tx, &a, "y", Some(v_str("from a")));
+        define(&mut tx, &a, "unset", None);
+        define(&mut tx, &b, "z", Some(v_int(3)));
+        define(&mut tx, &c, "own", Some(v_int(4)));
+        tx.set_property(&c, x, v_int(100)).unwrap();
+        tx.set_property(&b, y, v_str("from b")).unwrap();
+        tx.update_property_info(&c, y, Some(c), None, None).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        let all = tx.get_all_properties(&c).unwrap();
+        let names: Vec<_> = all.iter().map(|(p, ..)| p.name().to_string()).collect();
+        assert_eq!(names, vec!["own", "z", "x", "y", "unset"]);
+
+        for (propdef, value, source, perms) in &all {
+            let (resolved, resolved_value, resolved_perms, is_clear) = tx
+                .resolve_property(&c, Symbol::mk_case_insensitive(propdef.name()))
+                .unwrap();
+            assert_eq!(propdef.uuid(), resolved.uuid());
+            assert_eq!(propdef.definer(), resolved.definer());
+            assert_eq!(value, &resolved_value);
+            assert_eq!(perms, &resolved_perms);
+            assert_eq!(*source != c, is_clear);
+        }
+
+        let source_of = |name: &str| {
+            all.iter()
+                .find(|(p, ..)| p.name() == name)
+                .map(|(_, _, source, _)| *source)
+                .unwrap()
+        };
+        assert_eq!(source_of("x"), c);
+        assert_eq!(source_of("y"), b);
+        assert_eq!(source_of("z"), b);
+        assert_eq!(source_of("unset"), a);
+        let (_, _, _, y_perms) = all.iter().find(|(p, ..)| p.name() == "y").unwrap();
+        assert_eq!(y_perms.owner(), c);
+    }
+
     /// Test regression where parent properties were present via `properties()` on children.
     #[test]
     pub fn test_regression_properties() {
@@ -862,6 +1175,73 @@ mod tests {
         assert_eq!(tx.commit(), Ok(CommitResult::Success));
     }
 
+    #[test]
+    pub fn test_verb_resolve_most_specific_argspec() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), "test2"),
+            )
+            .unwrap();
+
+        let any_any_any = VerbArgsSpec {
+            dobj: ArgSpec::Any,
+            prep: PrepSpec::Any,
+            iobj: ArgSpec::Any,
+        };
+        // The general verb is defined first, so it would win if we went by definition order.
+        for (name, argspec) in [
+            ("foo_general", any_any_any),
+            ("foo_specific", VerbArgsSpec::this_none_this()),
+        ] {
+            tx.add_object_verb(
+                &a,
+                &a,
+                vec![Symbol::mk("foo"), Symbol::mk(name)],
+                ProgramType::MooR(Program::new()),
+                BitEnum::new_with(VerbFlag::Exec),
+                argspec,
+            )
+            .unwrap();
+        }
+
+        let resolve = |obj: &Obj, argspec: Option<VerbArgsSpec>| {
+            tx.resolve_verb(obj, Symbol::mk("foo"), argspec, None)
+                .unwrap()
+                .names()
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Repeat each lookup, so the second one goes through the resolution cache.
+        for obj in [&a, &a, &b, &b] {
+            // Without an argspec, first defined wins.
+            assert_eq!(resolve(obj, None), vec!["foo", "foo_general"]);
+            // `foo this none this` is an exact match, and beats `foo any any any`.
+            assert_eq!(
+                resolve(obj, Some(VerbArgsSpec::this_none_this())),
+                vec!["foo", "foo_specific"]
+            );
+            // Only the general verb matches `foo <something> none none`.
+            let argspec = VerbArgsSpec {
+                dobj: ArgSpec::Any,
+                prep: PrepSpec::None,
+                iobj: ArgSpec::None,
+            };
+            assert_eq!(resolve(obj, Some(argspec)), vec!["foo", "foo_general"]);
+        }
+    }
+
     #[test]
     pub fn test_verb_resolve_inherited() {
         let db = test_db();
@@ -931,6 +1311,78 @@ mod tests {
         assert_eq!(tx.commit(), Ok(CommitResult::Success));
     }
 
+    /// Reparenting an object must invalidate cached resolution for it and its descendants, even
+    /// when a concurrent transaction that cached the old inheritance commits afterwards.
+    #[test]
+    pub fn test_chparent_invalidates_resolution_caches() {
+        let db = test_db();
+        let test_verb = Symbol::mk_case_insensitive("test");
+        let exec = Some(BitEnum::new_with(VerbFlag::Exec));
+
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), "b"),
+            )
+            .unwrap();
+        let c = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, b, NOTHING, BitEnum::new(), "c"),
+            )
+            .unwrap();
+        tx.add_object_verb(
+            &a,
+            &a,
+            vec![test_verb],
+            ProgramType::MooR(Program::new()),
+            BitEnum::new_with(VerbFlag::Exec),
+            VerbArgsSpec::this_none_this(),
+        )
+        .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // A reader resolves (and caches) the inherited verb on both b and its child c.
+        let reader = db.start_transaction();
+        assert!(reader.resolve_verb(&b, test_verb, None, exec).is_ok());
+        assert!(reader.resolve_verb(&c, test_verb, None, exec).is_ok());
+
+        // Meanwhile b is detached from a.
+        let mut tx = db.start_transaction();
+        assert!(tx.resolve_verb(&c, test_verb, None, exec).is_ok());
+        tx.set_object_parent(&b, &NOTHING).unwrap();
+        assert!(tx.resolve_verb(&b, test_verb, None, exec).is_err());
+        assert!(tx.resolve_verb(&c, test_verb, None, exec).is_err());
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // The reader finishes after, offering up its now-stale cache.
+        assert_eq!(reader.commit(), Ok(CommitResult::Success));
+
+        // Push a write through the commit thread so the reader's cache has been dealt with.
+        let mut tx = db.start_transaction();
+        tx.set_object_name(&a, "a2".to_string()).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        assert_eq!(tx.get_object_parent(&b).unwrap(), NOTHING);
+        assert!(matches!(
+            tx.resolve_verb(&b, test_verb, None, exec),
+            Err(WorldStateError::VerbNotFound(_, _))
+        ));
+        assert!(matches!(
+            tx.resolve_verb(&c, test_verb, None, exec),
+            Err(WorldStateError::VerbNotFound(_, _))
+        ));
+        assert!(tx.resolve_verb(&a, test_verb, None, exec).is_ok());
+    }
+
     #[test]
     pub fn test_verb_resolve_wildcard() {
         let db = test_db();
@@ -1006,6 +1458,66 @@ mod tests {
         assert_eq!(tx.commit(), Ok(CommitResult::Success));
     }
 
+    #[test]
+    pub fn test_verb_by_name_wildcards() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), "test2"),
+            )
+            .unwrap();
+
+        for names in [&["l*ook"][..], &["get*"], &["*drop"], &["*"]] {
+            tx.add_object_verb(
+                &a,
+                &a,
+                names.iter().map(|n| Symbol::mk(n)).collect(),
+                ProgramType::MooR(Program::new()),
+                BitEnum::new_with(VerbFlag::Exec),
+                VerbArgsSpec::this_none_this(),
+            )
+            .unwrap();
+        }
+
+        let by_name = |name: &str| {
+            tx.get_verb_by_name(&a, Symbol::mk(name))
+                .unwrap()
+                .names()
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(by_name("l"), vec!["l*ook"]);
+        assert_eq!(by_name("loo"), vec!["l*ook"]);
+        assert_eq!(by_name("LOOK"), vec!["l*ook"]);
+        assert_eq!(by_name("get"), vec!["get*"]);
+        assert_eq!(by_name("getaway"), vec!["get*"]);
+        assert_eq!(by_name("dr"), vec!["*drop"]);
+        assert_eq!(by_name("drop"), vec!["*drop"]);
+        // Things none of the others match fall through to the bare star.
+        assert_eq!(by_name("lookat"), vec!["*"]);
+        assert_eq!(by_name("ge"), vec!["*"]);
+        assert_eq!(by_name("droppings"), vec!["*"]);
+
+        // Looking up an inherited verb by name on the child fails, since it's not defined
+        // there, but that mustn't poison resolution through the inheritance chain.
+        let look = Symbol::mk("look");
+        assert!(tx.resolve_verb(&b, look, None, None).is_ok());
+        assert!(matches!(
+            tx.get_verb_by_name(&b, look),
+            Err(WorldStateError::VerbNotFound(_, _))
+        ));
+        assert!(tx.resolve_verb(&b, look, None, None).is_ok());
+    }
+
     #[test]
     pub fn reparent_props() {
         let db = test_db();
@@ -1249,21 +1761,778 @@ mod tests {
         );
     }
 
-    // Verify that 'max_object' is the highest object id in the database, not one higher.
     #[test]
-    pub fn test_max_object() {
+    fn test_recycled_object_numbers_reused() {
         let db = test_db();
         let mut tx = db.start_transaction();
-        // Max object in a virgin DB should return #-1
-        let max_obj = tx.get_max_object().unwrap();
+        let mk = |tx: &mut WorldStateTransaction, id: Option<Obj>| {
+            tx.create_object(
+                id,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap()
+        };
+        let a = mk(&mut tx, None);
+        let b = mk(&mut tx, None);
+        let c = mk(&mut tx, None);
+        let max = tx.get_max_object().unwrap();
+        assert_eq!(max, c);
+
+        // Recycle two; the lowest freed number is handed out first, then the next.
+        tx.recycle_object(&c).unwrap();
+        tx.recycle_object(&a).unwrap();
+        assert!(
+            tx.get_free_objects()
+                .unwrap()
+                .is_same(ObjSet::from_items(&[c, a]))
+        );
+        assert_eq!(mk(&mut tx, None), a);
+        assert_eq!(mk(&mut tx, None), c);
+        assert_eq!(tx.get_max_object().unwrap(), max);
+
+        // With nothing free, numbering carries on past the max.
+        let d = mk(&mut tx, None);
+        assert_eq!(d, Obj::mk_id(c.id().0 + 1));
+
+        // Explicitly recreating a freed number takes it off the free list.
+        tx.recycle_object(&b).unwrap();
+        assert_eq!(mk(&mut tx, Some(b)), b);
+        assert!(tx.get_free_objects().unwrap().is_empty());
+        assert_eq!(mk(&mut tx, None), Obj::mk_id(d.id().0 + 1));
+
+        // And the free list is persisted across transactions.
+        tx.recycle_object(&a).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        let mut tx = db.start_transaction();
+        assert_eq!(mk(&mut tx, None), a);
+    }
+
+    #[test]
+    fn test_tombstone_undelete() {
+        let config = DatabaseConfig {
+            tombstone_retention: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let db = MoorDB::open(None, config).0;
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new_with(ObjFlag::Read), "b"),
+            )
+            .unwrap();
+        tx.define_property(
+            &a,
+            &a,
+            Symbol::mk_case_insensitive("inherited"),
+            &NOTHING,
+            BitEnum::new(),
+            Some(v_str("default")),
+        )
+        .unwrap();
+        let (inherited, _, _, _) = tx
+            .resolve_property(&b, Symbol::mk_case_insensitive("inherited"))
+            .unwrap();
+        tx.set_property(&b, inherited.uuid(), v_str("mine"))
+            .unwrap();
+        tx.define_property(
+            &b,
+            &b,
+            Symbol::mk_case_insensitive("own"),
+            &NOTHING,
+            BitEnum::new(),
+            Some(v_int(1)),
+        )
+        .unwrap();
+        tx.add_object_verb(
+            &b,
+            &b,
+            vec![Symbol::mk_case_insensitive("test")],
+            ProgramType::MooR(Program::new()),
+            BitEnum::new_with(VerbFlag::Exec),
+            VerbArgsSpec::this_none_this(),
+        )
+        .unwrap();
+
+        tx.recycle_object(&b).unwrap();
+        assert!(!tx.object_valid(&b).unwrap());
+        assert!(tx.get_object_children(&a).unwrap().is_empty());
+        // Tombstoned numbers aren't up for reuse.
+        assert!(tx.get_free_objects().unwrap().is_empty());
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let mut tx = db.start_transaction();
+        tx.undelete_object(&b).unwrap();
+        assert!(tx.object_valid(&b).unwrap());
+        assert_eq!(
+            tx.get_object_flags(&b).unwrap(),
+            BitEnum::new_with(ObjFlag::Read)
+        );
+        assert_eq!(tx.get_object_name(&b).unwrap(), "b");
+        assert_eq!(tx.get_object_parent(&b).unwrap(), a);
+        assert_eq!(
+            tx.get_object_children(&a).unwrap(),
+            ObjSet::from_items(&[b])
+        );
+        let (_, value, _, _) = tx
+            .resolve_property(&b, Symbol::mk_case_insensitive("inherited"))
+            .unwrap();
+        assert_eq!(value, v_str("mine"));
+        let (_, value, _, _) = tx
+            .resolve_property(&b, Symbol::mk_case_insensitive("own"))
+            .unwrap();
+        assert_eq!(value, v_int(1));
+        tx.resolve_verb(
+            &b,
+            Symbol::mk_case_insensitive("test"),
+            None,
+            Some(BitEnum::new_with(VerbFlag::Exec)),
+        )
+        .unwrap();
+
+        // It's no longer a tombstone.
+        assert_eq!(
+            tx.undelete_object(&b),
+            Err(WorldStateError::ObjectNotFound(ObjectRef::Id(b)))
+        );
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+    }
+
+    #[test]
+    fn test_tombstone_undelete_resyncs_inherited_properties() {
+        let config = DatabaseConfig {
+            tombstone_retention: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let db = MoorDB::open(None, config).0;
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), "b"),
+            )
+            .unwrap();
+        let doomed = tx
+            .define_property(
+                &a,
+                &a,
+                Symbol::mk_case_insensitive("doomed"),
+                &NOTHING,
+                BitEnum::new(),
+                Some(v_int(1)),
+            )
+            .unwrap();
+        tx.set_property(&b, doomed, v_int(2)).unwrap();
+        tx.recycle_object(&b).unwrap();
+
+        // While b is a tombstone, its parent gains one property and loses another.
+        tx.define_property(
+            &a,
+            &a,
+            Symbol::mk_case_insensitive("added"),
+            &NOTHING,
+            BitEnum::new(),
+            Some(v_str("new")),
+        )
+        .unwrap();
+        tx.delete_property(&a, doomed).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let mut tx = db.start_transaction();
+        tx.undelete_object(&b).unwrap();
+        let (_, value, _, is_clear) = tx
+            .resolve_property(&b, Symbol::mk_case_insensitive("added"))
+            .unwrap();
+        assert_eq!(value, v_str("new"));
+        assert!(is_clear);
+        assert_eq!(
+            tx.resolve_property(&b, Symbol::mk_case_insensitive("doomed"))
+                .err()
+                .unwrap(),
+            WorldStateError::PropertyNotFound(b, "doomed".to_string())
+        );
+        assert!(tx.retrieve_property_permissions(&b, doomed).is_err());
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+    }
+
+    #[test]
+    fn test_tombstone_cleared_by_explicit_create() {
+        let config = DatabaseConfig {
+            tombstone_retention: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        let db = MoorDB::open(None, config).0;
+        let mut tx = db.start_transaction();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "old"),
+            )
+            .unwrap();
+        tx.add_object_verb(
+            &b,
+            &b,
+            vec![Symbol::mk_case_insensitive("test")],
+            ProgramType::MooR(Program::new()),
+            BitEnum::new_with(VerbFlag::Exec),
+            VerbArgsSpec::this_none_this(),
+        )
+        .unwrap();
+        tx.recycle_object(&b).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // Reusing the number explicitly destroys the tombstoned object for good.
+        let mut tx = db.start_transaction();
+        assert_eq!(
+            tx.create_object(
+                Some(b),
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "new"),
+            )
+            .unwrap(),
+            b
+        );
+        assert!(tx.object_valid(&b).unwrap());
+        assert_eq!(tx.get_object_name(&b).unwrap(), "new");
+        assert!(tx.get_verbs(&b).unwrap().is_empty());
+        assert!(tx.get_free_objects().unwrap().is_empty());
+        assert_eq!(
+            tx.undelete_object(&b),
+            Err(WorldStateError::ObjectNotFound(ObjectRef::Id(b)))
+        );
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+    }
+
+    /// Ownership is transferred across every object at once, including what the same
+    /// transaction has only just added.
+    #[test]
+    fn test_transfer_ownership() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let mk = |tx: &mut WorldStateTransaction, name: &str| {
+            tx.create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), name),
+            )
+            .unwrap()
+        };
+        let (old, new, other) = (mk(&mut tx, "old"), mk(&mut tx, "new"), mk(&mut tx, "other"));
+        let (a, b) = (mk(&mut tx, "a"), mk(&mut tx, "b"));
+        let add_verb = |tx: &mut WorldStateTransaction, obj: &Obj, owner: &Obj, name: &str| {
+            tx.add_object_verb(
+                obj,
+                owner,
+                vec![Symbol::mk_case_insensitive(name)],
+                ProgramType::MooR(Program::new()),
+                BitEnum::new_with(VerbFlag::Exec),
+                VerbArgsSpec::this_none_this(),
+            )
+            .unwrap();
+        };
+        add_verb(&mut tx, &a, &old, "one");
+        add_verb(&mut tx, &a, &other, "two");
+        add_verb(&mut tx, &b, &old, "three");
+        tx.define_property(
+            &a,
+            &a,
+            Symbol::mk_case_insensitive("prop"),
+            &old,
+            BitEnum::new(),
+            Some(v_int(1)),
+        )
+        .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let mut tx = db.start_transaction();
+        add_verb(&mut tx, &b, &old, "four");
+        assert_eq!(tx.transfer_ownership(&old, &new).unwrap(), (3, 1));
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        let owners = |obj: &Obj| -> Vec<(String, Obj)> {
+            tx.get_verbs(obj)
+                .unwrap()
+                .iter()
+                .map(|v| (v.names()[0].to_string(), v.owner()))
+                .collect()
+        };
+        assert_eq!(
+            owners(&a),
+            vec![("one".to_string(), new), ("two".to_string(), other)]
+        );
+        assert_eq!(
+            owners(&b),
+            vec![("three".to_string(), new), ("four".to_string(), new)]
+        );
+        let prop = tx.get_properties(&a).unwrap().iter().next().unwrap();
+        assert_eq!(
+            tx.retrieve_property_permissions(&a, prop.uuid())
+                .unwrap()
+                .owner(),
+            new
+        );
+    }
+
+    #[test]
+    fn test_tombstone_retention() {
+        // With no retention to speak of, a tombstone is destroyed by the next recycle.
+        let config = DatabaseConfig {
+            tombstone_retention: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let db = MoorDB::open(None, config).0;
+        let mut tx = db.start_transaction();
+        let mk = |tx: &mut WorldStateTransaction| {
+            tx.create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap()
+        };
+        let a = mk(&mut tx);
+        let b = mk(&mut tx);
+        tx.recycle_object(&a).unwrap();
+        tx.recycle_object(&b).unwrap();
+        assert_eq!(
+            tx.undelete_object(&a),
+            Err(WorldStateError::ObjectNotFound(ObjectRef::Id(a)))
+        );
+        assert_eq!(
+            tx.get_object_name(&a).err().unwrap(),
+            WorldStateError::ObjectNotFound(ObjectRef::Id(a))
+        );
+        assert_eq!(tx.get_free_objects().unwrap(), ObjSet::from_items(&[a]));
+        tx.undelete_object(&b).unwrap();
+    }
+
+    // Verify that 'max_object' is the highest object id in the database, not one higher.
+    #[test]
+    pub fn test_max_object() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        // Max object in a virgin DB should return #-1
+        let max_obj = tx.get_max_object().unwrap();
         assert_eq!(max_obj, NOTHING);
         let obj = tx
             .create_object(
                 None,
-                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap();
+        assert_eq!(tx.get_max_object().unwrap(), obj);
+    }
+
+    #[test]
+    fn test_named_sequences() {
+        let db = test_db();
+        let tx = db.start_transaction();
+        assert_eq!(tx.get_named_sequence("ids"), 0);
+        assert_eq!(tx.next_named_sequence("ids"), 1);
+        assert_eq!(tx.next_named_sequence("ids"), 2);
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // The value carries over to the next transaction, and isn't undone by a rollback.
+        let tx = db.start_transaction();
+        assert_eq!(tx.get_named_sequence("ids"), 2);
+        assert_eq!(tx.next_named_sequence("ids"), 3);
+        tx.rollback().unwrap();
+
+        let tx = db.start_transaction();
+        assert_eq!(tx.get_named_sequence("ids"), 3);
+        assert_eq!(tx.next_named_sequence("ids"), 4);
+        // Sequences are independent of one another.
+        assert_eq!(tx.next_named_sequence("other"), 1);
+        assert_eq!(tx.get_named_sequence("ids"), 4);
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+    }
+
+    #[test]
+    fn test_replay_change_log() {
+        let source = test_db();
+        let changes = source.follow_changes();
+
+        let mut tx = source.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(None, ObjAttrs::new(NOTHING, a, a, BitEnum::new(), "b"))
+            .unwrap();
+        tx.define_property(
+            &a,
+            &a,
+            Symbol::mk("colour"),
+            &a,
+            BitEnum::new(),
+            Some(v_str("red")),
+        )
+        .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let mut tx = source.start_transaction();
+        let c = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "c"),
+            )
+            .unwrap();
+        tx.set_object_name(&b, "renamed".to_string()).unwrap();
+        tx.recycle_object(&c).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // A read-only commit doesn't produce a log entry.
+        let tx = source.start_transaction();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let logs: Vec<_> = (0..2)
+            .map(|_| changes.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert!(changes.try_recv().is_err());
+
+        let replica = test_db();
+        for log in &logs {
+            assert_eq!(replica.replay(log), Ok(CommitResult::Success));
+        }
+
+        let source_tx = source.start_transaction();
+        let mut tx = replica.start_transaction();
+        assert_eq!(tx.get_object_name(&a).unwrap(), "a");
+        assert_eq!(tx.get_object_name(&b).unwrap(), "renamed");
+        assert_eq!(tx.get_object_parent(&b).unwrap(), a);
+        assert_eq!(tx.get_object_location(&b).unwrap(), a);
+        assert_eq!(
+            tx.get_object_children(&a).unwrap(),
+            source_tx.get_object_children(&a).unwrap()
+        );
+        assert_eq!(
+            tx.get_object_contents(&a).unwrap(),
+            source_tx.get_object_contents(&a).unwrap()
+        );
+        let (_, value, _, _) = tx.resolve_property(&b, Symbol::mk("colour")).unwrap();
+        assert_eq!(value, v_str("red"));
+        assert!(!tx.object_valid(&c).unwrap());
+        assert_eq!(
+            tx.get_max_object().unwrap(),
+            source_tx.get_max_object().unwrap()
+        );
+
+        // The recycled number is free for reuse on the replica, just as on the source.
+        assert_eq!(tx.create_object(None, Default::default()).unwrap(), c);
+    }
+
+    #[test]
+    fn test_compact_reclaims_churn() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let blob = tx
+            .define_property(
+                &a,
+                &a,
+                Symbol::mk("blob"),
+                &a,
+                BitEnum::new(),
+                Some(v_str("")),
+            )
+            .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // Overwrite the same value many times, and create and recycle a batch of objects, leaving
+        // old versions and deletions behind in storage.
+        for i in 0..200 {
+            let mut tx = db.start_transaction();
+            tx.set_property(&a, blob, v_str(&format!("{i}").repeat(4096)))
+                .unwrap();
+            let junk = tx
+                .create_object(
+                    None,
+                    ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), &"x".repeat(1024)),
+                )
+                .unwrap();
+            tx.recycle_object(&junk).unwrap();
+            assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        }
+        // Writes to storage happen in the background, after the commit.
+        std::thread::sleep(Duration::from_millis(500));
+
+        let tx = db.start_transaction();
+        let (before, after) = tx.compact().unwrap();
+        assert!(after < before, "{after} should be less than {before}");
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // Nothing visible was lost.
+        let tx = db.start_transaction();
+        let (_, value, _, _) = tx.resolve_property(&a, Symbol::mk("blob")).unwrap();
+        assert_eq!(value, v_str(&"199".repeat(4096)));
+    }
+
+    #[test]
+    fn test_lifecycle_events() {
+        let db = test_db();
+        let events = db.follow_lifecycle_events();
+
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        tx.add_object_verb(
+            &a,
+            &a,
+            vec![Symbol::mk_case_insensitive("test")],
+            ProgramType::MooR(Program::new()),
+            BitEnum::new_with(VerbFlag::Exec),
+            VerbArgsSpec::this_none_this(),
+        )
+        .unwrap();
+        // Nothing is sent until the transaction commits.
+        assert!(events.try_recv().is_err());
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        assert_eq!(
+            events.recv_timeout(Duration::from_secs(5)).unwrap(),
+            LifecycleEvent {
+                obj: a,
+                kind: LifecycleKind::ObjectCreated
+            }
+        );
+        assert_eq!(
+            events.recv_timeout(Duration::from_secs(5)).unwrap(),
+            LifecycleEvent {
+                obj: a,
+                kind: LifecycleKind::VerbAdded
+            }
+        );
+
+        // Nor by one which is rolled back.
+        let mut tx = db.start_transaction();
+        tx.create_object(
+            None,
+            ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "b"),
+        )
+        .unwrap();
+        tx.rollback().unwrap();
+
+        let mut tx = db.start_transaction();
+        tx.recycle_object(&a).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        assert_eq!(
+            events.recv_timeout(Duration::from_secs(5)).unwrap(),
+            LifecycleEvent {
+                obj: a,
+                kind: LifecycleKind::ObjectRecycled
+            }
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_backup_restore() {
+        let source = test_db();
+        let mut tx = source.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(None, ObjAttrs::new(NOTHING, a, a, BitEnum::new(), "b"))
+            .unwrap();
+        let colour = tx
+            .define_property(
+                &a,
+                &a,
+                Symbol::mk("colour"),
+                &a,
+                BitEnum::new(),
+                Some(v_str("red")),
+            )
+            .unwrap();
+        let recycled = tx.create_object(None, Default::default()).unwrap();
+        tx.recycle_object(&recycled).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // The backup is of everything committed when it is taken, including commits made since
+        // the transaction taking it started, but not that transaction's own changes.
+        let mut backup_tx = source.start_transaction();
+        backup_tx
+            .set_object_name(&a, "uncommitted".to_string())
+            .unwrap();
+        let mut tx = source.start_transaction();
+        tx.set_object_name(&b, "renamed".to_string()).unwrap();
+        tx.set_property(&b, colour, v_str("blue")).unwrap();
+        tx.set_object_location(&b, &NOTHING).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        assert_eq!(backup_tx.next_named_sequence("ids"), 1);
+        assert_eq!(backup_tx.next_named_sequence("ids"), 2);
+
+        let snapshot = backup_tx.snapshot().unwrap();
+        let committed = source.start_transaction();
+
+        // Commits made after the backup is requested, but before it is written out, don't wait
+        // for it and aren't in it.
+        let mut tx = source.start_transaction();
+        tx.set_object_name(&a, "later".to_string()).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("backup");
+        let written = write_backup(&mut File::create(&path).unwrap(), snapshot).unwrap();
+
+        let replica = test_db();
+        assert_eq!(replica.restore(&path).unwrap(), written);
+        assert_eq!(replica.start_transaction().get_named_sequence("ids"), 2);
+
+        let mut tx = replica.start_transaction();
+        for obj in [a, b] {
+            assert_eq!(
+                tx.get_object_name(&obj).unwrap(),
+                committed.get_object_name(&obj).unwrap()
+            );
+            assert_eq!(
+                tx.get_object_parent(&obj).unwrap(),
+                committed.get_object_parent(&obj).unwrap()
+            );
+            assert_eq!(
+                tx.get_object_location(&obj).unwrap(),
+                committed.get_object_location(&obj).unwrap()
+            );
+            assert_eq!(
+                tx.get_object_contents(&obj).unwrap(),
+                committed.get_object_contents(&obj).unwrap()
+            );
+        }
+        assert_eq!(tx.get_object_name(&a).unwrap(), "a");
+        assert_eq!(tx.get_object_name(&b).unwrap(), "renamed");
+        assert_eq!(tx.get_object_location(&b).unwrap(), NOTHING);
+        let (_, value, _, _) = tx.resolve_property(&b, Symbol::mk("colour")).unwrap();
+        assert_eq!(value, v_str("blue"));
+        assert_eq!(
+            tx.get_max_object().unwrap(),
+            committed.get_max_object().unwrap()
+        );
+        assert_eq!(
+            tx.create_object(None, Default::default()).unwrap(),
+            recycled
+        );
+    }
+
+    #[test]
+    fn test_backup_path() {
+        let db = test_db();
+        let tx = db.start_transaction();
+        assert!(matches!(
+            tx.backup_path("backup"),
+            Err(WorldStateError::DatabaseError(_))
+        ));
+
+        let dir = TempDir::new().unwrap();
+        let config = DatabaseConfig {
+            backup_directory: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let db = MoorDB::open(None, config).0;
+        let tx = db.start_transaction();
+        assert_eq!(
+            tx.backup_path("nightly.bak").unwrap(),
+            dir.path().join("nightly.bak")
+        );
+        for name in [
+            "",
+            ".",
+            "..",
+            "/etc/passwd",
+            "../escape",
+            "sub/dir",
+            "./here",
+            "trailing/",
+        ] {
+            assert!(
+                matches!(tx.backup_path(name), Err(WorldStateError::DatabaseError(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_object_summary() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let parent = tx.create_object(None, Default::default()).unwrap();
+        let room = tx.create_object(None, Default::default()).unwrap();
+        let obj = tx
+            .create_object(
+                None,
+                ObjAttrs::new(
+                    parent,
+                    parent,
+                    room,
+                    BitEnum::new_with(ObjFlag::Read) | ObjFlag::Fertile,
+                    "thing",
+                ),
             )
             .unwrap();
-        assert_eq!(tx.get_max_object().unwrap(), obj);
+        tx.add_object_verb(
+            &obj,
+            &obj,
+            vec![Symbol::mk_case_insensitive("look")],
+            ProgramType::MooR(Program::new()),
+            BitEnum::new_with(VerbFlag::Exec),
+            VerbArgsSpec::this_none_this(),
+        )
+        .unwrap();
+        tx.define_property(
+            &obj,
+            &obj,
+            Symbol::mk("colour"),
+            &obj,
+            BitEnum::new(),
+            Some(v_str("red")),
+        )
+        .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        let summary = tx.get_object_summary(&obj).unwrap();
+        assert_eq!(summary.name, tx.get_object_name(&obj).unwrap());
+        assert_eq!(summary.owner, tx.get_object_owner(&obj).unwrap());
+        assert_eq!(summary.parent, tx.get_object_parent(&obj).unwrap());
+        assert_eq!(summary.location, tx.get_object_location(&obj).unwrap());
+        assert_eq!(summary.flags, tx.get_object_flags(&obj).unwrap());
+        assert_eq!(summary.verbs, Some(tx.get_verbs(&obj).unwrap()));
+        assert_eq!(summary.properties, Some(tx.get_properties(&obj).unwrap()));
+        assert_eq!(summary.owner, parent);
+        assert_eq!(summary.location, room);
+        assert_eq!(summary.verbs.unwrap().len(), 1);
+
+        assert_eq!(
+            tx.get_object_summary(&Obj::mk_id(1000)),
+            Err(WorldStateError::ObjectNotFound(ObjectRef::Id(Obj::mk_id(
+                1000
+            ))))
+        );
     }
 
     #[test]
@@ -1391,6 +2660,56 @@ mod tests {
         );
     }
 
+    /// Recycling an object in the middle of a hierarchy hands its children to its parent, which
+    /// must keep them in its children list.
+    #[test]
+    fn test_recycle_middle_object_reparents_children() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(Obj::mk_id(1), NOTHING, NOTHING, BitEnum::all(), "a"),
+            )
+            .unwrap();
+        let b = tx
+            .create_object(
+                None,
+                ObjAttrs::new(Obj::mk_id(1), a, NOTHING, BitEnum::all(), "b"),
+            )
+            .unwrap();
+        let c = tx
+            .create_object(
+                None,
+                ObjAttrs::new(Obj::mk_id(1), b, NOTHING, BitEnum::all(), "c"),
+            )
+            .unwrap();
+        let d = tx
+            .create_object(
+                None,
+                ObjAttrs::new(Obj::mk_id(1), b, NOTHING, BitEnum::all(), "d"),
+            )
+            .unwrap();
+
+        tx.recycle_object(&b).unwrap();
+
+        assert!(
+            tx.get_object_children(&a)
+                .unwrap()
+                .is_same(ObjSet::from_items(&[c, d]))
+        );
+        assert_eq!(tx.get_object_parent(&c).unwrap(), a);
+        assert_eq!(tx.get_object_parent(&d).unwrap(), a);
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        assert!(
+            tx.get_object_children(&a)
+                .unwrap()
+                .is_same(ObjSet::from_items(&[c, d]))
+        );
+    }
+
     #[test]
     fn test_regression_missing_child_propdef() {
         let db = test_db();
@@ -1454,6 +2773,107 @@ mod tests {
             .expect("Unable to get verb");
     }
 
+    #[test]
+    fn test_recycle_removes_verb_programs() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let oid = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "test"),
+            )
+            .unwrap();
+        tx.add_object_verb(
+            &oid,
+            &oid,
+            vec![Symbol::mk_case_insensitive("test")],
+            ProgramType::MooR(Program::new()),
+            BitEnum::new_with(VerbFlag::Exec),
+            VerbArgsSpec::this_none_this(),
+        )
+        .unwrap();
+        let uuid = tx.get_verbs(&oid).unwrap().iter().next().unwrap().uuid();
+        assert!(tx.get_verb_program(&oid, uuid).is_ok());
+
+        tx.recycle_object(&oid).unwrap();
+        assert!(!tx.object_valid(&oid).unwrap());
+        assert!(tx.get_verb_program(&oid, uuid).is_err());
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        assert!(tx.get_verb_program(&oid, uuid).is_err());
+    }
+
+    #[test]
+    fn test_counts_match_full_fetches() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+
+        // Nothing defined yet.
+        assert_eq!(tx.count_verbs(&a).unwrap(), 0);
+        assert_eq!(tx.count_properties(&a).unwrap(), 0);
+        assert_eq!(tx.count_children(&a).unwrap(), 0);
+
+        for name in ["one", "two", "three"] {
+            tx.add_object_verb(
+                &a,
+                &a,
+                vec![Symbol::mk_case_insensitive(name)],
+                ProgramType::MooR(Program::new()),
+                BitEnum::new_with(VerbFlag::Exec),
+                VerbArgsSpec::this_none_this(),
+            )
+            .unwrap();
+        }
+        for name in ["p1", "p2"] {
+            tx.define_property(
+                &a,
+                &a,
+                Symbol::mk_case_insensitive(name),
+                &NOTHING,
+                BitEnum::new(),
+                Some(v_int(1)),
+            )
+            .unwrap();
+        }
+        for name in ["b", "c", "d", "e"] {
+            tx.create_object(
+                None,
+                ObjAttrs::new(NOTHING, a, NOTHING, BitEnum::new(), name),
+            )
+            .unwrap();
+        }
+
+        let check = |tx: &crate::ws_transaction::WorldStateTransaction| {
+            assert_eq!(tx.count_verbs(&a).unwrap(), tx.get_verbs(&a).unwrap().len());
+            assert_eq!(
+                tx.count_properties(&a).unwrap(),
+                tx.get_properties(&a).unwrap().len()
+            );
+            assert_eq!(
+                tx.count_children(&a).unwrap(),
+                tx.get_object_children(&a).unwrap().len()
+            );
+        };
+        check(&tx);
+        assert_eq!(tx.count_verbs(&a).unwrap(), 3);
+        assert_eq!(tx.count_properties(&a).unwrap(), 2);
+        assert_eq!(tx.count_children(&a).unwrap(), 4);
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // And from a fresh transaction, reading through to the committed state.
+        let tx = db.start_transaction();
+        check(&tx);
+        assert_eq!(tx.count_verbs(&a).unwrap(), 3);
+        assert_eq!(tx.count_children(&a).unwrap(), 4);
+    }
+
     #[test]
     fn test_create_immediate_destroy() {
         // equiv of recycle(create($nothing));
@@ -1511,4 +2931,193 @@ mod tests {
             v_str("tx2_value")
         );
     }
+
+    #[test]
+    fn test_concurrent_property_adds() {
+        let db = test_db();
+
+        let mut tx = db.start_transaction();
+        let obj = tx.create_object(None, Default::default()).unwrap();
+        let prop_uuid = tx
+            .define_property(
+                &obj,
+                &obj,
+                Symbol::mk("counter"),
+                &obj,
+                BitEnum::new(),
+                Some(v_int(0)),
+            )
+            .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // Every transaction adds from the same starting value, and none of them conflict.
+        let mut txs: Vec<_> = (0..10).map(|_| db.start_transaction()).collect();
+        for tx in &mut txs {
+            assert_eq!(
+                tx.add_to_property(&obj, prop_uuid, v_int(1)).unwrap(),
+                Some(v_int(1))
+            );
+            assert_eq!(
+                tx.add_to_property(&obj, prop_uuid, v_int(2)).unwrap(),
+                Some(v_int(3))
+            );
+        }
+        for tx in txs {
+            assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        }
+        let tx = db.start_transaction();
+        assert_eq!(
+            tx.retrieve_property(&obj, prop_uuid).unwrap().0,
+            Some(v_int(30))
+        );
+        tx.rollback().unwrap();
+
+        // An add lands on top of a set that committed first...
+        let mut adder = db.start_transaction();
+        let mut setter = db.start_transaction();
+        adder
+            .add_to_property(&obj, prop_uuid, v_int(5))
+            .unwrap()
+            .unwrap();
+        setter.set_property(&obj, prop_uuid, v_int(100)).unwrap();
+        assert_eq!(setter.commit(), Ok(CommitResult::Success));
+
+        // ... while a set made from the value the add didn't see conflicts, rather than losing it.
+        let mut late_setter = db.start_transaction();
+        late_setter.set_property(&obj, prop_uuid, v_int(0)).unwrap();
+        assert_eq!(adder.commit(), Ok(CommitResult::Success));
+        assert_eq!(late_setter.commit(), Ok(CommitResult::ConflictRetry));
+
+        let tx = db.start_transaction();
+        assert_eq!(
+            tx.retrieve_property(&obj, prop_uuid).unwrap().0,
+            Some(v_int(105))
+        );
+        tx.rollback().unwrap();
+
+        // Values which don't add aren't merged.
+        let mut tx = db.start_transaction();
+        assert_eq!(
+            tx.add_to_property(&obj, prop_uuid, v_str("one")).unwrap(),
+            None
+        );
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn test_object_generation_bumps_on_verb_edit() {
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        assert_eq!(tx.get_object_generation(&a).unwrap(), 0);
+        tx.add_object_verb(
+            &a,
+            &a,
+            vec![Symbol::mk_case_insensitive("test")],
+            ProgramType::MooR(Program::new()),
+            BitEnum::new_with(VerbFlag::Exec),
+            VerbArgsSpec::this_none_this(),
+        )
+        .unwrap();
+        let [p1, p2] = ["p1", "p2"].map(|name| {
+            tx.define_property(
+                &a,
+                &a,
+                Symbol::mk_case_insensitive(name),
+                &NOTHING,
+                BitEnum::new(),
+                Some(v_int(1)),
+            )
+            .unwrap()
+        });
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // However much changed, that was one commit.
+        let tx = db.start_transaction();
+        assert_eq!(tx.get_object_generation(&a).unwrap(), 1);
+        let uuid = tx.get_verbs(&a).unwrap().iter().next().unwrap().uuid();
+        tx.rollback().unwrap();
+
+        // Editing the verb bumps it.
+        let mut tx = db.start_transaction();
+        tx.update_verb(
+            &a,
+            uuid,
+            VerbAttrs {
+                definer: None,
+                owner: None,
+                names: Some(vec![Symbol::mk_case_insensitive("test2")]),
+                flags: None,
+                args_spec: None,
+                program: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // Renaming the object doesn't.
+        let mut tx = db.start_transaction();
+        tx.set_object_name(&a, "b".to_string()).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        assert_eq!(tx.get_object_generation(&a).unwrap(), 2);
+        tx.rollback().unwrap();
+
+        // Concurrent edits to the same object's properties add up rather than conflicting over
+        // the generation.
+        let mut tx1 = db.start_transaction();
+        let mut tx2 = db.start_transaction();
+        tx1.set_property(&a, p1, v_int(2)).unwrap();
+        tx2.set_property(&a, p2, v_int(2)).unwrap();
+        assert_eq!(tx1.commit(), Ok(CommitResult::Success));
+        assert_eq!(tx2.commit(), Ok(CommitResult::Success));
+
+        let tx = db.start_transaction();
+        assert_eq!(tx.get_object_generation(&a).unwrap(), 4);
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn test_relation_stats_count_property_writes() {
+        let propvalue_writes = || {
+            relation_stats()
+                .into_iter()
+                .find(|(name, _)| name.as_str() == "object_propvalues")
+                .map(|(_, stats)| stats.writes.sum())
+                .unwrap()
+        };
+
+        let db = test_db();
+        let mut tx = db.start_transaction();
+        let a = tx
+            .create_object(
+                None,
+                ObjAttrs::new(NOTHING, NOTHING, NOTHING, BitEnum::new(), "a"),
+            )
+            .unwrap();
+        let uuid = tx
+            .define_property(
+                &a,
+                &a,
+                Symbol::mk_case_insensitive("p"),
+                &NOTHING,
+                BitEnum::new(),
+                Some(v_int(1)),
+            )
+            .unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+
+        // Other tests share the counters, so only check that ours moved it.
+        let before = propvalue_writes();
+        let mut tx = db.start_transaction();
+        tx.set_property(&a, uuid, v_int(2)).unwrap();
+        assert_eq!(tx.commit(), Ok(CommitResult::Success));
+        assert!(propvalue_writes() > before);
+    }
 }
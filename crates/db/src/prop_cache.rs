@@ -17,6 +17,8 @@ use moor_var::{Obj, Symbol};
 use std::hash::BuildHasherDefault;
 use std::sync::Mutex;
 
+use crate::verb_cache::ForkedCache;
+
 pub(crate) struct PropResolutionCache {
     inner: Mutex<Inner>,
 }
@@ -99,3 +101,29 @@ impl PropResolutionCache {
         inner.first_parent_with_props_cache.insert(*obj, parent);
     }
 }
+
+impl ForkedCache for PropResolutionCache {
+    fn fork(&self) -> Box<Self> {
+        PropResolutionCache::fork(self)
+    }
+
+    fn flush(&self) {
+        PropResolutionCache::flush(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        PropResolutionCache::has_changed(self)
+    }
+
+    fn orig_version(&self) -> i64 {
+        self.inner.lock().unwrap().orig_version
+    }
+
+    fn version(&self) -> i64 {
+        self.inner.lock().unwrap().version
+    }
+
+    fn was_flushed(&self) -> bool {
+        self.inner.lock().unwrap().flushed
+    }
+}
@@ -14,7 +14,8 @@
 mod relation;
 mod relation_tx;
 
-pub use relation::Relation;
+pub use relation::{Relation, RelationStats, relation_stats};
+pub(crate) use relation_tx::OpType;
 pub use relation_tx::{RelationTransaction, WorkingSet};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd)]
@@ -25,6 +26,10 @@ pub struct Tx {
     pub ts: Timestamp,
 }
 
+/// Combines a tuple's value with a delta, for relations which support commutative merges.
+/// Returns None if the two don't combine.
+pub type MergeFn<Codomain> = fn(&Codomain, &Codomain) -> Option<Codomain>;
+
 #[derive(Debug, Eq, PartialEq, thiserror::Error)]
 pub enum Error {
     #[error("Duplicate key")]
@@ -51,10 +56,22 @@ pub trait Provider<Domain, Codomain>: Clone {
     where
         F: Fn(&Domain, &Codomain) -> bool;
 
+    /// Ask for a point-in-time view of the store, as of every `put` and `del` made so far. It
+    /// arrives on the returned channel once those have been written; later writes don't wait for
+    /// it, and don't show up in it.
+    fn snapshot(&self) -> Result<oneshot::Receiver<Box<dyn TupleSnapshot>>, Error>;
+
     // Stop any background processing that is running on this provider.
     fn stop(&self) -> Result<(), Error>;
 }
 
+/// A provider's stored tuples as of some point in time, in their encoded form.
+pub trait TupleSnapshot: Send {
+    /// Call `f` with the domain and codomain bytes of each tuple in turn, stopping at the first
+    /// error.
+    fn for_each(&self, f: &mut dyn FnMut(&[u8], &[u8]) -> Result<(), Error>) -> Result<(), Error>;
+}
+
 /// A `SizedCache` is a cache that has a maximum size in bytes, and will attempt to evict entries
 /// when the cache size exceeds the maximum size.
 pub trait SizedCache {
@@ -71,4 +88,8 @@ pub trait Canonical<Domain, Codomain> {
     fn scan<F>(&self, f: &F) -> Result<Vec<(Timestamp, Domain, Codomain, usize)>, Error>
     where
         F: Fn(&Domain, &Codomain) -> bool;
+    /// Combine `value` with `delta` using the relation's merge function, if it has one.
+    fn merge(&self, value: &Codomain, delta: &Codomain) -> Option<Codomain>;
+    /// Count a lookup made through a transaction against this source.
+    fn record_read(&self);
 }
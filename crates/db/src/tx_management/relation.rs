@@ -13,16 +13,69 @@
 
 //! Global cache is a cache that acts as an origin for all local caches.
 
-use crate::tx_management::relation_tx::{OpType, RelationTransaction, WorkingSet};
-use crate::tx_management::{Canonical, Error, Provider, SizedCache, Timestamp, Tx};
+use crate::tx_management::relation_tx::{Op, OpType, RelationTransaction, WorkingSet};
+use crate::tx_management::{
+    Canonical, Error, MergeFn, Provider, SizedCache, Timestamp, TupleSnapshot, Tx,
+};
 use ahash::AHasher;
+use fast_counter::ConcurrentCounter;
+use lazy_static::lazy_static;
 use minstant::Instant;
 use moor_var::Symbol;
+use std::collections::HashMap;
 use std::hash::{BuildHasherDefault, Hash};
 use std::sync::{Arc, RwLock, RwLockWriteGuard};
 use std::time::Duration;
 use tracing::warn;
 
+/// Running totals of the traffic through a relation, for spotting hot relations.
+pub struct RelationStats {
+    /// Tuple lookups made by transactions.
+    pub reads: ConcurrentCounter,
+    /// Tuples inserted, updated, or deleted by committed transactions.
+    pub writes: ConcurrentCounter,
+    /// Commits which failed their check against this relation.
+    pub conflicts: ConcurrentCounter,
+}
+
+impl RelationStats {
+    fn new() -> Self {
+        Self {
+            reads: ConcurrentCounter::new(0),
+            writes: ConcurrentCounter::new(0),
+            conflicts: ConcurrentCounter::new(0),
+        }
+    }
+}
+
+lazy_static! {
+    /// Stats are kept per relation name for the life of the process, in the same way as the
+    /// other perf counters, so that they can be reported without a handle on the database.
+    static ref RELATION_STATS: RwLock<HashMap<Symbol, Arc<RelationStats>>> =
+        RwLock::new(HashMap::new());
+}
+
+fn stats_for(relation_name: Symbol) -> Arc<RelationStats> {
+    RELATION_STATS
+        .write()
+        .unwrap()
+        .entry(relation_name)
+        .or_insert_with(|| Arc::new(RelationStats::new()))
+        .clone()
+}
+
+/// The stats for every relation, sorted by relation name.
+pub fn relation_stats() -> Vec<(Symbol, Arc<RelationStats>)> {
+    let mut stats: Vec<_> = RELATION_STATS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(name, stats)| (*name, stats.clone()))
+        .collect();
+    stats.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+    stats
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Entry<T: Clone + PartialEq> {
     pub ts: Timestamp,
@@ -44,6 +97,11 @@ where
     index: Arc<RwLock<RelationIndex<Domain, Codomain>>>,
 
     source: Arc<Source>,
+
+    /// How deltas are combined with values, if this relation supports commutative merges.
+    merge: Option<MergeFn<Codomain>>,
+
+    stats: Arc<RelationStats>,
 }
 
 impl<Domain, Codomain, Source> Relation<Domain, Codomain, Source>
@@ -60,9 +118,18 @@ where
                 used_bytes: 0,
             })),
             source: provider,
+            merge: None,
+            stats: stats_for(relation_name),
         }
     }
 
+    /// Allow transactions to merge deltas into this relation's values with `merge`. Concurrent
+    /// merges into the same tuple don't conflict with each other at commit.
+    pub fn with_merge(mut self, merge: MergeFn<Codomain>) -> Self {
+        self.merge = Some(merge);
+        self
+    }
+
     pub fn write_lock(&self) -> RwLockWriteGuard<RelationIndex<Domain, Codomain>> {
         self.index.write().unwrap()
     }
@@ -92,6 +159,8 @@ where
     index: RelationIndex<Domain, Codomain>,
     relation_name: Symbol,
     source: Arc<P>,
+    merge: Option<MergeFn<Codomain>>,
+    stats: Arc<RelationStats>,
     dirty: bool,
 }
 
@@ -116,14 +185,26 @@ where
     /// Check the cache for conflicts with the given working set.
     /// Holds a lock on the cache while checking.
     /// This is the first phase of transaction commit, and does not mutate the contents of
-    /// the cache.
-    pub fn check(&mut self, working_set: &WorkingSet<Domain, Codomain>) -> Result<(), Error> {
+    /// the cache. Merges in the working set are resolved here into plain updates of the merged
+    /// value, so that what gets applied (and logged) is the final state of the tuple.
+    pub fn check(&mut self, working_set: &mut WorkingSet<Domain, Codomain>) -> Result<(), Error> {
+        let result = self.check_working_set(working_set);
+        if result == Err(Error::Conflict) {
+            self.stats.conflicts.add(1);
+        }
+        result
+    }
+
+    fn check_working_set(
+        &mut self,
+        working_set: &mut WorkingSet<Domain, Codomain>,
+    ) -> Result<(), Error> {
         let start_time = Instant::now();
         let mut last_check_time = start_time;
         let total_ops = working_set.len();
         self.dirty = !working_set.is_empty();
         // Check phase first.
-        for (n, (domain, op, _)) in working_set.iter().enumerate() {
+        for (n, (domain, op, codomain)) in working_set.iter_mut().enumerate() {
             if last_check_time.elapsed() > Duration::from_secs(5) {
                 warn!(
                     "Long check time for {}; running for {}s; {n}/{total_ops} checked",
@@ -132,6 +213,10 @@ where
                 );
                 last_check_time = Instant::now();
             }
+            if op.operation == OpType::Merge {
+                self.resolve_merge(domain, op, codomain)?;
+                continue;
+            }
             // Check local to see if we have one first, to see if there's a conflict.
             if let Some(local_entry) = self.index.entries.get(domain) {
                 // If what we have is an insert, and there's something already there, that's a
@@ -175,6 +260,49 @@ where
         Ok(())
     }
 
+    /// Combine a merge's delta with the tuple's current value, whoever last wrote it, turning
+    /// the operation into an update of the combined value. Only a tuple which has gone away, or
+    /// a value the delta no longer combines with, is a conflict.
+    fn resolve_merge(
+        &mut self,
+        domain: &Domain,
+        op: &mut Op,
+        codomain: &mut Option<Entry<Codomain>>,
+    ) -> Result<(), Error> {
+        let (Some(merge), Some(delta)) = (self.merge, codomain.as_mut()) else {
+            return Err(Error::Conflict);
+        };
+        let (ts, value) = match self.index.entries.get(domain) {
+            Some(entry) => (entry.ts, entry.value.clone()),
+            None => {
+                let Some((ts, value, size_bytes)) = self.source.get(domain)? else {
+                    return Err(Error::Conflict);
+                };
+                let entry = Entry {
+                    ts,
+                    hits: 0,
+                    value: value.clone(),
+                    size_bytes,
+                };
+                self.index.entries.insert(domain.clone(), entry);
+                (ts, value)
+            }
+        };
+        let Some(merged) = merge(&value, &delta.value) else {
+            return Err(Error::Conflict);
+        };
+        delta.value = merged;
+        op.read_ts = ts;
+        op.operation = OpType::Update;
+        // The value may have been written since by a transaction which started after ours. The
+        // merged value has to look newer than that, or anyone who read it in the meantime
+        // wouldn't see their write conflict with ours.
+        if ts >= op.write_ts {
+            op.write_ts = Timestamp(ts.0 + 1);
+        }
+        Ok(())
+    }
+
     /// Apply the given working set to the cache.
     /// This is the final phase of the transaction commit process, and mutates the cache and
     /// requests mutation into the Source.
@@ -208,8 +336,12 @@ where
                     self.index.insert_tombstone(op.write_ts, domain.clone());
                     self.source.del(op.write_ts, &domain).unwrap();
                 }
+                OpType::Merge => {
+                    unreachable!("Merge operations are resolved to updates during check")
+                }
             }
         }
+        self.stats.writes.add(total_ops as isize);
         Ok(())
     }
 
@@ -237,6 +369,8 @@ where
             index: index.clone(),
             relation_name: self.relation_name,
             source: self.source.clone(),
+            merge: self.merge,
+            stats: self.stats.clone(),
             dirty: false,
         }
     }
@@ -246,6 +380,13 @@ where
         let index = self.index.read().unwrap();
         index.used_bytes
     }
+
+    /// Ask for a point-in-time view of the relation as of the last commit applied to it (see
+    /// `Provider::snapshot`). Views requested together from the commit processing thread all
+    /// stop at the same commit.
+    pub fn snapshot(&self) -> Result<oneshot::Receiver<Box<dyn TupleSnapshot>>, Error> {
+        self.source.snapshot()
+    }
 }
 
 impl<Domain, Codomain> RelationIndex<Domain, Codomain>
@@ -319,6 +460,14 @@ where
         }
     }
 
+    fn merge(&self, value: &Codomain, delta: &Codomain) -> Option<Codomain> {
+        self.merge.and_then(|merge| merge(value, delta))
+    }
+
+    fn record_read(&self) {
+        self.stats.reads.add(1);
+    }
+
     fn scan<F>(&self, predicate: &F) -> Result<Vec<(Timestamp, Domain, Codomain, usize)>, Error>
     where
         F: Fn(&Domain, &Codomain) -> bool,
@@ -378,6 +527,20 @@ mod tests {
     #[derive(Debug, Clone, PartialEq, Eq)]
     struct TestCodomain(u64);
 
+    struct TestSnapshot(Vec<(u64, u64)>);
+
+    impl TupleSnapshot for TestSnapshot {
+        fn for_each(
+            &self,
+            f: &mut dyn FnMut(&[u8], &[u8]) -> Result<(), Error>,
+        ) -> Result<(), Error> {
+            for (domain, codomain) in &self.0 {
+                f(&domain.to_le_bytes(), &codomain.to_le_bytes())?;
+            }
+            Ok(())
+        }
+    }
+
     #[derive(Clone)]
     struct TestProvider {
         data: Arc<Mutex<HashMap<TestDomain, TestCodomain>>>,
@@ -428,6 +591,15 @@ mod tests {
                 .collect())
         }
 
+        fn snapshot(&self) -> Result<oneshot::Receiver<Box<dyn TupleSnapshot>>, Error> {
+            let data = self.data.lock().unwrap();
+            let tuples = data.iter().map(|(k, v)| (k.0, v.0)).collect();
+            let (send, recv) = oneshot::channel();
+            send.send(Box::new(TestSnapshot(tuples)) as Box<dyn TupleSnapshot>)
+                .ok();
+            Ok(recv)
+        }
+
         fn stop(&self) -> Result<(), Error> {
             Ok(())
         }
@@ -449,10 +621,10 @@ mod tests {
         lc.insert(domain.clone(), codomain.clone(), 16).unwrap();
         assert_eq!(lc.get(&domain).unwrap(), Some(codomain.clone()));
         assert_eq!(lc.get(&TestDomain(0)).unwrap(), Some(TestCodomain(0)));
-        let ws = lc.working_set();
+        let mut ws = lc.working_set();
 
         let mut cr = relation.begin_check();
-        cr.check(&ws).unwrap();
+        cr.check(&mut ws).unwrap();
         cr.apply(ws).unwrap();
         assert_eq!(relation.get(&domain).unwrap().unwrap().1, codomain.clone());
     }
@@ -477,11 +649,11 @@ mod tests {
         r_tx_a.insert(domain.clone(), codomain_a, 16).unwrap();
         let mut r_tx_b = relation.clone().start(&tx_b);
         r_tx_b.insert(domain.clone(), codomain_b, 16).unwrap();
-        let ws_a = r_tx_a.working_set();
-        let ws_b = r_tx_b.working_set();
+        let mut ws_a = r_tx_a.working_set();
+        let mut ws_b = r_tx_b.working_set();
         {
             let mut cr_a = relation.begin_check();
-            cr_a.check(&ws_a).unwrap();
+            cr_a.check(&mut ws_a).unwrap();
             cr_a.apply(ws_a).unwrap();
             let mut r = relation.index.write().unwrap();
             *r = cr_a.index;
@@ -490,7 +662,7 @@ mod tests {
             let mut cr_b = relation.begin_check();
 
             // This should fail because the first insert has already happened.
-            let check_result = cr_b.check(&ws_b);
+            let check_result = cr_b.check(&mut ws_b);
             assert!(matches!(check_result, Err(Error::Conflict)));
         }
     }
@@ -16,6 +16,7 @@ use crate::tx_management::{Canonical, Error, Timestamp, Tx};
 use ahash::AHasher;
 use indexmap::IndexMap;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::hash::{BuildHasherDefault, Hash};
 use std::sync::Arc;
 
@@ -43,6 +44,8 @@ where
 {
     operations: IndexMap<Domain, Op, BuildHasherDefault<AHasher>>,
     entries: im::HashMap<Domain, Entry<Codomain>, BuildHasherDefault<AHasher>>,
+    /// The accumulated delta for each tuple we've only merged into.
+    deltas: HashMap<Domain, Codomain, BuildHasherDefault<AHasher>>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -50,6 +53,8 @@ pub(crate) enum OpType {
     Insert,
     Update,
     Delete,
+    /// A commutative merge of a delta into the existing value; see `RelationTransaction::merge`.
+    Merge,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -76,6 +81,7 @@ where
         let inner = Inner {
             operations: IndexMap::default(),
             entries: canonical.clone(),
+            deltas: HashMap::default(),
         };
         RelationTransaction {
             tx,
@@ -150,13 +156,15 @@ where
             let read_ts = entry.ts;
             if let Some(old_entry) = index.operations.get_mut(domain) {
                 old_entry.operation = match old_entry.operation {
-                    OpType::Update => OpType::Update,
+                    OpType::Update | OpType::Merge => OpType::Update,
                     OpType::Delete => {
                         return Ok(None);
                     }
                     OpType::Insert => OpType::Insert,
                 };
                 old_entry.write_ts = self.tx.ts;
+                // An outright write replaces whatever we'd merged.
+                index.deltas.remove(domain);
             } else {
                 // We need to entry in the ops log which has to be "update" since we're updating.
                 index.operations.insert(
@@ -212,6 +220,62 @@ where
         Ok(Some(backing_value))
     }
 
+    /// Combine `delta` into the value for `domain` with the relation's merge function, returning
+    /// the combined value as this transaction now sees it.
+    /// Unless we've already written the tuple outright, this is logged as a merge rather than an
+    /// update: at commit the delta is combined with whatever the value is by then, so concurrent
+    /// merges into the same tuple don't conflict. Returns None if there's no such tuple, or the
+    /// values don't combine.
+    pub fn merge(&mut self, domain: &Domain, delta: Codomain) -> Result<Option<Codomain>, Error> {
+        // Make sure we hold the tuple locally.
+        if self.get(domain)?.is_none() {
+            return Ok(None);
+        }
+        let mut index = self.index.borrow_mut();
+        let index = &mut *index;
+        let existing_op = index.operations.get(domain).map(|op| op.operation);
+        if existing_op == Some(OpType::Delete) {
+            return Ok(None);
+        }
+        let entry = index
+            .entries
+            .get_mut(domain)
+            .expect("Tuple should be present after get");
+        let Some(merged) = self.backing_source.merge(&entry.value, &delta) else {
+            return Ok(None);
+        };
+
+        match existing_op {
+            // We already have a write of our own for this, which stands, just with the new value.
+            Some(OpType::Insert | OpType::Update) => {}
+            // Merging again, so fold this delta into the one we have.
+            Some(_) => {
+                let accumulated = index
+                    .deltas
+                    .get(domain)
+                    .and_then(|previous| self.backing_source.merge(previous, &delta));
+                let Some(accumulated) = accumulated else {
+                    return Ok(None);
+                };
+                index.deltas.insert(domain.clone(), accumulated);
+            }
+            None => {
+                index.operations.insert(
+                    domain.clone(),
+                    Op {
+                        read_ts: entry.ts,
+                        write_ts: self.tx.ts,
+                        operation: OpType::Merge,
+                    },
+                );
+                index.deltas.insert(domain.clone(), delta);
+            }
+        }
+        entry.value = merged.clone();
+
+        Ok(Some(merged))
+    }
+
     pub fn upsert(
         &mut self,
         domain: Domain,
@@ -232,6 +296,7 @@ where
     }
 
     pub fn get(&self, domain: &Domain) -> Result<Option<Codomain>, Error> {
+        self.backing_source.record_read();
         let mut index = self.index.borrow_mut();
 
         // Check entries
@@ -257,6 +322,38 @@ where
         }
     }
 
+    /// Like `get`, but hands the value to `f` by reference rather than returning a copy, for
+    /// callers which only need to look at it (e.g. to take its length).
+    pub fn get_with<R, F>(&self, domain: &Domain, f: F) -> Result<Option<R>, Error>
+    where
+        F: FnOnce(&Codomain) -> R,
+    {
+        self.backing_source.record_read();
+        let mut index = self.index.borrow_mut();
+
+        // Check entries
+        if let Some(entry) = index.entries.get(domain) {
+            return Ok(Some(f(&entry.value)));
+        }
+
+        // Try upstream.
+        match self.backing_source.get(domain)? {
+            Some((read_ts, value, size_bytes)) if read_ts < self.tx.ts => {
+                let result = f(&value);
+                // Shove in local index.
+                let entry = Entry {
+                    ts: read_ts,
+                    hits: 0,
+                    value,
+                    size_bytes,
+                };
+                index.entries.insert(domain.clone(), entry);
+                Ok(Some(result))
+            }
+            _ => Ok(None),
+        }
+    }
+
     pub fn delete(&mut self, domain: &Domain) -> Result<Option<Codomain>, Error> {
         // This is like update, but we're removing.
         let mut index = self.index.borrow_mut();
@@ -268,13 +365,14 @@ where
             // If we do, we can update it to its new state.
             if let Some(old_entry) = index.operations.get_mut(domain) {
                 old_entry.operation = match old_entry.operation {
-                    OpType::Update => OpType::Delete,
+                    OpType::Update | OpType::Merge => OpType::Delete,
                     OpType::Delete => {
                         return Ok(None);
                     }
                     OpType::Insert => OpType::Delete,
                 };
                 old_entry.write_ts = self.tx.ts;
+                index.deltas.remove(domain);
                 return Ok(Some(old_value));
             } else {
                 // Upstream may or may not have this key to delete, but we'll log the operation
@@ -362,11 +460,42 @@ where
         Ok(results)
     }
 
+    /// Replace, as one batch, every tuple matching `predicate` with what `rewrite` makes of it (a
+    /// new value and its size), returning how many were replaced. The relation is scanned once,
+    /// and the scan leaves the matches in the local index, so nothing is fetched upstream again.
+    pub fn update_where<P, F>(&mut self, predicate: &P, rewrite: F) -> Result<usize, Error>
+    where
+        P: Fn(&Domain, &Codomain) -> bool,
+        F: Fn(&Domain, &Codomain) -> (Codomain, usize),
+    {
+        let mut count = 0;
+        for (domain, value) in self.scan(predicate)? {
+            let (value, size_bytes) = rewrite(&domain, &value);
+            if self.update(&domain, value, size_bytes)?.is_some() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// The domains this transaction has written to (inserted, updated, merged into or deleted).
+    pub fn written_domains(&self) -> Vec<Domain> {
+        self.index.borrow().operations.keys().cloned().collect()
+    }
+
     pub fn working_set(self) -> WorkingSet<Domain, Codomain> {
         let mut index = self.index.into_inner();
         let mut working_set = Vec::new();
         for (domain, op) in index.operations {
-            let codomain = index.entries.remove(&domain);
+            let mut codomain = index.entries.remove(&domain);
+            // A merge carries its delta, to be combined with the value at commit.
+            if let (OpType::Merge, Some(entry), Some(delta)) = (
+                op.operation,
+                codomain.as_mut(),
+                index.deltas.remove(&domain),
+            ) {
+                entry.value = delta;
+            }
             working_set.push((domain, op, codomain));
         }
         working_set
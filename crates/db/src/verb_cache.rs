@@ -17,6 +17,24 @@ use moor_var::{Obj, Symbol};
 use std::hash::BuildHasherDefault;
 use std::sync::Mutex;
 
+/// The per-transaction resolution caches are forked from a global copy when a transaction starts,
+/// and offered back to the commit thread when it ends. These are the bits the commit thread needs
+/// to decide whether the returned copy can safely replace the global one.
+pub(crate) trait ForkedCache {
+    /// A fresh copy of this cache.
+    fn fork(&self) -> Box<Self>;
+    /// Drop all entries.
+    fn flush(&self);
+    /// Whether this copy has been modified (filled or flushed) since it was forked.
+    fn has_changed(&self) -> bool;
+    /// The version of the cache this copy was forked from.
+    fn orig_version(&self) -> i64;
+    /// The current version of this copy.
+    fn version(&self) -> i64;
+    /// Whether this copy was flushed since it was forked.
+    fn was_flushed(&self) -> bool;
+}
+
 pub(crate) struct VerbResolutionCache {
     inner: Mutex<Inner>,
 }
@@ -99,6 +117,32 @@ impl VerbResolutionCache {
     }
 }
 
+impl ForkedCache for VerbResolutionCache {
+    fn fork(&self) -> Box<Self> {
+        VerbResolutionCache::fork(self)
+    }
+
+    fn flush(&self) {
+        VerbResolutionCache::flush(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        VerbResolutionCache::has_changed(self)
+    }
+
+    fn orig_version(&self) -> i64 {
+        self.inner.lock().unwrap().orig_version
+    }
+
+    fn version(&self) -> i64 {
+        self.inner.lock().unwrap().version
+    }
+
+    fn was_flushed(&self) -> bool {
+        self.inner.lock().unwrap().flushed
+    }
+}
+
 pub struct AncestryCache {
     #[allow(clippy::type_complexity)]
     inner: Mutex<AncestryInner>,
@@ -160,3 +204,29 @@ impl AncestryCache {
         inner.version > inner.orig_version
     }
 }
+
+impl ForkedCache for AncestryCache {
+    fn fork(&self) -> Box<Self> {
+        AncestryCache::fork(self)
+    }
+
+    fn flush(&self) {
+        AncestryCache::flush(self)
+    }
+
+    fn has_changed(&self) -> bool {
+        AncestryCache::has_changed(self)
+    }
+
+    fn orig_version(&self) -> i64 {
+        self.inner.lock().unwrap().orig_version
+    }
+
+    fn version(&self) -> i64 {
+        self.inner.lock().unwrap().version
+    }
+
+    fn was_flushed(&self) -> bool {
+        self.inner.lock().unwrap().flushed
+    }
+}
@@ -11,29 +11,37 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
+use crate::backup::Snapshot;
+use crate::change_log::{Change, CommitLog, LoggedRelation};
+use crate::creation_rate::CreationRateLimiter;
 use crate::db_worldstate::db_counters;
 use crate::fjall_provider::FjallProvider;
-use crate::moor_db::WorkingSets;
+use crate::moor_db::{NamedSequences, WorkingSets};
 use crate::prop_cache::PropResolutionCache;
 use crate::tx_management::{Relation, RelationTransaction, Tx};
 use crate::verb_cache::{AncestryCache, VerbResolutionCache};
-use crate::{CommitSet, Error, ObjAndUUIDHolder, StringHolder};
+use crate::{CommitSet, Error, ObjAndUUIDHolder, StringHolder, Tombstone};
 use ahash::AHasher;
+use byteview::ByteView;
 use crossbeam_channel::Sender;
 use crossbeam_utils::CachePadded;
+use fjall::{Keyspace, PartitionCreateOptions};
 use moor_common::model::{
-    CommitResult, HasUuid, Named, ObjAttrs, ObjFlag, ObjSet, ObjectRef, PropDef, PropDefs,
-    PropFlag, PropPerms, ValSet, VerbArgsSpec, VerbAttrs, VerbDef, VerbDefs, VerbFlag,
-    WorldStateError,
+    CommitResult, HasUuid, LifecycleEvent, LifecycleKind, Named, ObjAttrs, ObjFlag, ObjSet,
+    ObjectRef, ObjectSummary, PropDef, PropDefs, PropFlag, PropPerms, ValSet, VerbArgsSpec,
+    VerbAttrs, VerbDef, VerbDefs, VerbFlag, WorldStateError,
 };
 use moor_common::program::ProgramType;
 use moor_common::util::{BitEnum, PerfTimerGuard};
-use moor_var::{AsByteBuffer, NOTHING, Obj, Symbol, Var, v_none};
+use moor_var::{AsByteBuffer, NOTHING, Obj, Symbol, Var, v_int, v_none};
+use std::cell::Cell;
+use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::{BuildHasherDefault, Hash};
-use std::sync::Arc;
+use std::path::{Component, Path, PathBuf};
 use std::sync::atomic::AtomicI64;
-use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tracing::warn;
 use uuid::Uuid;
 
@@ -57,6 +65,10 @@ pub struct WorldStateTransaction {
     /// Note that for now the usage doesn't include the current pending transaction.
     pub(crate) usage_channel: Sender<oneshot::Sender<usize>>,
 
+    /// The underlying storage, for maintenance (compaction) which is done on the requesting
+    /// thread, rather than holding up the commit thread.
+    pub(crate) keyspace: Keyspace,
+
     pub(crate) object_location: RTx<Obj, Obj>,
     pub(crate) object_contents: RTx<Obj, ObjSet>,
     pub(crate) object_flags: RTx<Obj, BitEnum<ObjFlag>>,
@@ -70,8 +82,18 @@ pub struct WorldStateTransaction {
     pub(crate) object_propdefs: RTx<Obj, PropDefs>,
     pub(crate) object_propvalues: RTx<ObjAndUUIDHolder, Var>,
     pub(crate) object_propflags: RTx<ObjAndUUIDHolder, PropPerms>,
+    pub(crate) object_freelist: RTx<Obj, ObjSet>,
+    pub(crate) object_tombstones: RTx<Obj, Tombstone>,
+    pub(crate) object_generations: RTx<Obj, Var>,
+    /// How long recycled objects are kept as tombstones, if they are.
+    pub(crate) tombstone_retention: Option<Duration>,
+    /// Where backups are written, if anywhere.
+    pub(crate) backup_directory: Option<Arc<Path>>,
+    /// Recent object creations by each owner, shared with every other transaction.
+    pub(crate) creation_rate: Arc<Mutex<CreationRateLimiter>>,
 
     pub(crate) sequences: [Arc<CachePadded<AtomicI64>>; 16],
+    pub(crate) named_sequences: Arc<NamedSequences>,
 
     /// Our fork of the global verb resolution cache. We fill or flush in our local copy, and
     /// when we submit ours becomes the new global.
@@ -86,9 +108,26 @@ pub struct WorldStateTransaction {
     /// True if this transaction has any *writes* at all. If not, our commits can be immediate
     /// and successful.
     pub(crate) has_mutations: bool,
+
+    /// The lifecycle events of this transaction, to be sent on when it commits, or None if nobody
+    /// is following them.
+    pub(crate) lifecycle_events: Option<Vec<LifecycleEvent>>,
+}
+
+/// The parent graph should never have a cycle, but a corrupt database could, and the walks up and
+/// down it fail with this rather than looping forever.
+fn inheritance_cycle(obj: &Obj) -> WorldStateError {
+    WorldStateError::DatabaseError(format!("Inheritance cycle detected at {obj}"))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
-fn upsert<Domain, Codomain>(
+pub(crate) fn upsert<Domain, Codomain>(
     table: &mut RTx<Domain, Codomain>,
     d: Domain,
     c: Codomain,
@@ -126,6 +165,9 @@ impl WorldStateTransaction {
                             if current.is_nothing() {
                                 break;
                             }
+                            if current.eq(obj) || ancestors.contains(&current) {
+                                return Err(inheritance_cycle(&current));
+                            }
                             ancestors.push(current);
                         }
                         Ok(None) => break,
@@ -191,6 +233,22 @@ impl WorldStateTransaction {
         Ok(Obj::mk_id(seq_max))
     }
 
+    /// The recycled object numbers which are available for reuse.
+    pub fn get_free_objects(&self) -> Result<ObjSet, WorldStateError> {
+        let r = self.object_freelist.get(&NOTHING).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error getting free objects: {:?}", e))
+        })?;
+        Ok(r.unwrap_or_default())
+    }
+
+    fn set_free_objects(&mut self, free_objects: ObjSet) -> Result<(), WorldStateError> {
+        upsert(&mut self.object_freelist, NOTHING, free_objects).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error setting free objects: {:?}", e))
+        })?;
+        self.has_mutations = true;
+        Ok(())
+    }
+
     pub fn get_object_owner(&self, obj: &Obj) -> Result<Obj, WorldStateError> {
         let r = self.object_owner.get(obj).map_err(|e| {
             WorldStateError::DatabaseError(format!("Error getting object owner: {:?}", e))
@@ -231,6 +289,20 @@ impl WorldStateTransaction {
         Ok(r.0)
     }
 
+    /// All of an object's core attributes, in one call.
+    pub fn get_object_summary(&self, obj: &Obj) -> Result<ObjectSummary, WorldStateError> {
+        let name = self.get_object_name(obj)?;
+        Ok(ObjectSummary {
+            name,
+            owner: self.get_object_owner(obj)?,
+            parent: self.get_object_parent(obj)?,
+            location: self.get_object_location(obj)?,
+            flags: self.get_object_flags(obj)?,
+            verbs: Some(self.get_verbs(obj)?),
+            properties: Some(self.get_properties(obj)?),
+        })
+    }
+
     pub fn set_object_name(&mut self, obj: &Obj, name: String) -> Result<(), WorldStateError> {
         upsert(&mut self.object_name, *obj, StringHolder(name)).map_err(|e| {
             WorldStateError::DatabaseError(format!("Error setting object name: {:?}", e))
@@ -244,8 +316,37 @@ impl WorldStateTransaction {
         id: Option<Obj>,
         attrs: ObjAttrs,
     ) -> Result<Obj, WorldStateError> {
+        // A tombstoned object whose number is being reused explicitly is gone for good, and
+        // must not be undeleted over the top of its replacement.
+        if let Some(id) = id {
+            let tombstone = self.object_tombstones.get(&id).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error getting tombstone: {:?}", e))
+            })?;
+            if tombstone.is_some() {
+                self.purge_tombstone(&id)?;
+            }
+        }
+
+        let free_objects = self.get_free_objects()?;
         let id = match id {
-            Some(id) => id,
+            // An explicitly numbered object (`recreate`, textdump load) may be taking a recycled
+            // number, in which case it's no longer free.
+            Some(id) => {
+                if free_objects.contains(id) {
+                    self.set_free_objects(free_objects.with_removed(id))?;
+                }
+                id
+            }
+            // Otherwise prefer the lowest recycled number, and only grow the number space when
+            // there isn't one.
+            None if !free_objects.is_empty() => {
+                let id = free_objects
+                    .iter()
+                    .min_by_key(|o| o.id().0)
+                    .expect("non-empty free list");
+                self.set_free_objects(free_objects.with_removed(id))?;
+                id
+            }
             None => {
                 let max = self.increment_sequence(SEQUENCE_MAX_OBJECT);
                 let max = if max < i32::MIN as i64 || max > i32::MAX as i64 {
@@ -294,45 +395,310 @@ impl WorldStateTransaction {
         // TODO: We could probably be more aggressive here, and fill ancestry for our ancestors.
         self.ancestors(&id, false).ok();
 
+        self.record_lifecycle(&id, LifecycleKind::ObjectCreated);
+
         Ok(id)
     }
 
+    /// Recycle an object: destroy it, or if tombstones are enabled, set it aside so that it can be
+    /// undeleted until its retention window is up. Tombstones whose window has passed are
+    /// destroyed along the way.
+    ///
+    /// The caller (e.g. the `recycle` builtin) is responsible for running `:recycle` on the object
+    /// and `:exitfunc` on its contents *before* calling this, while the object's verbs and
+    /// properties are still intact.
     pub fn recycle_object(&mut self, obj: &Obj) -> Result<(), WorldStateError> {
-        // First go through and move all objects that are in this object's contents to the
-        // to #-1.  It's up to the caller here to execute :exitfunc on all of them before invoking
-        // this method.
+        match self.tombstone_retention {
+            None => self.destroy_object(obj)?,
+            Some(retention) => {
+                self.purge_tombstones(retention)?;
+                self.tombstone_object(obj)?;
+            }
+        }
+        self.record_lifecycle(obj, LifecycleKind::ObjectRecycled);
+        Ok(())
+    }
+
+    fn record_lifecycle(&mut self, obj: &Obj, kind: LifecycleKind) {
+        if let Some(events) = self.lifecycle_events.as_mut() {
+            events.push(LifecycleEvent { obj: *obj, kind });
+        }
+    }
 
+    /// Take an object out of the world without destroying it. Its contents are moved to #-1 and
+    /// its children reparented, as for a real recycle, and it goes to #-1 itself, but its verbs,
+    /// properties, name, owner and parent all stay where they are. Only its flags move, into the
+    /// tombstone, which leaves the object invalid.
+    fn tombstone_object(&mut self, obj: &Obj) -> Result<(), WorldStateError> {
+        let flags = self.get_object_flags(obj)?;
         let contents = self.get_object_contents(obj)?;
+        let parent = self.get_object_parent(obj)?;
+        let children = self.get_object_children(obj)?;
+        let mut inherited = vec![];
+        for ancestor in self.ancestors(obj, false)?.iter() {
+            for p in self.get_properties(&ancestor)?.iter() {
+                inherited.push(p.uuid());
+            }
+        }
+
         for c in contents.iter() {
             self.set_object_location(&c, &NOTHING)?;
         }
+        for c in children.iter() {
+            self.set_object_parent(&c, &parent)?;
+        }
+        self.set_object_location(obj, &NOTHING)?;
+
+        // We keep our parent, to go back to, but are no longer one of its children.
+        let parent_children = self.get_object_children(&parent)?.with_removed(*obj);
+        upsert(&mut self.object_children, parent, parent_children).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error updating parent children: {:?}", e))
+        })?;
+
+        self.object_flags.delete(obj).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error deleting object flags: {:?}", e))
+        })?;
+        let tombstone = Tombstone {
+            flags,
+            recycled_at: now_secs(),
+            inherited,
+        };
+        upsert(&mut self.object_tombstones, *obj, tombstone).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error setting tombstone: {:?}", e))
+        })?;
         self.has_mutations = true;
 
-        // Now reparent all our immediate children to our parent.
-        // This should properly move all properties all the way down the chain.
+        self.verb_resolution_cache.flush();
+        self.ancestry_cache.flush();
+        self.prop_resolution_cache.flush();
+
+        Ok(())
+    }
+
+    /// Bring back an object recycled while tombstones were enabled, with its verbs and properties
+    /// as they were. It returns to its parent (or to none, if the parent has since gone) but not
+    /// to its location, which is #-1, nor to its children, which were reparented. Properties its
+    /// ancestors defined or removed in the meantime are added to or dropped from it.
+    pub fn undelete_object(&mut self, obj: &Obj) -> Result<(), WorldStateError> {
+        let tombstone = self
+            .object_tombstones
+            .get(obj)
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error getting tombstone: {:?}", e))
+            })?
+            .ok_or(WorldStateError::ObjectNotFound(ObjectRef::Id(*obj)))?;
+
+        // Its number may have been taken since by an object created with it explicitly.
+        if self.object_valid(obj)? {
+            return Err(WorldStateError::ObjectAlreadyExists(*obj));
+        }
+
+        let parent = self.get_object_parent(obj)?;
+        if self.object_valid(&parent)? {
+            let parent_children = self.get_object_children(&parent)?.with_appended(&[*obj]);
+            upsert(&mut self.object_children, parent, parent_children).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error updating parent children: {:?}", e))
+            })?;
+        } else {
+            upsert(&mut self.object_parent, *obj, NOTHING).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error updating object parent: {:?}", e))
+            })?;
+        }
+
+        upsert(&mut self.object_flags, *obj, tombstone.flags).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error setting object flags: {:?}", e))
+        })?;
+        self.object_tombstones.delete(obj).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error deleting tombstone: {:?}", e))
+        })?;
+        self.has_mutations = true;
+
+        self.verb_resolution_cache.flush();
+        self.ancestry_cache.flush();
+        self.prop_resolution_cache.flush();
+
+        self.sync_inherited_properties(obj, &tombstone.inherited)?;
+
+        self.record_lifecycle(obj, LifecycleKind::ObjectCreated);
+
+        Ok(())
+    }
+
+    /// Destroy the tombstoned objects which were recycled more than `retention` ago.
+    fn purge_tombstones(&mut self, retention: Duration) -> Result<(), WorldStateError> {
+        let cutoff = now_secs().saturating_sub(retention.as_secs());
+        let expired = self
+            .object_tombstones
+            .scan(&|_, tombstone| tombstone.recycled_at <= cutoff)
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error scanning tombstones: {:?}", e))
+            })?;
+        for (obj, _) in expired {
+            self.purge_tombstone(&obj)?;
+        }
+        Ok(())
+    }
+
+    /// Drop an object's tombstone, destroying the object behind it unless its number has since
+    /// been taken by a live object, which must be left alone.
+    fn purge_tombstone(&mut self, obj: &Obj) -> Result<(), WorldStateError> {
+        if !self.object_valid(obj)? {
+            self.destroy_object(obj)?;
+        }
+        self.object_tombstones.delete(obj).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error deleting tombstone: {:?}", e))
+        })?;
+        self.has_mutations = true;
+        Ok(())
+    }
+
+    /// Bring an object's inherited property slots in line with its current ancestry: give it
+    /// flags for properties its ancestors define that it has none for, and drop the values and
+    /// flags it holds for any of those it `previously_inherited` which no longer exist anywhere
+    /// above it.
+    fn sync_inherited_properties(
+        &mut self,
+        obj: &Obj,
+        previously_inherited: &[Uuid],
+    ) -> Result<(), WorldStateError> {
+        let owner = self.get_object_owner(obj)?;
+        let mut live = HashSet::new();
+        for p in self.get_properties(obj)?.iter() {
+            live.insert(p.uuid());
+        }
+        for ancestor in self.ancestors(obj, false)?.iter() {
+            for p in self.get_properties(&ancestor)?.iter() {
+                live.insert(p.uuid());
+                let key = ObjAndUUIDHolder::new(obj, p.uuid());
+                let existing = self.object_propflags.get(&key).map_err(|e| {
+                    WorldStateError::DatabaseError(format!("Error getting property flags: {:?}", e))
+                })?;
+                if existing.is_some() {
+                    continue;
+                }
+                let Some(perms) = self
+                    .object_propflags
+                    .get(&ObjAndUUIDHolder::new(&ancestor, p.uuid()))
+                    .map_err(|e| {
+                        WorldStateError::DatabaseError(format!(
+                            "Error getting property flags: {:?}",
+                            e
+                        ))
+                    })?
+                else {
+                    continue;
+                };
+                let perms = if perms.flags().contains(PropFlag::Chown) {
+                    perms.with_owner(owner)
+                } else {
+                    perms
+                };
+                upsert(&mut self.object_propflags, key, perms).map_err(|e| {
+                    WorldStateError::DatabaseError(format!("Error setting property flags: {:?}", e))
+                })?;
+            }
+        }
+
+        for uuid in previously_inherited {
+            if live.contains(uuid) {
+                continue;
+            }
+            let key = ObjAndUUIDHolder::new(obj, *uuid);
+            self.object_propflags.delete(&key).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error deleting property flags: {:?}", e))
+            })?;
+            self.object_propvalues.delete(&key).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error deleting property value: {:?}", e))
+            })?;
+        }
+        self.prop_resolution_cache.flush();
+
+        Ok(())
+    }
+
+    /// Remove an object and everything attached to it from the database. Ordering matters here.
+    /// This method:
+    ///
+    ///   1. gathers everything it needs to read (contents, children, parent, location, verbs,
+    ///      and property definitions), so that a lookup failure aborts before anything is mutated,
+    ///   2. moves contents to #-1 and reparents children to our parent,
+    ///   3. deletes verb programs and verb definitions,
+    ///   4. deletes property values, flags and definitions,
+    ///   5. and finally removes the object itself from the remaining relations.
+    fn destroy_object(&mut self, obj: &Obj) -> Result<(), WorldStateError> {
+        // Phase 1: read everything up front.
+        let contents = self.get_object_contents(obj)?;
         let parent = self.get_object_parent(obj)?;
         let children = self.get_object_children(obj)?;
+        let location = self.get_object_location(obj)?;
+        let location_contents = self.get_object_contents(&location)?;
+        let verbdefs = self.get_verbs(obj)?;
+
+        // Property values (and flags) can exist on this object both for properties it defines
+        // and for properties it inherits, so collect the definitions from the whole ancestry.
+        let mut prop_uuids = vec![];
+        for ancestor in self.ancestors(obj, true)?.iter() {
+            for p in self.get_properties(&ancestor)?.iter() {
+                prop_uuids.push(p.uuid());
+            }
+        }
+
+        // Phase 2: move all objects that are in this object's contents to #-1.  It's up to the
+        // caller here to execute :exitfunc on all of them before invoking this method.
+        for c in contents.iter() {
+            self.set_object_location(&c, &NOTHING)?;
+        }
+        self.has_mutations = true;
+
+        // Now reparent all our immediate children to our parent.
+        // This should properly move all properties all the way down the chain.
         for c in children.iter() {
             self.set_object_parent(&c, &parent)?;
         }
 
-        // Make sure we are removed from the parent's children list.
-        let parent_children = self.get_object_children(&parent)?;
-        let parent_children = parent_children.with_removed(*obj);
+        // Make sure we are removed from the parent's children list. This is read only now, since
+        // reparenting our children above has just added them to it.
+        let parent_children = self.get_object_children(&parent)?.with_removed(*obj);
         upsert(&mut self.object_children, parent, parent_children).map_err(|e| {
             WorldStateError::DatabaseError(format!("Error updating parent children: {:?}", e))
         })?;
 
         // Make sure we are removed from the location's contents list.
-        let location = self.get_object_location(obj)?;
-        let location_contents = self.get_object_contents(&location)?;
         let location_contents = location_contents.with_removed(*obj);
         upsert(&mut self.object_contents, location, location_contents).map_err(|e| {
             WorldStateError::DatabaseError(format!("Error updating location contents: {:?}", e))
         })?;
 
-        // Now we can remove this object from all relevant relations
-        // First the simple ones which are keyed on the object id.
+        // Phase 3: verbs. Programs first, then the definitions that point at them.
+        for v in verbdefs.iter() {
+            self.object_verbs
+                .delete(&ObjAndUUIDHolder::new(obj, v.uuid()))
+                .map_err(|e| {
+                    WorldStateError::DatabaseError(format!("Error deleting verb program: {:?}", e))
+                })?;
+        }
+        self.object_verbdefs.delete(obj).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error deleting object verbdefs: {:?}", e))
+        })?;
+
+        // Phase 4: properties. Values and flags, then the definitions.
+        for uuid in prop_uuids {
+            let key = ObjAndUUIDHolder::new(obj, uuid);
+            // Not every property will have a local value or flags, so these may not exist.
+            self.object_propvalues.delete(&key).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error deleting property value: {:?}", e))
+            })?;
+            self.object_propflags.delete(&key).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error deleting property flags: {:?}", e))
+            })?;
+        }
+
+        // We may or may not have propdefs yet...
+        self.object_propdefs.delete(obj).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error deleting property definitions: {:?}", e))
+        })?;
+
+        // Phase 5: the object itself, from the simple relations keyed on the object id.
         self.object_flags.delete(obj).map_err(|e| {
             WorldStateError::DatabaseError(format!("Error deleting object flags: {:?}", e))
         })?;
@@ -351,25 +717,13 @@ impl WorldStateTransaction {
         self.object_location.delete(obj).map_err(|e| {
             WorldStateError::DatabaseError(format!("Error deleting object location: {:?}", e))
         })?;
-        self.object_verbdefs.delete(obj).map_err(|e| {
-            WorldStateError::DatabaseError(format!("Error deleting object verbdefs: {:?}", e))
-        })?;
 
-        let propdefs = self.get_properties(obj)?;
-        for p in propdefs.iter() {
-            self.object_propvalues
-                .delete(&ObjAndUUIDHolder::new(obj, p.uuid()))
-                .map_err(|e| {
-                    WorldStateError::DatabaseError(format!(
-                        "Error deleting property value: {:?}",
-                        e
-                    ))
-                })?;
+        // Make the number available for reuse by the next `create_object`.
+        if obj.is_positive() {
+            let free_objects = self.get_free_objects()?;
+            self.set_free_objects(free_objects.with_inserted(*obj))?;
         }
 
-        // We may or may not have propdefs yet...
-        self.object_propdefs.delete(obj).ok();
-
         self.verb_resolution_cache.flush();
         self.ancestry_cache.flush();
         self.prop_resolution_cache.flush();
@@ -554,6 +908,17 @@ impl WorldStateTransaction {
         Ok(r.unwrap_or_default())
     }
 
+    /// The number of direct children of `obj`, without copying out the set.
+    pub fn count_children(&self, obj: &Obj) -> Result<usize, WorldStateError> {
+        let r = self
+            .object_children
+            .get_with(obj, |children| children.len())
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error counting object children: {:?}", e))
+            })?;
+        Ok(r.unwrap_or(0))
+    }
+
     pub fn get_object_location(&self, obj: &Obj) -> Result<Obj, WorldStateError> {
         let r = self.object_location.get(obj).map_err(|e| {
             WorldStateError::DatabaseError(format!("Error getting object location: {:?}", e))
@@ -690,6 +1055,26 @@ impl WorldStateTransaction {
         Ok(r.unwrap_or_else(VerbDefs::empty))
     }
 
+    /// How many committed transactions have changed `obj`'s verbs or properties. Zero if none
+    /// ever have.
+    pub fn get_object_generation(&self, obj: &Obj) -> Result<i64, WorldStateError> {
+        let r = self.object_generations.get(obj).map_err(|e| {
+            WorldStateError::DatabaseError(format!("Error getting object generation: {:?}", e))
+        })?;
+        Ok(r.and_then(|g| g.as_integer()).unwrap_or(0))
+    }
+
+    /// The number of verbs defined directly on `obj`, without copying out the verbdefs.
+    pub fn count_verbs(&self, obj: &Obj) -> Result<usize, WorldStateError> {
+        let r = self
+            .object_verbdefs
+            .get_with(obj, |verbdefs| verbdefs.len())
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error counting verbs: {:?}", e))
+            })?;
+        Ok(r.unwrap_or(0))
+    }
+
     pub fn get_verb_program(&self, obj: &Obj, uuid: Uuid) -> Result<ProgramType, WorldStateError> {
         let r = self
             .object_verbs
@@ -712,8 +1097,9 @@ impl WorldStateTransaction {
             _ => {
                 let verbdefs = self.get_verbs(obj)?;
                 let named = verbdefs.find_named(name);
+                // No miss is recorded here: the resolution cache is for lookups through the
+                // inheritance chain, and this verb may well still be defined on an ancestor.
                 let Some(verb) = named.first() else {
-                    self.verb_resolution_cache.fill_miss(obj, &name);
                     return Err(WorldStateError::VerbNotFound(*obj, name.to_string()));
                 };
 
@@ -724,16 +1110,12 @@ impl WorldStateTransaction {
         }
     }
 
+    /// The verb at (zero-based) `index` in definition order. Deleting a verb shifts the indices of
+    /// those defined after it down by one, as in MOO.
     pub fn get_verb_by_index(&self, obj: &Obj, index: usize) -> Result<VerbDef, WorldStateError> {
-        let verbs = self.get_verbs(obj)?;
-        if index >= verbs.len() {
-            return Err(WorldStateError::VerbNotFound(*obj, format!("{}", index)));
-        }
-        let verb = verbs
-            .iter()
-            .nth(index)
-            .ok_or_else(|| WorldStateError::VerbNotFound(*obj, format!("{}", index)))?;
-        Ok(verb.clone())
+        self.get_verbs(obj)?
+            .get(index)
+            .ok_or_else(|| WorldStateError::VerbNotFound(*obj, format!("{}", index)))
     }
 
     pub fn resolve_verb(
@@ -749,7 +1131,12 @@ impl WorldStateTransaction {
             let Some(verbdef) = cache_result else {
                 return Err(WorldStateError::VerbNotFound(*obj, name.to_string()));
             };
-            if verbdef.matches_spec(&argspec, &flagspec) {
+            // When matching a command, a less specific verb may have been cached ahead of a more
+            // specific one with the same name, so the cache is only conclusive for verbs that
+            // can't be beaten.
+            let conclusive =
+                argspec.is_none() || verbdef.args().specificity() == VerbArgsSpec::MAX_SPECIFICITY;
+            if conclusive && verbdef.matches_spec(&argspec, &flagspec) {
                 return Ok(verbdef.clone());
             }
         }
@@ -790,14 +1177,21 @@ impl WorldStateTransaction {
                 let named = verbdefs.find_named(name);
 
                 // Fill the verb cache.
-                let verb = named.first();
-                if let Some(verb) = verb {
+                if let Some(verb) = named.first() {
                     self.verb_resolution_cache.fill_hit(obj, &name, verb);
-
                     found = true;
-                    if verb.matches_spec(&argspec, &flagspec) {
-                        return Ok(verb.clone());
-                    }
+                }
+
+                // Without an argspec the first verb by that name wins. When matching a command,
+                // the most specific matching argspec wins, with ties going to whichever verb was
+                // defined first.
+                let mut matching = named.iter().filter(|v| v.matches_spec(&argspec, &flagspec));
+                let verb = match argspec {
+                    None => matching.next(),
+                    Some(_) => matching.min_by_key(|v| Reverse(v.args().specificity())),
+                };
+                if let Some(verb) = verb {
+                    return Ok(verb.clone());
                 }
             }
             search_o = self.get_object_parent(&search_o)?;
@@ -897,6 +1291,8 @@ impl WorldStateTransaction {
             WorldStateError::DatabaseError(format!("Error setting verb binary: {:?}", e))
         })?;
 
+        self.record_lifecycle(oid, LifecycleKind::VerbAdded);
+
         Ok(())
     }
 
@@ -916,6 +1312,7 @@ impl WorldStateTransaction {
             .map_err(|e| {
                 WorldStateError::DatabaseError(format!("Error deleting verb binary: {:?}", e))
             })?;
+        self.record_lifecycle(location, LifecycleKind::VerbRemoved);
         Ok(())
     }
 
@@ -926,6 +1323,17 @@ impl WorldStateTransaction {
         Ok(r.unwrap_or_else(PropDefs::empty))
     }
 
+    /// The number of properties defined directly on `obj`, without copying out the propdefs.
+    pub fn count_properties(&self, obj: &Obj) -> Result<usize, WorldStateError> {
+        let r = self
+            .object_propdefs
+            .get_with(obj, |propdefs| propdefs.len())
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error counting properties: {:?}", e))
+            })?;
+        Ok(r.unwrap_or(0))
+    }
+
     pub fn set_property(
         &mut self,
         obj: &Obj,
@@ -944,6 +1352,27 @@ impl WorldStateTransaction {
         Ok(())
     }
 
+    /// Add `delta` to the local value of a property, as a merge rather than an update, so that
+    /// concurrent additions to the same value don't conflict at commit. Returns the sum as this
+    /// transaction sees it, or None if the property has no local value or the two don't add.
+    pub fn add_to_property(
+        &mut self,
+        obj: &Obj,
+        uuid: Uuid,
+        delta: Var,
+    ) -> Result<Option<Var>, WorldStateError> {
+        let sum = self
+            .object_propvalues
+            .merge(&ObjAndUUIDHolder::new(obj, uuid), delta)
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error adding to property value: {:?}", e))
+            })?;
+        if sum.is_some() {
+            self.has_mutations = true;
+        }
+        Ok(sum)
+    }
+
     pub fn define_property(
         &mut self,
         definer: &Obj,
@@ -1012,6 +1441,8 @@ impl WorldStateTransaction {
             })?;
         }
 
+        self.record_lifecycle(location, LifecycleKind::PropertyAdded);
+
         Ok(u)
     }
 
@@ -1094,9 +1525,128 @@ impl WorldStateTransaction {
         }
         self.has_mutations = true;
         self.prop_resolution_cache.flush();
+        self.record_lifecycle(obj, LifecycleKind::PropertyRemoved);
         Ok(())
     }
 
+    /// Hand every verb and property owned by `from` over to `to`, returning the number of verbs
+    /// and properties which changed hands.
+    /// Rather than walking every object, the verbdef and propflag relations are each rewritten in
+    /// one batch: scanned once for what `from` owns, with the matches then updated in place.
+    pub fn transfer_ownership(
+        &mut self,
+        from: &Obj,
+        to: &Obj,
+    ) -> Result<(usize, usize), WorldStateError> {
+        let verb_count = Cell::new(0);
+        self.object_verbdefs
+            .update_where(
+                &|_, verbdefs| verbdefs.iter().any(|v| v.owner() == *from),
+                |obj, verbdefs| {
+                    let verbdefs: VerbDefs = verbdefs
+                        .iter()
+                        .map(|v| {
+                            if v.owner() != *from {
+                                return v;
+                            }
+                            verb_count.set(verb_count.get() + 1);
+                            VerbDef::new(
+                                v.uuid(),
+                                v.location(),
+                                *to,
+                                &v.names(),
+                                v.flags(),
+                                v.args(),
+                            )
+                        })
+                        .collect();
+                    let size = obj.size_bytes() + verbdefs.size_bytes();
+                    (verbdefs, size)
+                },
+            )
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error updating verb definitions: {:?}", e))
+            })?;
+        let verb_count = verb_count.get();
+
+        let prop_count = self
+            .object_propflags
+            .update_where(&|_, perms| perms.owner() == *from, |holder, perms| {
+                let perms = perms.with_owner(*to);
+                let size = holder.size_bytes() + perms.size_bytes();
+                (perms, size)
+            })
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error updating property flags: {:?}", e))
+            })?;
+
+        if verb_count > 0 || prop_count > 0 {
+            self.has_mutations = true;
+            self.verb_resolution_cache.flush();
+            self.prop_resolution_cache.flush();
+        }
+        Ok((verb_count, prop_count))
+    }
+
+    /// Every verb, on any valid object, with a name for which `matches` holds, ordered by object.
+    /// The predicate is applied during the scan, so only the objects with a match are ever
+    /// materialized.
+    pub fn find_verbs(
+        &self,
+        matches: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<(Obj, VerbDef)>, WorldStateError> {
+        let found = self
+            .object_verbdefs
+            .scan(&|_, verbdefs| verbdefs.iter().any(|v| v.names().into_iter().any(matches)))
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error scanning verb definitions: {:?}", e))
+            })?;
+        let mut results = vec![];
+        for (obj, verbdefs) in found {
+            // Recycled objects keep their definitions until they're purged.
+            if !self.object_valid(&obj)? {
+                continue;
+            }
+            for verbdef in verbdefs.iter() {
+                if verbdef.names().into_iter().any(matches) {
+                    results.push((obj, verbdef));
+                }
+            }
+        }
+        results.sort_by_key(|(obj, _)| *obj);
+        Ok(results)
+    }
+
+    /// Every property definition, on any valid object, whose name `matches`, ordered by the
+    /// defining object. As with `find_verbs`, only the objects with a match are materialized.
+    pub fn find_properties(
+        &self,
+        matches: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<(Obj, PropDef)>, WorldStateError> {
+        let found = self
+            .object_propdefs
+            .scan(&|_, propdefs| propdefs.iter().any(|p| matches(p.name())))
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!(
+                    "Error scanning property definitions: {:?}",
+                    e
+                ))
+            })?;
+        let mut results = vec![];
+        for (obj, propdefs) in found {
+            if !self.object_valid(&obj)? {
+                continue;
+            }
+            for propdef in propdefs.iter() {
+                if matches(propdef.name()) {
+                    results.push((obj, propdef));
+                }
+            }
+        }
+        results.sort_by_key(|(obj, _)| *obj);
+        Ok(results)
+    }
+
     pub fn retrieve_property(
         &self,
         obj: &Obj,
@@ -1235,6 +1785,45 @@ impl WorldStateTransaction {
         }
     }
 
+    /// Every property `obj` has, its own and inherited, with the effective value, the object that
+    /// value actually lives on, and `obj`'s permissions for it. Gives the same answers as calling
+    /// `resolve_property` for each one, but walks the ancestor chain just once. Properties come
+    /// in order of definer, from `obj` upwards, and in definition order within each.
+    pub fn get_all_properties(
+        &self,
+        obj: &Obj,
+    ) -> Result<Vec<(PropDef, Var, Obj, PropPerms)>, WorldStateError> {
+        let chain: Vec<Obj> = self.ancestors(obj, true)?.iter().collect();
+        let mut properties = vec![];
+        for (depth, definer) in chain.iter().enumerate() {
+            for propdef in self.get_properties(definer)?.iter() {
+                let perms = self.retrieve_property_permissions(obj, propdef.uuid())?;
+
+                // The value is on the nearest object between `obj` and the definer where the
+                // property isn't clear.
+                let mut found = None;
+                for holder in &chain[..=depth] {
+                    let value = self
+                        .object_propvalues
+                        .get(&ObjAndUUIDHolder::new(holder, propdef.uuid()))
+                        .map_err(|e| {
+                            WorldStateError::DatabaseError(format!(
+                                "Error getting property value: {:?}",
+                                e
+                            ))
+                        })?;
+                    if let Some(value) = value {
+                        found = Some((value, *holder));
+                        break;
+                    }
+                }
+                let (value, source) = found.unwrap_or((v_none(), *definer));
+                properties.push((propdef, value, source, perms));
+            }
+        }
+        Ok(properties)
+    }
+
     pub fn db_usage(&self) -> Result<usize, WorldStateError> {
         let (send, receive) = oneshot::channel();
         self.usage_channel
@@ -1243,7 +1832,69 @@ impl WorldStateTransaction {
         Ok(receive.recv().expect("Unable to receive usage response"))
     }
 
-    pub fn commit(self) -> Result<CommitResult, WorldStateError> {
+    /// Flush and then fully compact every partition, dropping the old versions and deletion
+    /// markers left behind by overwritten and deleted tuples. Returns the disk usage in bytes
+    /// before and after. Commits carry on meanwhile.
+    pub fn compact(&self) -> Result<(usize, usize), WorldStateError> {
+        let before = self.keyspace.disk_space() as usize;
+        for name in self.keyspace.list_partitions() {
+            // All the partitions are open already, so this just hands back the existing one.
+            let partition = self
+                .keyspace
+                .open_partition(&name, PartitionCreateOptions::default())
+                .map_err(|e| {
+                    WorldStateError::DatabaseError(format!("Unable to open partition {name}: {e}"))
+                })?;
+            partition
+                .rotate_memtable_and_wait()
+                .and_then(|_| partition.major_compact())
+                .map_err(|e| {
+                    WorldStateError::DatabaseError(format!(
+                        "Unable to compact partition {name}: {e}"
+                    ))
+                })?;
+        }
+        Ok((before, self.keyspace.disk_space() as usize))
+    }
+
+    /// Bump the generation of every object whose verbs or properties this transaction changed.
+    /// Generations outlive the objects themselves, so a recycled number that gets reused carries
+    /// on from where its predecessor left off rather than starting again.
+    fn bump_generations(&mut self) -> Result<(), WorldStateError> {
+        let mut changed: HashSet<Obj> = HashSet::new();
+        changed.extend(self.object_verbdefs.written_domains());
+        changed.extend(self.object_propdefs.written_domains());
+        for holder in self
+            .object_verbs
+            .written_domains()
+            .into_iter()
+            .chain(self.object_propvalues.written_domains())
+            .chain(self.object_propflags.written_domains())
+        {
+            changed.insert(holder.obj);
+        }
+        // Generations written already were bumped by whoever wrote them (e.g. a replayed commit),
+        // and mustn't be bumped twice.
+        for obj in self.object_generations.written_domains() {
+            changed.remove(&obj);
+        }
+        for obj in changed {
+            let bumped = self.object_generations.merge(&obj, v_int(1)).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Error bumping object generation: {:?}", e))
+            })?;
+            if bumped.is_none() {
+                upsert(&mut self.object_generations, obj, v_int(1)).map_err(|e| {
+                    WorldStateError::DatabaseError(format!(
+                        "Error setting object generation: {:?}",
+                        e
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn commit(mut self) -> Result<CommitResult, WorldStateError> {
         let counters = db_counters();
         let commit_start = Instant::now();
 
@@ -1263,6 +1914,8 @@ impl WorldStateTransaction {
             return Ok(CommitResult::Success);
         }
 
+        self.bump_generations()?;
+
         // Pull out the working sets
         let _t = PerfTimerGuard::new(&counters.tx_commit_mk_working_set_phase);
 
@@ -1278,6 +1931,9 @@ impl WorldStateTransaction {
         let object_propdefs = self.object_propdefs.working_set();
         let object_propvalues = self.object_propvalues.working_set();
         let object_propflags = self.object_propflags.working_set();
+        let object_freelist = self.object_freelist.working_set();
+        let object_tombstones = self.object_tombstones.working_set();
+        let object_generations = self.object_generations.working_set();
 
         let ws = Box::new(WorkingSets {
             tx: self.tx,
@@ -1293,9 +1949,13 @@ impl WorldStateTransaction {
             object_propdefs,
             object_propvalues,
             object_propflags,
+            object_freelist,
+            object_tombstones,
+            object_generations,
             verb_resolution_cache: self.verb_resolution_cache,
             prop_resolution_cache: self.prop_resolution_cache,
             ancestry_cache: self.ancestry_cache,
+            lifecycle_events: self.lifecycle_events.unwrap_or_default(),
         });
 
         let tuple_count = ws.total_tuples();
@@ -1345,8 +2005,14 @@ impl WorldStateTransaction {
             .unwrap_or_else(ObjSet::empty);
 
         let mut results_sans_self = vec![];
+        let mut seen: HashSet<_, BuildHasherDefault<AHasher>> = HashSet::default();
+        seen.insert(*obj);
         let mut queue: VecDeque<_> = children.iter().collect();
         while let Some(o) = queue.pop_front() {
+            // In a tree every object is reached exactly once.
+            if !seen.insert(o) {
+                return Err(inheritance_cycle(&o));
+            }
             results_sans_self.push(o);
             let children = self
                 .object_children
@@ -1373,7 +2039,129 @@ impl WorldStateTransaction {
     }
 }
 
+/// Apply one logged change to a relation.
+fn replay_change<Domain, Codomain>(
+    table: &mut RTx<Domain, Codomain>,
+    change: &Change,
+) -> Result<(), WorldStateError>
+where
+    Domain: AsByteBuffer + Clone + Eq + Hash,
+    Codomain: AsByteBuffer + Clone + PartialEq,
+{
+    let domain = Domain::from_bytes(ByteView::from(change.domain.clone())).map_err(|e| {
+        WorldStateError::DatabaseError(format!("Unable to decode replayed change: {:?}", e))
+    })?;
+    let result = match &change.codomain {
+        Some(codomain) => {
+            let codomain = Codomain::from_bytes(ByteView::from(codomain.clone())).map_err(|e| {
+                WorldStateError::DatabaseError(format!("Unable to decode replayed change: {:?}", e))
+            })?;
+            upsert(table, domain, codomain).map(|_| ())
+        }
+        None => table.delete(&domain).map(|_| ()),
+    };
+    result.map_err(|e| WorldStateError::DatabaseError(format!("Unable to replay change: {:?}", e)))
+}
+
 impl WorldStateTransaction {
+    /// Where a backup called `name` goes: that file in the configured backup directory. `name`
+    /// must be a plain file name, so that backups can't be written anywhere else.
+    pub fn backup_path(&self, name: &str) -> Result<PathBuf, WorldStateError> {
+        let Some(directory) = self.backup_directory.as_ref() else {
+            return Err(WorldStateError::DatabaseError(
+                "No backup directory is configured".to_string(),
+            ));
+        };
+        let mut components = Path::new(name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(file_name)), None) if file_name == name => {
+                Ok(directory.join(file_name))
+            }
+            _ => Err(WorldStateError::DatabaseError(format!(
+                "Backup name {:?} must be a plain file name",
+                name
+            ))),
+        }
+    }
+
+    /// Start a backup of everything committed as of now, to be written out with `write_backup`.
+    /// This transaction's own changes aren't included. Commits only wait for the request to be
+    /// queued; the reading happens as the backup is written.
+    pub fn snapshot(&self) -> Result<Snapshot, WorldStateError> {
+        let (send, reply) = oneshot::channel();
+        self.commit_channel
+            .send(CommitSet::Snapshot(send))
+            .map_err(|e| {
+                WorldStateError::DatabaseError(format!("Unable to request snapshot: {:?}", e))
+            })?;
+        reply.recv().map_err(|e| {
+            WorldStateError::DatabaseError(format!("Unable to receive snapshot: {:?}", e))
+        })?
+    }
+
+    /// Apply the changes from another database's commit, as captured in its change log.
+    pub fn replay(&mut self, log: &CommitLog) -> Result<(), WorldStateError> {
+        for change in &log.changes {
+            match change.relation {
+                LoggedRelation::ObjectLocation => replay_change(&mut self.object_location, change)?,
+                LoggedRelation::ObjectContents => replay_change(&mut self.object_contents, change)?,
+                LoggedRelation::ObjectFlags => {
+                    replay_change(&mut self.object_flags, change)?;
+                    // Objects are created with a write to their flags, so keep the maximum object
+                    // number in step with the source.
+                    if change.codomain.is_some() {
+                        let obj = Obj::from_bytes(ByteView::from(change.domain.clone())).map_err(
+                            |e| {
+                                WorldStateError::DatabaseError(format!(
+                                    "Unable to decode replayed change: {:?}",
+                                    e
+                                ))
+                            },
+                        )?;
+                        self.update_sequence_max(SEQUENCE_MAX_OBJECT, obj.id().0 as i64);
+                    }
+                }
+                LoggedRelation::ObjectParent => replay_change(&mut self.object_parent, change)?,
+                LoggedRelation::ObjectChildren => replay_change(&mut self.object_children, change)?,
+                LoggedRelation::ObjectOwner => replay_change(&mut self.object_owner, change)?,
+                LoggedRelation::ObjectName => replay_change(&mut self.object_name, change)?,
+                LoggedRelation::ObjectVerbdefs => replay_change(&mut self.object_verbdefs, change)?,
+                LoggedRelation::ObjectVerbs => replay_change(&mut self.object_verbs, change)?,
+                LoggedRelation::ObjectPropdefs => replay_change(&mut self.object_propdefs, change)?,
+                LoggedRelation::ObjectPropvalues => {
+                    replay_change(&mut self.object_propvalues, change)?
+                }
+                LoggedRelation::ObjectPropflags => {
+                    replay_change(&mut self.object_propflags, change)?
+                }
+                LoggedRelation::ObjectFreelist => replay_change(&mut self.object_freelist, change)?,
+                LoggedRelation::ObjectTombstones => {
+                    replay_change(&mut self.object_tombstones, change)?
+                }
+                LoggedRelation::ObjectGenerations => {
+                    replay_change(&mut self.object_generations, change)?
+                }
+            }
+        }
+        if !log.is_empty() {
+            self.has_mutations = true;
+            self.verb_resolution_cache.flush();
+            self.ancestry_cache.flush();
+            self.prop_resolution_cache.flush();
+        }
+        Ok(())
+    }
+
+    /// Increment the named sequence, creating it on first use, and return the new value.
+    pub fn next_named_sequence(&self, name: &str) -> i64 {
+        self.named_sequences.next(name)
+    }
+
+    /// The current value of the named sequence, 0 if it has never been incremented.
+    pub fn get_named_sequence(&self, name: &str) -> i64 {
+        self.named_sequences.current(name)
+    }
+
     /// Increment the given sequence, return the new value.
     fn increment_sequence(&self, seq: usize) -> i64 {
         self.sequences[seq].fetch_add(1, std::sync::atomic::Ordering::Relaxed);
@@ -1420,7 +2208,9 @@ impl WorldStateTransaction {
             if ancestor.eq(&NOTHING) || ancestor.eq(limit) {
                 return Ok(ancestor_set);
             }
-            ancestor_set.insert(ancestor);
+            if ancestor.eq(obj) || !ancestor_set.insert(ancestor) {
+                return Err(inheritance_cycle(&ancestor));
+            }
             search_obj = ancestor;
         }
     }
@@ -1439,7 +2229,9 @@ impl WorldStateTransaction {
             if ancestor.eq(&NOTHING) {
                 return Ok(ancestor_set);
             }
-            ancestor_set.insert(ancestor);
+            if ancestor.eq(obj) || !ancestor_set.insert(ancestor) {
+                return Err(inheritance_cycle(&ancestor));
+            }
             search_obj = ancestor;
         }
     }
@@ -268,11 +268,11 @@ mod tests {
                         }
                     }
 
-                    let ws = cache.working_set();
+                    let mut ws = cache.working_set();
 
                     {
                         let mut cr = backing_store.begin_check();
-                        cr.check(&ws).expect("check failed in begin");
+                        cr.check(&mut ws).expect("check failed in begin");
                         cr.apply(ws).expect("apply failed in begin");
                         let w = backing_store.write_lock();
                         cr.commit(Some(w))
@@ -303,10 +303,10 @@ mod tests {
                                 }
                             }
                         }
-                        let ws = cache.working_set();
+                        let mut ws = cache.working_set();
                         let mut cr = backing_store.begin_check();
 
-                        match cr.check(&ws) {
+                        match cr.check(&mut ws) {
                             Err(Error::Conflict) => {
                                 return Ok(());
                             }
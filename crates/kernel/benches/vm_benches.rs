@@ -133,8 +133,8 @@ fn execute(
             VMHostResponse::CompleteSuccess(_) => {
                 panic!("Unexpected success");
             }
-            VMHostResponse::AbortLimit(AbortLimitReason::Time(time)) => {
-                panic!("Unexpected abort: {:?}", time);
+            VMHostResponse::AbortLimit(reason) => {
+                panic!("Unexpected abort: {:?}", reason);
             }
             VMHostResponse::DispatchFork(f) => {
                 panic!("Unexpected fork: {:?}", f);
@@ -69,6 +69,29 @@ pub struct FeaturesConfig {
     ///
     /// This can break backwards compatibility with existing cores, so is off by default.
     pub use_symbols_in_builtins: bool,
+    /// Whether, when a command names an object that can't be matched and no verb handles it,
+    /// to offer "Did you mean...?" suggestions from the names of nearby objects.
+    #[serde(default)]
+    pub fuzzy_match_suggestions: bool,
+    /// Whether to log every `set_task_perms` call (who, to what, from which verb and line) under
+    /// the `moor::audit::task_perms` tracing target, for tracking privilege changes in a core.
+    #[serde(default)]
+    pub audit_task_perms: bool,
+    /// Whether to store the original source text of verbs alongside their compiled program, so
+    /// that `verb_code()` and editors get back exactly what was written, comments included,
+    /// rather than a decompilation.
+    #[serde(default)]
+    pub preserve_verb_source: bool,
+    /// If nonzero, the longest, in milliseconds, a single builtin function call may take before
+    /// its task is aborted. Ticks don't advance while a builtin runs, so this is the only way to
+    /// stop one that is blocked. Blocking work a builtin does through `run_blocking` is cut off
+    /// at the limit; any other call is checked once it returns.
+    #[serde(default = "default_builtin_duration_limit_ms")]
+    pub builtin_duration_limit_ms: u64,
+}
+
+fn default_builtin_duration_limit_ms() -> u64 {
+    5_000
 }
 
 impl Default for FeaturesConfig {
@@ -86,6 +109,10 @@ impl Default for FeaturesConfig {
             use_boolean_returns: false,
             use_symbols_in_builtins: false,
             custom_errors: false,
+            fuzzy_match_suggestions: false,
+            audit_task_perms: false,
+            preserve_verb_source: false,
+            builtin_duration_limit_ms: default_builtin_duration_limit_ms(),
         }
     }
 }
@@ -104,6 +131,13 @@ impl FeaturesConfig {
         }
     }
 
+    /// The longest a single builtin function call may take before its task is aborted, if
+    /// there is a limit.
+    pub fn builtin_duration_limit(&self) -> Option<Duration> {
+        (self.builtin_duration_limit_ms > 0)
+            .then(|| Duration::from_millis(self.builtin_duration_limit_ms))
+    }
+
     /// Returns true if the configuration is backwards compatible with LambdaMOO 1.8 features
     pub fn is_lambdamoo_compatible(&self) -> bool {
         !self.lexical_scopes
@@ -0,0 +1,195 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Single-stepping of MOO code.
+//! A task started under a debugger pauses before each statement (or, once continued, only at
+//! breakpoint lines), reports where it is and what its local variables hold, and then waits for
+//! the controller to tell it what to do next.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use moor_var::{Symbol, Var};
+
+/// Commands from the controller to a paused task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// Run to the start of the next statement.
+    Step,
+    /// Run until a breakpoint line is reached, or the task ends.
+    Continue,
+    /// Pause whenever a statement on this line is about to run.
+    SetBreakpoint(usize),
+    ClearBreakpoint(usize),
+    /// Kill the task.
+    Abort,
+}
+
+/// Where a paused task is, and the state of its innermost MOO frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugFrame {
+    pub verb_name: Symbol,
+    pub this: Var,
+    /// The line of the statement about to be executed.
+    pub line: usize,
+    /// The named variables which have a value, sorted by name.
+    pub locals: Vec<(Symbol, Var)>,
+}
+
+impl DebugFrame {
+    pub fn local(&self, name: &str) -> Option<&Var> {
+        self.locals
+            .iter()
+            .find(|(sym, _)| sym.as_str() == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Events from the task to the controller.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugEvent {
+    /// The task has paused before a statement and is waiting for a command.
+    Paused(DebugFrame),
+    /// The task has stopped running, and will send no more events.
+    Finished,
+}
+
+/// What a task should do after a pause.
+pub(crate) enum Resumption {
+    Run,
+    Abort,
+}
+
+/// The task's end of a debugging session.
+#[derive(Debug)]
+pub struct Debugger {
+    commands: Receiver<DebugCommand>,
+    events: Sender<DebugEvent>,
+    /// True if we should pause at the next statement, rather than only at breakpoints.
+    stepping: bool,
+    breakpoints: HashSet<usize>,
+    /// The tick count at the last pause, so that we don't pause again at the same statement.
+    last_pause_ticks: Option<usize>,
+}
+
+/// The controller's end of a debugging session.
+pub struct DebugHandle {
+    commands: Sender<DebugCommand>,
+    events: Receiver<DebugEvent>,
+}
+
+/// Create a connected debugger (to be attached to a task) and its controller handle.
+/// The task pauses before its first statement.
+pub fn debug_channel() -> (Debugger, DebugHandle) {
+    let (command_send, command_receive) = crossbeam_channel::unbounded();
+    let (event_send, event_receive) = crossbeam_channel::unbounded();
+    (
+        Debugger {
+            commands: command_receive,
+            events: event_send,
+            stepping: true,
+            breakpoints: HashSet::new(),
+            last_pause_ticks: None,
+        },
+        DebugHandle {
+            commands: command_send,
+            events: event_receive,
+        },
+    )
+}
+
+impl Debugger {
+    /// Whether the task should pause before the statement at `line`. `ticks` identifies the
+    /// point of execution, so that we pause at most once per statement.
+    pub(crate) fn should_pause(&self, line: usize, ticks: usize) -> bool {
+        if self.last_pause_ticks == Some(ticks) {
+            return false;
+        }
+        self.stepping || self.breakpoints.contains(&line)
+    }
+
+    /// Report the frame to the controller, and block until told to run again.
+    /// `interrupted` is polled while waiting, so that a paused task can still be killed.
+    /// Returns None if the controller has gone away, in which case the debugger should be
+    /// detached and the task left to run freely.
+    pub(crate) fn pause(
+        &mut self,
+        frame: DebugFrame,
+        ticks: usize,
+        interrupted: impl Fn() -> bool,
+    ) -> Option<Resumption> {
+        self.last_pause_ticks = Some(ticks);
+        self.events.send(DebugEvent::Paused(frame)).ok()?;
+        loop {
+            if interrupted() {
+                return Some(Resumption::Abort);
+            }
+            match self.commands.recv_timeout(Duration::from_millis(100)) {
+                Ok(DebugCommand::Step) => {
+                    self.stepping = true;
+                    return Some(Resumption::Run);
+                }
+                Ok(DebugCommand::Continue) => {
+                    self.stepping = false;
+                    return Some(Resumption::Run);
+                }
+                Ok(DebugCommand::SetBreakpoint(line)) => {
+                    self.breakpoints.insert(line);
+                }
+                Ok(DebugCommand::ClearBreakpoint(line)) => {
+                    self.breakpoints.remove(&line);
+                }
+                Ok(DebugCommand::Abort) => return Some(Resumption::Abort),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+/// The debugger travels with its task through suspension and retries, so it is only dropped
+/// once the task is done with.
+impl Drop for Debugger {
+    fn drop(&mut self) {
+        self.events.send(DebugEvent::Finished).ok();
+    }
+}
+
+impl DebugHandle {
+    pub fn step(&self) {
+        self.commands.send(DebugCommand::Step).ok();
+    }
+
+    pub fn cont(&self) {
+        self.commands.send(DebugCommand::Continue).ok();
+    }
+
+    pub fn set_breakpoint(&self, line: usize) {
+        self.commands.send(DebugCommand::SetBreakpoint(line)).ok();
+    }
+
+    pub fn clear_breakpoint(&self, line: usize) {
+        self.commands.send(DebugCommand::ClearBreakpoint(line)).ok();
+    }
+
+    pub fn abort(&self) {
+        self.commands.send(DebugCommand::Abort).ok();
+    }
+
+    /// Wait for the next event from the task. Returns None if nothing arrives in time, or the
+    /// task has gone away.
+    pub fn next_event(&self, timeout: Duration) -> Option<DebugEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Spotting connected players who have gone idle, so that the server can call the core's idle
+//! hook ($server_options.idle_hook) on them, once each time they pass $server_options.idle_threshold.
+
+use moor_var::Obj;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Remembers who has been reported idle, so that a player is only reported again after they've
+/// been active in between.
+#[derive(Default)]
+pub struct IdleWatch {
+    idle: HashSet<Obj>,
+}
+
+impl IdleWatch {
+    /// Given the connected players and how long each has been idle, return those (with their idle
+    /// seconds) who have been idle for at least `threshold` and weren't already at the last check.
+    pub fn check(
+        &mut self,
+        idle_players: impl IntoIterator<Item = (Obj, f64)>,
+        threshold: Duration,
+    ) -> Vec<(Obj, f64)> {
+        let mut idle = HashSet::new();
+        let mut newly_idle = vec![];
+        for (player, idle_seconds) in idle_players {
+            if idle_seconds < threshold.as_secs_f64() {
+                continue;
+            }
+            if !self.idle.contains(&player) {
+                newly_idle.push((player, idle_seconds));
+            }
+            idle.insert(player);
+        }
+        self.idle = idle;
+        newly_idle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_hook_fires_once_past_threshold() {
+        let (active, idler) = (Obj::mk_id(1), Obj::mk_id(2));
+        let threshold = Duration::from_secs(300);
+        let mut watch = IdleWatch::default();
+
+        assert!(
+            watch
+                .check([(active, 10.0), (idler, 200.0)], threshold)
+                .is_empty()
+        );
+
+        assert_eq!(
+            watch.check([(active, 10.0), (idler, 301.0)], threshold),
+            vec![(idler, 301.0)]
+        );
+        // Still idle, but already reported.
+        assert!(
+            watch
+                .check([(active, 10.0), (idler, 400.0)], threshold)
+                .is_empty()
+        );
+
+        // Once they've done something, going idle again is reported again.
+        assert!(
+            watch
+                .check([(active, 10.0), (idler, 0.0)], threshold)
+                .is_empty()
+        );
+        assert_eq!(
+            watch.check([(active, 10.0), (idler, 300.0)], threshold),
+            vec![(idler, 300.0)]
+        );
+
+        // As is reconnecting after having disconnected while idle.
+        assert!(watch.check([(active, 10.0)], threshold).is_empty());
+        assert_eq!(
+            watch.check([(idler, 500.0)], threshold),
+            vec![(idler, 500.0)]
+        );
+    }
+}
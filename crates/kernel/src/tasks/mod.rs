@@ -23,9 +23,12 @@ use moor_var::{Symbol, Var};
 
 pub use crate::tasks::tasks_db::{NoopTasksDb, TasksDb, TasksDbError};
 use crate::vm::Fork;
-use moor_common::tasks::{SchedulerError, TaskId};
+use moor_common::matching::default_command_aliases;
+use moor_common::tasks::{ListenerTls, SchedulerError, TaskId};
 use moor_common::util::PerfCounter;
 
+pub mod debugger;
+pub mod idle_watch;
 pub mod scheduler;
 
 pub(crate) mod scheduler_client;
@@ -98,10 +101,13 @@ pub struct TaskDescription {
     pub verb_definer: Obj,
     pub line_number: usize,
     pub this: Var,
+    /// The name the task gave itself with `set_task_name()`, if any.
+    pub name: Option<String>,
 }
 
 /// The set of options that can be configured for the server via core $server_options.
-/// bf_load_server_options refreshes the server options from the database.
+/// bf_load_server_options refreshes the server options from the database, as does SIGHUP on the
+/// daemon.
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct ServerOptions {
     /// The number of seconds allotted to background tasks.
@@ -114,6 +120,45 @@ pub struct ServerOptions {
     pub fg_ticks: usize,
     /// The maximum number of levels of nested verb calls.
     pub max_stack_depth: usize,
+    /// Extra variables (e.g. CONTACT, WEBSITE) to report to MUD listing sites over MSSP, from the
+    /// $server_options.mssp map. These override the ones the server fills in itself, like NAME.
+    pub mssp: Vec<(String, String)>,
+    /// If set, the number of seconds a connected player can go without input before their idle
+    /// hook verb is called, with their idle seconds as its argument.
+    pub idle_threshold: Option<u64>,
+    /// The verb called on a player when they pass the idle threshold.
+    pub idle_hook: Symbol,
+    /// Command prefixes which stand for a verb (e.g. `"` for `say`), as (prefix, verb) pairs,
+    /// from the $server_options.command_aliases map.
+    pub command_aliases: Vec<(String, String)>,
+    /// The object `create_player()` parents new players under, from
+    /// $server_options.player_prototype.
+    pub player_prototype: Option<Obj>,
+    /// The prototype's properties each new player is given its own copy of, rather than
+    /// inheriting, from the $server_options.player_starter_properties list of names.
+    pub player_starter_properties: Vec<Symbol>,
+    /// The certificate and key that `listen()` hands to hosts for TLS listeners, from the
+    /// $server_options.tls_certificate and $server_options.tls_key paths.
+    pub listener_tls: Option<ListenerTls>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            bg_seconds: DEFAULT_BG_SECONDS,
+            bg_ticks: DEFAULT_BG_TICKS,
+            fg_seconds: DEFAULT_FG_SECONDS,
+            fg_ticks: DEFAULT_FG_TICKS,
+            max_stack_depth: DEFAULT_MAX_STACK_DEPTH,
+            mssp: vec![],
+            idle_threshold: None,
+            idle_hook: Symbol::mk("idle_notification"),
+            command_aliases: default_command_aliases(),
+            player_prototype: None,
+            player_starter_properties: vec![],
+            listener_tls: None,
+        }
+    }
 }
 
 impl ServerOptions {
@@ -16,8 +16,8 @@ use crossbeam_channel::{Receiver, Select};
 use lazy_static::lazy_static;
 use minstant::Instant;
 use std::fs::File;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use std::thread::yield_now;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::{error, info, warn};
@@ -30,16 +30,14 @@ use moor_compiler::{compile, program_to_tree, to_literal, unparse};
 use moor_db::Database;
 
 use crate::config::{Config, ImportExportFormat};
+use crate::tasks::debugger::Debugger;
 use crate::tasks::scheduler_client::{SchedulerClient, SchedulerClientMsg};
 use crate::tasks::task::Task;
 use crate::tasks::task_q::{RunningTask, SuspensionQ, TaskQ, WakeCondition};
 use crate::tasks::task_scheduler_client::{TaskControlMsg, TaskSchedulerClient};
 use crate::tasks::tasks_db::TasksDb;
 use crate::tasks::workers::{WorkerRequest, WorkerResponse};
-use crate::tasks::{
-    DEFAULT_BG_SECONDS, DEFAULT_BG_TICKS, DEFAULT_FG_SECONDS, DEFAULT_FG_TICKS,
-    DEFAULT_MAX_STACK_DEPTH, ServerOptions, TaskHandle, TaskResult, TaskStart, sched_counters,
-};
+use crate::tasks::{ServerOptions, TaskHandle, TaskResult, TaskStart, sched_counters};
 use crate::vm::builtins::BuiltinRegistry;
 use crate::vm::{Fork, TaskSuspend};
 use moor_common::matching::ObjectNameMatcher;
@@ -54,7 +52,7 @@ use moor_common::tasks::{
     AbortLimitReason, CommandError, Event, NarrativeEvent, SchedulerError, TaskId,
     VerbProgramError, WorkerError,
 };
-use moor_common::tasks::{Session, SessionFactory, SystemControl};
+use moor_common::tasks::{ListenerTls, Session, SessionFactory, SystemControl};
 use moor_common::util::PerfTimerGuard;
 use moor_objdef::{collect_object_definitions, dump_object_definitions};
 use moor_textdump::{TextdumpWriter, make_textdump};
@@ -80,6 +78,14 @@ lazy_static! {
     static ref FG_SECONDS: Symbol = Symbol::mk("fg_seconds");
     static ref FG_TICKS: Symbol = Symbol::mk("fg_ticks");
     static ref MAX_STACK_DEPTH: Symbol = Symbol::mk("max_stack_depth");
+    static ref MSSP: Symbol = Symbol::mk("mssp");
+    static ref IDLE_THRESHOLD: Symbol = Symbol::mk("idle_threshold");
+    static ref IDLE_HOOK: Symbol = Symbol::mk("idle_hook");
+    static ref COMMAND_ALIASES: Symbol = Symbol::mk("command_aliases");
+    static ref PLAYER_PROTOTYPE: Symbol = Symbol::mk("player_prototype");
+    static ref PLAYER_STARTER_PROPERTIES: Symbol = Symbol::mk("player_starter_properties");
+    static ref TLS_CERTIFICATE: Symbol = Symbol::mk("tls_certificate");
+    static ref TLS_KEY: Symbol = Symbol::mk("tls_key");
     static ref DO_OUT_OF_BAND_COMMAND: Symbol = Symbol::mk("do_out_of_band_command");
 }
 /// Responsible for the dispatching, control, and accounting of tasks in the system.
@@ -100,6 +106,9 @@ pub struct Scheduler {
     next_task_id: usize,
 
     server_options: ServerOptions,
+    /// A copy of `server_options` shared with our `SchedulerClient`s, so they can read them
+    /// without a round-trip to the scheduler thread.
+    published_server_options: Arc<RwLock<ServerOptions>>,
 
     builtin_registry: BuiltinRegistry,
 
@@ -120,14 +129,186 @@ fn load_int_sysprop(server_options_obj: &Obj, name: Symbol, tx: &dyn WorldState)
         return None;
     };
     match value.variant() {
-        Variant::Int(i) if *i >= 0 => Some(*i as u64),
+        Variant::Int(i) if *i > 0 => Some(*i as u64),
         _ => {
-            warn!("$bg_seconds is not a positive integer");
+            warn!("$server_options.{name} is not a positive integer; using default");
             None
         }
     }
 }
 
+fn load_str_sysprop(server_options_obj: &Obj, name: Symbol, tx: &dyn WorldState) -> Option<String> {
+    let Ok(value) = tx.retrieve_property(&SYSTEM_OBJECT, server_options_obj, name) else {
+        return None;
+    };
+    match value.as_string() {
+        Some(s) if !s.is_empty() => Some(s.to_string()),
+        _ => {
+            warn!("$server_options.{name} is not a non-empty string; ignoring");
+            None
+        }
+    }
+}
+
+/// Read the map of extra MSSP variables. Values which aren't strings are reported as literals.
+fn load_mssp_sysprop(
+    server_options_obj: &Obj,
+    tx: &dyn WorldState,
+) -> Option<Vec<(String, String)>> {
+    let Ok(value) = tx.retrieve_property(&SYSTEM_OBJECT, server_options_obj, *MSSP) else {
+        return None;
+    };
+    let Variant::Map(map) = value.variant() else {
+        warn!("$server_options.mssp is not a map; ignoring");
+        return None;
+    };
+    let mut variables = vec![];
+    for (name, value) in map.iter() {
+        let Some(name) = name.as_string() else {
+            warn!(
+                ?name,
+                "$server_options.mssp has a non-string key; ignoring it"
+            );
+            continue;
+        };
+        let value = match value.as_string() {
+            Some(value) => value.to_string(),
+            None => to_literal(&value),
+        };
+        variables.push((name.to_string(), value));
+    }
+    Some(variables)
+}
+
+/// Read the map of command aliases, from the prefix to the verb it stands for. Entries which
+/// aren't both non-empty strings are skipped.
+fn load_command_aliases_sysprop(
+    server_options_obj: &Obj,
+    tx: &dyn WorldState,
+) -> Option<Vec<(String, String)>> {
+    let Ok(value) = tx.retrieve_property(&SYSTEM_OBJECT, server_options_obj, *COMMAND_ALIASES)
+    else {
+        return None;
+    };
+    let Variant::Map(map) = value.variant() else {
+        warn!("$server_options.command_aliases is not a map; using default");
+        return None;
+    };
+    let mut aliases = vec![];
+    for (prefix, verb) in map.iter() {
+        match (prefix.as_string(), verb.as_string()) {
+            (Some(prefix), Some(verb)) if !prefix.is_empty() && !verb.is_empty() => {
+                aliases.push((prefix.to_string(), verb.to_string()));
+            }
+            _ => warn!(
+                ?prefix,
+                ?verb,
+                "$server_options.command_aliases has an invalid entry; ignoring it"
+            ),
+        }
+    }
+    Some(aliases)
+}
+
+/// Read the names of the properties new players get their own copies of. Entries which aren't
+/// non-empty strings are skipped.
+fn load_starter_properties_sysprop(
+    server_options_obj: &Obj,
+    tx: &dyn WorldState,
+) -> Option<Vec<Symbol>> {
+    let Ok(value) = tx.retrieve_property(
+        &SYSTEM_OBJECT,
+        server_options_obj,
+        *PLAYER_STARTER_PROPERTIES,
+    ) else {
+        return None;
+    };
+    let Variant::List(names) = value.variant() else {
+        warn!("$server_options.player_starter_properties is not a list; ignoring");
+        return None;
+    };
+    let mut properties = vec![];
+    for name in names.iter() {
+        match name.as_string() {
+            Some(name) if !name.is_empty() => properties.push(Symbol::mk(name)),
+            _ => warn!(
+                ?name,
+                "$server_options.player_starter_properties has an invalid entry; ignoring it"
+            ),
+        }
+    }
+    Some(properties)
+}
+
+/// Build the server options from the properties on $server_options, as seen by `tx`.
+/// Anything missing or invalid (including $server_options itself) keeps its default.
+pub(crate) fn load_server_options(tx: &dyn WorldState) -> ServerOptions {
+    let mut so = ServerOptions::default();
+
+    let Ok(server_options_obj) =
+        tx.retrieve_property(&SYSTEM_OBJECT, &SYSTEM_OBJECT, *SERVER_OPTIONS)
+    else {
+        info!("No server options object found; using defaults");
+        return so;
+    };
+    let Some(server_options_obj) = server_options_obj.as_object() else {
+        info!("Server options property is not an object; using defaults");
+        return so;
+    };
+
+    if let Some(bg_seconds) = load_int_sysprop(&server_options_obj, *BG_SECONDS, tx) {
+        so.bg_seconds = bg_seconds;
+    }
+    if let Some(bg_ticks) = load_int_sysprop(&server_options_obj, *BG_TICKS, tx) {
+        so.bg_ticks = bg_ticks as usize;
+    }
+    if let Some(fg_seconds) = load_int_sysprop(&server_options_obj, *FG_SECONDS, tx) {
+        so.fg_seconds = fg_seconds;
+    }
+    if let Some(fg_ticks) = load_int_sysprop(&server_options_obj, *FG_TICKS, tx) {
+        so.fg_ticks = fg_ticks as usize;
+    }
+    if let Some(max_stack_depth) = load_int_sysprop(&server_options_obj, *MAX_STACK_DEPTH, tx) {
+        so.max_stack_depth = max_stack_depth as usize;
+    }
+    if let Some(mssp) = load_mssp_sysprop(&server_options_obj, tx) {
+        so.mssp = mssp;
+    }
+    so.idle_threshold = load_int_sysprop(&server_options_obj, *IDLE_THRESHOLD, tx);
+    if let Ok(idle_hook) = tx.retrieve_property(&SYSTEM_OBJECT, &server_options_obj, *IDLE_HOOK) {
+        match idle_hook.as_string() {
+            Some(idle_hook) if !idle_hook.is_empty() => so.idle_hook = Symbol::mk(idle_hook),
+            _ => warn!("$server_options.idle_hook is not a verb name; using default"),
+        }
+    }
+    if let Some(command_aliases) = load_command_aliases_sysprop(&server_options_obj, tx) {
+        so.command_aliases = command_aliases;
+    }
+    if let Ok(player_prototype) =
+        tx.retrieve_property(&SYSTEM_OBJECT, &server_options_obj, *PLAYER_PROTOTYPE)
+    {
+        match player_prototype.as_object() {
+            Some(player_prototype) => so.player_prototype = Some(player_prototype),
+            None => warn!("$server_options.player_prototype is not an object; ignoring"),
+        }
+    }
+    if let Some(properties) = load_starter_properties_sysprop(&server_options_obj, tx) {
+        so.player_starter_properties = properties;
+    }
+    so.listener_tls = match (
+        load_str_sysprop(&server_options_obj, *TLS_CERTIFICATE, tx),
+        load_str_sysprop(&server_options_obj, *TLS_KEY, tx),
+    ) {
+        (Some(certificate), Some(key)) => Some(ListenerTls { certificate, key }),
+        (None, None) => None,
+        _ => {
+            warn!("$server_options.tls_certificate and tls_key must be set together; ignoring");
+            None
+        }
+    };
+    so
+}
+
 impl Scheduler {
     pub fn new(
         version: semver::Version,
@@ -144,13 +325,7 @@ impl Scheduler {
         let task_q = TaskQ {
             active: Default::default(),
             suspended: suspension_q,
-        };
-        let default_server_options = ServerOptions {
-            bg_seconds: DEFAULT_BG_SECONDS,
-            bg_ticks: DEFAULT_BG_TICKS,
-            fg_seconds: DEFAULT_FG_SECONDS,
-            fg_ticks: DEFAULT_FG_TICKS,
-            max_stack_depth: DEFAULT_MAX_STACK_DEPTH,
+            object_locks: Default::default(),
         };
         let builtin_registry = BuiltinRegistry::new();
         Self {
@@ -165,7 +340,8 @@ impl Scheduler {
             scheduler_sender,
             scheduler_receiver,
             builtin_registry,
-            server_options: default_server_options,
+            server_options: ServerOptions::default(),
+            published_server_options: Default::default(),
             system_control,
             worker_request_send,
             worker_request_recv,
@@ -253,54 +429,32 @@ impl Scheduler {
         info!("Saved.");
     }
 
+    /// Rebuild the server options from $server_options, falling back to the defaults for anything
+    /// not set there. The new options replace the old ones in one go, and are picked up by tasks
+    /// started after this point.
     pub fn reload_server_options(&mut self) {
         // Load the server options from the database, if possible.
         let tx = self
             .database
             .new_world_state()
             .expect("Could not open transaction to read server properties");
-
-        let mut so = self.server_options.clone();
-
-        let Ok(server_options_obj) =
-            tx.retrieve_property(&SYSTEM_OBJECT, &SYSTEM_OBJECT, *SERVER_OPTIONS)
-        else {
-            info!("No server options object found; using defaults");
-            tx.rollback().unwrap();
-            return;
-        };
-        let Some(server_options_obj) = server_options_obj.as_object() else {
-            info!("Server options property is not an object; using defaults");
-            tx.rollback().unwrap();
-            return;
-        };
-
-        if let Some(bg_seconds) = load_int_sysprop(&server_options_obj, *BG_SECONDS, tx.as_ref()) {
-            so.bg_seconds = bg_seconds;
-        }
-        if let Some(bg_ticks) = load_int_sysprop(&server_options_obj, *BG_TICKS, tx.as_ref()) {
-            so.bg_ticks = bg_ticks as usize;
-        }
-        if let Some(fg_seconds) = load_int_sysprop(&server_options_obj, *FG_SECONDS, tx.as_ref()) {
-            so.fg_seconds = fg_seconds;
-        }
-        if let Some(fg_ticks) = load_int_sysprop(&server_options_obj, *FG_TICKS, tx.as_ref()) {
-            so.fg_ticks = fg_ticks as usize;
-        }
-        if let Some(max_stack_depth) =
-            load_int_sysprop(&server_options_obj, *MAX_STACK_DEPTH, tx.as_ref())
-        {
-            so.max_stack_depth = max_stack_depth as usize;
-        }
+        let so = load_server_options(tx.as_ref());
         tx.rollback().unwrap();
 
-        self.server_options = so;
+        self.set_server_options(so);
+    }
 
-        info!("Server options refreshed.");
+    fn set_server_options(&mut self, server_options: ServerOptions) {
+        info!(?server_options, "Server options refreshed.");
+        *self.published_server_options.write().unwrap() = server_options.clone();
+        self.server_options = server_options;
     }
 
     pub fn client(&self) -> Result<SchedulerClient, SchedulerError> {
-        Ok(SchedulerClient::new(self.scheduler_sender.clone()))
+        Ok(SchedulerClient::new(
+            self.scheduler_sender.clone(),
+            self.published_server_options.clone(),
+        ))
     }
 
     /// Start a transaction, match the object name and verb name, and if it exists and the
@@ -345,7 +499,11 @@ impl Scheduler {
                 names: None,
                 flags: None,
                 args_spec: None,
-                program: Some(ProgramType::MooR(program)),
+                program: Some(if self.config.features_config.preserve_verb_source {
+                    ProgramType::MooRWithSource(program, code.clone())
+                } else {
+                    ProgramType::MooR(program)
+                }),
             };
             tx.update_verb_with_id(perms, &o, verbdef.uuid(), update_attrs)
                 .map_err(|_| VerbProgramFailed(VerbProgramError::NoVerbToProgram))?;
@@ -394,6 +552,7 @@ impl Scheduler {
                     self.database.as_ref(),
                     self.builtin_registry.clone(),
                     self.config.clone(),
+                    None,
                 );
                 reply
                     .send(result)
@@ -407,6 +566,7 @@ impl Scheduler {
                 argstr,
                 perms,
                 session,
+                debugger,
                 reply,
             } => {
                 // We need to translate Vloc and any of of the arguments into valid references
@@ -451,6 +611,7 @@ impl Scheduler {
                     self.database.as_ref(),
                     self.builtin_registry.clone(),
                     self.config.clone(),
+                    debugger,
                 );
                 reply
                     .send(result)
@@ -521,6 +682,7 @@ impl Scheduler {
                     self.database.as_ref(),
                     self.builtin_registry.clone(),
                     self.config.clone(),
+                    None,
                 );
                 reply
                     .send(result)
@@ -548,6 +710,7 @@ impl Scheduler {
                     self.database.as_ref(),
                     self.builtin_registry.clone(),
                     self.config.clone(),
+                    None,
                 );
                 reply
                     .send(result)
@@ -616,6 +779,12 @@ impl Scheduler {
                 let result = self.checkpoint();
                 reply.send(result).expect("Could not send checkpoint reply");
             }
+            SchedulerClientMsg::ReloadServerOptions(reply) => {
+                self.reload_server_options();
+                reply
+                    .send(Ok(()))
+                    .expect("Could not send server options reload reply");
+            }
             SchedulerClientMsg::RequestProperties {
                 player,
                 perms,
@@ -806,6 +975,14 @@ impl Scheduler {
                         }
                     };
 
+                // If the source was stored with the program, hand that back as-is.
+                if let Some(source) = program.source() {
+                    reply
+                        .send(Ok((verbdef, source.to_vec())))
+                        .expect("Could not send verb code reply");
+                    return;
+                }
+
                 // If the binary is empty, just return empty rather than try to decode it.
                 if program.is_empty() {
                     reply
@@ -815,7 +992,9 @@ impl Scheduler {
                 }
 
                 #[allow(irrefutable_let_patterns)]
-                let ProgramType::MooR(program) = program else {
+                let (ProgramType::MooR(program) | ProgramType::MooRWithSource(program, _)) =
+                    program
+                else {
                     reply
                         .send(Err(SchedulerError::VerbRetrievalFailed(
                             WorldStateError::DatabaseError(format!(
@@ -887,6 +1066,42 @@ impl Scheduler {
                     .send(Ok(omatch))
                     .expect("Could not send object resolution reply");
             }
+            SchedulerClientMsg::KillTask {
+                task_id,
+                player,
+                reply,
+            } => {
+                let world_state = match self.database.new_world_state() {
+                    Ok(ws) => ws,
+                    Err(e) => {
+                        reply
+                            .send(Err(CommandExecutionError(CommandError::DatabaseError(e))))
+                            .expect("Could not send kill task reply");
+                        return;
+                    }
+                };
+                let flags = match world_state.flags_of(&player) {
+                    Ok(flags) => flags,
+                    Err(e) => {
+                        reply
+                            .send(Err(CommandExecutionError(CommandError::DatabaseError(e))))
+                            .expect("Could not send kill task reply");
+                        return;
+                    }
+                };
+
+                // Tasks the player isn't allowed to kill are reported the same as ones that
+                // don't exist (anymore).
+                let result = match self
+                    .task_q
+                    .kill_task(task_id, Perms { who: player, flags })
+                    .variant()
+                {
+                    Variant::Err(_) => Err(SchedulerError::TaskNotFound(task_id)),
+                    _ => Ok(()),
+                };
+                reply.send(result).expect("Could not send kill task reply");
+            }
         }
     }
 
@@ -896,6 +1111,27 @@ impl Scheduler {
         let counters = sched_counters();
         let _t = PerfTimerGuard::new(&counters.handle_task_msg);
 
+        // Options the task loaded take effect only once its transaction has committed, which it
+        // has by the time it reports any of these. (On abort or a conflict retry they're dropped
+        // along with its transaction.)
+        let committed = matches!(
+            msg,
+            TaskControlMsg::TaskSuccess(_)
+                | TaskControlMsg::TaskException(_)
+                | TaskControlMsg::TaskSuspend(..)
+                | TaskControlMsg::TaskRequestInput(_)
+        );
+        if committed {
+            let server_options = self
+                .task_q
+                .active
+                .get_mut(&task_id)
+                .and_then(|task| task.pending_server_options.take());
+            if let Some(server_options) = server_options {
+                self.set_server_options(server_options);
+            }
+        }
+
         let task_q = &mut self.task_q;
         match msg {
             TaskControlMsg::TaskSuccess(value) => {
@@ -970,6 +1206,10 @@ impl Scheduler {
                         warn!(?task_id, time = ?t, "Task aborted, time exceeded");
                         format!("Abort: Task exceeded time limit of {:?}", t)
                     }
+                    AbortLimitReason::BuiltinDuration(name) => {
+                        warn!(?task_id, builtin = ?name, "Task aborted, builtin ran too long");
+                        format!("Abort: Task aborted, builtin {name}() ran too long")
+                    }
                 };
 
                 // Commit the session
@@ -1120,6 +1360,15 @@ impl Scheduler {
                 }
                 // TODO: add non-queued tasks.
             }
+            TaskControlMsg::RequestTaskStack {
+                queued_task_id,
+                reply,
+            } => {
+                let stack = self.task_q.suspended.task_stack(queued_task_id);
+                if let Err(e) = reply.send(stack) {
+                    error!(?e, "Could not send task stack to requester");
+                }
+            }
             TaskControlMsg::KillTask {
                 victim_task_id,
                 sender_permissions,
@@ -1180,15 +1429,28 @@ impl Scheduler {
                 host_type,
                 port,
                 print_messages,
+                tls,
                 reply,
             } => {
                 let Some(_task) = task_q.active.get_mut(&task_id) else {
                     warn!(task_id, "Task not found for listen request");
                     return;
                 };
+                let tls = match (tls, &self.server_options.listener_tls) {
+                    (false, _) => None,
+                    (true, Some(listener_tls)) => Some(listener_tls.clone()),
+                    (true, None) => {
+                        let error = E_INVARG
+                            .msg("TLS listeners need $server_options.tls_certificate and tls_key");
+                        reply
+                            .send(Some(error))
+                            .expect("Could not send listen reply");
+                        return;
+                    }
+                };
                 let result = self
                     .system_control
-                    .listen(handler_object, &host_type, port, print_messages)
+                    .listen(handler_object, &host_type, port, print_messages, tls)
                     .err();
                 reply.send(result).expect("Could not send listen reply");
             }
@@ -1240,6 +1502,7 @@ impl Scheduler {
                     self.database.as_ref(),
                     self.builtin_registry.clone(),
                     self.config.clone(),
+                    None,
                 );
                 match result {
                     Err(e) => {
@@ -1260,8 +1523,12 @@ impl Scheduler {
                     error!(?e, "Could not checkpoint");
                 }
             }
-            TaskControlMsg::RefreshServerOptions => {
-                self.reload_server_options();
+            TaskControlMsg::RefreshServerOptions(server_options) => {
+                let Some(task) = self.task_q.active.get_mut(&task_id) else {
+                    warn!(task_id, "Task not found for server options refresh");
+                    return;
+                };
+                task.pending_server_options = Some(server_options);
             }
             TaskControlMsg::ActiveTasks { reply } => {
                 let mut results = vec![];
@@ -1272,6 +1539,12 @@ impl Scheduler {
                     error!(?e, "Could not send active tasks to requester");
                 }
             }
+            TaskControlMsg::LockObject { obj, reply } => {
+                reply.send(task_q.lock_object(obj, task_id)).ok();
+            }
+            TaskControlMsg::UnlockObject { obj, reply } => {
+                reply.send(task_q.unlock_object(obj, task_id)).ok();
+            }
         }
     }
 
@@ -1435,6 +1708,7 @@ impl Scheduler {
             self.database.as_ref(),
             self.builtin_registry.clone(),
             self.config.clone(),
+            None,
         ) {
             Ok(th) => th,
             Err(e) => {
@@ -1502,6 +1776,7 @@ impl TaskQ {
         database: &dyn Database,
         builtin_registry: BuiltinRegistry,
         config: Arc<Config>,
+        debugger: Option<Debugger>,
     ) -> Result<TaskHandle, SchedulerError> {
         let perfc = sched_counters();
         let _t = PerfTimerGuard::new(&perfc.start_task);
@@ -1520,6 +1795,9 @@ impl TaskQ {
             server_options,
             kill_switch.clone(),
         );
+        if let Some(debugger) = debugger {
+            task.attach_debugger(debugger);
+        }
 
         // If this task is delayed, stick it into suspension state immediately.
         if let Some(delay) = delay_start {
@@ -1533,7 +1811,11 @@ impl TaskQ {
                 }
             };
 
-            if !task.setup_task_start(control_sender, world_state.as_mut()) {
+            if !task.setup_task_start(
+                control_sender,
+                world_state.as_mut(),
+                config.features_config.as_ref(),
+            ) {
                 error!(task_id, "Could not setup task start");
                 return Err(SchedulerError::CouldNotStartTask);
             }
@@ -1560,10 +1842,12 @@ impl TaskQ {
         // Otherwise, we create a task control record and fire up a thread.
         let task_control = RunningTask {
             player: *player,
+            owner: *perms,
             kill_switch,
             task_start,
             session: session.clone(),
             result_sender: (!is_background).then_some(sender),
+            pending_server_options: None,
         };
 
         // Footgun warning: ALWAYS `self.tasks.insert` before spawning the task thread!
@@ -1584,7 +1868,11 @@ impl TaskQ {
             .spawn(move || {
                 // Start the db transaction, which will initially be used to resolve the verb before the task
                 // starts executing.
-                if !task.setup_task_start(&control_sender, world_state.as_mut()) {
+                if !task.setup_task_start(
+                    &control_sender,
+                    world_state.as_mut(),
+                    config.features_config.as_ref(),
+                ) {
                     // Log level should be low here as this happens on every command if `do_command`
                     // is not found.
                     return;
@@ -1636,17 +1924,19 @@ impl TaskQ {
         };
 
         let task_id = task.task_id;
-        let player = task.perms;
+        let player = task.player;
 
         // Brand new kill switch for the resumed task. The old one may have gotten toggled.
         let kill_switch = Arc::new(AtomicBool::new(false));
         task.kill_switch = kill_switch.clone();
         let task_control = RunningTask {
             player,
+            owner: task.perms,
             kill_switch,
             session: session.clone(),
             result_sender,
             task_start: task.task_start.clone(),
+            pending_server_options: None,
         };
 
         self.active.insert(task_id, task_control);
@@ -1672,6 +1962,8 @@ impl TaskQ {
     }
 
     fn send_task_result(&mut self, task_id: TaskId, result: Result<Var, SchedulerError>) {
+        // The task is done with, even if it was already taken out of the active list.
+        self.release_object_locks(task_id);
         let Some(mut task_control) = self.active.remove(&task_id) else {
             // Missing task, must have ended already or gone into suspension?
             // This is odd though? So we'll warn.
@@ -1738,10 +2030,12 @@ impl TaskQ {
         // Otherwise, we create a task control record and fire up a thread.
         let task_control = RunningTask {
             player: old_tc.player,
+            owner: old_tc.owner,
             kill_switch,
             session: new_session.clone(),
             result_sender: old_tc.result_sender,
             task_start: task.task_start.clone(),
+            pending_server_options: None,
         };
 
         // Footgun warning: ALWAYS `self.tasks.insert` before spawning the task thread!
@@ -1782,10 +2076,10 @@ impl TaskQ {
         // We need to do perms check first, which means checking both running and suspended tasks,
         // and getting their permissions. And may as well remember whether it was in suspended or
         // active at the same time.
-        let (perms, is_suspended) = match self.suspended.perms_check(victim_task_id, false) {
-            Some(perms) => (perms, true),
+        let (owner, is_suspended) = match self.suspended.perms_check(victim_task_id, false) {
+            Some(owner) => (owner, true),
             None => match self.active.get(&victim_task_id) {
-                Some(tc) => (tc.player, false),
+                Some(tc) => (tc.owner, false),
                 None => {
                     return v_err(E_INVARG);
                 }
@@ -1793,16 +2087,11 @@ impl TaskQ {
         };
 
         // We reject this outright if the sender permissions are not sufficient:
-        //   The either have to be the owner of the task (task.programmer == sender_permissions.task_perms)
-        //   Or they have to be a wizard.
-        // TODO: Verify kill task permissions is right
-        //   Will have to verify that it's enough that .player on task control can
-        //   be considered "owner" of the task, or there needs to be some more
-        //   elaborate consideration here?
+        //   They either have to be the owner of the task, or they have to be a wizard.
         if !sender_permissions
             .check_is_wizard()
             .expect("Could not check wizard status for kill request")
-            && sender_permissions.who != perms
+            && sender_permissions.who != owner
         {
             return v_err(E_PERM);
         }
@@ -1815,6 +2104,7 @@ impl TaskQ {
                     "Task not found in suspended list for kill request"
                 );
             }
+            self.release_object_locks(victim_task_id);
             return v_none();
         }
 
@@ -1827,6 +2117,7 @@ impl TaskQ {
             }
         };
         victim_task.kill_switch.store(true, Ordering::SeqCst);
+        self.release_object_locks(victim_task_id);
         v_none()
     }
 
@@ -1852,16 +2143,16 @@ impl TaskQ {
             return v_err(E_INVARG);
         }
 
-        let Some(perms) = self.suspended.perms_check(queued_task_id, true) else {
+        let Some(owner) = self.suspended.perms_check(queued_task_id, true) else {
             error!(task = queued_task_id, "Task not found for resume request");
             return v_err(E_INVARG);
         };
 
-        // No permissions.
+        // Only the task's owner, or a wizard, may resume it.
         if !sender_permissions
             .check_is_wizard()
             .expect("Could not check wizard status for resume request")
-            && sender_permissions.who != perms
+            && sender_permissions.who != owner
         {
             return v_err(E_PERM);
         }
@@ -1915,7 +2206,9 @@ impl TaskQ {
             tc.kill_switch.store(true, Ordering::SeqCst);
         }
         // Prune out non-background tasks for the player.
-        self.suspended.prune_foreground_tasks(player);
+        for task_id in self.suspended.prune_foreground_tasks(player) {
+            self.release_object_locks(task_id);
+        }
     }
 }
 
@@ -12,7 +12,7 @@
 //
 
 use crossbeam_channel::Sender;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use uuid::Uuid;
 
@@ -21,10 +21,11 @@ use moor_compiler::{Program, compile};
 use moor_var::{List, Obj, Symbol, Var};
 
 use crate::config::FeaturesConfig;
-use crate::tasks::TaskHandle;
+use crate::tasks::debugger::{DebugHandle, Debugger, debug_channel};
+use crate::tasks::{ServerOptions, TaskHandle};
 use moor_common::tasks::SchedulerError;
 use moor_common::tasks::SchedulerError::CompilationError;
-use moor_common::tasks::Session;
+use moor_common::tasks::{Session, TaskId};
 
 /// A handle for talking to the scheduler from the outside world.
 /// This is not meant to be used by running tasks, but by the rpc daemon, tests, etc.
@@ -32,11 +33,18 @@ use moor_common::tasks::Session;
 #[derive(Clone)]
 pub struct SchedulerClient {
     scheduler_sender: Sender<SchedulerClientMsg>,
+    server_options: Arc<RwLock<ServerOptions>>,
 }
 
 impl SchedulerClient {
-    pub fn new(scheduler_sender: Sender<SchedulerClientMsg>) -> Self {
-        Self { scheduler_sender }
+    pub fn new(
+        scheduler_sender: Sender<SchedulerClientMsg>,
+        server_options: Arc<RwLock<ServerOptions>>,
+    ) -> Self {
+        Self {
+            scheduler_sender,
+            server_options,
+        }
     }
 
     /// Submit a command to the scheduler for execution.
@@ -88,6 +96,7 @@ impl SchedulerClient {
                 argstr,
                 perms: *perms,
                 session,
+                debugger: None,
                 reply,
             })
             .map_err(|_| SchedulerError::SchedulerNotResponding)?;
@@ -97,6 +106,41 @@ impl SchedulerClient {
             .map_err(|_| SchedulerError::SchedulerNotResponding)?
     }
 
+    /// Execute a verb under a debugger. The task pauses before its first statement, and is then
+    /// driven with the returned `DebugHandle`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit_debug_verb_task(
+        &self,
+        player: &Obj,
+        vloc: &ObjectRef,
+        verb: Symbol,
+        args: List,
+        argstr: String,
+        perms: &Obj,
+        session: Arc<dyn Session>,
+    ) -> Result<(TaskHandle, DebugHandle), SchedulerError> {
+        let (debugger, debug_handle) = debug_channel();
+        let (reply, receive) = oneshot::channel();
+        self.scheduler_sender
+            .send(SchedulerClientMsg::SubmitVerbTask {
+                player: *player,
+                vloc: vloc.clone(),
+                verb: Symbol::mk_case_insensitive(verb.as_str()),
+                args,
+                argstr,
+                perms: *perms,
+                session,
+                debugger: Some(debugger),
+                reply,
+            })
+            .map_err(|_| SchedulerError::SchedulerNotResponding)?;
+
+        let task_handle = receive
+            .recv_timeout(Duration::from_secs(5))
+            .map_err(|_| SchedulerError::SchedulerNotResponding)??;
+        Ok((task_handle, debug_handle))
+    }
+
     /// Receive input that the (suspended) task previously requested, using the given
     /// `input_request_id`.
     /// The request is identified by the `input_request_id`, and given the input and resumed under
@@ -246,6 +290,41 @@ impl SchedulerClient {
             .map_err(|_| SchedulerError::SchedulerNotResponding)?
     }
 
+    /// Re-read $server_options, so that tasks started from now on use the new limits.
+    /// Running tasks keep the limits they started with.
+    pub fn reload_server_options(&self) -> Result<(), SchedulerError> {
+        let (reply, receive) = oneshot::channel();
+        self.scheduler_sender
+            .send(SchedulerClientMsg::ReloadServerOptions(reply))
+            .map_err(|_| SchedulerError::SchedulerNotResponding)?;
+
+        receive
+            .recv_timeout(Duration::from_secs(5))
+            .map_err(|_| SchedulerError::SchedulerNotResponding)?
+    }
+
+    /// The server options currently in effect, as last published by the scheduler.
+    pub fn server_options(&self) -> ServerOptions {
+        self.server_options.read().unwrap().clone()
+    }
+
+    /// Kill a task on behalf of `player`, e.g. to interrupt a command they've changed their mind
+    /// about. As with `kill_task()`, they must own the task or be a wizard.
+    pub fn kill_task(&self, task_id: TaskId, player: &Obj) -> Result<(), SchedulerError> {
+        let (reply, receive) = oneshot::channel();
+        self.scheduler_sender
+            .send(SchedulerClientMsg::KillTask {
+                task_id,
+                player: *player,
+                reply,
+            })
+            .map_err(|_| SchedulerError::SchedulerNotResponding)?;
+
+        receive
+            .recv_timeout(Duration::from_secs(5))
+            .map_err(|_| SchedulerError::SchedulerNotResponding)?
+    }
+
     pub fn request_verbs(
         &self,
         player: &Obj,
@@ -364,6 +443,8 @@ pub enum SchedulerClientMsg {
         argstr: String,
         perms: Obj,
         session: Arc<dyn Session>,
+        /// If present, run the task under this debugger.
+        debugger: Option<Debugger>,
         reply: oneshot::Sender<Result<TaskHandle, SchedulerError>>,
     },
     /// Submit input to a task that is waiting for it.
@@ -443,8 +524,16 @@ pub enum SchedulerClientMsg {
         obj: ObjectRef,
         reply: oneshot::Sender<Result<Var, SchedulerError>>,
     },
+    /// Kill a task on behalf of a player.
+    KillTask {
+        task_id: TaskId,
+        player: Obj,
+        reply: oneshot::Sender<Result<(), SchedulerError>>,
+    },
     /// Submit a request to checkpoint the database.
     Checkpoint(oneshot::Sender<Result<(), SchedulerError>>),
+    /// Submit a request to reload the server options from $server_options.
+    ReloadServerOptions(oneshot::Sender<Result<(), SchedulerError>>),
     /// Submit a (non-task specific) request to shutdown the scheduler
     Shutdown(String, oneshot::Sender<Result<(), SchedulerError>>),
 }
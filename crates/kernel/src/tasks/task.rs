@@ -24,7 +24,7 @@
 //!
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use bincode::de::{BorrowDecoder, Decoder};
 use bincode::enc::Encoder;
@@ -32,19 +32,21 @@ use bincode::error::{DecodeError, EncodeError};
 use bincode::{BorrowDecode, Decode, Encode};
 use crossbeam_channel::Sender;
 use lazy_static::lazy_static;
-use tracing::{error, warn};
+use tracing::field::Empty;
+use tracing::{error, info_span, warn};
 
 use moor_common::model::{CommitResult, VerbDef, WorldState, WorldStateError};
 use moor_common::tasks::CommandError;
 use moor_common::tasks::CommandError::PermissionDenied;
 use moor_common::tasks::TaskId;
 use moor_common::util::{PerfTimerGuard, parse_into_words};
+use moor_var::{FAILED_MATCH, NOTHING, SYSTEM_OBJECT};
 use moor_var::{List, v_int, v_str};
-use moor_var::{NOTHING, SYSTEM_OBJECT};
 use moor_var::{Obj, v_obj};
 use moor_var::{Symbol, Variant};
 
 use crate::config::{Config, FeaturesConfig};
+use crate::tasks::debugger::{Debugger, Resumption};
 use crate::tasks::task_scheduler_client::{TaskControlMsg, TaskSchedulerClient};
 use crate::tasks::{ServerOptions, TaskStart, sched_counters};
 use crate::vm::builtins::BuiltinRegistry;
@@ -52,8 +54,8 @@ use crate::vm::exec_state::VMExecState;
 use crate::vm::vm_host::VmHost;
 use crate::vm::{VMHostResponse, VerbCall};
 use moor_common::matching::{
-    CommandParser, DefaultObjectNameMatcher, DefaultParseCommand, ParseCommandError, ParsedCommand,
-    WsMatchEnv,
+    CommandParser, DefaultObjectNameMatcher, DefaultParseCommand, ObjectNameMatcher,
+    ParseCommandError, ParsedCommand, WsMatchEnv, default_command_aliases,
 };
 use moor_common::program::ProgramType;
 use moor_common::tasks::Session;
@@ -81,6 +83,12 @@ pub struct Task {
     /// A copy of the VM state at the time the task was created or last committed/suspended.
     /// For restoring on retry.
     pub(crate) retry_state: VMExecState,
+    /// If the task is being debugged, the debugger's end of the session. Not persisted; a task
+    /// restored from the database runs freely.
+    pub(crate) debugger: Option<Debugger>,
+    /// The command aliases in force when the task was created, for parsing its command. Not
+    /// persisted; a task restored from the database uses the defaults.
+    pub(crate) command_aliases: Vec<(String, String)>,
 }
 
 impl Task {
@@ -117,9 +125,48 @@ impl Task {
             kill_switch,
             retries: 0,
             retry_state,
+            debugger: None,
+            command_aliases: server_options.command_aliases.clone(),
         })
     }
 
+    /// Run this task under a debugger, pausing before statements to report to its controller.
+    pub(crate) fn attach_debugger(&mut self, debugger: Debugger) {
+        self.vm_host.set_single_step(true);
+        self.debugger = Some(debugger);
+    }
+
+    /// If a debugger is attached and wants to stop before the statement about to run, pause
+    /// until it lets the task go on. Returns false if the task should be aborted.
+    fn debug_checkpoint(&mut self) -> bool {
+        let Some(debugger) = self.debugger.as_mut() else {
+            return true;
+        };
+        let Some(frame) = self.vm_host.debug_frame() else {
+            return true;
+        };
+        let ticks = self.vm_host.tick_count();
+        if !debugger.should_pause(frame.line, ticks) {
+            return true;
+        }
+        let paused_at = Instant::now();
+        let kill_switch = self.kill_switch.clone();
+        let resumption = debugger.pause(frame, ticks, || {
+            kill_switch.load(std::sync::atomic::Ordering::Relaxed)
+        });
+        self.vm_host.extend_time(paused_at.elapsed());
+        match resumption {
+            Some(Resumption::Run) => true,
+            Some(Resumption::Abort) => false,
+            None => {
+                // The controller has gone away, so there's nobody to pause for.
+                self.debugger = None;
+                self.vm_host.set_single_step(false);
+                true
+            }
+        }
+    }
+
     pub fn run_task_loop(
         mut task: Box<Task>,
         task_scheduler_client: &TaskSchedulerClient,
@@ -131,12 +178,27 @@ impl Task {
         // Try to pick a high thread priority for user tasks.
         gdt_cpus::set_thread_priority(gdt_cpus::ThreadPriority::AboveNormal).ok();
 
+        // Everything the task logs is within a span saying which task it is, and what it has
+        // named itself with `set_task_name()`, which it may do at any point.
+        let span = info_span!("task", task_id = task.task_id, task_name = Empty);
+        let _span = span.enter();
+        let mut task_name = None;
+
         while task.vm_host.is_running() {
+            if task.vm_host.task_name() != task_name.as_deref() {
+                task_name = task.vm_host.task_name().map(str::to_string);
+                span.record("task_name", task_name.as_deref().unwrap_or(""));
+            }
+
             // Check kill switch.
             if task.kill_switch.load(std::sync::atomic::Ordering::Relaxed) {
                 task_scheduler_client.abort_cancelled();
                 break;
             }
+            if !task.debug_checkpoint() {
+                task_scheduler_client.abort_cancelled();
+                break;
+            }
             if let Some(continuation_task) = task.vm_dispatch(
                 task_scheduler_client,
                 session.as_ref(),
@@ -263,9 +325,12 @@ impl Task {
                             command: command.clone(),
                         };
 
-                        if let Err(e) =
-                            self.setup_start_parse_command(&player, &command, world_state.as_mut())
-                        {
+                        if let Err(e) = self.setup_start_parse_command(
+                            &player,
+                            &command,
+                            world_state.as_mut(),
+                            config,
+                        ) {
                             task_scheduler_client.command_error(e);
                         }
                         return Some((self, world_state));
@@ -373,6 +438,7 @@ impl Task {
         &mut self,
         control_sender: &Sender<(TaskId, TaskControlMsg)>,
         world_state: &mut dyn WorldState,
+        config: &FeaturesConfig,
     ) -> bool {
         let perfc = sched_counters();
         let _t = PerfTimerGuard::new(&perfc.setup_task);
@@ -385,9 +451,13 @@ impl Task {
                 command,
             } => {
                 let (handler_object, player, command) = (*handler_object, *player, command.clone());
-                if let Err(e) =
-                    self.start_command(&handler_object, &player, command.as_str(), world_state)
-                {
+                if let Err(e) = self.start_command(
+                    &handler_object,
+                    &player,
+                    command.as_str(),
+                    world_state,
+                    config,
+                ) {
                     control_sender
                         .send((self.task_id, TaskControlMsg::TaskCommandError(e)))
                         .expect("Could not send start response");
@@ -484,6 +554,7 @@ impl Task {
         player: &Obj,
         command: &str,
         world_state: &mut dyn WorldState,
+        config: &FeaturesConfig,
     ) -> Result<(), CommandError> {
         let perfc = sched_counters();
         let _t = PerfTimerGuard::new(&perfc.start_command);
@@ -508,7 +579,7 @@ impl Task {
 
         match do_command {
             Err(WorldStateError::VerbNotFound(_, _)) => {
-                self.setup_start_parse_command(player, command, world_state)?;
+                self.setup_start_parse_command(player, command, world_state, config)?;
             }
             Ok((program, verbdef)) => {
                 let arguments = parse_into_words(command);
@@ -546,8 +617,9 @@ impl Task {
         player: &Obj,
         command: &str,
         world_state: &mut dyn WorldState,
+        config: &FeaturesConfig,
     ) -> Result<(), CommandError> {
-        let (player_location, parsed_command) = {
+        let (player_location, parsed_command, suggestions) = {
             let perfc = sched_counters();
             let _t = PerfTimerGuard::new(&perfc.parse_command);
 
@@ -570,7 +642,7 @@ impl Task {
                 env: me,
                 player: *player,
             };
-            let command_parser = DefaultParseCommand::new();
+            let command_parser = DefaultParseCommand::with_aliases(self.command_aliases.clone());
             let parsed_command = match command_parser.parse_command(command, &matcher) {
                 Ok(pc) => pc,
                 Err(ParseCommandError::PermissionDenied) => {
@@ -581,7 +653,15 @@ impl Task {
                 }
             };
 
-            (player_location, parsed_command)
+            // If an object named in the command failed to match, work out what it may have been
+            // a typo for, in case nothing ends up handling the command.
+            let suggestions = if config.fuzzy_match_suggestions {
+                unmatched_object_suggestions(&parsed_command, &matcher)
+            } else {
+                None
+            };
+
+            (player_location, parsed_command, suggestions)
         };
 
         // Look for the verb...
@@ -592,15 +672,21 @@ impl Task {
             Some((verb_info, target)) => (verb_info, target),
             // Otherwise, we want to try to call :huh, if it exists.
             None => {
+                let no_match = || match suggestions {
+                    Some((object_name, suggestions)) => {
+                        CommandError::NoObjectMatchSuggestions(object_name, suggestions)
+                    }
+                    None => CommandError::NoCommandMatch,
+                };
                 if player_location == NOTHING {
-                    return Err(CommandError::NoCommandMatch);
+                    return Err(no_match());
                 }
                 // Try to find :huh. If it exists, we'll dispatch to that, instead.
                 // If we don't find it, that's the end of the line.
                 let Ok((program, verbdef)) =
                     world_state.find_method_verb_on(&self.perms, &player_location, *HUH_SYM)
                 else {
-                    return Err(CommandError::NoCommandMatch);
+                    return Err(no_match());
                 };
                 ((program, verbdef), player_location)
             }
@@ -625,6 +711,27 @@ impl Task {
     }
 }
 
+/// If the direct or indirect object string in `pc` failed to match anything, the string along with
+/// nearby object names it may have been meant as.
+fn unmatched_object_suggestions<M: ObjectNameMatcher>(
+    pc: &ParsedCommand,
+    matcher: &M,
+) -> Option<(String, Vec<String>)> {
+    let unmatched = [(&pc.dobjstr, pc.dobj), (&pc.iobjstr, pc.iobj)];
+    for (objstr, obj) in unmatched {
+        let (Some(objstr), Some(FAILED_MATCH)) = (objstr, obj) else {
+            continue;
+        };
+        let Ok(suggestions) = matcher.suggest_names(objstr) else {
+            continue;
+        };
+        if !suggestions.is_empty() {
+            return Some((objstr.clone(), suggestions));
+        }
+    }
+    None
+}
+
 #[allow(clippy::type_complexity)]
 fn find_verb_for_command(
     player: &Obj,
@@ -699,6 +806,8 @@ impl<C> Decode<C> for Task {
             kill_switch,
             retries,
             retry_state,
+            debugger: None,
+            command_aliases: default_command_aliases(),
         })
     }
 }
@@ -723,6 +832,8 @@ impl<'de, C> BorrowDecode<'de, C> for Task {
             kill_switch,
             retries,
             retry_state,
+            debugger: None,
+            command_aliases: default_command_aliases(),
         })
     }
 }
@@ -731,8 +842,9 @@ impl<'de, C> BorrowDecode<'de, C> for Task {
 //   a simple program.
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
     use std::sync::atomic::AtomicBool;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
     use crossbeam_channel::{Receiver, unbounded};
 
@@ -744,18 +856,35 @@ mod tests {
     use moor_common::util::BitEnum;
     use moor_compiler::{CompileOptions, Program, compile};
     use moor_db::{DatabaseConfig, TxDB};
-    use moor_var::E_DIV;
+    use moor_var::{E_DIV, E_PERM};
     use moor_var::{NOTHING, SYSTEM_OBJECT};
-    use moor_var::{Symbol, v_obj};
-    use moor_var::{v_int, v_str};
+    use moor_var::{Obj, Symbol, v_obj};
+    use moor_var::{v_int, v_list, v_str};
 
-    use crate::config::Config;
+    use crate::config::{Config, FeaturesConfig};
+    use crate::tasks::debugger::{DebugEvent, debug_channel};
     use crate::tasks::task::Task;
     use crate::tasks::task_scheduler_client::{TaskControlMsg, TaskSchedulerClient};
     use crate::tasks::{ServerOptions, TaskStart};
     use crate::vm::activation::Frame;
     use crate::vm::builtins::BuiltinRegistry;
-    use moor_common::tasks::NoopClientSession;
+    use crate::vm::builtins::bf_server::TASK_PERMS_AUDIT_TARGET;
+    use crate::vm::verb_trace::VERB_TRACE_TARGET;
+    use moor_common::tasks::{MockClientSession, NoopClientSession};
+
+    /// Collects tracing output for inspection.
+    struct CapturedLog(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLog {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 
     struct TestVerb {
         name: Symbol,
@@ -783,6 +912,12 @@ mod tests {
             fg_seconds: 5,
             fg_ticks: 50000,
             max_stack_depth: 5,
+            mssp: vec![],
+            idle_threshold: None,
+            idle_hook: Symbol::mk("idle_notification"),
+            command_aliases: vec![],
+            player_prototype: None,
+            player_starter_properties: vec![],
         };
         let task_scheduler_client = TaskSchedulerClient::new(1, control_sender.clone());
         let mut task = Task::new(
@@ -828,7 +963,7 @@ mod tests {
             )
             .unwrap();
         }
-        task.setup_task_start(&control_sender, tx.as_mut());
+        task.setup_task_start(&control_sender, tx.as_mut(), &FeaturesConfig::default());
 
         (
             kill_switch,
@@ -931,6 +1066,227 @@ mod tests {
         assert_eq!(exception.error.err_type, E_DIV);
     }
 
+    /// An uncaught raise() carries its message and value out to the scheduler, and the message
+    /// leads the traceback.
+    #[test]
+    fn test_uncaught_raise_message_and_value() {
+        let (_kill_switch, task, _db, tx, task_scheduler_client, control_receiver) =
+            setup_test_env_eval(r#"raise(E_PERM, "nope", 17);"#);
+
+        let session = Arc::new(NoopClientSession::new());
+        Task::run_task_loop(
+            task,
+            &task_scheduler_client,
+            session,
+            tx,
+            BuiltinRegistry::new(),
+            Arc::new(Config::default()),
+        );
+
+        let (_, msg) = control_receiver.recv().unwrap();
+        let TaskControlMsg::TaskException(exception) = msg else {
+            panic!("Expected TaskException, got {:?}", msg);
+        };
+        assert_eq!(exception.error.err_type, E_PERM);
+        assert_eq!(exception.error.message(), "nope");
+        assert_eq!(exception.error.value.as_deref(), Some(&v_int(17)));
+        let first_line = exception.backtrace[0].as_string().unwrap().to_string();
+        assert!(first_line.contains("nope"), "{first_line}");
+    }
+
+    /// player_info() gathers everything a `@who` listing needs about the connected players.
+    #[test]
+    fn test_player_info() {
+        let (_kill_switch, task, _db, tx, task_scheduler_client, control_receiver) =
+            setup_test_env_eval("return {player_info(), length(player_info(1))};");
+
+        let session = Arc::new(MockClientSession::new());
+        session.connect(Obj::mk_id(0), 60.0, 5.0);
+        session.connect(Obj::mk_id(-3), 10.0, 10.0);
+        session.connect(Obj::mk_id(1), 120.5, 30.0);
+        Task::run_task_loop(
+            task,
+            &task_scheduler_client,
+            session,
+            tx,
+            BuiltinRegistry::new(),
+            Arc::new(Config::default()),
+        );
+
+        let (_, msg) = control_receiver.recv().unwrap();
+        let TaskControlMsg::TaskSuccess(result) = msg else {
+            panic!("Expected TaskSuccess, got {:?}", msg);
+        };
+        assert_eq!(
+            result,
+            v_list(&[
+                v_list(&[
+                    v_list(&[
+                        v_obj(Obj::mk_id(0)),
+                        v_int(5),
+                        v_int(60),
+                        v_str("player-#0")
+                    ]),
+                    v_list(&[
+                        v_obj(Obj::mk_id(1)),
+                        v_int(30),
+                        v_int(120),
+                        v_str("player-#1")
+                    ]),
+                ]),
+                v_int(3),
+            ])
+        );
+    }
+
+    /// With auditing on, set_task_perms() emits an entry under the audit tracing target.
+    #[test]
+    fn test_set_task_perms_audit() {
+        let (_kill_switch, task, _db, tx, task_scheduler_client, control_receiver) =
+            setup_test_env_eval("set_task_perms(#0); return 1;");
+
+        let log = Arc::new(Mutex::new(vec![]));
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer({
+                let log = log.clone();
+                move || CapturedLog(log.clone())
+            })
+            .finish();
+        let config = Config {
+            features_config: Arc::new(FeaturesConfig {
+                audit_task_perms: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        tracing::subscriber::with_default(subscriber, || {
+            let session = Arc::new(NoopClientSession::new());
+            Task::run_task_loop(
+                task,
+                &task_scheduler_client,
+                session,
+                tx,
+                BuiltinRegistry::new(),
+                Arc::new(config),
+            );
+        });
+
+        let (_, msg) = control_receiver.recv().unwrap();
+        let TaskControlMsg::TaskSuccess(_) = msg else {
+            panic!("Expected TaskSuccess, got {:?}", msg);
+        };
+        let log = String::from_utf8(log.lock().unwrap().clone()).unwrap();
+        let entry = log
+            .lines()
+            .find(|l| l.contains(TASK_PERMS_AUDIT_TARGET))
+            .unwrap_or_else(|| panic!("No audit entry in log: {log}"));
+        assert!(entry.contains("set_task_perms"));
+        assert!(entry.contains("caller=#0"));
+        assert!(entry.contains("perms=#0"));
+        assert!(entry.contains("line=1"));
+    }
+
+    /// Turning on tracing for a verb logs the opcodes it executes, and only those.
+    #[test]
+    fn test_verb_trace() {
+        let program = compile(
+            "set_verb_trace(#0, \"traced\", 1); return #0:traced();",
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let (_kill_switch, task, _db, tx, task_scheduler_client, control_receiver) = setup_test_env(
+            TaskStart::StartEval {
+                player: SYSTEM_OBJECT,
+                program,
+            },
+            &[TestVerb {
+                name: Symbol::mk("traced"),
+                program: compile("x = 5;\nreturn x + 1;", CompileOptions::default()).unwrap(),
+                argspec: VerbArgsSpec::this_none_this(),
+            }],
+        );
+
+        let log = Arc::new(Mutex::new(vec![]));
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer({
+                let log = log.clone();
+                move || CapturedLog(log.clone())
+            })
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let session = Arc::new(NoopClientSession::new());
+            Task::run_task_loop(
+                task,
+                &task_scheduler_client,
+                session,
+                tx,
+                BuiltinRegistry::new(),
+                Arc::new(Config::default()),
+            );
+        });
+
+        let (_, msg) = control_receiver.recv().unwrap();
+        let TaskControlMsg::TaskSuccess(result) = msg else {
+            panic!("Expected TaskSuccess, got {:?}", msg);
+        };
+        assert_eq!(result, v_int(6));
+
+        let log = String::from_utf8(log.lock().unwrap().clone()).unwrap();
+        let trace: Vec<_> = log
+            .lines()
+            .filter(|l| l.contains(VERB_TRACE_TARGET))
+            .collect();
+        assert!(!trace.is_empty(), "No trace events in log: {log}");
+        assert!(trace.iter().all(|l| l.contains("verb=traced")));
+        assert!(trace.iter().any(|l| l.contains("line=1")));
+        assert!(trace.iter().any(|l| l.contains("line=2")));
+    }
+
+    /// Run `set_verb_code` then `verb_code` with and without source preservation, and check that
+    /// comments and formatting only survive when it's on.
+    #[test]
+    fn test_verb_source_preservation() {
+        let program = r#"
+            add_verb(#0, {#0, "xd", "test"}, {"this", "none", "this"});
+            set_verb_code(#0, "test", {"// Says hello.", "return  1;   /* spaced */"});
+            return verb_code(#0, "test");
+        "#;
+        for (preserve_verb_source, expected) in [
+            (
+                true,
+                v_list(&[v_str("// Says hello."), v_str("return  1;   /* spaced */")]),
+            ),
+            (false, v_list(&[v_str("return 1;")])),
+        ] {
+            let (_kill_switch, task, _db, tx, task_scheduler_client, control_receiver) =
+                setup_test_env_eval(program);
+            let config = Config {
+                features_config: Arc::new(FeaturesConfig {
+                    preserve_verb_source,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            let session = Arc::new(NoopClientSession::new());
+            Task::run_task_loop(
+                task,
+                &task_scheduler_client,
+                session,
+                tx,
+                BuiltinRegistry::new(),
+                Arc::new(config),
+            );
+
+            let (_, msg) = control_receiver.recv().unwrap();
+            let TaskControlMsg::TaskSuccess(result) = msg else {
+                panic!("Expected TaskSuccess, got {:?}", msg);
+            };
+            assert_eq!(result, expected);
+        }
+    }
+
     // notify() will dispatch to the scheduler
     #[test]
     fn test_notify_invocation() {
@@ -966,6 +1322,34 @@ mod tests {
         assert_eq!(result, v_int(123));
     }
 
+    /// notify_confirmed() goes straight to the session, and says whether the player was there.
+    #[test]
+    fn test_notify_confirmed() {
+        let (_kill_switch, task, _db, tx, task_scheduler_client, control_receiver) =
+            setup_test_env_eval(
+                r#"return {notify_confirmed(#0, "12345"), notify_confirmed(#5, "12345")};"#,
+            );
+
+        let session = Arc::new(MockClientSession::new());
+        session.connect(SYSTEM_OBJECT, 10.0, 0.0);
+        Task::run_task_loop(
+            task,
+            &task_scheduler_client,
+            session,
+            tx,
+            BuiltinRegistry::new(),
+            Arc::new(Config::default()),
+        );
+
+        // Nothing is spooled through the scheduler, so the first thing it hears is the result.
+        let (task_id, msg) = control_receiver.recv().unwrap();
+        assert_eq!(task_id, 1);
+        let TaskControlMsg::TaskSuccess(result) = msg else {
+            panic!("Expected TaskSuccess, got {:?}", msg);
+        };
+        assert_eq!(result, v_list(&[v_int(1), v_int(0)]));
+    }
+
     /// Trigger a task-suspend-resume
     #[test]
     fn test_simple_run_suspend() {
@@ -1010,6 +1394,57 @@ mod tests {
         assert_eq!(result, v_int(123));
     }
 
+    /// Suspending commits the task's transaction, so its writes are visible to other
+    /// transactions while it sleeps, and it resumes in a fresh transaction.
+    #[test]
+    fn test_suspend_commits_writes() {
+        let (_kill_switch, task, db, tx, task_scheduler_client, control_receiver) =
+            setup_test_env_eval(r#"#0.name = "changed"; suspend(1); return #0.name;"#);
+
+        let session = Arc::new(NoopClientSession::new());
+        Task::run_task_loop(
+            task,
+            &task_scheduler_client,
+            session.clone(),
+            tx,
+            BuiltinRegistry::new(),
+            Arc::new(Config::default()),
+        );
+
+        let (task_id, msg) = control_receiver.recv().unwrap();
+        assert_eq!(task_id, 1);
+        let TaskControlMsg::TaskSuspend(_, mut resume_task) = msg else {
+            panic!("Expected TaskSuspend, got {:?}", msg);
+        };
+
+        // Another transaction started during the suspension should see the write.
+        let observer = db.new_world_state().unwrap();
+        assert_eq!(
+            observer
+                .retrieve_property(&SYSTEM_OBJECT, &SYSTEM_OBJECT, Symbol::mk("name"))
+                .unwrap(),
+            v_str("changed")
+        );
+        observer.rollback().unwrap();
+
+        resume_task.vm_host.resume_execution(v_int(0));
+        let tx = db.new_world_state().unwrap();
+        Task::run_task_loop(
+            resume_task,
+            &task_scheduler_client,
+            session,
+            tx,
+            BuiltinRegistry::new(),
+            Arc::new(Config::default()),
+        );
+        let (task_id, msg) = control_receiver.recv().unwrap();
+        assert_eq!(task_id, 1);
+        let TaskControlMsg::TaskSuccess(result) = msg else {
+            panic!("Expected TaskSuccess, got {:?}", msg);
+        };
+        assert_eq!(result, v_str("changed"));
+    }
+
     /// Trigger a simulated read()
     #[test]
     fn test_simple_run_read() {
@@ -1141,6 +1576,46 @@ mod tests {
         };
     }
 
+    /// With fuzzy match suggestions turned on, a typo'd object name in a command that nothing
+    /// handles produces suggestions instead of a bare NoCommandMatch.
+    #[test]
+    fn test_command_no_match_suggestions() {
+        let (_kill_switch, mut task, _db, mut tx, _task_scheduler_client, _control_receiver) =
+            setup_test_env_command("look lampp", &[]);
+
+        // Put the player in a room with a lamp in it.
+        let room = tx
+            .create_object(&SYSTEM_OBJECT, &NOTHING, &SYSTEM_OBJECT, BitEnum::all())
+            .unwrap();
+        let lamp = tx
+            .create_object(&SYSTEM_OBJECT, &NOTHING, &SYSTEM_OBJECT, BitEnum::all())
+            .unwrap();
+        tx.update_property(&SYSTEM_OBJECT, &lamp, Symbol::mk("name"), &v_str("lamp"))
+            .unwrap();
+        tx.move_object(&SYSTEM_OBJECT, &SYSTEM_OBJECT, &room)
+            .unwrap();
+        tx.move_object(&SYSTEM_OBJECT, &lamp, &room).unwrap();
+
+        let (control_sender, control_receiver) = unbounded();
+        let features = FeaturesConfig {
+            fuzzy_match_suggestions: true,
+            ..Default::default()
+        };
+        task.setup_task_start(&control_sender, tx.as_mut(), &features);
+
+        let (task_id, msg) = control_receiver.recv().unwrap();
+        assert_eq!(task_id, 1);
+        let TaskControlMsg::TaskCommandError(CommandError::NoObjectMatchSuggestions(
+            object_name,
+            suggestions,
+        )) = msg
+        else {
+            panic!("Expected NoObjectMatchSuggestions, got {:?}", msg);
+        };
+        assert_eq!(object_name, "lampp");
+        assert_eq!(suggestions, vec!["lamp".to_string()]);
+    }
+
     /// Install a simple verb that will match and execute, without $do_command.
     #[test]
     fn test_command_match() {
@@ -1277,4 +1752,61 @@ mod tests {
         };
         assert_eq!(result, v_int(1));
     }
+
+    /// Drive a task one statement at a time under the debugger, looking at a local variable as
+    /// it changes.
+    #[test]
+    fn test_debugger_step() {
+        let (_kill_switch, mut task, _db, tx, task_scheduler_client, control_receiver) =
+            setup_test_env_eval("x = 1;\nx = x + 10;\nreturn x * 2;");
+        let (debugger, debug_handle) = debug_channel();
+        task.attach_debugger(debugger);
+
+        let task_thread = std::thread::spawn(move || {
+            let session = Arc::new(NoopClientSession::new());
+            Task::run_task_loop(
+                task,
+                &task_scheduler_client,
+                session,
+                tx,
+                BuiltinRegistry::new(),
+                Arc::new(Config::default()),
+            );
+        });
+
+        let next_pause = || {
+            let event = debug_handle.next_event(Duration::from_secs(5));
+            let Some(DebugEvent::Paused(frame)) = event else {
+                panic!("Expected a pause, got {:?}", event);
+            };
+            frame
+        };
+
+        let frame = next_pause();
+        assert_eq!(frame.line, 1);
+        assert_eq!(frame.local("x"), None);
+
+        debug_handle.step();
+        let frame = next_pause();
+        assert_eq!(frame.line, 2);
+        assert_eq!(frame.local("x"), Some(&v_int(1)));
+
+        debug_handle.step();
+        let frame = next_pause();
+        assert_eq!(frame.line, 3);
+        assert_eq!(frame.local("x"), Some(&v_int(11)));
+
+        debug_handle.cont();
+        assert_eq!(
+            debug_handle.next_event(Duration::from_secs(5)),
+            Some(DebugEvent::Finished)
+        );
+        task_thread.join().unwrap();
+
+        let (_, msg) = control_receiver.recv().unwrap();
+        let TaskControlMsg::TaskSuccess(result) = msg else {
+            panic!("Expected TaskSuccess, got {:?}", msg);
+        };
+        assert_eq!(result, v_int(22));
+    }
 }
@@ -29,7 +29,8 @@ use uuid::Uuid;
 use moor_var::Obj;
 
 use crate::tasks::task::Task;
-use crate::tasks::{TaskDescription, TaskResult, TaskStart, TasksDb};
+use crate::tasks::{ServerOptions, TaskDescription, TaskResult, TaskStart, TasksDb};
+use crate::vm::exec_state::Caller;
 use moor_common::tasks::{NoopClientSession, Session, SessionFactory};
 use moor_common::tasks::{SchedulerError, TaskId};
 
@@ -43,6 +44,9 @@ pub struct TaskQ {
     ///     Suspended foreground tasks that are either indefinitely suspended or will execute someday
     ///     Suspended tasks waiting for input from the player or a task id to complete
     pub(crate) suspended: SuspensionQ,
+    /// Advisory object locks taken with `lock_object()`, and the task holding each. A task's
+    /// locks are released when it leaves the queue, whether it finished or was killed.
+    pub(crate) object_locks: HashMap<Obj, TaskId, BuildHasherDefault<AHasher>>,
 }
 
 /// Scheduler-side per-task record. Lives in the scheduler thread and owned by the scheduler and
@@ -52,6 +56,10 @@ pub struct TaskQ {
 pub(crate) struct RunningTask {
     /// For which player this task is running on behalf of.
     pub(crate) player: Obj,
+    /// The owner of the task: the permissions it started with (for a fork, those of the verb
+    /// that forked it). This, not `player` or whatever `set_task_perms` has since changed the
+    /// permissions to, is what decides who may kill or resume the task.
+    pub(crate) owner: Obj,
     /// What triggered this task to start.
     pub(crate) task_start: TaskStart,
     /// A kill switch to signal the task to stop. True means the VM execution thread should stop
@@ -61,6 +69,9 @@ pub(crate) struct RunningTask {
     pub(crate) session: Arc<dyn Session>,
     /// A mailbox to deliver the result of the task to a waiting party with a subscription, if any.
     pub(crate) result_sender: Option<Sender<(TaskId, Result<TaskResult, SchedulerError>)>>,
+    /// Server options the task read off $server_options with `load_server_options()`, to be
+    /// applied once its transaction commits.
+    pub(crate) pending_server_options: Option<ServerOptions>,
 }
 
 fn none_or_push(vec: &mut Option<Vec<TaskId>>, task: TaskId) {
@@ -72,6 +83,32 @@ fn none_or_push(vec: &mut Option<Vec<TaskId>>, task: TaskId) {
 }
 
 impl TaskQ {
+    /// Take the advisory lock on `obj` for `task_id`. Returns false if some other task holds it.
+    /// Taking a lock the task already holds succeeds.
+    pub(crate) fn lock_object(&mut self, obj: Obj, task_id: TaskId) -> bool {
+        if let Some(holder) = self.object_locks.get(&obj) {
+            if *holder != task_id {
+                return false;
+            }
+        }
+        self.object_locks.insert(obj, task_id);
+        true
+    }
+
+    /// Release `task_id`'s lock on `obj`. Returns false if it didn't hold it.
+    pub(crate) fn unlock_object(&mut self, obj: Obj, task_id: TaskId) -> bool {
+        if self.object_locks.get(&obj) != Some(&task_id) {
+            return false;
+        }
+        self.object_locks.remove(&obj);
+        true
+    }
+
+    /// Release every lock held by `task_id`, which is leaving the queue.
+    pub(crate) fn release_object_locks(&mut self, task_id: TaskId) {
+        self.object_locks.retain(|_, holder| *holder != task_id);
+    }
+
     /// Collect tasks that need to be woken up, pull them from our suspended list, and return them.
     pub(crate) fn collect_wake_tasks(&mut self) -> Option<Vec<SuspendedTask>> {
         if self.suspended.tasks.is_empty() {
@@ -323,13 +360,20 @@ impl SuspensionQ {
                 verb_definer: sr.task.vm_host.verb_definer(),
                 line_number: sr.task.vm_host.line_number(),
                 this: sr.task.vm_host.this(),
+                name: sr.task.vm_host.task_name().map(str::to_string),
             });
         }
         tasks
     }
 
-    /// Check if the task is suspended, and if so, return its permissions.
-    /// If `filter_input` is true, filter out WaitingInput tasks.
+    /// The owner and activation stack of the suspended task `task_id`, if there is one.
+    pub(crate) fn task_stack(&self, task_id: TaskId) -> Option<(Obj, Vec<Caller>)> {
+        let sr = self.tasks.get(&task_id)?;
+        Some((sr.task.perms, sr.task.vm_host.frames()))
+    }
+
+    /// The owner of the suspended task `task_id` (the permissions it started with), if there is
+    /// one and, with `filter_input`, it isn't waiting for input.
     pub(crate) fn perms_check(&self, task_id: TaskId, filter_input: bool) -> Option<Obj> {
         let sr = self.tasks.get(&task_id)?;
         if filter_input {
@@ -340,8 +384,8 @@ impl SuspensionQ {
         Some(sr.task.perms)
     }
 
-    /// Remove all non-background tasks for the given player.
-    pub(crate) fn prune_foreground_tasks(&mut self, player: &Obj) {
+    /// Remove all non-background tasks for the given player, returning their ids.
+    pub(crate) fn prune_foreground_tasks(&mut self, player: &Obj) -> Vec<TaskId> {
         let to_remove = self
             .tasks
             .iter()
@@ -350,9 +394,10 @@ impl SuspensionQ {
                     .then_some(*task_id)
             })
             .collect::<Vec<_>>();
-        for task_id in to_remove {
-            self.remove_task(task_id);
+        for task_id in &to_remove {
+            self.remove_task(*task_id);
         }
+        to_remove
     }
 }
 
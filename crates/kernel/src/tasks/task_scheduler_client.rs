@@ -16,7 +16,8 @@ use std::time::Duration;
 use crossbeam_channel::Sender;
 
 use crate::tasks::task::Task;
-use crate::tasks::{TaskDescription, TaskStart};
+use crate::tasks::{ServerOptions, TaskDescription, TaskStart};
+use crate::vm::exec_state::Caller;
 use crate::vm::{Fork, TaskSuspend};
 use moor_common::model::Perms;
 use moor_common::tasks::{AbortLimitReason, CommandError, Exception, NarrativeEvent, TaskId};
@@ -139,6 +140,24 @@ impl TaskSchedulerClient {
             .expect("Could not receive queued tasks -- scheduler shut down?")
     }
 
+    /// Ask the scheduler for the owner and activation stack of a suspended task, if there is one
+    /// with that id.
+    pub fn task_stack(&self, queued_task_id: TaskId) -> Option<(Obj, Vec<Caller>)> {
+        let (reply, receive) = oneshot::channel();
+        self.scheduler_sender
+            .send((
+                self.task_id,
+                TaskControlMsg::RequestTaskStack {
+                    queued_task_id,
+                    reply,
+                },
+            ))
+            .expect("Could not deliver client message -- scheduler shut down?");
+        receive
+            .recv()
+            .expect("Could not receive task stack -- scheduler shut down?")
+    }
+
     /// Request that the scheduler abort another task.
     pub fn kill_task(&self, victim_task_id: TaskId, sender_permissions: Perms) -> Var {
         let (reply, receive) = oneshot::channel();
@@ -208,6 +227,7 @@ impl TaskSchedulerClient {
         host_type: String,
         port: u16,
         print_messages: bool,
+        tls: bool,
     ) -> Option<Error> {
         let (reply, receive) = oneshot::channel();
         self.scheduler_sender
@@ -219,6 +239,7 @@ impl TaskSchedulerClient {
                     host_type,
                     port,
                     print_messages,
+                    tls,
                 },
             ))
             .expect("Unable to send listen message to scheduler");
@@ -255,10 +276,14 @@ impl TaskSchedulerClient {
             .expect("Could not receive unlisten reply -- scheduler shut down?")
     }
 
-    /// Request that the server refresh its set of information off $server_options
-    pub fn refresh_server_options(&self) {
+    /// Replace the server's options with those the task read off $server_options, once the
+    /// task's transaction commits.
+    pub fn refresh_server_options(&self, server_options: ServerOptions) {
         self.scheduler_sender
-            .send((self.task_id, TaskControlMsg::RefreshServerOptions))
+            .send((
+                self.task_id,
+                TaskControlMsg::RefreshServerOptions(server_options),
+            ))
             .expect("Could not deliver client message -- scheduler shut down?");
     }
 
@@ -291,6 +316,28 @@ impl TaskSchedulerClient {
             .recv()
             .expect("Could not receive active tasks -- scheduler shut down?")
     }
+
+    /// Take the advisory lock on `obj` for this task. False if another task holds it.
+    pub fn lock_object(&self, obj: Obj) -> bool {
+        let (reply, receive) = oneshot::channel();
+        self.scheduler_sender
+            .send((self.task_id, TaskControlMsg::LockObject { obj, reply }))
+            .expect("Could not deliver client message -- scheduler shut down?");
+        receive
+            .recv()
+            .expect("Could not receive lock result -- scheduler shut down?")
+    }
+
+    /// Release this task's advisory lock on `obj`. False if it didn't hold it.
+    pub fn unlock_object(&self, obj: Obj) -> bool {
+        let (reply, receive) = oneshot::channel();
+        self.scheduler_sender
+            .send((self.task_id, TaskControlMsg::UnlockObject { obj, reply }))
+            .expect("Could not deliver client message -- scheduler shut down?");
+        receive
+            .recv()
+            .expect("Could not receive unlock result -- scheduler shut down?")
+    }
 }
 
 pub type ActiveTaskDescriptions = Vec<(TaskId, Obj, TaskStart)>;
@@ -321,6 +368,11 @@ pub enum TaskControlMsg {
     TaskRequestInput(Box<Task>),
     /// Task is requesting a list of all other tasks known to the scheduler.
     RequestTasks(oneshot::Sender<Vec<TaskDescription>>),
+    /// Task is requesting the owner and stack of a suspended task.
+    RequestTaskStack {
+        queued_task_id: TaskId,
+        reply: oneshot::Sender<Option<(Obj, Vec<Caller>)>>,
+    },
     /// Task is requesting that the scheduler abort another task.
     KillTask {
         victim_task_id: TaskId,
@@ -348,11 +400,13 @@ pub enum TaskControlMsg {
     /// Ask hosts to listen for connections on `port` and send them to `handler_object`
     /// `print_messages` is a flag to enable or disable printing of connected etc strings
     /// `host_type` is a string identifying the type of host
+    /// `tls` asks for TLS, with the certificate and key from $server_options
     Listen {
         handler_object: Obj,
         host_type: String,
         port: u16,
         print_messages: bool,
+        tls: bool,
         reply: oneshot::Sender<Option<Error>>,
     },
     /// Ask hosts of type `host_type` to stop listening on `port`
@@ -361,8 +415,9 @@ pub enum TaskControlMsg {
         port: u16,
         reply: oneshot::Sender<Option<Error>>,
     },
-    /// Request that the server refresh its set of information off $server_options
-    RefreshServerOptions,
+    /// Replace the server options with those read off $server_options by the task, once its
+    /// transaction commits.
+    RefreshServerOptions(ServerOptions),
     /// Task requesting shutdown
     Shutdown(Option<String>),
     /// Ask the scheduler to force input from the client.
@@ -375,6 +430,16 @@ pub enum TaskControlMsg {
     ActiveTasks {
         reply: oneshot::Sender<Result<ActiveTaskDescriptions, Error>>,
     },
+    /// Take an advisory lock on an object, replying false if another task holds it.
+    LockObject {
+        obj: Obj,
+        reply: oneshot::Sender<bool>,
+    },
+    /// Release an advisory lock held by the task, replying false if it wasn't held.
+    UnlockObject {
+        obj: Obj,
+        reply: oneshot::Sender<bool>,
+    },
 }
 
 #[cfg(test)]
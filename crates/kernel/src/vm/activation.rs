@@ -22,6 +22,7 @@ use uuid::Uuid;
 use moor_common::model::VerbArgsSpec;
 use moor_common::model::VerbDef;
 use moor_common::model::VerbFlag;
+use moor_common::tasks::Exception;
 use moor_common::util::BitEnum;
 use moor_compiler::BuiltinId;
 use moor_compiler::Program;
@@ -209,6 +210,11 @@ pub struct BfFrame {
     pub(crate) bf_trampoline: Option<usize>,
     /// And an optional argument that can be passed with the above...
     pub(crate) bf_trampoline_arg: Option<Var>,
+    /// If set, an error raised out of a verb dispatched by this builtin stops here instead of
+    /// unwinding through it; the builtin is re-entered with the error in `raised`, and is then
+    /// responsible for passing it on.
+    pub(crate) catch_raise: bool,
+    pub(crate) raised: Option<Box<Exception>>,
 
     /// Return value into this frame.
     pub(crate) return_value: Option<Var>,
@@ -226,7 +232,7 @@ impl Activation {
         let program = verb_call_request.program;
         let verb_owner = verb_call_request.resolved_verb.owner();
 
-        let ProgramType::MooR(program) = program else {
+        let (ProgramType::MooR(program) | ProgramType::MooRWithSource(program, _)) = program else {
             unimplemented!("Only MOO programs are supported")
         };
         let frame = Box::new(MooStackFrame::new(program));
@@ -347,6 +353,8 @@ impl Activation {
             bf_id,
             bf_trampoline: None,
             bf_trampoline_arg: None,
+            catch_raise: false,
+            raised: None,
             return_value: None,
         };
         let frame = Frame::Bf(bf_frame);
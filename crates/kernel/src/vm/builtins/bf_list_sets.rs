@@ -256,7 +256,7 @@ fn perform_regex_match(
     // MOO match() returns 9 subpatterns, no more, no less. So we start with a Vec of 9
     // (-1, -1) pairs and then fill that in with the captured groups, if any.
     let mut match_vec = vec![(0, -1); 9];
-    for i in 1..=8 {
+    for i in 1..=9 {
         if let Some((start, end)) = region.pos(i) {
             match_vec[i - 1] = ((start + 1) as isize, end as isize);
         }
@@ -351,60 +351,87 @@ fn perform_pcre_match(
         }
     };
 
+    // Named groups are reported under their name, rather than their number.
+    let mut group_names = HashMap::new();
+    regex.foreach_name(|name, indices| {
+        for i in indices {
+            group_names.insert(*i as usize, name.to_string());
+        }
+        true
+    });
+
     let mut region = Region::new();
     let mut matches = Vec::new();
     let mut start = 0;
     let end = target.len();
-    while regex
-        .search_with_options(
-            target,
-            start,
-            end,
-            SearchOptions::SEARCH_OPTION_NONE,
-            Some(&mut region),
-        )
-        .is_some()
+    while start <= end
+        && regex
+            .search_with_options(
+                target,
+                start,
+                end,
+                SearchOptions::SEARCH_OPTION_NONE,
+                Some(&mut region),
+            )
+            .is_some()
     {
+        let mut groups = vec![];
+        for i in 0..region.len() {
+            // Optional groups which didn't participate in the match have no position.
+            let Some((start, end)) = region.pos(i) else {
+                continue;
+            };
+            let key = match group_names.get(&i) {
+                Some(name) => v_str(name),
+                None => v_string(i.to_string()),
+            };
+            let matched = v_str(&target[start..end]);
+            let position = v_list(&[v_int((start as i64) + 1), v_int(end as i64)]);
+            groups.push((key, matched, position));
+        }
         if map_support {
-            let mut map = vec![];
-            for i in 0..region.len() {
-                let (start, end) = region.pos(i).unwrap();
-                let match_map = vec![
-                    (v_str("match"), v_str(&target[start..end])),
-                    (
-                        v_str("position"),
-                        v_list(&[v_int((start as i64) + 1), v_int(end as i64)]),
-                    ),
-                ];
-                map.push((v_string(i.to_string()), v_map(&match_map)));
-            }
-            let map = v_map(&map);
-            matches.push(map);
-            start = region.pos(0).unwrap().1;
+            let map: Vec<_> = groups
+                .into_iter()
+                .map(|(key, matched, position)| {
+                    let match_map = vec![(v_str("match"), matched), (v_str("position"), position)];
+                    (key, v_map(&match_map))
+                })
+                .collect();
+            matches.push(v_map(&map));
         } else {
-            let mut assoc_list = vec![];
-            for i in 0..region.len() {
-                let (start, end) = region.pos(i).unwrap();
-                let match_list = vec![
-                    v_list(&[v_str("match"), v_str(&target[start..end])]),
-                    v_list(&[
-                        v_str("position"),
-                        v_list(&[v_int((start as i64) + 1), v_int(end as i64)]),
-                    ]),
-                ];
-                assoc_list.push(v_list(&[v_string(i.to_string()), v_list(&match_list)]));
-            }
+            let assoc_list: Vec<_> = groups
+                .into_iter()
+                .map(|(key, matched, position)| {
+                    let match_list = vec![
+                        v_list(&[v_str("match"), matched]),
+                        v_list(&[v_str("position"), position]),
+                    ];
+                    v_list(&[key, v_list(&match_list)])
+                })
+                .collect();
             matches.push(v_list(&assoc_list));
-            start = region.pos(0).unwrap().1;
         }
         if !repeat {
             break;
         }
+        start = next_search_start(target, region.pos(0).unwrap());
     }
 
     Ok(List::mk_list(&matches))
 }
 
+/// Where to resume searching after a match at `(match_start, match_end)`. An empty match has to
+/// step forward a character, or we'd find it again forever.
+fn next_search_start(target: &str, (match_start, match_end): (usize, usize)) -> usize {
+    if match_end > match_start {
+        return match_end;
+    }
+    match target[match_end..].chars().next() {
+        Some(c) => match_end + c.len_utf8(),
+        None => match_end + 1,
+    }
+}
+
 fn perform_pcre_replace(target: &str, replace_str: &str) -> Result<String, Error> {
     let separator = {
         let mut chars = replace_str.chars();
@@ -431,7 +458,7 @@ fn perform_pcre_replace(target: &str, replace_str: &str) -> Result<String, Error
 
     // Split using the separator
     let components: Vec<_> = replace_str.splitn(4, separator).collect();
-    if components.len() < 2 {
+    if components.len() < 3 {
         return Err(E_INVARG.msg("Invalid regex pattern"));
     };
 
@@ -466,7 +493,7 @@ fn perform_pcre_replace(target: &str, replace_str: &str) -> Result<String, Error
     let mut region = Region::new();
     let end = target.len();
     let mut matches = vec![];
-    'outer: loop {
+    while start <= end {
         let match_num = regex.search_with_options(
             target,
             start,
@@ -478,19 +505,15 @@ fn perform_pcre_replace(target: &str, replace_str: &str) -> Result<String, Error
         if match_num.is_none() {
             break;
         }
-        if region.is_empty() {
+        // Only the overall match (group 0) is replaced; capture groups are within it.
+        let Some(whole_match) = region.pos(0) else {
+            break;
+        };
+        matches.push(whole_match);
+        if !global {
             break;
         }
-        for (match_start, end) in region.iter() {
-            // Append the match to our matches.
-            // If not `global`, break afterwords.
-            // If global, move "start" past it, and continue
-            matches.push((match_start, end));
-            if !global {
-                break 'outer;
-            }
-            start = end;
-        }
+        start = next_search_start(target, whole_match);
     }
 
     // Now compose the string looking at the matches, replacing the `replacement` in every place
@@ -601,76 +624,62 @@ fn bf_pcre_match(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(Var::from_variant(Variant::List(result))))
 }
 
-fn substitute(template: &str, subs: &[(isize, isize)], source: &str) -> Result<String, Error> {
-    // textual patterns of form %<int> (e.g. %1, %9, %11) are replaced by the text matched by the
-    // offsets (1-indexed) into source given by the corresponding value in `subs`.
-
-    // We'll append to this result.
+/// Expand the `%0`..`%9` references in `template` to the text of `source` matched as a whole
+/// (`whole`) or by each group (`groups`), as 1-indexed inclusive byte offsets like `match()`
+/// returns. `%%` stands for a single `%`.
+fn substitute(
+    template: &str,
+    whole: (isize, isize),
+    groups: &[(isize, isize)],
+    source: &str,
+) -> Result<String, Error> {
     let mut result = String::new();
-
-    // Then char-by-char iterate through `source`; if we see a %, we'll start lexing a # until we
-    // see a non-digit, then we'll parse the number and look it up in `subs`.
     let mut chars = template.chars();
     while let Some(c) = chars.next() {
         if c != '%' {
-            // We've seen a non-%, so we'll just append it to `result`.
             result.push(c);
             continue;
         }
-
-        // We've seen a %, so we'll start lexing a number. But if the next char is a %, we'll
-        // just append a % to `result` and continue.
-        let mut number = String::new();
-        let mut last_c = None;
-        for c in chars.by_ref() {
-            if c.is_ascii_digit() {
-                number.push(c);
-            } else {
-                // We've seen a non-digit, so we'll stop lexing, but keep the character to append
-                // after our substitution.
-                last_c = Some(c);
-                break;
+        let (start, end) = match chars.next() {
+            Some('%') => {
+                result.push('%');
+                continue;
             }
-        }
-        // Now we'll parse the number.
-        let Ok(number) = number.parse::<usize>() else {
-            // If we can't parse the number, we'll raise an error.
-            return Err(E_INVARG.msg("Invalid number"));
-        };
-
-        // If the number is out of range, we'll raise an E_INVARG. E_RANGE would be nice, but
-        // that's not what MOO does.
-        if number > subs.len() {
-            return Err(E_INVARG.msg("Number out of range"));
-        }
-
-        // Special case for 0
-        let (start, end) = if number == 0 {
-            (subs[0].0, subs[0].1)
-        } else {
-            // We're 1-indexed, so we'll subtract 1 from the number.
-            let number = number - 1;
-
-            // Look it up in matching `subs` pairs.
-            (subs[number].0, subs[number].1)
+            Some('0') => whole,
+            Some(c) if c.is_ascii_digit() => {
+                let group = c as usize - '1' as usize;
+                let Some(span) = groups.get(group) else {
+                    return Err(E_INVARG.msg(format!("no group {} in subs", group + 1)));
+                };
+                *span
+            }
+            _ => return Err(E_INVARG.msg("% must be followed by a digit or %")),
         };
 
-        // Now validate the range in the source string, and if the range is invalid, we just skip,
-        // as this seems to be how LambdaMOO behaves.
-        if start < 0 || start > end || end > (source.len() as isize) {
+        // Groups which didn't take part in the match are {0, -1}, and expand to nothing, as
+        // does any other range which doesn't fit the source, as in LambdaMOO.
+        if start < 1 || end < start - 1 || end > source.len() as isize {
             continue;
         }
-
-        let (start, end) = (start as usize - 1, end as usize);
-        // Now append the corresponding substring to `result`.
-        result.push_str(&source[start..end]);
-        if let Some(last_c) = last_c {
-            result.push(last_c);
+        if let Some(text) = source.get(start as usize - 1..end as usize) {
+            result.push_str(text);
         }
     }
     Ok(result)
 }
 
+/// Read an `{<start>, <end>}` pair of integers from a match result.
+fn match_span(span: &Var) -> Option<(isize, isize)> {
+    let span = span.as_list()?;
+    if span.len() != 2 {
+        return None;
+    }
+    let (Ok(start), Ok(end)) = (span.index(0), span.index(1)) else {
+        return None;
+    };
+    Some((start.as_integer()? as isize, end.as_integer()? as isize))
+}
+
 fn bf_substitute(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 2 {
         return Err(BfErr::Code(E_ARGS));
@@ -680,38 +689,35 @@ fn bf_substitute(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         _ => return Err(BfErr::Code(E_TYPE)),
     };
 
-    // Subs is of form {<start>, <end>, <replacements>, <subject>}
-    // "replacement" and subject are what we're interested in.
+    // Subs is of the form match() returns: {<start>, <end>, <group spans>, <subject>}.
+    let malformed = || BfErr::ErrValue(E_INVARG.msg("substitute() subs must be a match() result"));
     if subs.len() != 4 {
-        return Err(BfErr::Code(E_INVARG));
+        return Err(malformed());
     }
-
-    let (Ok(a), Ok(b)) = (subs.index(2), subs.index(3)) else {
-        return Err(BfErr::Code(E_INVARG));
+    let (Ok(start), Ok(end), Ok(groups), Ok(source)) =
+        (subs.index(0), subs.index(1), subs.index(2), subs.index(3))
+    else {
+        return Err(malformed());
     };
-    let (Variant::List(subs), Variant::Str(source)) = (a.variant(), b.variant()) else {
-        return Err(BfErr::Code(E_INVARG));
+    let (Some(start), Some(end), Variant::List(groups), Variant::Str(source)) = (
+        start.as_integer(),
+        end.as_integer(),
+        groups.variant(),
+        source.variant(),
+    ) else {
+        return Err(malformed());
     };
-
-    // Turn psubs into a Vec<(isize, isize)>. Raising errors on the way if they're not
-    let mut mysubs = Vec::new();
-    for sub in subs.iter() {
-        let Some(sub) = sub.as_list() else {
-            return Err(BfErr::Code(E_INVARG));
-        };
-        if sub.len() != 2 {
-            return Err(BfErr::Code(E_INVARG));
-        }
-        let (Ok(start), Ok(end)) = (sub.index(0), sub.index(1)) else {
-            return Err(BfErr::Code(E_INVARG));
-        };
-        let (Some(start), Some(end)) = (start.as_integer(), end.as_integer()) else {
-            return Err(BfErr::Code(E_INVARG));
-        };
-        mysubs.push((start as isize, end as isize));
+    let mut spans = Vec::with_capacity(groups.len());
+    for group in groups.iter() {
+        spans.push(match_span(&group).ok_or_else(malformed)?);
     }
 
-    match substitute(template.as_str(), &mysubs, source.as_str()) {
+    match substitute(
+        template.as_str(),
+        (start as isize, end as isize),
+        &spans,
+        source.as_str(),
+    ) {
         Ok(r) => Ok(Ret(v_string(r))),
         Err(e) => Err(BfErr::ErrValue(e)),
     }
@@ -899,7 +905,7 @@ mod tests {
         perform_pcre_match, perform_pcre_replace, perform_regex_match, substitute,
     };
     use moor_compiler::to_literal;
-    use moor_var::{Var, Variant, v_int, v_list, v_map, v_str};
+    use moor_var::{E_INVARG, Sequence, Var, Variant, v_int, v_list, v_map, v_str};
 
     #[test]
     fn test_match_substitute() {
@@ -922,17 +928,23 @@ mod tests {
                 (0, -1)
             ]
         );
-        let result = substitute("I thank you for your %1 here in %2.", &subs, source).unwrap();
+        let result = substitute(
+            "I thank you for your %1 here in %2.",
+            overall,
+            &subs,
+            source,
+        )
+        .unwrap();
         assert_eq!(result, "I thank you for your Welcome here in LambdaMOO.");
     }
 
     #[test]
     fn test_substitute_regression() {
         let source = "help @options";
-        let (_, subs) = perform_regex_match("^help %('%|[^ <][^ ]*%)$", source, false, false)
+        let (overall, subs) = perform_regex_match("^help %('%|[^ <][^ ]*%)$", source, false, false)
             .unwrap()
             .unwrap();
-        let result = substitute("%1", &subs, source).unwrap();
+        let result = substitute("%1", overall, &subs, source).unwrap();
         assert_eq!(result, "@options");
     }
 
@@ -962,10 +974,34 @@ mod tests {
                 (0, -1),
             ]
         );
-        let result = substitute("%1", &subs, source).unwrap();
+        let result = substitute("%1", overall, &subs, source).unwrap();
         assert_eq!(result, "edit");
     }
 
+    #[test]
+    fn test_substitute_references() {
+        let source = "*** Welcome to LambdaMOO!!!";
+        let (overall, subs) = perform_regex_match("%(%w*%) to %(%w*%)", source, false, false)
+            .unwrap()
+            .unwrap();
+        // %0 is the whole match, %% a literal %, and unmatched groups expand to nothing.
+        assert_eq!(
+            substitute("[%0] 100%% %2%3%1", overall, &subs, source).unwrap(),
+            "[Welcome to LambdaMOO] 100% LambdaMOOWelcome"
+        );
+        // Only a single digit is read after the %.
+        assert_eq!(
+            substitute("%10", overall, &subs, source).unwrap(),
+            "Welcome0"
+        );
+        let err = substitute("%x", overall, &subs, source).unwrap_err();
+        assert_eq!(err, E_INVARG);
+        let err = substitute("trailing %", overall, &subs, source).unwrap_err();
+        assert_eq!(err, E_INVARG);
+        let err = substitute("%3", overall, &subs[..2], source).unwrap_err();
+        assert_eq!(err, E_INVARG);
+    }
+
     #[test]
     fn test_match_regression() {
         let source = "2";
@@ -1002,6 +1038,21 @@ mod tests {
         );
     }
 
+    /// All nine groups are reported, the ninth included.
+    #[test]
+    fn test_match_nine_groups() {
+        let (overall, subs) = perform_regex_match(
+            "%(a%)%(b%)%(c%)%(d%)%(e%)%(f%)%(g%)%(h%)%(i%)",
+            "abcdefghi",
+            false,
+            false,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(overall, (1, 9));
+        assert_eq!(subs, (1..=9).map(|i| (i, i)).collect::<Vec<_>>());
+    }
+
     /// This pattern was causing an E_INVARG in BfMatch, due to the "-" after the 9.
     /// Turning on SyntaxBehavior::SYNTAX_BEHAVIOR_ALLOW_DOUBLE_RANGE_OP_IN_CC seems to fix it.
     #[test]
@@ -1081,4 +1132,105 @@ mod tests {
             "moose and Dogs and moose"
         );
     }
+
+    #[test]
+    fn test_pcre_match_named_groups() {
+        let regex = "(?<month>[0-9]{2})/(?<day>[0-9]{2})";
+        let result = perform_pcre_match(true, true, regex, "on 09/12 and 01/21", true).unwrap();
+        let v = Var::from_variant(Variant::List(result));
+        let expected = v_list(&[
+            v_map(&[
+                (
+                    v_str("0"),
+                    v_map(&[
+                        (v_str("match"), v_str("09/12")),
+                        (v_str("position"), v_list(&[v_int(4), v_int(8)])),
+                    ]),
+                ),
+                (
+                    v_str("month"),
+                    v_map(&[
+                        (v_str("match"), v_str("09")),
+                        (v_str("position"), v_list(&[v_int(4), v_int(5)])),
+                    ]),
+                ),
+                (
+                    v_str("day"),
+                    v_map(&[
+                        (v_str("match"), v_str("12")),
+                        (v_str("position"), v_list(&[v_int(7), v_int(8)])),
+                    ]),
+                ),
+            ]),
+            v_map(&[
+                (
+                    v_str("0"),
+                    v_map(&[
+                        (v_str("match"), v_str("01/21")),
+                        (v_str("position"), v_list(&[v_int(14), v_int(18)])),
+                    ]),
+                ),
+                (
+                    v_str("month"),
+                    v_map(&[
+                        (v_str("match"), v_str("01")),
+                        (v_str("position"), v_list(&[v_int(14), v_int(15)])),
+                    ]),
+                ),
+                (
+                    v_str("day"),
+                    v_map(&[
+                        (v_str("match"), v_str("21")),
+                        (v_str("position"), v_list(&[v_int(17), v_int(18)])),
+                    ]),
+                ),
+            ]),
+        ]);
+        assert_eq!(
+            v,
+            expected,
+            "Expected: \n{}\nGot: \n{}",
+            to_literal(&expected),
+            to_literal(&v)
+        );
+    }
+
+    #[test]
+    fn test_pcre_match_case_and_edge_cases() {
+        // Case matters
+        let result = perform_pcre_match(true, true, "cat", "CAT cat", true).unwrap();
+        assert_eq!(result.len(), 1);
+        // Case doesn't matter
+        let result = perform_pcre_match(true, false, "cat", "CAT cat", true).unwrap();
+        assert_eq!(result.len(), 2);
+        // Empty matches must not loop forever.
+        let result = perform_pcre_match(false, true, "x*", "abc", true).unwrap();
+        assert_eq!(result.len(), 4);
+        // Optional groups which don't participate are left out.
+        let result = perform_pcre_match(false, true, "a(b)?", "a", false).unwrap();
+        let Variant::List(groups) = result[0].variant() else {
+            panic!("expected a list");
+        };
+        assert_eq!(groups.len(), 1);
+        // Invalid patterns are E_INVARG.
+        let err = perform_pcre_match(true, true, "(unclosed", "abc", false).unwrap_err();
+        assert_eq!(err, E_INVARG);
+    }
+
+    #[test]
+    fn test_pcre_replace_edge_cases() {
+        // Capture groups in the pattern only replace the overall match.
+        assert_eq!(
+            perform_pcre_replace("cats and dogs", "s/(c)ats/moose/g").unwrap(),
+            "moose and dogs"
+        );
+        // Empty matches are replaced once per position, without looping forever.
+        assert_eq!(perform_pcre_replace("abc", "s/x*/-/g").unwrap(), "-a-b-c-");
+        // Missing replacement component, and bad patterns, are E_INVARG.
+        assert_eq!(perform_pcre_replace("abc", "s/foo").unwrap_err(), E_INVARG);
+        assert_eq!(
+            perform_pcre_replace("abc", "s/(unclosed/x/").unwrap_err(),
+            E_INVARG
+        );
+    }
 }
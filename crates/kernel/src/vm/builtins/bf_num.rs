@@ -11,14 +11,15 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use moor_compiler::offset_for_builtin;
-use moor_var::{E_ARGS, E_INVARG, E_TYPE, Sequence, Var, Variant};
+use moor_var::{E_ARGS, E_FLOAT, E_INVARG, E_TYPE, List, Sequence, Var, Variant};
 use moor_var::{v_float, v_int, v_str};
 
-use crate::vm::builtins::BfRet::Ret;
-use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinFunction};
+use crate::vm::builtins::BfRet::{Ret, RetNil};
+use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinFunction, world_state_bf_err};
 
 fn bf_abs(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 1 {
@@ -77,16 +78,43 @@ fn bf_random(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         ));
     }
 
-    let mut rng = rand::thread_rng();
-    if bf_args.args.is_empty() {
-        Ok(Ret(v_int(rng.gen_range(1..=2147483647))))
+    let max = if bf_args.args.is_empty() {
+        2147483647
     } else {
-        match &bf_args.args[0].variant() {
-            Variant::Int(i) if *i > 0 => Ok(Ret(v_int(rng.gen_range(1..=*i)))),
-            Variant::Int(_) => Err(BfErr::Code(E_INVARG)),
-            _ => Err(BfErr::Code(E_TYPE)),
+        match bf_args.args[0].variant() {
+            Variant::Int(i) if *i > 0 => *i,
+            Variant::Int(_) => return Err(BfErr::Code(E_INVARG)),
+            _ => return Err(BfErr::Code(E_TYPE)),
         }
+    };
+
+    let mut rng = bf_args.rng.lock().unwrap();
+    Ok(Ret(v_int(rng.gen_range(1..=max))))
+}
+
+fn bf_reseed_random(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("reseed_random() takes 1 argument"),
+        ));
     }
+
+    let Variant::Int(seed) = bf_args.args[0].variant() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("reseed_random() requires an integer seed"),
+        ));
+    };
+    let seed = *seed as u64;
+
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+
+    *bf_args.rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+
+    Ok(RetNil)
 }
 
 fn bf_floatstr(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
@@ -134,193 +162,130 @@ fn numeric_arg(arg: &Var) -> Result<f64, BfErr> {
     Ok(x)
 }
 
-fn bf_sin(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("sin() takes 1 argument")));
+/// Shared body for the single-argument float functions: check arity, coerce the argument, reject
+/// anything outside `in_domain` with `E_INVARG`, and apply `op`. As with arithmetic, a result
+/// too large to represent raises `E_FLOAT` rather than becoming infinity.
+fn unary_float_fn(
+    name: &str,
+    args: &List,
+    op: fn(f64) -> f64,
+    in_domain: fn(f64) -> bool,
+) -> Result<Var, BfErr> {
+    if args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg(format!("{name}() takes 1 argument")),
+        ));
     }
 
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.sin())))
-}
+    let x = numeric_arg(&args[0])?;
 
-fn bf_cos(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("cos() takes 1 argument")));
+    if !in_domain(x) {
+        return Err(BfErr::ErrValue(
+            E_INVARG.msg(format!("{name}() argument out of range")),
+        ));
     }
 
-    let x = numeric_arg(&bf_args.args[0])?;
+    let result = op(x);
+    if result.is_infinite() {
+        return Err(BfErr::ErrValue(
+            E_FLOAT.msg(format!("{name}() result out of range")),
+        ));
+    }
+    Ok(v_float(result))
+}
 
-    Ok(Ret(v_float(x.cos())))
+fn any(_: f64) -> bool {
+    true
 }
 
-fn bf_tan(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("tan() takes 1 argument")));
-    }
+fn non_negative(x: f64) -> bool {
+    x >= 0.0
+}
 
-    let x = numeric_arg(&bf_args.args[0])?;
+fn positive(x: f64) -> bool {
+    x > 0.0
+}
 
-    Ok(Ret(v_float(x.tan())))
+fn unit_range(x: f64) -> bool {
+    (-1.0..=1.0).contains(&x)
 }
 
-fn bf_sqrt(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("sqrt() takes 1 argument")));
-    }
+fn bf_sin(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    unary_float_fn("sin", &bf_args.args, f64::sin, any).map(Ret)
+}
 
-    let x = numeric_arg(&bf_args.args[0])?;
+fn bf_cos(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    unary_float_fn("cos", &bf_args.args, f64::cos, any).map(Ret)
+}
 
-    if x < 0.0 {
-        return Err(BfErr::ErrValue(
-            E_ARGS.msg("sqrt() takes a non-negative number"),
-        ));
-    }
+fn bf_tan(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    unary_float_fn("tan", &bf_args.args, f64::tan, any).map(Ret)
+}
 
-    Ok(Ret(v_float(x.sqrt())))
+fn bf_sqrt(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    unary_float_fn("sqrt", &bf_args.args, f64::sqrt, non_negative).map(Ret)
 }
 
 fn bf_asin(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("asin() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    if !(-1.0..=1.0).contains(&x) {
-        return Err(BfErr::ErrValue(
-            E_ARGS.msg("asin() takes a number between -1 and 1"),
-        ));
-    }
-
-    Ok(Ret(v_float(x.asin())))
+    unary_float_fn("asin", &bf_args.args, f64::asin, unit_range).map(Ret)
 }
 
 fn bf_acos(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("acos() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
+    unary_float_fn("acos", &bf_args.args, f64::acos, unit_range).map(Ret)
+}
 
-    if !(-1.0..=1.0).contains(&x) {
-        return Err(BfErr::ErrValue(
-            E_ARGS.msg("acos() takes a number between -1 and 1"),
-        ));
+/// `atan(y)` is the plain arc-tangent; `atan(y, x)` is the two-argument form, returning the angle
+/// of the point `(x, y)` in the range `[-pi..pi]`.
+fn atan(args: &List) -> Result<Var, BfErr> {
+    match args.len() {
+        1 => unary_float_fn("atan", args, f64::atan, any),
+        2 => {
+            let y = numeric_arg(&args[0])?;
+            let x = numeric_arg(&args[1])?;
+            Ok(v_float(y.atan2(x)))
+        }
+        _ => Err(BfErr::ErrValue(E_ARGS.msg("atan() takes 1 or 2 arguments"))),
     }
-
-    Ok(Ret(v_float(x.acos())))
 }
 
 fn bf_atan(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.is_empty() || bf_args.args.len() > 2 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("atan() takes 1 or 2 arguments")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-    let y = numeric_arg(&bf_args.args[1])?;
-
-    Ok(Ret(v_float(y.atan2(x))))
+    atan(&bf_args.args).map(Ret)
 }
 
 fn bf_sinh(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("sinh() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.sinh())))
+    unary_float_fn("sinh", &bf_args.args, f64::sinh, any).map(Ret)
 }
 
 fn bf_cosh(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("cosh() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.cosh())))
+    unary_float_fn("cosh", &bf_args.args, f64::cosh, any).map(Ret)
 }
 
 fn bf_tanh(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("tanh() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.tanh())))
+    unary_float_fn("tanh", &bf_args.args, f64::tanh, any).map(Ret)
 }
 
 fn bf_exp(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("exp() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.exp())))
+    unary_float_fn("exp", &bf_args.args, f64::exp, any).map(Ret)
 }
 
 fn bf_log(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("log() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    if x <= 0.0 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("log() takes a positive number")));
-    }
-
-    Ok(Ret(v_float(x.ln())))
+    unary_float_fn("log", &bf_args.args, f64::ln, positive).map(Ret)
 }
 
 fn bf_log10(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("log10() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    if x <= 0.0 {
-        return Err(BfErr::ErrValue(
-            E_ARGS.msg("log10() takes a positive number"),
-        ));
-    }
-
-    Ok(Ret(v_float(x.log10())))
+    unary_float_fn("log10", &bf_args.args, f64::log10, positive).map(Ret)
 }
 
 fn bf_ceil(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("ceil() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.ceil())))
+    unary_float_fn("ceil", &bf_args.args, f64::ceil, any).map(Ret)
 }
 
 fn bf_floor(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("floor() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.floor())))
+    unary_float_fn("floor", &bf_args.args, f64::floor, any).map(Ret)
 }
 
 fn bf_trunc(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
-        return Err(BfErr::ErrValue(E_ARGS.msg("trunc() takes 1 argument")));
-    }
-
-    let x = numeric_arg(&bf_args.args[0])?;
-
-    Ok(Ret(v_float(x.trunc())))
+    unary_float_fn("trunc", &bf_args.args, f64::trunc, any).map(Ret)
 }
 
 pub(crate) fn register_bf_num(builtins: &mut [Box<BuiltinFunction>]) {
@@ -328,6 +293,7 @@ pub(crate) fn register_bf_num(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("min")] = Box::new(bf_min);
     builtins[offset_for_builtin("max")] = Box::new(bf_max);
     builtins[offset_for_builtin("random")] = Box::new(bf_random);
+    builtins[offset_for_builtin("reseed_random")] = Box::new(bf_reseed_random);
     builtins[offset_for_builtin("floatstr")] = Box::new(bf_floatstr);
     builtins[offset_for_builtin("sqrt")] = Box::new(bf_sqrt);
     builtins[offset_for_builtin("sin")] = Box::new(bf_sin);
@@ -346,3 +312,103 @@ pub(crate) fn register_bf_num(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("floor")] = Box::new(bf_floor);
     builtins[offset_for_builtin("trunc")] = Box::new(bf_trunc);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::builtins::BfErr;
+    use crate::vm::builtins::bf_num::{any, atan, non_negative, positive, unary_float_fn};
+    use moor_var::{
+        E_ARGS, E_FLOAT, E_INVARG, E_TYPE, ErrorCode, List, Var, Variant, v_float, v_int, v_str,
+    };
+
+    fn assert_close(result: Result<Var, BfErr>, expected: f64) {
+        let result = result.unwrap();
+        let Variant::Float(f) = result.variant() else {
+            panic!("Expected float, got {:?}", result);
+        };
+        assert!((f - expected).abs() < 1e-12, "expected {expected}, got {f}");
+    }
+
+    fn error_code(result: Result<Var, BfErr>) -> ErrorCode {
+        match result {
+            Err(BfErr::ErrValue(e)) => e.err_type,
+            other => panic!("Expected error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unary_values() {
+        let half = List::mk_list(&[v_float(0.5)]);
+        assert_close(unary_float_fn("sin", &half, f64::sin, any), 0.5f64.sin());
+        assert_close(unary_float_fn("cos", &half, f64::cos, any), 0.5f64.cos());
+        assert_close(unary_float_fn("tanh", &half, f64::tanh, any), 0.5f64.tanh());
+        assert_close(unary_float_fn("exp", &half, f64::exp, any), 0.5f64.exp());
+        assert_close(unary_float_fn("log", &half, f64::ln, positive), 0.5f64.ln());
+        assert_close(
+            unary_float_fn("sqrt", &List::mk_list(&[v_int(2)]), f64::sqrt, non_negative),
+            std::f64::consts::SQRT_2,
+        );
+        assert_close(
+            unary_float_fn("floor", &List::mk_list(&[v_float(-1.5)]), f64::floor, any),
+            -2.0,
+        );
+    }
+
+    #[test]
+    fn test_atan_forms() {
+        assert_close(
+            atan(&List::mk_list(&[v_float(1.0)])),
+            std::f64::consts::FRAC_PI_4,
+        );
+        assert_close(
+            atan(&List::mk_list(&[v_float(1.0), v_float(-1.0)])),
+            3.0 * std::f64::consts::FRAC_PI_4,
+        );
+        assert_eq!(error_code(atan(&List::mk_list(&[]))), E_ARGS);
+    }
+
+    #[test]
+    fn test_domain_and_type_errors() {
+        let neg = List::mk_list(&[v_float(-1.0)]);
+        let zero = List::mk_list(&[v_int(0)]);
+        assert_eq!(
+            error_code(unary_float_fn("sqrt", &neg, f64::sqrt, non_negative)),
+            E_INVARG
+        );
+        assert_eq!(
+            error_code(unary_float_fn("log", &zero, f64::ln, positive)),
+            E_INVARG
+        );
+        assert_eq!(
+            error_code(unary_float_fn(
+                "sin",
+                &List::mk_list(&[v_str("x")]),
+                f64::sin,
+                any
+            )),
+            E_TYPE
+        );
+    }
+
+    #[test]
+    fn test_overflow_raises_e_float() {
+        let big = List::mk_list(&[v_float(1000.0)]);
+        assert_eq!(
+            error_code(unary_float_fn("exp", &big, f64::exp, any)),
+            E_FLOAT
+        );
+        assert_eq!(
+            error_code(unary_float_fn("sinh", &big, f64::sinh, any)),
+            E_FLOAT
+        );
+        assert_eq!(
+            error_code(unary_float_fn(
+                "cosh",
+                &List::mk_list(&[v_float(-1000.0)]),
+                f64::cosh,
+                any
+            )),
+            E_FLOAT
+        );
+    }
+}
@@ -18,16 +18,19 @@ use moor_common::model::Named;
 use moor_common::model::WorldStateError;
 use moor_common::model::{ObjFlag, ValSet};
 use moor_common::util::BitEnum;
-use moor_compiler::offset_for_builtin;
+use moor_compiler::{offset_for_builtin, to_literal};
 use moor_var::{E_ARGS, E_INVARG, E_NACC, E_PERM, E_TYPE};
-use moor_var::{List, Variant, v_bool};
+use moor_var::{List, Obj, Var, Variant, v_bool};
 use moor_var::{NOTHING, v_list_iter};
 use moor_var::{Sequence, Symbol, v_list};
-use moor_var::{v_int, v_obj, v_str, v_sym_str};
+use moor_var::{v_int, v_map, v_obj, v_str, v_sym_str};
 
+use crate::tasks::scheduler::load_server_options;
 use crate::vm::builtins::BfRet::{Ret, RetNil, VmInstr};
 use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinFunction, world_state_bf_err};
+use crate::vm::vm_host::ExecutionResult;
 use crate::vm::vm_host::ExecutionResult::DispatchVerb;
+use crate::vm::vm_unwind::FinallyReason;
 use crate::vm::{VerbCall, VerbExecutionRequest};
 
 lazy_static! {
@@ -36,7 +39,10 @@ lazy_static! {
     static ref ENTERFUNC_SYM: Symbol = Symbol::mk("enterfunc");
     static ref CREATE_SYM: Symbol = Symbol::mk("create");
     static ref RECYCLE_SYM: Symbol = Symbol::mk("recycle");
+    static ref NAME_SYM: Symbol = Symbol::mk("name");
+    static ref OWNER_SYM: Symbol = Symbol::mk("owner");
     static ref ACCEPT_SYM: Symbol = Symbol::mk("accept");
+    static ref ALIASES_SYM: Symbol = Symbol::mk("aliases");
 }
 /*
 Function: int valid (obj object)
@@ -149,6 +155,38 @@ fn bf_children(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_list(&children)))
 }
 
+/*
+Function: int child_count (obj object)
+Returns the number of children of the given object, as `length(children(object))` would, without
+fetching them. If object is not valid, then E_INVARG is raised.
+ */
+fn bf_child_count(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("child_count() takes 1 argument"),
+        ));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("child_count() first argument must be an object"),
+        ));
+    };
+    if !bf_args
+        .world_state
+        .valid(&obj)
+        .map_err(world_state_bf_err)?
+    {
+        return Err(BfErr::ErrValue(
+            E_INVARG.msg("child_count() argument must be a valid object"),
+        ));
+    }
+    let count = bf_args
+        .world_state
+        .count_children(&bf_args.task_perms_who(), &obj)
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_int(count as i64)))
+}
+
 fn bf_descendants(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 1 {
         return Err(BfErr::ErrValue(
@@ -401,9 +439,11 @@ fn bf_create(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
                 return Ok(Ret(v_obj(new_obj)));
             };
 
+            // If :initialize raises, we want it back so we can undo the creation (see below).
             let bf_frame = bf_args.bf_frame_mut();
             bf_frame.bf_trampoline = Some(BF_CREATE_OBJECT_TRAMPOLINE_DONE);
             bf_frame.bf_trampoline_arg = Some(v_obj(new_obj));
+            bf_frame.catch_raise = true;
 
             let ve = VerbExecutionRequest {
                 permissions: bf_args.task_perms_who(),
@@ -428,6 +468,27 @@ fn bf_create(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
                 panic!("Missing/invalid trampoline argument for bf_create");
             };
 
+            // :initialize raised an error. Don't leave a half-initialized object behind: recycle
+            // it, then carry on unwinding with the original error (and its traceback).
+            if let Some(exception) = bf_args.bf_frame_mut().raised.take() {
+                let new_obj = new_obj
+                    .as_object()
+                    .expect("bf_create trampoline arg is an object");
+                if let Err(e) = bf_args
+                    .world_state
+                    .recycle_object(&bf_args.task_perms_who(), &new_obj)
+                {
+                    error!(
+                        ?new_obj,
+                        ?e,
+                        "Unable to recycle object after failed :initialize"
+                    );
+                }
+                return Ok(VmInstr(ExecutionResult::Unwind(FinallyReason::Raise(
+                    exception,
+                ))));
+            }
+
             Ok(Ret(new_obj))
         }
         _ => {
@@ -862,6 +923,180 @@ fn bf_move(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     }
 }
 
+/// Dispatch `verb` on `target` with `what` as its argument, as move() does for its hooks, or
+/// None if `target` has no such verb.
+fn move_hook_call(
+    bf_args: &mut BfCallState<'_>,
+    verb: Symbol,
+    target: Obj,
+    what: Obj,
+) -> Result<Option<ExecutionResult>, BfErr> {
+    match bf_args
+        .world_state
+        .find_method_verb_on(&bf_args.task_perms_who(), &target, verb)
+    {
+        Ok((program, resolved_verb)) => Ok(Some(DispatchVerb(Box::new(VerbExecutionRequest {
+            permissions: bf_args.task_perms_who(),
+            resolved_verb,
+            program,
+            call: Box::new(VerbCall {
+                verb_name: verb,
+                location: v_obj(target),
+                this: v_obj(target),
+                player: bf_args.exec_state.top().player,
+                args: List::mk_list(&[v_obj(what)]),
+                argstr: "".to_string(),
+                caller: bf_args.exec_state.top().this.clone(),
+            }),
+            command: None,
+        })))),
+        Err(WorldStateError::VerbNotFound(_, _)) => Ok(None),
+        Err(e) => {
+            error!("Error looking up {verb} verb: {:?}", e);
+            Err(BfErr::Code(E_NACC))
+        }
+    }
+}
+
+/*
+Function: none move_all (list moves)
+Performs each {what, where} move in `moves` as move() would, but all or nothing: every
+destination's :accept is asked first, and if any refuses, nothing is moved. Only once all the
+moves are done are the :exitfunc and :enterfunc hooks called, in order.
+*/
+fn bf_move_all(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(E_ARGS.msg("move_all() takes 1 argument")));
+    }
+    let Some(moves) = bf_args.args[0].as_list() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("move_all() argument must be a list"),
+        ));
+    };
+    let mut pairs = Vec::with_capacity(moves.len());
+    for pair in moves.iter() {
+        let Some(pair) = pair.as_list() else {
+            return Err(BfErr::ErrValue(
+                E_TYPE.msg("move_all() moves must be {what, where} lists"),
+            ));
+        };
+        if pair.len() != 2 {
+            return Err(BfErr::ErrValue(
+                E_INVARG.msg("move_all() moves must be {what, where} lists"),
+            ));
+        }
+        let (Some(what), Some(whereto)) = (pair[0].as_object(), pair[1].as_object()) else {
+            return Err(BfErr::ErrValue(
+                E_TYPE.msg("move_all() moves must be between objects"),
+            ));
+        };
+        pairs.push((what, whereto));
+    }
+    let n = pairs.len();
+
+    // 'Trampoline' state machine, over the moves in turn:
+    //    [0, 2n)       => even: call :accept for move t/2 (skipped for #-1, or a wizard with no
+    //                     :accept); odd: check what it returned, raising E_NACC if it refused.
+    //    2n            => make every move, keeping the original locations in the trampoline arg.
+    //    (2n, 4n + 1)  => call :exitfunc on the original location, then :enterfunc on the
+    //                     destination, of each move in turn. Results are ignored.
+    //    4n + 1        => done.
+    let mut tramp = bf_args.bf_frame_mut().bf_trampoline.take().unwrap_or(0);
+    let perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    loop {
+        if tramp < 2 * n {
+            let (what, whereto) = pairs[tramp / 2];
+            if tramp % 2 == 1 {
+                let result = bf_args.exec_state.top().frame.return_value();
+                if !result.is_true() && !perms.check_is_wizard().map_err(world_state_bf_err)? {
+                    return Err(BfErr::Code(E_NACC));
+                }
+                tramp += 1;
+                continue;
+            }
+            if whereto.is_nothing() {
+                tramp += 2;
+                continue;
+            }
+            let Some(call) = move_hook_call(bf_args, *ACCEPT_SYM, whereto, what)? else {
+                if !perms.check_is_wizard().map_err(world_state_bf_err)? {
+                    return Err(BfErr::Code(E_NACC));
+                }
+                tramp += 2;
+                continue;
+            };
+            bf_args.bf_frame_mut().bf_trampoline = Some(tramp + 1);
+            return Ok(VmInstr(call));
+        }
+
+        if tramp == 2 * n {
+            trace!(moves = n, "move_all: all moves accepted, moving");
+            let who = bf_args.task_perms_who();
+            let mut original_locations = Vec::with_capacity(n);
+            for (what, whereto) in &pairs {
+                let moved = match bf_args.world_state.location_of(&who, what) {
+                    Ok(original_location) => bf_args
+                        .world_state
+                        .move_object(&who, what, whereto)
+                        .map(|_| original_location),
+                    Err(e) => Err(e),
+                };
+                match moved {
+                    Ok(original_location) => original_locations.push(original_location),
+                    Err(e) => {
+                        // Put back everything already moved, so that none of the moves happen.
+                        // Every object is tried even if one can't be put back, and then the first
+                        // failure to put one back is reported in place of the failed move.
+                        let mut restore_error = None;
+                        for ((what, _), original_location) in
+                            pairs.iter().zip(&original_locations).rev()
+                        {
+                            let restored =
+                                bf_args
+                                    .world_state
+                                    .move_object(&who, what, original_location);
+                            if let Err(restore_e) = restored {
+                                restore_error.get_or_insert(restore_e);
+                            }
+                        }
+                        return Err(world_state_bf_err(restore_error.unwrap_or(e)));
+                    }
+                }
+            }
+            bf_args.bf_frame_mut().bf_trampoline_arg =
+                Some(v_list_iter(original_locations.into_iter().map(v_obj)));
+            tramp += 1;
+            continue;
+        }
+
+        let hook = tramp - (2 * n + 1);
+        if hook == 2 * n {
+            return Ok(RetNil);
+        }
+        let (what, whereto) = pairs[hook / 2];
+        let (verb, target) = if hook % 2 == 0 {
+            let original_location = bf_args
+                .bf_frame()
+                .bf_trampoline_arg
+                .as_ref()
+                .and_then(|locations| locations.as_list())
+                .and_then(|locations| locations[hook / 2].as_object())
+                .expect("move_all() original locations missing");
+            (*EXITFUNC_SYM, original_location)
+        } else {
+            (*ENTERFUNC_SYM, whereto)
+        };
+        tramp += 1;
+        if target.is_nothing() {
+            continue;
+        }
+        if let Some(call) = move_hook_call(bf_args, verb, target, what)? {
+            bf_args.bf_frame_mut().bf_trampoline = Some(tramp);
+            return Ok(VmInstr(call));
+        }
+    }
+}
+
 fn bf_verbs(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 1 {
         return Err(BfErr::ErrValue(E_ARGS.msg("verbs() takes 1 argument")));
@@ -907,6 +1142,113 @@ fn bf_properties(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_list(&props)))
 }
 
+/*
+Function: int verb_count (obj object)
+Returns the number of verbs defined directly on the given object, as `length(verbs(object))` would,
+without fetching them. If object is not valid, then E_INVIND is raised. If the programmer does not
+have read permission on object, then E_PERM is raised.
+ */
+fn bf_verb_count(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(E_ARGS.msg("verb_count() takes 1 argument")));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("verb_count() first argument must be an object"),
+        ));
+    };
+    let count = bf_args
+        .world_state
+        .count_verbs(&bf_args.task_perms_who(), &obj)
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_int(count as i64)))
+}
+
+/*
+Function: int property_count (obj object)
+Returns the number of properties defined directly on the given object, as
+`length(properties(object))` would, without fetching them. If object is not valid, then E_INVIND
+is raised. If the programmer does not have read permission on object, then E_PERM is raised.
+ */
+fn bf_property_count(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("property_count() takes 1 argument"),
+        ));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("property_count() first argument must be an object"),
+        ));
+    };
+    let count = bf_args
+        .world_state
+        .count_properties(&bf_args.task_perms_who(), &obj)
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_int(count as i64)))
+}
+
+/*
+Function: map object_summary (obj object)
+Returns a map of the core attributes of the object, fetched together rather than with a separate
+call for each: "name", "owner", "parent" and "location", "flags" (a list of "player",
+"programmer", "wizard", "read", "write" and "fertile", as set), and, if the programmer has read
+permission on the object, "verbs" and "properties" as `verbs()` and `properties()` return them
+(with "" for a verb that has no names). If object is not valid, then E_INVIND is raised.
+ */
+fn bf_object_summary(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("object_summary() takes 1 argument"),
+        ));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("object_summary() first argument must be an object"),
+        ));
+    };
+    let summary = bf_args
+        .world_state
+        .object_summary(&bf_args.task_perms_who(), &obj)
+        .map_err(world_state_bf_err)?;
+
+    let flags: Vec<_> = [
+        (ObjFlag::User, "player"),
+        (ObjFlag::Programmer, "programmer"),
+        (ObjFlag::Wizard, "wizard"),
+        (ObjFlag::Read, "read"),
+        (ObjFlag::Write, "write"),
+        (ObjFlag::Fertile, "fertile"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| summary.flags.contains(*flag))
+    .map(|(_, name)| v_str(name))
+    .collect();
+    let mut entries = vec![
+        (v_str("name"), v_str(&summary.name)),
+        (v_str("owner"), v_obj(summary.owner)),
+        (v_str("parent"), v_obj(summary.parent)),
+        (v_str("location"), v_obj(summary.location)),
+        (v_str("flags"), v_list(&flags)),
+    ];
+    if let Some(verbs) = summary.verbs {
+        let verbs: Vec<_> = verbs
+            .iter()
+            .map(|v| v_str(v.names().first().copied().unwrap_or_default()))
+            .collect();
+        entries.push((v_str("verbs"), v_list(&verbs)));
+    }
+    if let Some(props) = summary.properties {
+        let props: Vec<_> = if bf_args.config.use_symbols_in_builtins {
+            props.iter().map(|p| v_sym_str(p.name())).collect()
+        } else {
+            props.iter().map(|p| v_str(p.name())).collect()
+        };
+        entries.push((v_str("properties"), v_list(&props)));
+    }
+    Ok(Ret(v_map(&entries)))
+}
+
 fn bf_set_player_flag(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 2 {
         return Err(BfErr::ErrValue(
@@ -965,21 +1307,402 @@ fn bf_players(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_list_iter(players.iter().map(v_obj))))
 }
 
+/*
+Function: list chown_all (obj from, obj to)
+Transfers ownership of every verb and property owned by `from`, on any object, to `to`, in one
+transaction. Returns a list of the number of verbs and the number of properties transferred.
+`from` need not be valid, so that the leftovers of a recycled player can be reassigned, but `to`
+must be, or E_INVARG is raised. Wizard only.
+ */
+fn bf_chown_all(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 2 {
+        return Err(BfErr::ErrValue(E_ARGS.msg("chown_all() takes 2 arguments")));
+    }
+    let (Some(from), Some(to)) = (bf_args.args[0].as_object(), bf_args.args[1].as_object()) else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("chown_all() arguments must be objects"),
+        ));
+    };
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+    // The world state checks that `to` is valid; `from` needn't be.
+    match bf_args
+        .world_state
+        .chown_all(&bf_args.task_perms_who(), &from, &to)
+    {
+        Ok((verbs, props)) => Ok(Ret(v_list(&[v_int(verbs as i64), v_int(props as i64)]))),
+        Err(WorldStateError::ObjectNotFound(_)) => Err(BfErr::ErrValue(
+            E_INVARG.msg("chown_all() new owner must be a valid object"),
+        )),
+        Err(e) => Err(world_state_bf_err(e)),
+    }
+}
+
+/// Case-insensitive glob match of `name` against `pattern`, where `*` matches any run of
+/// characters and `?` any single one.
+fn glob_matches(pattern: &[char], name: &str) -> bool {
+    let name: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the most recent `*`, should what follows it stop matching.
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => {
+                let Some((star, from)) = backtrack else {
+                    return false;
+                };
+                backtrack = Some((star, from + 1));
+                p = star + 1;
+                n = from + 1;
+            }
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// The pattern argument to find_verbs() or find_properties(), ready for `glob_matches`.
+fn find_pattern(bf_args: &BfCallState<'_>, bf_name: &str) -> Result<Vec<char>, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.with_msg(|| format!("{bf_name}() takes 1 argument")),
+        ));
+    }
+    let Some(pattern) = bf_args.args[0].as_string() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.with_msg(|| format!("{bf_name}() pattern must be a string")),
+        ));
+    };
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+    Ok(pattern.chars().flat_map(char::to_lowercase).collect())
+}
+
+/*
+Function: list find_verbs (str pattern)
+Returns {obj, names} for every verb, on any object, with a name matching the glob `pattern`
+(case-insensitively; `*` matches any run of characters and `?` any single one). `names` is the
+verb's full space-separated name, as verb_info() gives it. Results are ordered by object.
+Wizard only.
+ */
+fn bf_find_verbs(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    let pattern = find_pattern(bf_args, "find_verbs")?;
+    let found = bf_args
+        .world_state
+        .find_verbs(&bf_args.task_perms_who(), &|name| {
+            glob_matches(&pattern, name)
+        })
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_list_iter(found.into_iter().map(|(obj, verbdef)| {
+        v_list(&[v_obj(obj), v_str(&verbdef.names().join(" "))])
+    }))))
+}
+
+/*
+Function: list find_properties (str pattern)
+Returns {obj, name} for every property definition with a name matching the glob `pattern`, as
+for find_verbs(). `obj` is the object which defines the property, not those inheriting it.
+Wizard only.
+ */
+fn bf_find_properties(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    let pattern = find_pattern(bf_args, "find_properties")?;
+    let found = bf_args
+        .world_state
+        .find_properties(&bf_args.task_perms_who(), &|name| {
+            glob_matches(&pattern, name)
+        })
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_list_iter(found.into_iter().map(|(obj, propdef)| {
+        v_list(&[v_obj(obj), v_str(propdef.name())])
+    }))))
+}
+
+/// The current contents of `obj.aliases`, which must be a list.
+fn aliases_of(bf_args: &mut BfCallState<'_>, bf_name: &str, obj: &Obj) -> Result<Vec<Var>, BfErr> {
+    let aliases = bf_args
+        .world_state
+        .retrieve_property(&bf_args.task_perms_who(), obj, *ALIASES_SYM)
+        .map_err(world_state_bf_err)?;
+    let Some(aliases) = aliases.as_list() else {
+        return Err(BfErr::ErrValue(E_TYPE.with_msg(|| {
+            format!("{bf_name}() requires the aliases property to be a list")
+        })));
+    };
+    Ok(aliases.iter().collect())
+}
+
+/// Check `aliases` is all strings with no duplicates (ignoring case, as the matcher does), then
+/// store it in `obj.aliases`, with the usual property permission checks.
+fn store_aliases(
+    bf_args: &mut BfCallState<'_>,
+    bf_name: &str,
+    obj: &Obj,
+    aliases: Vec<Var>,
+) -> Result<(), BfErr> {
+    for (i, alias) in aliases.iter().enumerate() {
+        if alias.as_string().is_none() {
+            return Err(BfErr::ErrValue(
+                E_TYPE.with_msg(|| format!("{bf_name}() aliases must be strings")),
+            ));
+        }
+        if aliases[..i].contains(alias) {
+            return Err(BfErr::ErrValue(E_INVARG.with_msg(|| {
+                format!(
+                    "{bf_name}() alias {} is given more than once",
+                    to_literal(alias)
+                )
+            })));
+        }
+    }
+    bf_args
+        .world_state
+        .update_property(
+            &bf_args.task_perms_who(),
+            obj,
+            *ALIASES_SYM,
+            &v_list(&aliases),
+        )
+        .map_err(world_state_bf_err)
+}
+
+/*
+Function: none set_aliases (obj object, list aliases)
+Replaces the aliases of `object`, which the command matcher recognizes it by along with its name.
+The aliases must all be strings, with none repeated (ignoring case), or E_TYPE or E_INVARG is
+raised. They are kept in the `aliases` property, which must already exist and be writable by the
+programmer.
+ */
+fn bf_set_aliases(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 2 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("set_aliases() takes 2 arguments"),
+        ));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("set_aliases() first argument must be an object"),
+        ));
+    };
+    let Some(aliases) = bf_args.args[1].as_list() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("set_aliases() second argument must be a list"),
+        ));
+    };
+    let aliases = aliases.iter().collect();
+    store_aliases(bf_args, "set_aliases", &obj, aliases)?;
+    Ok(RetNil)
+}
+
+/*
+Function: list add_alias (obj object, str alias)
+Adds `alias` to the end of the aliases of `object`, returning the new list of aliases. Raises
+E_INVARG if it is already one of them. See set_aliases().
+ */
+fn bf_add_alias(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 2 {
+        return Err(BfErr::ErrValue(E_ARGS.msg("add_alias() takes 2 arguments")));
+    }
+    let (Some(obj), Some(_)) = (bf_args.args[0].as_object(), bf_args.args[1].as_string()) else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("add_alias() takes an object and a string"),
+        ));
+    };
+    let alias = bf_args.args[1].clone();
+    let mut aliases = aliases_of(bf_args, "add_alias", &obj)?;
+    aliases.push(alias);
+    store_aliases(bf_args, "add_alias", &obj, aliases.clone())?;
+    Ok(Ret(v_list(&aliases)))
+}
+
+/*
+Function: list remove_alias (obj object, str alias)
+Removes `alias` (ignoring case) from the aliases of `object`, returning the new list of aliases.
+Raises E_INVARG if it isn't one of them. See set_aliases().
+ */
+fn bf_remove_alias(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 2 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("remove_alias() takes 2 arguments"),
+        ));
+    }
+    let (Some(obj), Some(_)) = (bf_args.args[0].as_object(), bf_args.args[1].as_string()) else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("remove_alias() takes an object and a string"),
+        ));
+    };
+    let alias = bf_args.args[1].clone();
+    let mut aliases = aliases_of(bf_args, "remove_alias", &obj)?;
+    let Some(position) = aliases.iter().position(|a| *a == alias) else {
+        return Err(BfErr::ErrValue(E_INVARG.with_msg(|| {
+            format!("remove_alias() {} is not an alias", to_literal(&alias))
+        })));
+    };
+    aliases.remove(position);
+    store_aliases(bf_args, "remove_alias", &obj, aliases.clone())?;
+    Ok(Ret(v_list(&aliases)))
+}
+
+/*
+Function: none undelete (obj object)
+Brings back `object`, recycled while the database was keeping tombstones, with its verbs and
+properties as they were. It comes back in #-1, without the children it had. Raises E_INVARG if
+`object` isn't a tombstone, or its number has been reused. Wizard only.
+ */
+fn bf_undelete(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(E_ARGS.msg("undelete() takes 1 argument")));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("undelete() argument must be an object"),
+        ));
+    };
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+    match bf_args
+        .world_state
+        .undelete_object(&bf_args.task_perms_who(), &obj)
+    {
+        Ok(()) => Ok(RetNil),
+        Err(WorldStateError::ObjectNotFound(_)) => Err(BfErr::ErrValue(
+            E_INVARG.msg("undelete() argument must be a recycled object"),
+        )),
+        Err(WorldStateError::ObjectAlreadyExists(_)) => Err(BfErr::ErrValue(
+            E_INVARG.msg("undelete() object number is in use"),
+        )),
+        Err(e) => Err(world_state_bf_err(e)),
+    }
+}
+
+/// Create a player from $server_options.player_prototype: a child of the prototype, owning
+/// itself, with its own copies of the prototype's starter properties. Unlike `create()`, no
+/// `:initialize` is called; the core's own creation code carries on from here.
+fn bf_create_player(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("create_player() takes 1 argument"),
+        ));
+    }
+    let Some(name) = bf_args.args[0].as_string() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("create_player() argument must be a string"),
+        ));
+    };
+    let name = name.to_string();
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+
+    // Read fresh, so a prototype set up earlier in the same task is used.
+    let server_options = load_server_options(bf_args.world_state);
+    let prototype = match server_options.player_prototype {
+        Some(prototype)
+            if bf_args
+                .world_state
+                .valid(&prototype)
+                .map_err(world_state_bf_err)? =>
+        {
+            prototype
+        }
+        _ => {
+            return Err(BfErr::ErrValue(E_INVARG.msg(
+                "create_player() requires $server_options.player_prototype to be a valid object",
+            )));
+        }
+    };
+
+    let perms = bf_args.task_perms_who();
+    let player = bf_args
+        .world_state
+        .create_object(&perms, &prototype, &perms, BitEnum::new_with(ObjFlag::User))
+        .map_err(world_state_bf_err)?;
+    for (property, value) in [(*NAME_SYM, v_str(&name)), (*OWNER_SYM, v_obj(player))] {
+        bf_args
+            .world_state
+            .update_property(&perms, &player, property, &value)
+            .map_err(world_state_bf_err)?;
+    }
+    for property in server_options.player_starter_properties {
+        let value = bf_args
+            .world_state
+            .retrieve_property(&perms, &prototype, property)
+            .map_err(world_state_bf_err)?;
+        bf_args
+            .world_state
+            .update_property(&perms, &player, property, &value)
+            .map_err(world_state_bf_err)?;
+    }
+    Ok(Ret(v_obj(player)))
+}
+
+/// The object's generation, which goes up with each committed change to its verbs or properties.
+fn bf_object_generation(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("object_generation() takes 1 argument"),
+        ));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("object_generation() argument must be an object"),
+        ));
+    };
+    match bf_args.world_state.object_generation(&obj) {
+        Ok(generation) => Ok(Ret(v_int(generation))),
+        Err(WorldStateError::ObjectNotFound(_)) => Err(BfErr::ErrValue(
+            E_INVARG.msg("object_generation() argument must be a valid object"),
+        )),
+        Err(e) => Err(world_state_bf_err(e)),
+    }
+}
+
 pub(crate) fn register_bf_objects(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("create")] = Box::new(bf_create);
     builtins[offset_for_builtin("valid")] = Box::new(bf_valid);
     builtins[offset_for_builtin("verbs")] = Box::new(bf_verbs);
     builtins[offset_for_builtin("properties")] = Box::new(bf_properties);
+    builtins[offset_for_builtin("verb_count")] = Box::new(bf_verb_count);
+    builtins[offset_for_builtin("property_count")] = Box::new(bf_property_count);
+    builtins[offset_for_builtin("object_summary")] = Box::new(bf_object_summary);
     builtins[offset_for_builtin("parent")] = Box::new(bf_parent);
     builtins[offset_for_builtin("children")] = Box::new(bf_children);
+    builtins[offset_for_builtin("child_count")] = Box::new(bf_child_count);
     builtins[offset_for_builtin("ancestors")] = Box::new(bf_ancestors);
     builtins[offset_for_builtin("isa")] = Box::new(bf_isa);
     builtins[offset_for_builtin("descendants")] = Box::new(bf_descendants);
     builtins[offset_for_builtin("move")] = Box::new(bf_move);
+    builtins[offset_for_builtin("move_all")] = Box::new(bf_move_all);
     builtins[offset_for_builtin("chparent")] = Box::new(bf_chparent);
     builtins[offset_for_builtin("set_player_flag")] = Box::new(bf_set_player_flag);
     builtins[offset_for_builtin("recycle")] = Box::new(bf_recycle);
     builtins[offset_for_builtin("max_object")] = Box::new(bf_max_object);
     builtins[offset_for_builtin("players")] = Box::new(bf_players);
     builtins[offset_for_builtin("locations")] = Box::new(bf_locations);
+    builtins[offset_for_builtin("chown_all")] = Box::new(bf_chown_all);
+    builtins[offset_for_builtin("find_verbs")] = Box::new(bf_find_verbs);
+    builtins[offset_for_builtin("find_properties")] = Box::new(bf_find_properties);
+    builtins[offset_for_builtin("set_aliases")] = Box::new(bf_set_aliases);
+    builtins[offset_for_builtin("add_alias")] = Box::new(bf_add_alias);
+    builtins[offset_for_builtin("remove_alias")] = Box::new(bf_remove_alias);
+    builtins[offset_for_builtin("undelete")] = Box::new(bf_undelete);
+    builtins[offset_for_builtin("object_generation")] = Box::new(bf_object_generation);
+    builtins[offset_for_builtin("create_player")] = Box::new(bf_create_player);
 }
@@ -137,6 +137,23 @@ fn bf_is_clear_property(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(bf_args.v_bool(is_clear)))
 }
 
+// property_definer (obj <object>, str <prop-name>) => obj
+// The ancestor that defined the property, as opposed to <object> which may only inherit it.
+fn bf_property_definer(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 2 {
+        return Err(Code(E_ARGS));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(Code(E_TYPE));
+    };
+    let prop_name = bf_args.args[1].as_symbol().map_err(ErrValue)?;
+    let (pdef, _) = bf_args
+        .world_state
+        .get_property_info(&bf_args.task_perms_who(), &obj, prop_name)
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_obj(pdef.definer())))
+}
+
 fn bf_clear_property(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 2 {
         return Err(Code(E_ARGS));
@@ -152,6 +169,30 @@ fn bf_clear_property(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_empty_list()))
 }
 
+// property_add (obj <object>, str <prop-name>, num <delta>) => num
+// Adds to a numeric property without conflicting with other tasks adding to it at the same time.
+fn bf_property_add(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 3 {
+        return Err(Code(E_ARGS));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(Code(E_TYPE));
+    };
+    let prop_name = bf_args.args[1].as_symbol().map_err(ErrValue)?;
+    let delta = bf_args.args[2].clone();
+    if !matches!(delta.variant(), Variant::Int(_) | Variant::Float(_)) {
+        return Err(Code(E_TYPE));
+    }
+    let sum = bf_args
+        .world_state
+        .add_to_property(&bf_args.task_perms_who(), &obj, prop_name, &delta)
+        .map_err(world_state_bf_err)?;
+    let Some(sum) = sum else {
+        return Err(ErrValue(E_INVARG.msg("Integer overflow")));
+    };
+    Ok(Ret(sum))
+}
+
 // add_property (obj <object>, str <prop-name>, <value>, list <info>) => none
 fn bf_add_property(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 4 {
@@ -191,6 +232,53 @@ fn bf_add_property(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(RetNil)
 }
 
+/// Function: none copy_property(obj from, str prop-name, obj to [, obj owner])
+///
+/// Define a property on `to` with the same flags and value as the one `from` has (defined there or
+/// inherited), owned by `owner`, or by the caller if not given.
+fn bf_copy_property(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() < 3 || bf_args.args.len() > 4 {
+        return Err(Code(E_ARGS));
+    }
+    let (Some(from), Some(to)) = (bf_args.args[0].as_object(), bf_args.args[2].as_object()) else {
+        return Err(Code(E_TYPE));
+    };
+    let prop_name = bf_args.args[1].as_symbol().map_err(ErrValue)?;
+    let owner = if bf_args.args.len() == 4 {
+        let Some(owner) = bf_args.args[3].as_object() else {
+            return Err(Code(E_TYPE));
+        };
+        owner
+    } else {
+        bf_args.task_perms_who()
+    };
+
+    let perms = bf_args.task_perms_who();
+    let (_, prop_perms) = bf_args
+        .world_state
+        .get_property_info(&perms, &from, prop_name)
+        .map_err(world_state_bf_err)?;
+    let value = bf_args
+        .world_state
+        .retrieve_property(&perms, &from, prop_name)
+        .map_err(world_state_bf_err)?;
+
+    // define_property checks we may write `to`, and give the property to `owner`.
+    bf_args
+        .world_state
+        .define_property(
+            &perms,
+            &to,
+            &to,
+            prop_name,
+            &owner,
+            prop_perms.flags(),
+            Some(value),
+        )
+        .map_err(world_state_bf_err)?;
+    Ok(RetNil)
+}
+
 fn bf_delete_property(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 2 {
         return Err(Code(E_ARGS));
@@ -213,4 +301,7 @@ pub(crate) fn register_bf_properties(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("clear_property")] = Box::new(bf_clear_property);
     builtins[offset_for_builtin("add_property")] = Box::new(bf_add_property);
     builtins[offset_for_builtin("delete_property")] = Box::new(bf_delete_property);
+    builtins[offset_for_builtin("copy_property")] = Box::new(bf_copy_property);
+    builtins[offset_for_builtin("property_add")] = Box::new(bf_property_add);
+    builtins[offset_for_builtin("property_definer")] = Box::new(bf_property_definer);
 }
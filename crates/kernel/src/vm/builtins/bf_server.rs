@@ -19,14 +19,18 @@ use chrono_tz::{OffsetName, Tz};
 use iana_time_zone::get_timezone;
 use tracing::{error, info, warn};
 
+use crate::tasks::scheduler::load_server_options;
 use crate::tasks::{TaskStart, sched_counters};
 use crate::vm::TaskSuspend;
+use crate::vm::activation::Frame;
 use crate::vm::builtins::BfErr::{Code, ErrValue};
 use crate::vm::builtins::BfRet::{Ret, RetNil, VmInstr};
 use crate::vm::builtins::{
     BfCallState, BfErr, BfRet, BuiltinFunction, bf_perf_counters, world_state_bf_err,
 };
+use crate::vm::exec_state::EvalBudget;
 use crate::vm::vm_host::ExecutionResult;
+use crate::vm::{VerbCall, VerbExecutionRequest};
 use moor_common::build::{PKG_VERSION, SHORT_COMMIT};
 use moor_common::model::{Named, ObjFlag, WorldStateError};
 use moor_common::tasks::Event::{Present, Unpresent};
@@ -35,9 +39,12 @@ use moor_common::tasks::{NarrativeEvent, Presentation};
 use moor_common::util::PerfCounter;
 use moor_compiler::compile;
 use moor_compiler::{ArgCount, ArgType, BUILTINS, Builtin, offset_for_builtin};
-use moor_db::db_counters;
+use moor_db::{db_counters, relation_stats};
 use moor_var::VarType::TYPE_STR;
-use moor_var::{E_ARGS, E_INVARG, E_INVIND, E_PERM, E_QUOTA, E_TYPE, Error, Symbol, v_list_iter};
+use moor_var::{
+    E_ARGS, E_INVARG, E_INVIND, E_NACC, E_PERM, E_QUOTA, E_TYPE, Error, List, Obj, Symbol,
+    v_list_iter,
+};
 use moor_var::{Sequence, v_map};
 use moor_var::{Var, v_float, v_int, v_list, v_none, v_obj, v_str, v_string};
 use moor_var::{Variant, v_sym};
@@ -102,6 +109,99 @@ fn bf_notify(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_int(1)))
 }
 
+/// notify_confirmed(player, message [, content_type])
+/// Like `notify`, but sends the message right away instead of when the task commits, and returns
+/// whether it was actually delivered to one of the player's connections: 1 if so, 0 if the player
+/// isn't connected or the write failed.
+fn bf_notify_confirmed(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() < 2 || bf_args.args.len() > 3 {
+        return Err(ErrValue(
+            E_ARGS.msg("notify_confirmed() requires 2 or 3 arguments"),
+        ));
+    }
+    if !bf_args.config.rich_notify {
+        if bf_args.args.len() != 2 {
+            return Err(ErrValue(
+                E_ARGS.msg("notify_confirmed() requires 2 arguments"),
+            ));
+        }
+        if bf_args.args[1].type_code() != TYPE_STR {
+            return Err(ErrValue(E_TYPE.msg(
+                "notify_confirmed() requires a string as the second argument",
+            )));
+        }
+    }
+
+    let Some(player) = bf_args.args[0].as_object() else {
+        return Err(ErrValue(E_TYPE.msg(
+            "notify_confirmed() requires an object as the first argument",
+        )));
+    };
+
+    let task_perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    task_perms
+        .check_obj_owner_perms(&player)
+        .map_err(world_state_bf_err)?;
+
+    let content_type = if bf_args.args.len() == 3 {
+        Some(bf_args.args[2].as_symbol().map_err(ErrValue)?)
+    } else {
+        None
+    };
+    let event = NarrativeEvent::notify(
+        bf_args.exec_state.this(),
+        bf_args.args[1].clone(),
+        content_type,
+    );
+    let delivered = bf_args
+        .session
+        .send_event_confirmed(player, Box::new(event))
+        .map_err(|_| {
+            ErrValue(E_INVARG.msg("notify_confirmed() could not reach the player's connections"))
+        })?;
+
+    Ok(Ret(bf_args.v_bool(delivered)))
+}
+
+/// send_binary(conn, content_type, bytes)
+/// Sends binary content, e.g. an image, to the player's connections, for clients which can take it
+/// out of band. Web clients get it as a binary frame, telnet clients which have turned on the
+/// `oob-binary` connection option get it base64 encoded in an out-of-band line, and others a
+/// notice of what they were sent.
+fn bf_send_binary(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 3 {
+        return Err(ErrValue(E_ARGS.msg("send_binary() requires 3 arguments")));
+    }
+
+    let Some(player) = bf_args.args[0].as_object() else {
+        return Err(ErrValue(
+            E_TYPE.msg("send_binary() requires an object as the first argument"),
+        ));
+    };
+    let content_type = bf_args.args[1].as_symbol().map_err(ErrValue)?;
+    let bytes = match bf_args.args[2].variant() {
+        Variant::Binary(b) => b.as_bytes().to_vec(),
+        Variant::Str(s) => s.as_str().as_bytes().to_vec(),
+        _ => {
+            return Err(ErrValue(
+                E_TYPE.msg("send_binary() requires binary or string content"),
+            ));
+        }
+    };
+
+    let task_perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    task_perms
+        .check_obj_owner_perms(&player)
+        .map_err(world_state_bf_err)?;
+
+    let event = NarrativeEvent::binary(bf_args.exec_state.this(), content_type, bytes);
+    bf_args
+        .task_scheduler_client
+        .notify(player, Box::new(event));
+
+    Ok(RetNil)
+}
+
 /// presentation(player, id : string, [content_type : string, target : string, content: string, [ attributes : list / map]])
 /// Emits a presentation event to the client. The client should interpret this as a request to present
 /// the content provided as a pop-up, panel, or other client-specific UI element (depending on 'target')
@@ -297,6 +397,72 @@ fn bf_connected_players(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_list_iter(map)))
 }
 
+/*
+Syntax:  player_info ([include-all])   => list
+
+Returns `{player, idle_seconds, connected_seconds, connection_name}` for each connected player, as
+`connected_players()`, `idle_seconds()`, `connected_seconds()` and `connection_name()` would, but
+in one call. The connection name is only given to wizards and to the player themselves; otherwise
+it is "".
+ */
+fn bf_player_info(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() > 1 {
+        return Err(ErrValue(
+            E_ARGS.msg("player_info() takes at most 1 argument"),
+        ));
+    }
+    let include_all = if bf_args.args.len() == 1 {
+        let Some(include_all) = bf_args.args[0].as_integer() else {
+            return Err(ErrValue(
+                E_TYPE.msg("player_info() requires an integer as the first argument"),
+            ));
+        };
+        include_all == 1
+    } else {
+        false
+    };
+
+    let is_wizard = bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_is_wizard()
+        .map_err(world_state_bf_err)?;
+    let caller = bf_args.caller_perms();
+
+    let connected_player_set = bf_args
+        .session
+        .connected_players()
+        .expect("Connected players should always be available");
+    let mut info = Vec::with_capacity(connected_player_set.len());
+    for player in connected_player_set {
+        if player.id().0 < 0 && !include_all {
+            continue;
+        }
+        // A player who disconnects while we're looking is left out.
+        let (Ok(idle_seconds), Ok(connected_seconds)) = (
+            bf_args.session.idle_seconds(player),
+            bf_args.session.connected_seconds(player),
+        ) else {
+            continue;
+        };
+        let connection_name = if is_wizard || caller == player {
+            let Ok(connection_name) = bf_args.session.connection_name(player) else {
+                continue;
+            };
+            connection_name
+        } else {
+            String::new()
+        };
+        info.push(v_list(&[
+            v_obj(player),
+            v_int(idle_seconds as i64),
+            v_int(connected_seconds as i64),
+            v_string(connection_name),
+        ]));
+    }
+    Ok(Ret(v_list(&info)))
+}
+
 fn bf_is_player(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 1 {
         return Err(ErrValue(E_ARGS.msg("is_player() requires 1 argument")));
@@ -329,6 +495,9 @@ fn bf_caller_perms(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_obj(bf_args.caller_perms())))
 }
 
+/// The tracing target under which `set_task_perms` calls are logged, when auditing is enabled.
+pub const TASK_PERMS_AUDIT_TARGET: &str = "moor::audit::task_perms";
+
 fn bf_set_task_perms(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 1 {
         return Err(ErrValue(E_ARGS.msg("set_task_perms() requires 1 argument")));
@@ -346,11 +515,51 @@ fn bf_set_task_perms(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
             "set_task_perms() requires the caller to be a wizard or the caller itself",
         )));
     }
+    if bf_args.config.audit_task_perms {
+        let activation = bf_args.exec_state.top();
+        info!(
+            target: TASK_PERMS_AUDIT_TARGET,
+            task_id = bf_args.exec_state.task_id,
+            caller = %perms.who,
+            perms = %perms_for,
+            player = %activation.player,
+            this = ?activation.this,
+            verb = %activation.verb_name,
+            definer = %activation.verb_definer(),
+            line = activation.frame.find_line_no().unwrap_or(0),
+            "set_task_perms"
+        );
+    }
     bf_args.exec_state.set_task_perms(perms_for);
 
     Ok(RetNil)
 }
 
+/// The longest name a task may give itself.
+const MAX_TASK_NAME_LEN: usize = 256;
+
+/// Function: none set_task_name(str name)
+/// Name the current task, for telling it apart in `queued_tasks()` and the server's logs. An
+/// empty name clears it.
+fn bf_set_task_name(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(ErrValue(E_ARGS.msg("set_task_name() requires 1 argument")));
+    }
+    let Some(name) = bf_args.args[0].as_string() else {
+        return Err(ErrValue(
+            E_TYPE.msg("set_task_name() requires a string as the first argument"),
+        ));
+    };
+    if name.len() > MAX_TASK_NAME_LEN {
+        return Err(ErrValue(E_INVARG.msg(format!(
+            "set_task_name() name must be at most {MAX_TASK_NAME_LEN} bytes"
+        ))));
+    }
+    bf_args.exec_state.task_name = (!name.is_empty()).then(|| name.to_string());
+
+    Ok(RetNil)
+}
+
 fn bf_callers(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if !bf_args.args.is_empty() {
         return Err(ErrValue(
@@ -379,6 +588,88 @@ fn bf_callers(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     }))))
 }
 
+/*
+Function: list task_stack(int task-id [, include-line-numbers])
+Returns the activation stack of the suspended task <task-id>, top first, with each frame described
+as by callers(): {this, verb-name, programmer, verb-loc, player}, plus the line number if
+<include-line-numbers> is true. Raises E_INVARG if there is no such suspended task, and E_PERM
+unless the caller is a wizard or owns the task.
+ */
+fn bf_task_stack(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.is_empty() || bf_args.args.len() > 2 {
+        return Err(ErrValue(
+            E_ARGS.msg("task_stack() requires 1 or 2 arguments"),
+        ));
+    }
+    let Some(task_id) = bf_args.args[0].as_integer() else {
+        return Err(ErrValue(
+            E_TYPE.msg("task_stack() requires an integer as the first argument"),
+        ));
+    };
+    let include_line_numbers = bf_args.args.len() == 2 && bf_args.args[1].is_true();
+
+    let Some((owner, frames)) = bf_args.task_scheduler_client.task_stack(task_id as TaskId) else {
+        return Err(ErrValue(E_INVARG.with_msg(|| {
+            format!("task_stack() found no suspended task {task_id}")
+        })));
+    };
+    let perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    if perms.who != owner && !perms.check_is_wizard().map_err(world_state_bf_err)? {
+        return Err(ErrValue(E_PERM.msg(
+            "task_stack() requires wizard permissions or ownership of the task",
+        )));
+    }
+
+    Ok(Ret(v_list_iter(frames.iter().map(|c| {
+        let mut frame = vec![
+            c.this.clone(),
+            v_string(c.verb_name.to_string()),
+            v_obj(c.programmer),
+            v_obj(c.definer),
+            v_obj(c.player),
+        ];
+        if include_line_numbers {
+            frame.push(v_int(c.line_number as i64));
+        }
+        v_list(&frame)
+    }))))
+}
+
+/// Returns a map of the calling verb's variables which currently have a value, by name.
+fn bf_locals(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if !bf_args.args.is_empty() {
+        return Err(ErrValue(E_ARGS.msg("locals() does not take any arguments")));
+    }
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_programmer()
+        .map_err(world_state_bf_err)?;
+
+    // The nearest MOO frame under our own builtin frame is the verb that called us.
+    let Some(frame) =
+        bf_args
+            .exec_state
+            .stack
+            .iter()
+            .rev()
+            .find_map(|activation| match &activation.frame {
+                Frame::Moo(frame) => Some(frame),
+                Frame::Bf(_) => None,
+            })
+    else {
+        return Err(ErrValue(
+            E_INVARG.msg("locals() must be called from a verb"),
+        ));
+    };
+    let locals: Vec<_> = frame
+        .locals()
+        .into_iter()
+        .map(|(name, value)| (v_str(name.as_str()), value))
+        .collect();
+    Ok(Ret(v_map(&locals)))
+}
+
 fn bf_task_id(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if !bf_args.args.is_empty() {
         return Err(ErrValue(
@@ -469,6 +760,137 @@ fn bf_connection_name(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_string(connection_name)))
 }
 
+/// Connection options which can be set with `set_connection_option`.
+const CONNECTION_OPTIONS: &[&str] = &["echo", "char-mode", "oob-binary"];
+
+fn bf_set_connection_option(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    // Syntax:  set_connection_option(<conn>, <option>, <value>)   => none
+    //
+    // Sets the given option on the connection. With "echo" false the client is asked to stop
+    // echoing its own input (e.g. for password entry). With "char-mode" true, input is sent a
+    // character at a time as it's typed, each one arriving as a line of its own (e.g. for
+    // single-keypress menus).
+    if bf_args.args.len() != 3 {
+        return Err(ErrValue(
+            E_ARGS.msg("set_connection_option() requires 3 arguments"),
+        ));
+    }
+
+    let Some(player) = bf_args.args[0].as_object() else {
+        return Err(ErrValue(E_TYPE.msg(
+            "set_connection_option() requires an object as the first argument",
+        )));
+    };
+
+    let Some(option) = bf_args.args[1].as_string() else {
+        return Err(ErrValue(E_TYPE.msg(
+            "set_connection_option() requires a string as the second argument",
+        )));
+    };
+    let option = option.to_lowercase();
+    if !CONNECTION_OPTIONS.contains(&option.as_str()) {
+        return Err(ErrValue(
+            E_INVARG.msg(format!("unknown connection option: {option}")),
+        ));
+    }
+
+    let task_perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    if task_perms.who != player && !task_perms.check_is_wizard().map_err(world_state_bf_err)? {
+        return Err(ErrValue(E_PERM.msg(
+            "set_connection_option() requires the caller to be a wizard or the connection's player",
+        )));
+    }
+
+    // Options are normalized to booleans before being handed to the host.
+    let value = v_int(bf_args.args[2].is_true() as i64);
+    if bf_args
+        .session
+        .set_connection_option(player, Symbol::mk(&option), value)
+        .is_err()
+    {
+        return Err(ErrValue(E_INVARG.msg(
+            "set_connection_option() requires a connected player as the first argument",
+        )));
+    }
+
+    Ok(RetNil)
+}
+
+/// Check the caller may read `player`'s connection options, and fetch them.
+fn connection_options(
+    bf_args: &mut BfCallState<'_>,
+    bf_name: &str,
+    player: Obj,
+) -> Result<Vec<(Symbol, Var)>, BfErr> {
+    let task_perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    if task_perms.who != player && !task_perms.check_is_wizard().map_err(world_state_bf_err)? {
+        return Err(ErrValue(E_PERM.with_msg(|| {
+            format!("{bf_name}() requires the caller to be a wizard or the connection's player")
+        })));
+    }
+    bf_args.session.connection_options(player).map_err(|_| {
+        ErrValue(
+            E_INVARG.with_msg(|| {
+                format!("{bf_name}() requires a connected player as the first argument")
+            }),
+        )
+    })
+}
+
+fn bf_connection_options(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    // Syntax:  connection_options(<conn>)   => list
+    //
+    // Returns a list of {name, value} pairs for the options of the connection: those set with
+    // set_connection_option(), and those the connection reports itself, such as the client's
+    // "window-width" and "window-height" (80 and 24 for telnet clients which don't say).
+    if bf_args.args.len() != 1 {
+        return Err(ErrValue(
+            E_ARGS.msg("connection_options() requires 1 argument"),
+        ));
+    }
+    let Some(player) = bf_args.args[0].as_object() else {
+        return Err(ErrValue(E_TYPE.msg(
+            "connection_options() requires an object as the first argument",
+        )));
+    };
+    let options = connection_options(bf_args, "connection_options", player)?;
+    let options: Vec<_> = options
+        .into_iter()
+        .map(|(name, value)| v_list(&[v_str(name.as_str()), value]))
+        .collect();
+    Ok(Ret(v_list(&options)))
+}
+
+fn bf_connection_option(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    // Syntax:  connection_option(<conn>, <name>)   => value
+    //
+    // Returns the value of the named option on the connection, as connection_options() would
+    // list it. Raises E_INVARG if the connection has no such option.
+    if bf_args.args.len() != 2 {
+        return Err(ErrValue(
+            E_ARGS.msg("connection_option() requires 2 arguments"),
+        ));
+    }
+    let Some(player) = bf_args.args[0].as_object() else {
+        return Err(ErrValue(E_TYPE.msg(
+            "connection_option() requires an object as the first argument",
+        )));
+    };
+    let Some(name) = bf_args.args[1].as_string() else {
+        return Err(ErrValue(E_TYPE.msg(
+            "connection_option() requires a string as the second argument",
+        )));
+    };
+    let name = Symbol::mk(name);
+    let options = connection_options(bf_args, "connection_option", player)?;
+    let Some((_, value)) = options.into_iter().find(|(option, _)| *option == name) else {
+        return Err(ErrValue(
+            E_INVARG.msg(format!("unknown connection option: {name}")),
+        ));
+    };
+    Ok(Ret(value))
+}
+
 fn bf_shutdown(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() > 1 {
         return Err(ErrValue(E_ARGS.msg("shutdown() requires 0 or 1 arguments")));
@@ -740,7 +1162,9 @@ fn bf_queued_tasks(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
 
     // return in form:
     //     {<task-id>, <start-time>, <x>, <y>,
-    //      <programmer>, <verb-loc>, <verb-name>, <line>, <this>}
+    //      <programmer>, <verb-loc>, <verb-name>, <line>, <this>, <task-size>, <name>}
+    // Task sizes aren't measured, so are always 0; the slot is kept so that the name doesn't
+    // take the place LambdaMOO cores expect the size in.
     let tasks = tasks.iter().map(|task| {
         let task_id = v_int(task.task_id as i64);
         let start_time = match task.start_time {
@@ -757,8 +1181,10 @@ fn bf_queued_tasks(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         let verb_name = v_str(task.verb_name.as_str());
         let line = v_int(task.line_number as i64);
         let this = task.this.clone();
+        let size = v_int(0);
+        let name = v_str(task.name.as_deref().unwrap_or(""));
         v_list(&[
-            task_id, start_time, x, y, programmer, verb_loc, verb_name, line, this,
+            task_id, start_time, x, y, programmer, verb_loc, verb_name, line, this, size, name,
         ])
     });
 
@@ -1009,6 +1435,69 @@ fn bf_resume(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(result))
 }
 
+/// The object argument to lock_object()/unlock_object(), which must be valid, and the caller a
+/// programmer.
+fn lock_target(bf_args: &mut BfCallState<'_>, name: &str) -> Result<Obj, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(ErrValue(
+            E_ARGS.with_msg(|| format!("{name}() requires 1 argument")),
+        ));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(ErrValue(E_TYPE.with_msg(|| {
+            format!("{name}() requires an object as the first argument")
+        })));
+    };
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_programmer()
+        .map_err(world_state_bf_err)?;
+    if !bf_args
+        .world_state
+        .valid(&obj)
+        .map_err(world_state_bf_err)?
+    {
+        return Err(ErrValue(
+            E_INVARG.with_msg(|| format!("{name}() requires a valid object")),
+        ));
+    }
+    Ok(obj)
+}
+
+/*
+Syntax:  lock_object (obj <object>)   => none
+
+Takes an advisory lock on <object> for the current task, so that editors can coordinate changes to
+it. The lock is held until released with `unlock_object()`, or the task finishes; suspending does
+not release it. Raises E_NACC if another task holds the lock. Locks are not enforced: nothing stops
+a task which doesn't ask for the lock from modifying the object.
+ */
+fn bf_lock_object(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    let obj = lock_target(bf_args, "lock_object")?;
+    if !bf_args.task_scheduler_client.lock_object(obj) {
+        return Err(ErrValue(
+            E_NACC.with_msg(|| format!("{obj} is locked by another task")),
+        ));
+    }
+    Ok(Ret(v_none()))
+}
+
+/*
+Syntax:  unlock_object (obj <object>)   => none
+
+Releases the current task's lock on <object>. Raises E_INVARG if the task doesn't hold it.
+ */
+fn bf_unlock_object(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    let obj = lock_target(bf_args, "unlock_object")?;
+    if !bf_args.task_scheduler_client.unlock_object(obj) {
+        return Err(ErrValue(
+            E_INVARG.with_msg(|| format!("{obj} is not locked by this task")),
+        ));
+    }
+    Ok(Ret(v_none()))
+}
+
 fn bf_ticks_left(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     // Syntax:  ticks_left()   => int
     //
@@ -1234,8 +1723,8 @@ fn bf_listen(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         .check_wizard()
         .map_err(world_state_bf_err)?;
 
-    if bf_args.args.len() < 2 || bf_args.args.len() > 4 {
-        return Err(ErrValue(E_ARGS.msg("listen() requires 2 to 4 arguments")));
+    if bf_args.args.len() < 2 || bf_args.args.len() > 5 {
+        return Err(ErrValue(E_ARGS.msg("listen() requires 2 to 5 arguments")));
     }
 
     let Some(object) = bf_args.args[0].as_object() else {
@@ -1270,7 +1759,7 @@ fn bf_listen(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         false
     };
 
-    let host_type = if bf_args.args.len() == 4 {
+    let host_type = if bf_args.args.len() >= 4 {
         let Some(host_type) = bf_args.args[3].as_string() else {
             return Err(ErrValue(
                 E_TYPE.msg("listen() requires a string as the fourth argument"),
@@ -1281,11 +1770,14 @@ fn bf_listen(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         "tcp".to_string()
     };
 
+    // TLS listeners use the certificate and key from $server_options.
+    let tls = bf_args.args.len() == 5 && bf_args.args[4].is_true();
+
     // Ask the scheduler to broadcast a listen request out to all the hosts.
     if let Some(error) =
         bf_args
             .task_scheduler_client
-            .listen(object, host_type, port, print_messages)
+            .listen(object, host_type, port, print_messages, tls)
     {
         return Err(ErrValue(error));
     }
@@ -1368,14 +1860,25 @@ fn bf_unlisten(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
 pub const BF_SERVER_EVAL_TRAMPOLINE_START_INITIALIZE: usize = 0;
 pub const BF_SERVER_EVAL_TRAMPOLINE_RESUME: usize = 1;
 
+/// The ticks and seconds code run by `eval()` gets, unless a wizard asks for something else.
+const DEFAULT_EVAL_TICKS: usize = 30_000;
+const DEFAULT_EVAL_SECONDS: f64 = 3.0;
+
+/*
+Function: list eval (str code [, int ticks [, num seconds]])
+Compiles and runs `code`, returning {1, value} or, if it doesn't compile, {0, errors}. The code
+gets its own budget of ticks and seconds, 30000 and 3 unless given (which only wizards may do),
+though never more than the caller has left. If it runs out, it is abandoned, and eval() raises
+E_QUOTA for the caller to handle, rather than the whole task being aborted.
+ */
 fn bf_eval(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     bf_args
         .task_perms()
         .map_err(world_state_bf_err)?
         .check_programmer()
         .map_err(world_state_bf_err)?;
-    if bf_args.args.len() != 1 {
-        return Err(ErrValue(E_ARGS.msg("bf_eval() requires 1 argument")));
+    if bf_args.args.is_empty() || bf_args.args.len() > 3 {
+        return Err(ErrValue(E_ARGS.msg("bf_eval() requires 1 to 3 arguments")));
     }
     let Some(program_code) = bf_args.args[0].as_string() else {
         return Err(ErrValue(
@@ -1394,10 +1897,23 @@ fn bf_eval(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
 
     match tramp {
         BF_SERVER_EVAL_TRAMPOLINE_START_INITIALIZE => {
+            let (max_ticks, max_time) = eval_budget(bf_args)?;
             let program = match compile(&program_code_string, bf_args.config.compile_options()) {
                 Ok(program) => program,
-                Err(e) => return Ok(Ret(v_list(&[v_int(0), v_string(e.to_string())]))),
+                Err(e) => {
+                    let error_lines = e.to_string().lines().map(v_str).collect::<Vec<_>>();
+                    return Ok(Ret(v_list(&[v_int(0), v_list(&error_lines)])));
+                }
             };
+            let depth = bf_args.exec_state.stack.len() - 1;
+            let start_ticks = bf_args.exec_state.tick_count;
+            bf_args.exec_state.eval_budgets.push(EvalBudget {
+                depth,
+                max_ticks,
+                max_time,
+                start_ticks,
+                start_time: SystemTime::now(),
+            });
             let bf_frame = bf_args.bf_frame_mut();
             bf_frame.bf_trampoline = Some(BF_SERVER_EVAL_TRAMPOLINE_RESUME);
             // Now we have to construct things to set up for eval. Which means tramping through with a
@@ -1409,6 +1925,7 @@ fn bf_eval(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
             }))
         }
         BF_SERVER_EVAL_TRAMPOLINE_RESUME => {
+            bf_args.exec_state.eval_budgets.pop();
             // Value must be on in our activation's "return value"
             let value = bf_args.exec_state.top().frame.return_value();
             Ok(Ret(v_list(&[bf_args.v_bool(true), value])))
@@ -1419,6 +1936,153 @@ fn bf_eval(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     }
 }
 
+/// The ticks and time `eval()` is to give the code it runs: the defaults, or for a wizard,
+/// whatever they asked for.
+fn eval_budget(bf_args: &BfCallState<'_>) -> Result<(usize, Duration), BfErr> {
+    if bf_args.args.len() == 1 {
+        return Ok((
+            DEFAULT_EVAL_TICKS,
+            Duration::from_secs_f64(DEFAULT_EVAL_SECONDS),
+        ));
+    }
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+    let ticks = match bf_args.args[1].variant() {
+        Variant::Int(ticks) if *ticks > 0 => *ticks as usize,
+        Variant::Int(_) => {
+            return Err(ErrValue(E_INVARG.msg("eval() ticks must be positive")));
+        }
+        _ => return Err(ErrValue(E_TYPE.msg("eval() ticks must be an integer"))),
+    };
+    let seconds = if bf_args.args.len() > 2 {
+        match bf_args.args[2].variant() {
+            Variant::Int(seconds) => *seconds as f64,
+            Variant::Float(seconds) => *seconds,
+            _ => return Err(ErrValue(E_TYPE.msg("eval() seconds must be a number"))),
+        }
+    } else {
+        DEFAULT_EVAL_SECONDS
+    };
+    if !seconds.is_finite() || seconds <= 0.0 {
+        return Err(ErrValue(E_INVARG.msg("eval() seconds must be positive")));
+    }
+    let seconds = Duration::try_from_secs_f64(seconds)
+        .map_err(|_| ErrValue(E_INVARG.msg("eval() seconds is too large")))?;
+    Ok((ticks, seconds))
+}
+
+pub const BF_SERVER_PROFILE_TRAMPOLINE_START: usize = 0;
+pub const BF_SERVER_PROFILE_TRAMPOLINE_RESUME: usize = 1;
+
+fn bf_profile(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    // Syntax:  profile(<code>)   => list
+    //          profile(<object>, <verb> [, <args>])   => list
+    //
+    // Runs the given code, or calls the given verb, and returns {ticks, seconds, value}: the
+    // ticks and wall-clock seconds it took, and what it returned. The ticks it uses are charged
+    // to the caller, as for any other call.
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_programmer()
+        .map_err(world_state_bf_err)?;
+
+    let tramp = bf_args
+        .bf_frame_mut()
+        .bf_trampoline
+        .take()
+        .unwrap_or(BF_SERVER_PROFILE_TRAMPOLINE_START);
+
+    match tramp {
+        BF_SERVER_PROFILE_TRAMPOLINE_START => {
+            let args: Vec<_> = bf_args.args.iter().collect();
+            let dispatch = match &args[..] {
+                [code] => {
+                    let Some(code) = code.as_string() else {
+                        return Err(ErrValue(E_TYPE.msg("profile() code must be a string")));
+                    };
+                    let program = compile(code, bf_args.config.compile_options())
+                        .map_err(|e| ErrValue(E_INVARG.with_msg(|| e.to_string())))?;
+                    ExecutionResult::DispatchEval {
+                        permissions: bf_args.task_perms_who(),
+                        player: bf_args.exec_state.top().player,
+                        program,
+                    }
+                }
+                [object, verb, rest @ ..] if rest.len() <= 1 => {
+                    let (Some(object), Some(verb)) = (object.as_object(), verb.as_string()) else {
+                        return Err(ErrValue(
+                            E_TYPE.msg("profile() takes an object and a verb name"),
+                        ));
+                    };
+                    let args = match rest.first() {
+                        None => List::mk_list(&[]),
+                        Some(args) => args.as_list().cloned().ok_or_else(|| {
+                            ErrValue(E_TYPE.msg("profile() verb arguments must be a list"))
+                        })?,
+                    };
+                    let verb = Symbol::mk(verb);
+                    let (program, resolved_verb) = bf_args
+                        .world_state
+                        .find_method_verb_on(&bf_args.task_perms_who(), &object, verb)
+                        .map_err(world_state_bf_err)?;
+                    ExecutionResult::DispatchVerb(Box::new(VerbExecutionRequest {
+                        permissions: bf_args.task_perms_who(),
+                        resolved_verb,
+                        program,
+                        call: Box::new(VerbCall {
+                            verb_name: verb,
+                            location: v_obj(object),
+                            this: v_obj(object),
+                            player: bf_args.exec_state.top().player,
+                            args,
+                            argstr: "".to_string(),
+                            caller: bf_args.exec_state.top().this.clone(),
+                        }),
+                        command: None,
+                    }))
+                }
+                _ => return Err(ErrValue(E_ARGS.msg("profile() takes 1 to 3 arguments"))),
+            };
+            let started = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs_f64();
+            let bf_frame = bf_args.bf_frame_mut();
+            bf_frame.bf_trampoline = Some(BF_SERVER_PROFILE_TRAMPOLINE_RESUME);
+            bf_frame.bf_trampoline_arg = Some(v_list(&[
+                v_int(bf_args.exec_state.tick_count as i64),
+                v_float(started),
+            ]));
+            Ok(VmInstr(dispatch))
+        }
+        BF_SERVER_PROFILE_TRAMPOLINE_RESUME => {
+            let value = bf_args.exec_state.top().frame.return_value();
+            let start = bf_args.bf_frame().bf_trampoline_arg.clone();
+            let Some((start_ticks, started)) = start
+                .as_ref()
+                .and_then(|start| start.as_list())
+                .and_then(|start| Some((start[0].as_integer()?, start[1].as_float()?)))
+            else {
+                panic!("Invalid trampoline argument for bf_profile");
+            };
+            let seconds = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs_f64()
+                - started;
+            let ticks = bf_args.exec_state.tick_count as i64 - start_ticks;
+            Ok(Ret(v_list(&[v_int(ticks), v_float(seconds), value])))
+        }
+        _ => {
+            panic!("Invalid trampoline value for bf_profile: {}", tramp);
+        }
+    }
+}
+
 fn bf_dump_database(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     bf_args
         .task_perms()
@@ -1508,13 +2172,125 @@ fn db_disk_size(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_int(disk_size as i64)))
 }
 
+/*
+Syntax:  compact_database ()   => list
+
+Compacts the database's storage, reclaiming the space taken up by overwritten values and by
+recycled objects, and returns {<bytes before>, <bytes after>}, as db_disk_size() would report them.
+This can take a while on a large database, during which the calling task waits but others carry on.
+Wizard only.
+ */
+fn bf_compact_database(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if !bf_args.args.is_empty() {
+        return Err(ErrValue(
+            E_ARGS.msg("compact_database() does not take any arguments"),
+        ));
+    }
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+
+    let (before, after) = bf_args.world_state.compact().map_err(world_state_bf_err)?;
+    Ok(Ret(v_list(&[v_int(before as i64), v_int(after as i64)])))
+}
+
+/*
+Syntax:  backup (str <name>)   => int
+
+Writes a point-in-time copy of the whole database, as of the last commit, to the file <name> in
+the server's backup directory (set with `--backup-directory`), returning the number of tuples
+written. Named sequences are included; the calling task's own uncommitted changes aren't. Other
+tasks, and their commits, carry on while it is written. <name> must be a plain file name, and E_INVARG
+is raised if it isn't, or no backup directory is configured. The backup can be loaded into a new
+database by starting the server with `--restore`. Wizard only.
+ */
+fn bf_backup(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(ErrValue(E_ARGS.msg("backup() requires 1 argument")));
+    }
+    let Some(name) = bf_args.args[0].as_string() else {
+        return Err(ErrValue(
+            E_TYPE.msg("backup() requires a string as the first argument"),
+        ));
+    };
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+
+    let tuples = bf_args
+        .world_state
+        .backup(name)
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_int(tuples as i64)))
+}
+
+/// The sequence name argument to next_sequence()/current_sequence(), which must be a non-empty
+/// string, and the caller a programmer.
+fn sequence_name(bf_args: &mut BfCallState<'_>, bf_name: &str) -> Result<String, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(ErrValue(
+            E_ARGS.with_msg(|| format!("{bf_name}() requires 1 argument")),
+        ));
+    }
+    let Some(name) = bf_args.args[0].as_string() else {
+        return Err(ErrValue(E_TYPE.with_msg(|| {
+            format!("{bf_name}() requires a string as the first argument")
+        })));
+    };
+    if name.is_empty() {
+        return Err(ErrValue(E_INVARG.with_msg(|| {
+            format!("{bf_name}() requires a non-empty sequence name")
+        })));
+    }
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_programmer()
+        .map_err(world_state_bf_err)?;
+    Ok(name.to_string())
+}
+
+/*
+Syntax:  next_sequence (str <name>)   => int
+
+Increments the persistent counter called <name>, creating it if it doesn't exist yet, and returns
+the new value; the first value a sequence hands out is 1. Sequences are not rolled back with the
+task's transaction, so a value is never handed out twice, e.g. for generating unique IDs.
+ */
+fn bf_next_sequence(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    let name = sequence_name(bf_args, "next_sequence")?;
+    let value = bf_args
+        .world_state
+        .next_sequence(&name)
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_int(value)))
+}
+
+/*
+Syntax:  current_sequence (str <name>)   => int
+
+Returns the last value handed out by the sequence called <name>, or 0 if it has never been used.
+ */
+fn bf_current_sequence(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    let name = sequence_name(bf_args, "current_sequence")?;
+    let value = bf_args
+        .world_state
+        .current_sequence(&name)
+        .map_err(world_state_bf_err)?;
+    Ok(Ret(v_int(value)))
+}
+
 /* Function: none load_server_options ()
 
    This causes the server to consult the current common of properties on $server_options, updating
    the corresponding server option settings (see section Server Options Set in the Database)
    accordingly. If the programmer is not a wizard, then E_PERM is raised.
 */
-fn load_server_options(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+fn bf_load_server_options(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if !bf_args.args.is_empty() {
         return Err(ErrValue(
             E_ARGS.msg("load_server_options() does not take any arguments"),
@@ -1527,7 +2303,13 @@ fn load_server_options(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         .check_wizard()
         .map_err(world_state_bf_err)?;
 
-    bf_args.task_scheduler_client.refresh_server_options();
+    // Read the options through the task's own transaction, so that changes made to
+    // $server_options earlier in the same task are seen. The scheduler holds on to them until
+    // the task commits, so options from a transaction that's rolled back never take effect.
+    let server_options = load_server_options(bf_args.world_state);
+    bf_args
+        .task_scheduler_client
+        .refresh_server_options(server_options);
 
     Ok(RetNil)
 }
@@ -1580,6 +2362,36 @@ fn bf_db_counters(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     )))
 }
 
+/// Returns a map of database relation name to `{reads, writes, conflicts}`, counted since the
+/// server started.
+fn bf_relation_stats(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    bf_args
+        .task_perms()
+        .map_err(world_state_bf_err)?
+        .check_wizard()
+        .map_err(world_state_bf_err)?;
+
+    let use_symbols = bf_args.config.use_symbols_in_builtins && bf_args.config.symbol_type;
+    let mut result = vec![];
+    for (name, stats) in relation_stats() {
+        let name = if use_symbols {
+            v_sym(name)
+        } else {
+            v_str(name.as_str())
+        };
+        result.push((
+            name,
+            v_list(&[
+                v_int(stats.reads.sum() as i64),
+                v_int(stats.writes.sum() as i64),
+                v_int(stats.conflicts.sum() as i64),
+            ]),
+        ));
+    }
+
+    Ok(Ret(v_map(&result)))
+}
+
 fn bf_sched_counters(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     bf_args
         .task_perms()
@@ -1697,15 +2509,24 @@ fn bf_worker_request(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
 
 pub(crate) fn register_bf_server(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("notify")] = Box::new(bf_notify);
+    builtins[offset_for_builtin("notify_confirmed")] = Box::new(bf_notify_confirmed);
+    builtins[offset_for_builtin("send_binary")] = Box::new(bf_send_binary);
     builtins[offset_for_builtin("connected_players")] = Box::new(bf_connected_players);
+    builtins[offset_for_builtin("player_info")] = Box::new(bf_player_info);
     builtins[offset_for_builtin("is_player")] = Box::new(bf_is_player);
     builtins[offset_for_builtin("caller_perms")] = Box::new(bf_caller_perms);
     builtins[offset_for_builtin("set_task_perms")] = Box::new(bf_set_task_perms);
+    builtins[offset_for_builtin("set_task_name")] = Box::new(bf_set_task_name);
     builtins[offset_for_builtin("callers")] = Box::new(bf_callers);
+    builtins[offset_for_builtin("task_stack")] = Box::new(bf_task_stack);
     builtins[offset_for_builtin("task_id")] = Box::new(bf_task_id);
+    builtins[offset_for_builtin("locals")] = Box::new(bf_locals);
     builtins[offset_for_builtin("idle_seconds")] = Box::new(bf_idle_seconds);
     builtins[offset_for_builtin("connected_seconds")] = Box::new(bf_connected_seconds);
     builtins[offset_for_builtin("connection_name")] = Box::new(bf_connection_name);
+    builtins[offset_for_builtin("set_connection_option")] = Box::new(bf_set_connection_option);
+    builtins[offset_for_builtin("connection_option")] = Box::new(bf_connection_option);
+    builtins[offset_for_builtin("connection_options")] = Box::new(bf_connection_options);
     builtins[offset_for_builtin("time")] = Box::new(bf_time);
     builtins[offset_for_builtin("ftime")] = Box::new(bf_ftime);
     builtins[offset_for_builtin("ctime")] = Box::new(bf_ctime);
@@ -1718,6 +2539,8 @@ pub(crate) fn register_bf_server(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("queue_info")] = Box::new(bf_queue_info);
     builtins[offset_for_builtin("kill_task")] = Box::new(bf_kill_task);
     builtins[offset_for_builtin("resume")] = Box::new(bf_resume);
+    builtins[offset_for_builtin("lock_object")] = Box::new(bf_lock_object);
+    builtins[offset_for_builtin("unlock_object")] = Box::new(bf_unlock_object);
     builtins[offset_for_builtin("ticks_left")] = Box::new(bf_ticks_left);
     builtins[offset_for_builtin("seconds_left")] = Box::new(bf_seconds_left);
     builtins[offset_for_builtin("boot_player")] = Box::new(bf_boot_player);
@@ -1728,14 +2551,20 @@ pub(crate) fn register_bf_server(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("listen")] = Box::new(bf_listen);
     builtins[offset_for_builtin("unlisten")] = Box::new(bf_unlisten);
     builtins[offset_for_builtin("eval")] = Box::new(bf_eval);
+    builtins[offset_for_builtin("profile")] = Box::new(bf_profile);
     builtins[offset_for_builtin("read")] = Box::new(bf_read);
     builtins[offset_for_builtin("dump_database")] = Box::new(bf_dump_database);
     builtins[offset_for_builtin("memory_usage")] = Box::new(bf_memory_usage);
     builtins[offset_for_builtin("db_disk_size")] = Box::new(db_disk_size);
-    builtins[offset_for_builtin("load_server_options")] = Box::new(load_server_options);
+    builtins[offset_for_builtin("compact_database")] = Box::new(bf_compact_database);
+    builtins[offset_for_builtin("backup")] = Box::new(bf_backup);
+    builtins[offset_for_builtin("next_sequence")] = Box::new(bf_next_sequence);
+    builtins[offset_for_builtin("current_sequence")] = Box::new(bf_current_sequence);
+    builtins[offset_for_builtin("load_server_options")] = Box::new(bf_load_server_options);
     builtins[offset_for_builtin("bf_counters")] = Box::new(bf_bf_counters);
     builtins[offset_for_builtin("db_counters")] = Box::new(bf_db_counters);
     builtins[offset_for_builtin("sched_counters")] = Box::new(bf_sched_counters);
+    builtins[offset_for_builtin("relation_stats")] = Box::new(bf_relation_stats);
     builtins[offset_for_builtin("force_input")] = Box::new(bf_force_input);
     builtins[offset_for_builtin("wait_task")] = Box::new(bf_wait_task);
     builtins[offset_for_builtin("commit")] = Box::new(bf_commit);
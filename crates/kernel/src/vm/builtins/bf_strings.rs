@@ -16,16 +16,20 @@ use argon2::{Algorithm, Argon2, Params, PasswordHasher, PasswordVerifier, Versio
 use base64::Engine;
 use base64::engine::general_purpose;
 use md5::Digest;
-use moor_compiler::offset_for_builtin;
-use moor_var::{E_ARGS, E_INVARG, E_TYPE};
-use moor_var::{Sequence, Variant};
-use moor_var::{v_int, v_map, v_str, v_string};
+use moor_common::model::{ValSet, WorldStateError};
+use moor_compiler::{offset_for_builtin, to_literal};
+use moor_var::{E_ARGS, E_INVARG, E_TYPE, Error, Var};
+use moor_var::{Sequence, Symbol, Variant};
+use moor_var::{v_binary, v_int, v_map, v_str, v_string};
 use rand::distributions::Alphanumeric;
 use rand::{Rng, thread_rng};
 use serde_json::{self, Value as JsonValue};
+use std::iter::Peekable;
+use std::str::Chars;
 use tracing::warn;
 
 use crate::vm::builtins::BfRet::Ret;
+use crate::vm::builtins::bf_values::tostr;
 use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinFunction, world_state_bf_err};
 
 fn strsub(subject: &str, what: &str, with: &str, case_matters: bool) -> String {
@@ -202,31 +206,80 @@ fn bf_crypt(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         };
         String::from(salt)
     };
-    if let Some(text) = bf_args.args[0].as_string() {
-        let crypted = pwhash::unix::crypt(text, salt.as_str()).unwrap();
-        Ok(Ret(v_string(crypted)))
-    } else {
-        Err(BfErr::Code(E_TYPE))
+    let Some(text) = bf_args.args[0].as_string() else {
+        return Err(BfErr::Code(E_TYPE));
+    };
+    // DES salts are two characters, MD5 ones "$1$<salt>", and so on; anything pwhash doesn't
+    // recognise is the caller's mistake, not ours.
+    let crypted = pwhash::unix::crypt(text, salt.as_str())
+        .map_err(|e| BfErr::ErrValue(E_INVARG.msg(format!("invalid crypt() salt: {e}"))))?;
+    Ok(Ret(v_string(crypted)))
+}
+
+/// Hash `bytes` with the named algorithm ("MD5", the default, "SHA1" or "SHA256"), returning
+/// the digest as an uppercase hex string, or as binary if `binary` is set.
+fn hash_bytes(bytes: &[u8], args: &[Var], name: &str) -> Result<BfRet, BfErr> {
+    let algorithm = match args.first() {
+        None => "MD5".to_string(),
+        Some(algorithm) => {
+            let Some(algorithm) = algorithm.as_string() else {
+                return Err(BfErr::Code(E_TYPE));
+            };
+            algorithm.to_uppercase()
+        }
+    };
+    let binary = match args.get(1) {
+        None => false,
+        Some(binary) => {
+            let Some(binary) = binary.as_integer() else {
+                return Err(BfErr::Code(E_TYPE));
+            };
+            binary != 0
+        }
+    };
+    let digest = match algorithm.as_str() {
+        "MD5" => md5::Md5::digest(bytes).to_vec(),
+        "SHA1" => sha1::Sha1::digest(bytes).to_vec(),
+        "SHA256" => sha2::Sha256::digest(bytes).to_vec(),
+        _ => {
+            return Err(BfErr::ErrValue(E_INVARG.msg(format!(
+                "{name}() does not support the {algorithm} algorithm"
+            ))));
+        }
+    };
+    if binary {
+        return Ok(Ret(v_binary(digest)));
     }
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<String>();
+    Ok(Ret(v_string(hex)))
 }
 
+/// Function: str string_hash(str text [, str algorithm [, int binary]])
 fn bf_string_hash(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    if bf_args.args.len() != 1 {
+    if bf_args.args.is_empty() || bf_args.args.len() > 3 {
         return Err(BfErr::Code(E_ARGS));
     }
-    match bf_args.args[0].variant() {
-        Variant::Str(s) => {
-            let hash_digest = md5::Md5::digest(s.as_str().as_bytes());
-            Ok(Ret(v_str(
-                format!("{:x}", hash_digest).to_uppercase().as_str(),
-            )))
-        }
-        _ => Err(BfErr::Code(E_INVARG)),
-    }
+    let Some(text) = bf_args.args[0].as_string() else {
+        return Err(BfErr::Code(E_TYPE));
+    };
+    let rest: Vec<Var> = bf_args.args.iter().skip(1).collect();
+    hash_bytes(text.as_bytes(), &rest, "string_hash")
 }
 
-fn bf_binary_hash(_bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
-    Err(BfErr::Code(E_INVARG))
+/// Function: str binary_hash(binary bytes [, str algorithm [, int binary]])
+fn bf_binary_hash(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.is_empty() || bf_args.args.len() > 3 {
+        return Err(BfErr::Code(E_ARGS));
+    }
+    let rest: Vec<Var> = bf_args.args.iter().skip(1).collect();
+    match bf_args.args[0].variant() {
+        Variant::Binary(b) => hash_bytes(b.as_bytes(), &rest, "binary_hash"),
+        Variant::Str(s) => hash_bytes(s.as_str().as_bytes(), &rest, "binary_hash"),
+        _ => Err(BfErr::Code(E_TYPE)),
+    }
 }
 
 // password (string), salt (string), iterations, memory, parallelism
@@ -378,7 +431,6 @@ fn bf_decode_base64(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         }
     };
 
-    use moor_var::v_binary;
     Ok(Ret(v_binary(decoded_bytes)))
 }
 
@@ -406,7 +458,7 @@ fn moo_value_to_json(value: &moor_var::Var) -> Result<JsonValue, BfErr> {
             Ok(JsonValue::Number(num))
         }
         Variant::Str(s) => Ok(JsonValue::String(s.as_str().to_string())),
-        Variant::Obj(o) => Ok(JsonValue::String(format!("#{}", o))),
+        Variant::Obj(o) => Ok(JsonValue::String(o.to_string())),
         Variant::List(list) => {
             let mut json_array = Vec::new();
             for item in list.iter() {
@@ -422,7 +474,7 @@ fn moo_value_to_json(value: &moor_var::Var) -> Result<JsonValue, BfErr> {
                     Variant::Str(s) => s.as_str().to_string(),
                     Variant::Int(i) => i.to_string(),
                     Variant::Float(f) => f.to_string(),
-                    Variant::Obj(o) => format!("#{}", o),
+                    Variant::Obj(o) => o.to_string(),
                     _ => return Err(BfErr::Code(E_TYPE)), // Complex keys not supported
                 };
                 json_obj.insert(key, moo_value_to_json(&v)?);
@@ -483,6 +535,74 @@ fn bf_parse_json(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     }
 }
 
+/// Function: str object_to_json(obj object)
+///
+/// Returns a JSON object describing `object` as the caller sees it, for a web API to serve: its
+/// id, name, owner, parent and location, and a "properties" object of the values of all its
+/// properties, inherited ones included, which the caller may read. Properties the caller can't
+/// read are left out, as are those defined on ancestors the caller can't read. Values with no
+/// JSON equivalent are given as their MOO literal.
+fn bf_object_to_json(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 1 {
+        return Err(BfErr::Code(E_ARGS));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::Code(E_TYPE));
+    };
+    if !bf_args
+        .world_state
+        .valid(&obj)
+        .map_err(world_state_bf_err)?
+    {
+        return Err(BfErr::Code(E_INVARG));
+    }
+    let perms = bf_args.task_perms_who();
+    let summary = bf_args
+        .world_state
+        .object_summary(&perms, &obj)
+        .map_err(world_state_bf_err)?;
+
+    let ancestors = bf_args
+        .world_state
+        .ancestors_of(&perms, &obj, true)
+        .map_err(world_state_bf_err)?;
+    let mut properties = serde_json::Map::new();
+    for ancestor in ancestors.iter() {
+        let propdefs = match bf_args.world_state.properties(&perms, &ancestor) {
+            Ok(propdefs) => propdefs,
+            Err(WorldStateError::ObjectPermissionDenied) => continue,
+            Err(e) => return Err(world_state_bf_err(e)),
+        };
+        for propdef in propdefs.iter() {
+            let value = match bf_args.world_state.retrieve_property(
+                &perms,
+                &obj,
+                Symbol::mk(propdef.name()),
+            ) {
+                Ok(value) => value,
+                Err(WorldStateError::PropertyPermissionDenied) => continue,
+                Err(e) => return Err(world_state_bf_err(e)),
+            };
+            let value =
+                moo_value_to_json(&value).unwrap_or_else(|_| JsonValue::String(to_literal(&value)));
+            properties.insert(propdef.name().to_string(), value);
+        }
+    }
+
+    let json = serde_json::json!({
+        "id": obj.to_string(),
+        "name": summary.name,
+        "owner": summary.owner.to_string(),
+        "parent": summary.parent.to_string(),
+        "location": summary.location.to_string(),
+        "properties": properties,
+    });
+    match serde_json::to_string(&json) {
+        Ok(json_str) => Ok(Ret(v_string(json_str))),
+        Err(_) => Err(BfErr::Code(E_INVARG)),
+    }
+}
+
 // str string_hmac(str text, str key [, str algo [, binary]])
 fn bf_string_hmac(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     let arg_count = bf_args.args.len();
@@ -548,6 +668,177 @@ fn bf_string_hmac(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     }
 }
 
+/// C's `%g`: `precision` significant digits, in exponent form only if the number is very large
+/// or very small, and without trailing zeros.
+fn format_general(f: f64, precision: usize) -> String {
+    if !f.is_finite() {
+        return f.to_string();
+    }
+    let precision = precision.max(1);
+    let scientific = format!("{:.*e}", precision - 1, f);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    let trim = |s: &str| {
+        if s.contains('.') {
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            s.to_string()
+        }
+    };
+    if exponent < -4 || exponent >= precision as i32 {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{}e{sign}{:02}", trim(mantissa), exponent.abs())
+    } else {
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        trim(&format!("{:.*}", decimals, f))
+    }
+}
+
+/// Fill `text` out to `width` characters, with spaces on the left (or on the right if
+/// `left_justify`), or with zeros after any sign if `zero_pad`.
+fn pad(text: String, width: usize, left_justify: bool, zero_pad: bool) -> String {
+    let len = text.chars().count();
+    if len >= width {
+        return text;
+    }
+    let fill = width - len;
+    if left_justify {
+        format!("{text}{}", " ".repeat(fill))
+    } else if zero_pad {
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text.as_str()),
+        };
+        format!("{sign}{}{digits}", "0".repeat(fill))
+    } else {
+        format!("{}{text}", " ".repeat(fill))
+    }
+}
+
+/// The largest width or precision a `format()` specifier may ask for, so that a template can't
+/// demand an arbitrarily large allocation.
+const MAX_FORMAT_WIDTH: usize = 10_000;
+
+/// The number at the front of `chars`, if there is one. Raises E_INVARG if it's larger than
+/// `MAX_FORMAT_WIDTH`.
+fn digits(chars: &mut Peekable<Chars>) -> Result<Option<usize>, Error> {
+    let mut n = None;
+    while let Some(d) = chars.next_if(char::is_ascii_digit) {
+        n = n
+            .unwrap_or(0usize)
+            .checked_mul(10)
+            .and_then(|n| n.checked_add(d.to_digit(10).unwrap() as usize))
+            .filter(|n| *n <= MAX_FORMAT_WIDTH)
+            .map(Some)
+            .ok_or_else(|| {
+                E_INVARG.with_msg(|| {
+                    format!("format() width and precision can be at most {MAX_FORMAT_WIDTH}")
+                })
+            })?;
+    }
+    Ok(n)
+}
+
+/// Expand the `%` specifiers in `template` with `args`, in order. A specifier is
+/// `%[-][0][width][.precision]conversion`, where the conversion is one of:
+///
+///   - `d`: an integer.
+///   - `f`: a number, with `precision` (default 6) digits after the point.
+///   - `g`: a number, with `precision` (default 6) significant digits.
+///   - `s`: any value, as `tostr` would show it, cut to `precision` characters if given.
+///   - `o`: an object, as `#123`.
+///   - `%`: a literal `%`, taking no argument.
+///
+/// `-` left-justifies within `width`, and `0` pads numbers with zeros instead of spaces. Neither
+/// `width` nor `precision` may exceed `MAX_FORMAT_WIDTH`.
+fn format_template(template: &str, args: &[Var]) -> Result<String, Error> {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        let (mut left_justify, mut zero_pad) = (false, false);
+        while let Some(flag) = chars.next_if(|c| *c == '-' || *c == '0') {
+            match flag {
+                '-' => left_justify = true,
+                _ => zero_pad = true,
+            }
+        }
+        let width = digits(&mut chars)?.unwrap_or(0);
+        let precision = match chars.next_if_eq(&'.') {
+            Some(_) => Some(digits(&mut chars)?.unwrap_or(0)),
+            None => None,
+        };
+        let Some(conversion) = chars.next() else {
+            return Err(E_INVARG.msg("format() template ends inside a specifier"));
+        };
+        if conversion == '%' {
+            result.push('%');
+            continue;
+        }
+        let Some(arg) = args.next() else {
+            return Err(E_INVARG.msg("format() has too few arguments for its template"));
+        };
+        let text = match (conversion, arg.variant()) {
+            ('d', Variant::Int(i)) if precision.is_none() => i.to_string(),
+            ('o', Variant::Obj(o)) if precision.is_none() => o.to_string(),
+            ('f', Variant::Int(i)) => format!("{:.*}", precision.unwrap_or(6), *i as f64),
+            ('f', Variant::Float(f)) => format!("{:.*}", precision.unwrap_or(6), f),
+            ('g', Variant::Int(i)) => format_general(*i as f64, precision.unwrap_or(6)),
+            ('g', Variant::Float(f)) => format_general(*f, precision.unwrap_or(6)),
+            ('s', _) => {
+                let text = tostr(arg);
+                match precision {
+                    Some(precision) => text.chars().take(precision).collect(),
+                    None => text,
+                }
+            }
+            ('d' | 'o' | 'f' | 'g', _) => {
+                return Err(E_INVARG.with_msg(|| {
+                    format!("format() %{conversion} cannot format {}", to_literal(arg))
+                }));
+            }
+            _ => {
+                return Err(
+                    E_INVARG.with_msg(|| format!("format() has no %{conversion} specifier"))
+                );
+            }
+        };
+        let zero_pad = zero_pad && conversion != 's' && conversion != 'o';
+        result.push_str(&pad(text, width, left_justify, zero_pad));
+    }
+    if args.next().is_some() {
+        return Err(E_INVARG.msg("format() has too many arguments for its template"));
+    }
+    Ok(result)
+}
+
+/*
+Function: str format (str template, ...)
+Returns `template` with each `%` specifier replaced by the next argument, printf-style. The
+specifiers are `%d`, `%f`, `%g`, `%s`, `%o` (objects) and `%%`, with optional `-` and `0` flags,
+width and precision (each at most 10000). Raises E_INVARG if the arguments don't match the
+specifiers, or a width or precision is too large.
+ */
+fn bf_format(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.is_empty() {
+        return Err(BfErr::ErrValue(
+            E_ARGS.msg("format() requires at least 1 argument"),
+        ));
+    }
+    let Some(template) = bf_args.args[0].as_string() else {
+        return Err(BfErr::ErrValue(
+            E_TYPE.msg("format() template must be a string"),
+        ));
+    };
+    let args: Vec<_> = bf_args.args.iter().skip(1).collect();
+    let result = format_template(template, &args).map_err(BfErr::ErrValue)?;
+    Ok(Ret(v_str(&result)))
+}
+
 pub(crate) fn register_bf_strings(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("strsub")] = Box::new(bf_strsub);
     builtins[offset_for_builtin("index")] = Box::new(bf_index);
@@ -564,12 +855,16 @@ pub(crate) fn register_bf_strings(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("decode_base64")] = Box::new(bf_decode_base64);
     builtins[offset_for_builtin("generate_json")] = Box::new(bf_generate_json);
     builtins[offset_for_builtin("parse_json")] = Box::new(bf_parse_json);
+    builtins[offset_for_builtin("object_to_json")] = Box::new(bf_object_to_json);
+    builtins[offset_for_builtin("format")] = Box::new(bf_format);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::vm::builtins::bf_strings::{json_value_to_moo, moo_value_to_json, strsub};
-    use moor_var::{Associative, v_int, v_list, v_map, v_str};
+    use crate::vm::builtins::bf_strings::{
+        format_template, json_value_to_moo, moo_value_to_json, strsub,
+    };
+    use moor_var::{Associative, E_INVARG, Obj, v_float, v_int, v_list, v_map, v_obj, v_str};
     use serde_json::json;
 
     #[test]
@@ -632,6 +927,10 @@ mod tests {
         // Test string
         let str_val = v_str("hello");
         assert_eq!(moo_value_to_json(&str_val).unwrap(), json!("hello"));
+
+        // Test object
+        let obj_val = v_obj(Obj::mk_id(42));
+        assert_eq!(moo_value_to_json(&obj_val).unwrap(), json!("#42"));
     }
 
     #[test]
@@ -700,4 +999,83 @@ mod tests {
             _ => panic!("Expected map"),
         };
     }
+
+    #[test]
+    fn test_format_width() {
+        assert_eq!(format_template("[%5d]", &[v_int(42)]).unwrap(), "[   42]");
+        assert_eq!(format_template("[%-5d]", &[v_int(42)]).unwrap(), "[42   ]");
+        assert_eq!(format_template("[%05d]", &[v_int(-42)]).unwrap(), "[-0042]");
+        assert_eq!(
+            format_template("[%6s]", &[v_str("ab")]).unwrap(),
+            "[    ab]"
+        );
+        assert_eq!(format_template("[%1s]", &[v_str("abc")]).unwrap(), "[abc]");
+        assert_eq!(
+            format_template("%10000d", &[v_int(1)]).unwrap().len(),
+            10_000
+        );
+        assert_eq!(
+            format_template("%d%% of %s", &[v_int(50), v_list(&[])]).unwrap(),
+            "50% of {list}"
+        );
+    }
+
+    #[test]
+    fn test_format_precision() {
+        assert_eq!(
+            format_template("%.2f", &[v_float(1.23456)]).unwrap(),
+            "1.23"
+        );
+        assert_eq!(
+            format_template("[%8.3f]", &[v_int(2)]).unwrap(),
+            "[   2.000]"
+        );
+        assert_eq!(format_template("%f", &[v_float(0.5)]).unwrap(), "0.500000");
+        assert_eq!(format_template("%g", &[v_float(0.0001)]).unwrap(), "0.0001");
+        assert_eq!(
+            format_template("%g", &[v_float(100000.0)]).unwrap(),
+            "100000"
+        );
+        assert_eq!(format_template("%g", &[v_float(1e6)]).unwrap(), "1e+06");
+        assert_eq!(format_template("%g", &[v_float(0.0)]).unwrap(), "0");
+        assert_eq!(
+            format_template("%.3g", &[v_float(1234.5)]).unwrap(),
+            "1.23e+03"
+        );
+        assert_eq!(
+            format_template("%.3g", &[v_float(0.00001234)]).unwrap(),
+            "1.23e-05"
+        );
+        assert_eq!(format_template("%.3s", &[v_str("abcdef")]).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_format_objref() {
+        let o = v_obj(Obj::mk_id(42));
+        assert_eq!(format_template("%o", &[o.clone()]).unwrap(), "#42");
+        assert_eq!(format_template("[%-5o]", &[o.clone()]).unwrap(), "[#42  ]");
+        assert_eq!(format_template("%s", &[o]).unwrap(), "#42");
+    }
+
+    #[test]
+    fn test_format_mismatch() {
+        for (template, args) in [
+            ("%d", vec![v_str("1")]),
+            ("%d", vec![v_float(1.0)]),
+            ("%.2d", vec![v_int(1)]),
+            ("%o", vec![v_int(1)]),
+            ("%g", vec![v_str("1.0")]),
+            ("%d %d", vec![v_int(1)]),
+            ("%d", vec![v_int(1), v_int(2)]),
+            ("%q", vec![v_int(1)]),
+            ("%5", vec![v_int(1)]),
+            ("%10001d", vec![v_int(1)]),
+            ("%.10001f", vec![v_float(1.0)]),
+            ("%99999999999999999999999d", vec![v_int(1)]),
+            ("%.99999999999999999999999s", vec![v_str("a")]),
+        ] {
+            let result = format_template(template, &args);
+            assert_eq!(result.unwrap_err().err_type, E_INVARG, "{template}");
+        }
+    }
 }
@@ -11,13 +11,14 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
-use crate::vm::builtins::BfRet::Ret;
+use crate::vm::builtins::BfRet::{Ret, RetNil};
 use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinFunction, world_state_bf_err};
 use md5::Digest;
+use moor_common::model::WorldStateError;
 use moor_compiler::{offset_for_builtin, to_literal};
-use moor_var::{AsByteBuffer, Sequence};
+use moor_var::{AsByteBuffer, SYSTEM_OBJECT, Sequence, Symbol};
 use moor_var::{E_ARGS, E_INVARG, E_RANGE, E_TYPE};
-use moor_var::{Variant, v_err};
+use moor_var::{Var, Variant, v_err};
 use moor_var::{v_float, v_int, v_obj, v_objid, v_str, v_sym, v_sym_str};
 
 fn bf_typeof(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
@@ -25,29 +26,35 @@ fn bf_typeof(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(Ret(v_int(arg.type_code() as i64)))
 }
 
+/// A value as `tostr` shows it: strings as they are, other scalars as their literal, and
+/// collections as a placeholder.
+pub(crate) fn tostr(value: &Var) -> String {
+    match value.variant() {
+        Variant::None => "None".to_string(),
+        Variant::Bool(b) => format!("{}", b),
+        Variant::Int(i) => i.to_string(),
+        Variant::Float(f) => format!("{:?}", f),
+        Variant::Str(s) => s.as_str().to_string(),
+        Variant::Binary(b) => format!("<binary {} bytes>", b.len()),
+        Variant::Obj(o) => o.to_string(),
+        Variant::List(_) => "{list}".to_string(),
+        Variant::Map(_) => "[map]".to_string(),
+        Variant::Sym(s) => s.to_string(),
+        Variant::Err(e) => e.name().as_str().to_string(),
+        Variant::Flyweight(fl) => {
+            if fl.is_sealed() {
+                "<sealed flyweight>".to_string()
+            } else {
+                "<flyweight>".to_string()
+            }
+        }
+    }
+}
+
 fn bf_tostr(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     let mut result = String::new();
     for arg in bf_args.args.iter() {
-        match arg.variant() {
-            Variant::None => result.push_str("None"),
-            Variant::Bool(b) => result.push_str(format!("{}", b).as_str()),
-            Variant::Int(i) => result.push_str(&i.to_string()),
-            Variant::Float(f) => result.push_str(format!("{:?}", f).as_str()),
-            Variant::Str(s) => result.push_str(s.as_str()),
-            Variant::Binary(b) => result.push_str(&format!("<binary {} bytes>", b.len())),
-            Variant::Obj(o) => result.push_str(&o.to_string()),
-            Variant::List(_) => result.push_str("{list}"),
-            Variant::Map(_) => result.push_str("[map]"),
-            Variant::Sym(s) => result.push_str(&s.to_string()),
-            Variant::Err(e) => result.push_str(e.name().as_str()),
-            Variant::Flyweight(fl) => {
-                if fl.is_sealed() {
-                    result.push_str("<sealed flyweight>")
-                } else {
-                    result.push_str("<flyweight>")
-                }
-            }
-        }
+        result.push_str(&tostr(&arg));
     }
     Ok(Ret(v_str(result.as_str())))
 }
@@ -150,6 +157,25 @@ fn bf_toobj(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
                 Err(_) => Ok(Ret(v_objid(0))),
             }
         }
+        // A `$name` reference to a property on the system object.
+        Variant::Str(s) if s.as_str().starts_with('$') => {
+            let name = &s.as_str()[1..];
+            let value = bf_args
+                .world_state
+                .retrieve_property(&bf_args.caller_perms(), &SYSTEM_OBJECT, Symbol::mk(name))
+                .map_err(|e| match e {
+                    WorldStateError::PropertyNotFound(_, _) => {
+                        BfErr::ErrValue(E_INVARG.with_msg(|| format!("${name} is not defined")))
+                    }
+                    e => world_state_bf_err(e),
+                })?;
+            let Some(o) = value.as_object() else {
+                return Err(BfErr::ErrValue(
+                    E_INVARG.with_msg(|| format!("${name} is not an object")),
+                ));
+            };
+            Ok(Ret(v_obj(o)))
+        }
         Variant::Str(s) => {
             let i = s.as_str().parse::<i32>();
             match i {
@@ -230,6 +256,74 @@ fn bf_value_hash(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     )))
 }
 
+/// Whether `arg` is of a type allowed by `spec`: a type code, a list of them, or -1 for any type.
+fn arg_type_allowed(spec: &Var, arg: &Var) -> Result<bool, BfErr> {
+    match spec.variant() {
+        Variant::Int(-1) => Ok(true),
+        Variant::Int(code) => Ok(arg.type_code() as i64 == *code),
+        Variant::List(codes) => {
+            for code in codes.iter() {
+                if code.as_integer().is_none() {
+                    return Err(BfErr::ErrValue(
+                        E_INVARG.msg("check_args() type alternatives must be type codes"),
+                    ));
+                }
+                if arg_type_allowed(&code, arg)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        _ => Err(BfErr::ErrValue(
+            E_INVARG.msg("check_args() types must be type codes or lists of them"),
+        )),
+    }
+}
+
+/// check_args(args, types [, required]): raise E_ARGS unless there are between `required`
+/// (by default, all of them) and length(types) args, and E_TYPE unless each is of the type given
+/// for it in `types`. `required` must be between 0 and length(types), or E_INVARG is raised.
+fn bf_check_args(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    let (Some(args), Some(types)) = (bf_args.args[0].as_list(), bf_args.args[1].as_list()) else {
+        return Err(BfErr::Code(E_TYPE));
+    };
+    let required = if bf_args.args.len() > 2 {
+        match bf_args.args[2].as_integer() {
+            Some(required) if required >= 0 && required as usize <= types.len() => {
+                required as usize
+            }
+            _ => return Err(BfErr::Code(E_INVARG)),
+        }
+    } else {
+        types.len()
+    };
+
+    if args.len() < required || args.len() > types.len() {
+        let expected = if required == types.len() {
+            required.to_string()
+        } else {
+            format!("{required} to {}", types.len())
+        };
+        return Err(BfErr::ErrValue(E_ARGS.with_msg(|| {
+            format!("expected {expected} arguments, got {}", args.len())
+        })));
+    }
+
+    for (i, (arg, spec)) in args.iter().zip(types.iter()).enumerate() {
+        if !arg_type_allowed(&spec, &arg)? {
+            return Err(BfErr::ErrValue(E_TYPE.with_msg(|| {
+                format!(
+                    "argument {} is {}, which is not allowed",
+                    i + 1,
+                    arg.type_code().to_literal()
+                )
+            })));
+        }
+    }
+
+    Ok(RetNil)
+}
+
 fn bf_length(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 1 {
         return Err(BfErr::ErrValue(
@@ -310,6 +404,7 @@ pub(crate) fn register_bf_values(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("object_bytes")] = Box::new(bf_object_bytes);
     builtins[offset_for_builtin("value_hash")] = Box::new(bf_value_hash);
     builtins[offset_for_builtin("length")] = Box::new(bf_length);
+    builtins[offset_for_builtin("check_args")] = Box::new(bf_check_args);
     builtins[offset_for_builtin("error_code")] = Box::new(bf_error_code);
     builtins[offset_for_builtin("error_message")] = Box::new(bf_error_message);
 }
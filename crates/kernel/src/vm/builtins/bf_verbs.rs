@@ -16,6 +16,7 @@ use tracing::{error, warn};
 
 use crate::vm::builtins::BfRet::{Ret, RetNil};
 use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinFunction, world_state_bf_err};
+use crate::vm::verb_trace::set_verb_traced;
 use moor_common::model::WorldStateError;
 use moor_common::model::{ArgSpec, VerbArgsSpec};
 use moor_common::model::{HasUuid, Named};
@@ -23,12 +24,13 @@ use moor_common::model::{ObjFlag, verb_perms_string};
 use moor_common::model::{VerbAttrs, VerbFlag};
 use moor_common::model::{VerbDef, parse_preposition_spec, preposition_to_string};
 use moor_common::program::ProgramType;
-use moor_common::program::names::GlobalName;
+use moor_common::program::names::{GlobalName, Name};
 use moor_common::util::BitEnum;
 use moor_compiler::Program;
 use moor_compiler::offset_for_builtin;
 use moor_compiler::program_to_tree;
 use moor_compiler::unparse;
+use moor_compiler::{BUILTINS, Label, Op};
 use moor_compiler::{compile, to_literal};
 use moor_var::Obj;
 use moor_var::Sequence;
@@ -354,13 +356,19 @@ fn bf_verb_code(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         .retrieve_verb(&bf_args.task_perms_who(), &obj, verbdef.uuid())
         .map_err(world_state_bf_err)?;
 
+    // If the source was stored with the program, hand that back as-is.
+    if let Some(source) = verb_info.0.source() {
+        return Ok(Ret(v_list_iter(source.iter().map(|s| v_str(s)))));
+    }
+
     // If the binary is empty, just return empty rather than try to decode it.
     if verb_info.0.is_empty() {
         return Ok(Ret(v_empty_list()));
     }
 
     #[allow(irrefutable_let_patterns)]
-    let ProgramType::MooR(program) = &verb_info.0 else {
+    let (ProgramType::MooR(program) | ProgramType::MooRWithSource(program, _)) = &verb_info.0
+    else {
         return Err(BfErr::ErrValue(
             E_INVARG.msg("verb_code: verb program is not Moo"),
         ));
@@ -420,6 +428,7 @@ fn bf_set_verb_code(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     // Code should be a list of strings.
     // Which we will join (with linefeeds) into one string.
     let mut code_string = String::new();
+    let mut source_lines = Vec::with_capacity(program_code.len());
     for line in program_code.iter() {
         let line = match line.variant() {
             Variant::Str(line) => line,
@@ -427,6 +436,7 @@ fn bf_set_verb_code(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         };
         code_string.push_str(line.as_str());
         code_string.push('\n');
+        source_lines.push(line.as_str().to_string());
     }
     // Now try to compile...
     let program = match compile(code_string.as_str(), bf_args.config.compile_options()) {
@@ -446,7 +456,11 @@ fn bf_set_verb_code(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
         names: None,
         flags: None,
         args_spec: None,
-        program: Some(ProgramType::MooR(program)),
+        program: Some(if bf_args.config.preserve_verb_source {
+            ProgramType::MooRWithSource(program, source_lines)
+        } else {
+            ProgramType::MooR(program)
+        }),
     };
     bf_args
         .world_state
@@ -460,6 +474,35 @@ fn bf_set_verb_code(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(RetNil)
 }
 
+// Function: none set_verb_trace (obj object, str verb-desc, int enabled)
+// Turns logging of each opcode the verb executes on or off. See `VERB_TRACE_TARGET`.
+fn bf_set_verb_trace(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() != 3 {
+        return Err(BfErr::Code(E_ARGS));
+    }
+    let Some(obj) = bf_args.args[0].as_object() else {
+        return Err(BfErr::Code(E_TYPE));
+    };
+    if !bf_args
+        .world_state
+        .valid(&obj)
+        .map_err(world_state_bf_err)?
+    {
+        return Err(BfErr::Code(E_INVARG));
+    }
+
+    let perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    perms.check_programmer().map_err(world_state_bf_err)?;
+
+    let verbdef = get_verbdef(&obj, bf_args.args[1].clone(), bf_args)?;
+    perms
+        .check_verb_allows(&verbdef.owner(), verbdef.flags(), VerbFlag::Write)
+        .map_err(world_state_bf_err)?;
+
+    set_verb_traced(verbdef.uuid(), bf_args.args[2].is_true());
+    Ok(RetNil)
+}
+
 // Function: none add_verb (obj object, list info, list args)
 fn bf_add_verb(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 3 {
@@ -510,6 +553,63 @@ fn bf_add_verb(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     Ok(RetNil)
 }
 
+/// Function: none copy_verb(obj from, str|int verb-desc, obj to [, obj owner])
+///
+/// Add a copy of a verb on `from` (its names, flags, argument spec and program) to `to`, owned by
+/// `owner`, or by the caller if not given. The caller must be able to read the verb and write
+/// `to`, and only wizards may give the copy to someone else.
+fn bf_copy_verb(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+    if bf_args.args.len() < 3 || bf_args.args.len() > 4 {
+        return Err(BfErr::Code(E_ARGS));
+    }
+    let (Some(from), Some(to)) = (bf_args.args[0].as_object(), bf_args.args[2].as_object()) else {
+        return Err(BfErr::Code(E_TYPE));
+    };
+    for obj in [&from, &to] {
+        if !bf_args.world_state.valid(obj).map_err(world_state_bf_err)? {
+            return Err(BfErr::Code(E_INVARG));
+        }
+    }
+
+    let perms = bf_args.task_perms().map_err(world_state_bf_err)?;
+    if !perms.flags.contains(ObjFlag::Programmer) {
+        return Err(BfErr::Code(E_PERM));
+    }
+    let owner = if bf_args.args.len() == 4 {
+        let Some(owner) = bf_args.args[3].as_object() else {
+            return Err(BfErr::Code(E_TYPE));
+        };
+        if owner != perms.who && !perms.check_is_wizard().map_err(world_state_bf_err)? {
+            return Err(BfErr::Code(E_PERM));
+        }
+        owner
+    } else {
+        perms.who
+    };
+
+    let verbdef = get_verbdef(&from, bf_args.args[1].clone(), bf_args)?;
+    let (program, verbdef) = bf_args
+        .world_state
+        .retrieve_verb(&perms.who, &from, verbdef.uuid())
+        .map_err(world_state_bf_err)?;
+    let names = verbdef.names().into_iter().map(Symbol::mk).collect();
+
+    bf_args
+        .world_state
+        .add_verb(
+            &perms.who,
+            &to,
+            names,
+            &owner,
+            verbdef.flags(),
+            verbdef.args(),
+            program,
+        )
+        .map_err(world_state_bf_err)?;
+
+    Ok(RetNil)
+}
+
 //Function: none delete_verb (obj object, str verb-desc)
 fn bf_delete_verb(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 2 {
@@ -578,7 +678,7 @@ fn bf_disassemble(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     }
 
     #[allow(irrefutable_let_patterns)]
-    let ProgramType::MooR(program) = &program else {
+    let (ProgramType::MooR(program) | ProgramType::MooRWithSource(program, _)) = &program else {
         return Err(BfErr::ErrValue(
             E_INVARG.msg("disassemble(): verb program is not Moo"),
         ));
@@ -616,24 +716,58 @@ fn bf_disassemble(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
 
     // TODO: dump fork vectors in program disassembly stream
 
-    // Display main vector (program); opcodes are indexed by their offset
+    // Display main vector (program); opcodes are indexed by their offset, and marked with the
+    // line of each statement they begin.
     disassembly.push(v_str("OPCODES:"));
     for (i, op) in program.main_vector().iter().enumerate() {
-        let mut line_no_string = String::new();
-        let mut last_line_no = 0;
-        for (pc, line_no) in program.line_number_spans().iter() {
-            if *pc == i {
-                line_no_string = format!("\t\t(line {})", last_line_no);
-                break;
-            }
-            last_line_no = *line_no;
-        }
-        disassembly.push(v_string(format!("{: >3}: {:?}{}", i, op, line_no_string)));
+        let line_no_string = program
+            .line_number_spans()
+            .iter()
+            .find(|(pc, _)| *pc == i)
+            .map(|(_, line_no)| format!("\t\t(line {})", line_no))
+            .unwrap_or_default();
+        let operand_note = describe_operand(program, op)
+            .map(|note| format!(" ({})", note))
+            .unwrap_or_default();
+        disassembly.push(v_string(format!(
+            "{: >3}: {:?}{}{}",
+            i, op, operand_note, line_no_string
+        )));
     }
 
     Ok(Ret(v_list(&disassembly)))
 }
 
+/// What an opcode's operand refers to, for disassembly: the variable it names, the literal it
+/// pushes, the builtin it calls, or the position it jumps to.
+fn describe_operand(program: &Program, op: &Op) -> Option<String> {
+    let var_name = |name: &Name| program.var_names().name_of(name).map(|s| s.to_string());
+    let jump_to = |label: &Label| format!("-> {}", program.jump_label(*label).position.0);
+    match op {
+        Op::Push(name) | Op::Put(name) | Op::ContinueComprehension(name) => var_name(name),
+        Op::Fork { id: Some(id), .. } => var_name(id),
+        Op::ForRange { id, end_label, .. } | Op::WhileId { id, end_label, .. } => {
+            Some(format!("{}, {}", var_name(id)?, jump_to(end_label)))
+        }
+        Op::Imm(literal) => program.find_literal(literal).map(|l| to_literal(&l)),
+        Op::FuncCall { id } => BUILTINS.name_of(*id).map(|s| s.to_string()),
+        Op::And(label)
+        | Op::Or(label)
+        | Op::IfQues(label)
+        | Op::If(label, _)
+        | Op::Eif(label, _)
+        | Op::EndCatch(label)
+        | Op::EndExcept(label)
+        | Op::ExitId(label)
+        | Op::Exit { label, .. }
+        | Op::Jump { label }
+        | Op::While {
+            jump_label: label, ..
+        } => Some(jump_to(label)),
+        _ => None,
+    }
+}
+
 fn bf_respond_to(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
     if bf_args.args.len() != 2 {
         return Err(BfErr::Code(E_ARGS));
@@ -687,8 +821,10 @@ pub(crate) fn register_bf_verbs(builtins: &mut [Box<BuiltinFunction>]) {
     builtins[offset_for_builtin("set_verb_args")] = Box::new(bf_set_verb_args);
     builtins[offset_for_builtin("verb_code")] = Box::new(bf_verb_code);
     builtins[offset_for_builtin("set_verb_code")] = Box::new(bf_set_verb_code);
+    builtins[offset_for_builtin("set_verb_trace")] = Box::new(bf_set_verb_trace);
     builtins[offset_for_builtin("add_verb")] = Box::new(bf_add_verb);
     builtins[offset_for_builtin("delete_verb")] = Box::new(bf_delete_verb);
+    builtins[offset_for_builtin("copy_verb")] = Box::new(bf_copy_verb);
     builtins[offset_for_builtin("disassemble")] = Box::new(bf_disassemble);
     builtins[offset_for_builtin("respond_to")] = Box::new(bf_respond_to);
 }
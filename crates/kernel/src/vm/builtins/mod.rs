@@ -12,7 +12,10 @@
 //
 
 use lazy_static::lazy_static;
-use std::sync::Arc;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use thiserror::Error;
 
 use crate::config::FeaturesConfig;
@@ -93,6 +96,9 @@ pub fn bf_perf_counters<'a>() -> &'a BfCounters {
 pub struct BuiltinRegistry {
     // The set of built-in functions, indexed by their Name offset in the variable stack.
     pub(crate) builtins: Arc<Vec<Box<BuiltinFunction>>>,
+    /// The generator behind `random()`. It's shared by every task on this server (each clone of
+    /// the registry), so that `reseed_random()` pins the sequence for all of them.
+    pub(crate) rng: Arc<Mutex<StdRng>>,
 }
 
 impl Default for BuiltinRegistry {
@@ -121,6 +127,7 @@ impl BuiltinRegistry {
 
         BuiltinRegistry {
             builtins: Arc::new(builtins),
+            rng: Arc::new(Mutex::new(StdRng::from_entropy())),
         }
     }
 
@@ -146,6 +153,10 @@ pub(crate) struct BfCallState<'a> {
     pub(crate) task_scheduler_client: &'a TaskSchedulerClient,
     /// Config
     pub(crate) config: &'a FeaturesConfig,
+    /// The server's random number generator.
+    pub(crate) rng: &'a Mutex<StdRng>,
+    /// When the call has to be finished by, if there is a builtin duration limit.
+    pub(crate) deadline: Option<Instant>,
 }
 
 impl BfCallState<'_> {
@@ -177,6 +188,34 @@ impl BfCallState<'_> {
         frame
     }
 
+    /// Run blocking work, such as network or file I/O, on a thread of its own, and wait for it
+    /// only until the call's deadline. If the deadline passes first, the call fails with
+    /// `BfErr::DeadlineExceeded` and the task is aborted; the work carries on in the background,
+    /// but its result is thrown away. Without a deadline, the work is just run here.
+    pub fn run_blocking<T, F>(&self, work: F) -> Result<T, BfErr>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let Some(deadline) = self.deadline else {
+            return Ok(work());
+        };
+        let (send, receive) = oneshot::channel();
+        std::thread::Builder::new()
+            .name(format!("moor-bf-{}", self.name))
+            .spawn(move || {
+                send.send(work()).ok();
+            })
+            .expect("Could not spawn thread for blocking builtin");
+        match receive.recv_deadline(deadline) {
+            Ok(result) => Ok(result),
+            Err(oneshot::RecvTimeoutError::Timeout) => Err(BfErr::DeadlineExceeded),
+            Err(oneshot::RecvTimeoutError::Disconnected) => {
+                panic!("Blocking work for builtin {} panicked", self.name)
+            }
+        }
+    }
+
     /// Construct a boolean value from a truthy value but convert to mooR boolean only if that
     /// feature is enabled.
     pub fn v_bool(&self, truthy: bool) -> Var {
@@ -203,6 +242,11 @@ pub(crate) enum BfRet {
     VmInstr(ExecutionResult),
 }
 
+/// Failure of a built-in function.
+/// Permission failures and other MOO errors are always reported through one of these, never as
+/// an `Ok(Ret(v_err(..)))`: the VM then raises the error if the calling verb has the `d` flag (so
+/// `try`/`except` and `!` can catch it) and returns it as the call's value otherwise, as for any
+/// other error. Returning an error value directly would skip that, and never be raised.
 #[derive(Debug, Clone, PartialEq, Error)]
 pub(crate) enum BfErr {
     #[error("Error in built-in function: {0}")]
@@ -213,11 +257,18 @@ pub(crate) enum BfErr {
     Raise(Error),
     #[error("Transaction rollback-retry")]
     Rollback,
+    /// A database error, whose detail is only shown to wizards.
+    #[error("Database error: {0}")]
+    Database(WorldStateError),
+    /// The call ran past its deadline; see `BfCallState::run_blocking`.
+    #[error("Builtin call ran past its deadline")]
+    DeadlineExceeded,
 }
 
 pub(crate) fn world_state_bf_err(err: WorldStateError) -> BfErr {
     match err {
         WorldStateError::RollbackRetry => BfErr::Rollback,
+        WorldStateError::DatabaseError(_) => BfErr::Database(err),
         _ => BfErr::ErrValue(err.into()),
     }
 }
@@ -22,7 +22,7 @@ use crate::vm::activation::{Activation, Frame};
 use moor_common::tasks::TaskId;
 
 // {this, verb-name, programmer, verb-loc, player, line-number}
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Caller {
     pub this: Var,
     pub verb_name: Symbol,
@@ -32,6 +32,20 @@ pub struct Caller {
     pub line_number: usize,
 }
 
+/// The tick and time allowance of code run by `eval()`, which is cut off once it has used either
+/// up, independent of (though never more than) what the task as a whole has left.
+#[derive(Clone, Debug, Encode, Decode)]
+pub(crate) struct EvalBudget {
+    /// The position on the stack of the `eval()` builtin's frame.
+    pub(crate) depth: usize,
+    pub(crate) max_ticks: usize,
+    pub(crate) max_time: Duration,
+    /// The task's tick count and the time when the evaluated code started (or, like the task's
+    /// own, last resumed after a suspend).
+    pub(crate) start_ticks: usize,
+    pub(crate) start_time: SystemTime,
+}
+
 /// Represents the state of VM execution for a given task.
 #[derive(Clone, Debug, Encode, Decode)]
 pub(crate) struct VMExecState {
@@ -51,6 +65,11 @@ pub(crate) struct VMExecState {
     pub(crate) start_time: Option<SystemTime>,
     /// The amount of time the task is allowed to run.
     pub(crate) maximum_time: Option<Duration>,
+    /// The budgets of any `eval()` calls in progress, outermost first.
+    pub(crate) eval_budgets: Vec<EvalBudget>,
+    /// The name the task gave itself with `set_task_name()`, to tell it apart in
+    /// `queued_tasks()` and traces.
+    pub(crate) task_name: Option<String>,
 
     unsync: PhantomUnsync,
 }
@@ -65,17 +84,22 @@ impl VMExecState {
             max_ticks,
             tick_slice: 0,
             maximum_time: None,
+            eval_budgets: vec![],
+            task_name: None,
             unsync: Default::default(),
         }
     }
 
     /// Return the callers stack, in the format expected by the `callers` built-in function.
     pub(crate) fn callers(&self) -> Vec<Caller> {
-        let mut callers_iter = self.stack.iter().rev();
-        callers_iter.next(); // skip the top activation, that's our current frame
+        // Skip the top activation, that's our current frame.
+        self.frames().into_iter().skip(1).collect()
+    }
 
+    /// Every activation on the stack, top first, as `callers()` describes them.
+    pub(crate) fn frames(&self) -> Vec<Caller> {
         let mut callers = vec![];
-        for activation in callers_iter {
+        for activation in self.stack.iter().rev() {
             let verb_name = activation.verb_name;
             let definer = activation.verb_definer();
             let player = activation.player;
@@ -171,6 +195,32 @@ impl VMExecState {
         self.top_mut().frame.set_return_value(v);
     }
 
+    /// The fewest ticks any `eval()` in progress has left, if there is one.
+    pub(crate) fn eval_ticks_left(&self) -> Option<usize> {
+        self.eval_budgets
+            .iter()
+            .map(|b| (b.start_ticks + b.max_ticks).saturating_sub(self.tick_count))
+            .min()
+    }
+
+    /// The outermost `eval()` which has run out of ticks or time, as the stack position of its
+    /// frame and a description of what it ran out of.
+    pub(crate) fn exhausted_eval_budget(&self) -> Option<(usize, &'static str)> {
+        self.eval_budgets.iter().find_map(|b| {
+            if self.tick_count >= b.start_ticks + b.max_ticks {
+                return Some((b.depth, "eval() ran out of ticks"));
+            }
+            let elapsed = b.start_time.elapsed().unwrap_or_default();
+            (elapsed > b.max_time).then_some((b.depth, "eval() ran out of seconds"))
+        })
+    }
+
+    /// Drop the budgets of `eval()` calls whose frames are no longer on the stack.
+    pub(crate) fn prune_eval_budgets(&mut self) {
+        let depth = self.stack.len();
+        self.eval_budgets.retain(|b| b.depth < depth);
+    }
+
     pub(crate) fn time_left(&self) -> Option<Duration> {
         let max_time = self.maximum_time?;
         let now = SystemTime::now();
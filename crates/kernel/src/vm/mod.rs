@@ -14,11 +14,12 @@
 use std::time::Duration;
 
 use bincode::{Decode, Encode};
+use moor_common::model::{ObjFlag, WorldState, WorldStateError};
 pub use moor_common::program::ProgramType;
 use moor_common::program::names::Name;
 use moor_common::tasks::{AbortLimitReason, Exception, TaskId};
 use moor_compiler::Offset;
-use moor_var::{List, Obj, Symbol, Var};
+use moor_var::{Error, List, Obj, Symbol, Var};
 pub use vm_call::VerbExecutionRequest;
 pub use vm_unwind::FinallyReason;
 
@@ -27,6 +28,7 @@ use crate::vm::activation::Activation;
 pub(crate) mod activation;
 pub(crate) mod exec_state;
 pub(crate) mod moo_execute;
+pub(crate) mod verb_trace;
 pub(crate) mod vm_call;
 pub(crate) mod vm_unwind;
 
@@ -111,9 +113,34 @@ pub struct VerbCall {
     pub caller: Var,
 }
 
+/// Translate an error from the world state into the MOO error raised for it, on behalf of
+/// `perms`. Database errors carry internal detail, which is only passed on to wizards.
+pub(crate) fn world_state_error(
+    world_state: &dyn WorldState,
+    perms: &Obj,
+    err: &WorldStateError,
+) -> Error {
+    if err.database_error_msg().is_none() {
+        return err.to_error();
+    }
+    let is_wizard = world_state
+        .flags_of(perms)
+        .map(|flags| flags.contains(ObjFlag::Wizard))
+        .unwrap_or(false);
+    if is_wizard {
+        err.to_error()
+    } else {
+        err.to_redacted_error()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::vm::VMHostResponse;
+    use crate::vm::{VMHostResponse, world_state_error};
+    use moor_common::model::{WorldStateError, WorldStateSource};
+    use moor_common::util::BitEnum;
+    use moor_db::{DatabaseConfig, TxDB};
+    use moor_var::{E_INVARG, NOTHING, SYSTEM_OBJECT};
 
     #[test]
     fn test_width_structs_enums() {
@@ -125,4 +152,25 @@ mod tests {
             size_of::<VMHostResponse>()
         );
     }
+
+    #[test]
+    fn test_database_error_detail_for_wizards_only() {
+        let (db, _) = TxDB::open(None, DatabaseConfig::default());
+        let mut tx = db.new_world_state().unwrap();
+        let wizard = tx
+            .create_object(&SYSTEM_OBJECT, &NOTHING, &SYSTEM_OBJECT, BitEnum::all())
+            .unwrap();
+        let player = tx
+            .create_object(&wizard, &NOTHING, &wizard, BitEnum::new())
+            .unwrap();
+
+        let err = WorldStateError::DatabaseError("index corrupted".to_string());
+        let for_wizard = world_state_error(tx.as_ref(), &wizard, &err);
+        assert_eq!(for_wizard, E_INVARG);
+        assert!(for_wizard.message().contains("index corrupted"));
+
+        let for_player = world_state_error(tx.as_ref(), &player, &err);
+        assert_eq!(for_player, E_INVARG);
+        assert!(!for_player.message().contains("index corrupted"));
+    }
 }
@@ -13,12 +13,14 @@
 
 use crate::config::FeaturesConfig;
 use crate::vm::moo_frame::{CatchType, MooStackFrame, ScopeType};
+use crate::vm::verb_trace::VERB_TRACE_TARGET;
 use crate::vm::vm_host::ExecutionResult;
 use crate::vm::vm_unwind::FinallyReason;
+use crate::vm::world_state_error;
 use lazy_static::lazy_static;
 use moor_common::model::WorldState;
 use moor_compiler::{Op, ScatterLabel, to_literal};
-use moor_var::{E_ARGS, E_DIV, E_INVARG, E_INVIND, E_RANGE, E_TYPE, E_VARNF, v_error};
+use moor_var::{E_ARGS, E_DIV, E_FLOAT, E_INVARG, E_INVIND, E_RANGE, E_TYPE, E_VARNF, v_error};
 use moor_var::{
     Error, IndexMode, Obj, Sequence, TypeClass, Var, Variant, v_bool_int, v_empty_list,
     v_empty_map, v_err, v_float, v_flyweight, v_int, v_list, v_map, v_none, v_obj, v_str, v_sym,
@@ -26,16 +28,40 @@ use moor_var::{
 use moor_var::{Symbol, VarType};
 use std::ops::Add;
 use std::time::Duration;
+use tracing::info;
 
 lazy_static! {
     static ref DELEGATE_SYM: Symbol = Symbol::mk("delegate");
     static ref SLOTS_SYM: Symbol = Symbol::mk("slots");
 }
 
+/// True for a float which is NaN or infinite, which can't be meaningfully compared.
+fn is_non_finite(v: &Var) -> bool {
+    matches!(v.variant(), Variant::Float(f) if !f.is_finite())
+}
+
+/// As in LambdaMOO, arithmetic never produces infinity (raising E_FLOAT instead) or NaN
+/// (raising E_INVARG).
+fn non_finite_error(v: &Var) -> Option<Error> {
+    match v.variant() {
+        Variant::Float(f) if f.is_nan() => {
+            Some(E_INVARG.msg("floating-point result is not a number"))
+        }
+        Variant::Float(f) if f.is_infinite() => {
+            Some(E_FLOAT.msg("floating-point result out of range"))
+        }
+        _ => None,
+    }
+}
+
 macro_rules! binary_bool_op {
     ( $f:ident, $op:tt, $bi:expr ) => {
         let rhs = $f.pop();
         let lhs = $f.peek_top();
+        if is_non_finite(lhs) || is_non_finite(&rhs) {
+            $f.pop();
+            return ExecutionResult::PushError(E_INVARG.msg("cannot compare NaN or infinity"));
+        }
         let bres : bool = *lhs $op rhs;
         let result = {
             if $bi {
@@ -54,7 +80,13 @@ macro_rules! binary_var_op {
         let lhs = $f.peek_top();
         let result = lhs.$op(&rhs);
         match result {
-            Ok(result) => $f.poke(0, result),
+            Ok(result) => match non_finite_error(&result) {
+                Some(err) => {
+                    $f.pop();
+                    return ExecutionResult::PushError(err);
+                }
+                None => $f.poke(0, result),
+            },
             Err(err_code) => {
                 $f.pop();
                 return ExecutionResult::PushError(err_code);
@@ -71,6 +103,7 @@ pub fn moo_frame_execute(
     f: &mut MooStackFrame,
     world_state: &mut dyn WorldState,
     features_config: &FeaturesConfig,
+    trace: bool,
 ) -> ExecutionResult {
     // Special case for empty opcodes set, just return v_none() immediately.
     if f.opcodes().is_empty() {
@@ -98,6 +131,15 @@ pub fn moo_frame_execute(
         let op = f.opcodes()[f.pc];
         f.pc += 1;
 
+        if trace {
+            info!(
+                target: VERB_TRACE_TARGET,
+                pc = f.pc - 1,
+                line = f.find_line_no(f.pc).unwrap_or(0),
+                ?op,
+            );
+        }
+
         match op {
             Op::If(label, environment_width)
             | Op::Eif(label, environment_width)
@@ -473,7 +515,20 @@ pub fn moo_frame_execute(
             }
             Op::In => {
                 let (lhs, rhs) = (f.pop(), f.peek_top());
-                let r = lhs.index_in(rhs, false, IndexMode::OneBased);
+                // `in` finds the (case-insensitive) position of a value among a list's elements,
+                // or a map's values; keys are checked with `maphaskey`. As in LambdaMOO, it is not
+                // a substring search, so strings (and binaries) are E_TYPE.
+                let r = match lhs.variant() {
+                    Variant::List(_) | Variant::Map(_) | Variant::Flyweight(_) => {
+                        lhs.index_in(rhs, false, IndexMode::OneBased)
+                    }
+                    _ => Err(E_TYPE.with_msg(|| {
+                        format!(
+                            "Cannot check for membership in type {}",
+                            lhs.type_code().to_literal()
+                        )
+                    })),
+                };
                 match r {
                     Ok(v) => {
                         f.poke(0, v);
@@ -677,7 +732,11 @@ pub fn moo_frame_execute(
                         f.poke(0, rhs);
                     }
                     Err(e) => {
-                        return ExecutionResult::PushError(e.to_error());
+                        return ExecutionResult::PushError(world_state_error(
+                            world_state,
+                            &permissions,
+                            &e,
+                        ));
                     }
                 }
             }
@@ -1066,7 +1125,7 @@ fn get_property(
             let result = world_state.retrieve_property(permissions, obj, propname);
             match result {
                 Ok(v) => Ok(v),
-                Err(e) => Err(e.to_error()),
+                Err(e) => Err(world_state_error(world_state, permissions, &e)),
             }
         }
         Variant::Flyweight(flyweight) => {
@@ -1099,7 +1158,7 @@ fn get_property(
                 let result = world_state.retrieve_property(permissions, delegate, propname);
                 match result {
                     Ok(v) => v,
-                    Err(e) => return Err(e.to_error()),
+                    Err(e) => return Err(world_state_error(world_state, permissions, &e)),
                 }
             };
             Ok(value)
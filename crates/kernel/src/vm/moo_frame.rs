@@ -17,10 +17,10 @@ use bincode::enc::Encoder;
 use bincode::error::{DecodeError, EncodeError};
 use bincode::{BorrowDecode, Decode, Encode};
 use moor_common::program::labels::Offset;
-use moor_common::program::names::{GlobalName, Name};
+use moor_common::program::names::{GlobalName, Name, VarName};
 use moor_compiler::{Label, Op, Program};
 use moor_var::VarType::TYPE_NONE;
-use moor_var::{Error, Var, v_none};
+use moor_var::{Error, Symbol, Var, v_none};
 use std::cmp::max;
 use strum::EnumCount;
 
@@ -120,7 +120,52 @@ impl MooStackFrame {
     }
 
     pub(crate) fn find_line_no(&self, pc: usize) -> Option<usize> {
-        Some(self.program.line_num_for_position(pc))
+        let line_no = match self.pc_type {
+            PcType::Main => self.program.line_num_for_position(pc),
+            PcType::ForkVector(fork_vector) => {
+                self.program.fork_line_num_for_position(fork_vector, pc)
+            }
+        };
+        Some(line_no)
+    }
+
+    /// If the next opcode to execute is the first of a source line, that line's number.
+    pub(crate) fn statement_start(&self) -> Option<usize> {
+        let spans = match self.pc_type {
+            PcType::Main => self.program.line_number_spans(),
+            PcType::ForkVector(fork_vector) => self
+                .program
+                .0
+                .fork_line_number_spans
+                .get(fork_vector.0 as usize)?
+                .as_slice(),
+        };
+        spans
+            .iter()
+            .find(|(offset, _)| *offset == self.pc)
+            .map(|(_, line)| *line)
+    }
+
+    /// The named variables which currently have a value in this frame, sorted by name.
+    pub(crate) fn locals(&self) -> Vec<(Symbol, Var)> {
+        let mut locals: Vec<_> = self
+            .program
+            .var_names()
+            .bound
+            .iter()
+            .filter_map(|(name, variable)| {
+                let VarName::Named(sym) = variable.nr else {
+                    return None;
+                };
+                let value = self
+                    .environment
+                    .get(name.1 as usize)?
+                    .get(name.0 as usize)?;
+                (value.type_code() != TYPE_NONE).then(|| (sym, value.clone()))
+            })
+            .collect();
+        locals.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        locals
     }
 
     #[inline]
@@ -129,6 +174,11 @@ impl MooStackFrame {
         self.environment[0][pos] = value;
     }
 
+    #[inline]
+    pub fn get_gvar(&self, gname: GlobalName) -> &Var {
+        &self.environment[0][gname as usize]
+    }
+
     #[inline]
     pub fn set_variable(&mut self, id: &Name, v: Var) {
         // This is a "trust us we know what we're doing" use of the explicit offset without check
@@ -0,0 +1,47 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Per-verb execution tracing, turned on with `set_verb_trace()`.
+//! Every opcode executed in a traced verb is logged, with its line, as a tracing event under
+//! `VERB_TRACE_TARGET`, so it goes wherever the host's tracing subscriber sends it.
+//! Trace flags are kept in memory only, and are lost on restart.
+
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use lazy_static::lazy_static;
+use uuid::Uuid;
+
+pub const VERB_TRACE_TARGET: &str = "moor::trace::verb";
+
+lazy_static! {
+    static ref TRACED_VERBS: RwLock<HashSet<Uuid>> = RwLock::new(HashSet::new());
+}
+
+/// Set whenever any verb is traced, so the common case doesn't have to take the lock.
+static ANY_TRACED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_verb_traced(verb: Uuid, traced: bool) {
+    let mut traced_verbs = TRACED_VERBS.write().unwrap();
+    if traced {
+        traced_verbs.insert(verb);
+    } else {
+        traced_verbs.remove(&verb);
+    }
+    ANY_TRACED.store(!traced_verbs.is_empty(), Ordering::Relaxed);
+}
+
+pub(crate) fn is_verb_traced(verb: Uuid) -> bool {
+    ANY_TRACED.load(Ordering::Relaxed) && TRACED_VERBS.read().unwrap().contains(&verb)
+}
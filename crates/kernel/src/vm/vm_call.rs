@@ -20,6 +20,7 @@ use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinRegistry, bf_perf_co
 use crate::vm::exec_state::VMExecState;
 use crate::vm::vm_host::ExecutionResult;
 use crate::vm::vm_unwind::FinallyReason;
+use crate::vm::world_state_error;
 
 use lazy_static::lazy_static;
 use minstant::Instant;
@@ -28,7 +29,8 @@ use moor_common::model::VerbDef;
 use moor_common::model::WorldState;
 use moor_common::model::WorldStateError;
 use moor_common::program::ProgramType;
-use moor_common::tasks::Session;
+use moor_common::program::names::GlobalName;
+use moor_common::tasks::{AbortLimitReason, Session};
 use moor_compiler::{BUILTINS, BuiltinId, Program};
 use moor_var::VarType::TYPE_NONE;
 use moor_var::{E_INVIND, E_PERM, E_TYPE, E_VERBNF};
@@ -48,6 +50,16 @@ lazy_static! {
     static ref FLYWEIGHT_SYM: Symbol = Symbol::mk("flyweight");
 }
 
+/// The variables describing the command being run, which a called verb inherits from its caller.
+const COMMAND_GLOBALS: [GlobalName; 6] = [
+    GlobalName::argstr,
+    GlobalName::dobj,
+    GlobalName::dobjstr,
+    GlobalName::prepstr,
+    GlobalName::iobj,
+    GlobalName::iobjstr,
+];
+
 /// The set of parameters for a scheduler-requested *resolved* verb method dispatch.
 #[derive(Debug, Clone, PartialEq)]
 pub struct VerbExecutionRequest {
@@ -223,7 +235,10 @@ impl VMExecState {
             Err(WorldStateError::RollbackRetry) => {
                 return ExecutionResult::TaskRollbackRestart;
             }
-            Err(e) => return self.raise_error(e.to_error()),
+            Err(e) => {
+                let e = world_state_error(world_state, permissions, &e);
+                return self.raise_error(e);
+            }
         };
         let verb = self.top().verb_name;
 
@@ -242,7 +257,10 @@ impl VMExecState {
                 Err(WorldStateError::RollbackRetry) => {
                     return ExecutionResult::TaskRollbackRestart;
                 }
-                Err(e) => return self.raise_error(e.to_error()),
+                Err(e) => {
+                    let e = world_state_error(world_state, permissions, &e);
+                    return self.raise_error(e);
+                }
             };
 
         let caller = self.caller();
@@ -269,7 +287,23 @@ impl VMExecState {
     /// (non-command) in this VM.
     /// Actually creates the activation record and puts it on the stack.
     pub fn exec_call_request(&mut self, call_request: Box<VerbExecutionRequest>) {
-        let a = Activation::for_call(call_request);
+        // As in LambdaMOO, a verb called from another verb -- directly or through pass() -- gets
+        // the command context as the calling verb currently has it, rather than as originally
+        // parsed, so any changes the caller made to e.g. `dobj` carry through.
+        let inherited: Option<Vec<(GlobalName, Var)>> =
+            self.stack.iter().rev().find_map(|a| match &a.frame {
+                Frame::Moo(frame) => Some(
+                    COMMAND_GLOBALS
+                        .iter()
+                        .map(|gname| (gname.clone(), frame.get_gvar(gname.clone()).clone()))
+                        .collect(),
+                ),
+                Frame::Bf(_) => None,
+            });
+        let mut a = Activation::for_call(call_request);
+        for (gname, value) in inherited.into_iter().flatten() {
+            a.frame.set_global_variable(gname, value);
+        }
         self.stack.push(a);
     }
 
@@ -379,6 +413,7 @@ impl VMExecState {
             flags,
             self.top().player,
         ));
+        let duration_limit = exec_args.config.builtin_duration_limit();
         let mut bf_args = BfCallState {
             exec_state: self,
             name: bf_name,
@@ -387,16 +422,25 @@ impl VMExecState {
             args,
             task_scheduler_client: exec_args.task_scheduler_client,
             config: exec_args.config,
+            rng: &exec_args.builtin_registry.rng,
+            deadline: duration_limit.map(|limit| std::time::Instant::now() + limit),
         };
         let bf_counters = bf_perf_counters();
         bf_counters.counter_for(bf_id).invocations.add(1);
 
         let result = bf(&mut bf_args);
-        let elapsed_nanos = start.elapsed().as_nanos();
+        let elapsed = start.elapsed();
         bf_counters
             .counter_for(bf_id)
             .cumulative_duration_nanos
-            .add(elapsed_nanos as isize);
+            .add(elapsed.as_nanos() as isize);
+
+        // Ticks don't advance while a builtin runs. Blocking work done through `run_blocking` is
+        // cut off at the deadline; anything else that ran long is caught here, once it returns.
+        // Whatever it came back with, the task is done.
+        if duration_limit.is_some_and(|limit| elapsed > limit) {
+            return ExecutionResult::TaskAbortLimit(AbortLimitReason::BuiltinDuration(bf_name));
+        }
         match result {
             Ok(BfRet::Ret(result)) => {
                 assert_ne!(
@@ -412,6 +456,13 @@ impl VMExecState {
             Err(BfErr::Code(c)) => self.push_bf_error(c.into()),
             Err(BfErr::Raise(e)) => self.push_bf_error(e),
             Err(BfErr::Rollback) => ExecutionResult::TaskRollbackRestart,
+            Err(BfErr::Database(e)) => {
+                let e = world_state_error(world_state, &self.task_perms(), &e);
+                self.push_bf_error(e)
+            }
+            Err(BfErr::DeadlineExceeded) => {
+                ExecutionResult::TaskAbortLimit(AbortLimitReason::BuiltinDuration(bf_name))
+            }
             Ok(BfRet::VmInstr(vmi)) => vmi,
         }
     }
@@ -442,6 +493,7 @@ impl VMExecState {
         let bf = exec_args.builtin_registry.builtin_for(&bf_id);
         let verb_name = self.top().verb_name;
         let args = self.top().args.clone();
+        let duration_limit = exec_args.config.builtin_duration_limit();
         let mut bf_args = BfCallState {
             exec_state: self,
             name: verb_name,
@@ -451,15 +503,20 @@ impl VMExecState {
             args,
             task_scheduler_client: exec_args.task_scheduler_client,
             config: exec_args.config,
+            rng: &exec_args.builtin_registry.rng,
+            deadline: duration_limit.map(|limit| std::time::Instant::now() + limit),
         };
 
         let result = bf(&mut bf_args);
-        let elapsed_nanos = start.elapsed().as_nanos();
+        let elapsed = start.elapsed();
         let bf_counters = bf_perf_counters();
         bf_counters
             .counter_for(bf_id)
             .cumulative_duration_nanos
-            .add(elapsed_nanos as isize);
+            .add(elapsed.as_nanos() as isize);
+        if duration_limit.is_some_and(|limit| elapsed > limit) {
+            return ExecutionResult::TaskAbortLimit(AbortLimitReason::BuiltinDuration(verb_name));
+        }
         match result {
             Ok(BfRet::Ret(result)) => self.unwind_stack(FinallyReason::Return(result.clone())),
             Ok(BfRet::RetNil) => self.unwind_stack(FinallyReason::Return(v_int(0))),
@@ -467,6 +524,13 @@ impl VMExecState {
             Err(BfErr::ErrValue(e)) => self.push_bf_error(e),
             Err(BfErr::Raise(e)) => self.push_bf_error(e),
             Err(BfErr::Rollback) => ExecutionResult::TaskRollbackRestart,
+            Err(BfErr::Database(e)) => {
+                let e = world_state_error(world_state, &self.task_perms(), &e);
+                self.push_bf_error(e)
+            }
+            Err(BfErr::DeadlineExceeded) => {
+                ExecutionResult::TaskAbortLimit(AbortLimitReason::BuiltinDuration(verb_name))
+            }
             Ok(BfRet::VmInstr(vmi)) => vmi,
         }
     }
@@ -18,9 +18,9 @@ use bincode::de::{BorrowDecoder, Decoder};
 use bincode::enc::Encoder;
 use bincode::error::{DecodeError, EncodeError};
 use bincode::{BorrowDecode, Decode, Encode};
-use tracing::{debug, error, warn};
+use tracing::{debug, error, info_span, warn};
 
-use moor_common::model::ObjFlag;
+use moor_common::model::{HasUuid, ObjFlag};
 use moor_common::model::{VerbDef, WorldState};
 use moor_common::tasks::{AbortLimitReason, TaskId};
 use moor_compiler::Program;
@@ -34,13 +34,15 @@ use moor_var::{Symbol, v_none};
 
 use crate::PhantomUnsync;
 use crate::config::FeaturesConfig;
+use crate::tasks::debugger::DebugFrame;
 use crate::tasks::task_scheduler_client::TaskSchedulerClient;
 use crate::vm::FinallyReason;
 use crate::vm::VMHostResponse::{AbortLimit, ContinueOk, DispatchFork, Suspend};
 use crate::vm::activation::Frame;
 use crate::vm::builtins::BuiltinRegistry;
-use crate::vm::exec_state::VMExecState;
+use crate::vm::exec_state::{Caller, VMExecState};
 use crate::vm::moo_execute::moo_frame_execute;
+use crate::vm::verb_trace::{VERB_TRACE_TARGET, is_verb_traced};
 use crate::vm::vm_call::VmExecParams;
 use crate::vm::{Fork, VMHostResponse, VerbExecutionRequest};
 use crate::vm::{TaskSuspend, VerbCall};
@@ -109,6 +111,9 @@ pub(crate) enum ExecutionResult {
     TaskRollbackRestart,
     /// Just rollback and die. Kills all task DB mutations. Output (Session) is optionally committed.
     TaskRollback(bool),
+    /// Abort the task for exceeding a limit which can only be detected mid-execution, such as a
+    /// builtin call having taken longer than the builtin duration limit.
+    TaskAbortLimit(AbortLimitReason),
 }
 
 /// A 'host' for running some kind of interpreter / virtual machine inside a running moor task.
@@ -123,6 +128,9 @@ pub struct VmHost {
     /// The maximum amount of time allotted to this task
     max_time: Duration,
     running: bool,
+    /// If set, the interpreter yields back to the task after every opcode, so that a debugger
+    /// can pause it at statement boundaries.
+    single_step: bool,
 
     unsync: PhantomUnsync,
 }
@@ -154,6 +162,7 @@ impl VmHost {
             max_ticks,
             max_time,
             running: false,
+            single_step: false,
             unsync: Default::default(),
         }
     }
@@ -281,11 +290,24 @@ impl VmHost {
             }
         };
 
-        // Grant the loop its next tick slice.
-        self.vm_exec_state.tick_slice = self.max_ticks - self.vm_exec_state.tick_count;
+        // Grant the loop its next tick slice, ending it early enough to cut off an `eval()`
+        // which runs out of its own budget.
+        self.vm_exec_state.tick_slice = if self.single_step {
+            1
+        } else {
+            let ticks_left = self.max_ticks - self.vm_exec_state.tick_count;
+            self.vm_exec_state
+                .eval_ticks_left()
+                .map_or(ticks_left, |eval_ticks_left| {
+                    eval_ticks_left.min(ticks_left)
+                })
+        };
 
         // Actually invoke the VM, asking it to loop until it's ready to yield back to us.
-        let mut result = self.run_interpreter(&exec_params, world_state, session);
+        let mut result = match self.vm_exec_state.cut_off_exhausted_eval() {
+            Some(result) => result,
+            None => self.run_interpreter(&exec_params, world_state, session),
+        };
         while self.is_running() {
             match result {
                 ExecutionResult::More => return ContinueOk,
@@ -397,6 +419,9 @@ impl VmHost {
                 ExecutionResult::TaskRollback(commit_session) => {
                     return VMHostResponse::CompleteRollback(commit_session);
                 }
+                ExecutionResult::TaskAbortLimit(reason) => {
+                    return AbortLimit(reason);
+                }
             }
         }
 
@@ -427,6 +452,22 @@ impl VmHost {
         // Pick the right kind of execution flow depending on the activation -- builtin or MOO?
         let mut tick_count = self.vm_exec_state.tick_count;
         let tick_slice = self.vm_exec_state.tick_slice;
+        let task_id = self.vm_exec_state.task_id;
+
+        // Traced verbs log each opcode, within a span saying which verb (and task) it is.
+        let trace = is_verb_traced(self.vm_exec_state.top().verbdef.uuid());
+        let _trace_span = trace.then(|| {
+            let activation = self.vm_exec_state.top();
+            info_span!(
+                target: VERB_TRACE_TARGET,
+                "verb_trace",
+                task_id,
+                task_name = self.vm_exec_state.task_name.as_deref(),
+                this = ?activation.this,
+                verb = %activation.verb_name,
+            )
+            .entered()
+        });
         let activation = self.vm_exec_state.top_mut();
 
         let (result, new_tick_count) = match &mut activation.frame {
@@ -438,6 +479,7 @@ impl VmHost {
                     fr,
                     world_state,
                     vm_exec_params.config,
+                    trace,
                 );
                 (result, tick_count)
             }
@@ -459,6 +501,10 @@ impl VmHost {
     pub fn resume_execution(&mut self, value: Var) {
         self.vm_exec_state.start_time = Some(SystemTime::now());
         self.vm_exec_state.tick_count = 0;
+        for budget in &mut self.vm_exec_state.eval_budgets {
+            budget.start_ticks = 0;
+            budget.start_time = SystemTime::now();
+        }
         self.running = true;
 
         // If there's no activations at all, that means we're a Fork, not returning to something.
@@ -512,6 +558,13 @@ impl VmHost {
     pub fn line_number(&self) -> usize {
         self.vm_exec_state.top().frame.find_line_no().unwrap_or(0)
     }
+    /// The whole activation stack, top first, as `callers()` describes it.
+    pub fn frames(&self) -> Vec<Caller> {
+        self.vm_exec_state.frames()
+    }
+    pub fn task_name(&self) -> Option<&str> {
+        self.vm_exec_state.task_name.as_deref()
+    }
 
     pub fn reset_ticks(&mut self) {
         self.vm_exec_state.tick_count = 0;
@@ -522,6 +575,30 @@ impl VmHost {
     pub fn reset_time(&mut self) {
         self.vm_exec_state.start_time = Some(SystemTime::now());
     }
+    pub(crate) fn set_single_step(&mut self, single_step: bool) {
+        self.single_step = single_step;
+    }
+    /// Don't count time spent paused in a debugger against the task's time limit.
+    pub(crate) fn extend_time(&mut self, paused: Duration) {
+        if let Some(start_time) = self.vm_exec_state.start_time.as_mut() {
+            *start_time += paused;
+        }
+    }
+    /// If the innermost activation is a MOO verb that is about to start a statement, describe it
+    /// for a debugger.
+    pub(crate) fn debug_frame(&self) -> Option<DebugFrame> {
+        let activation = self.vm_exec_state.stack.last()?;
+        let Frame::Moo(frame) = &activation.frame else {
+            return None;
+        };
+        let line = frame.statement_start()?;
+        Some(DebugFrame {
+            verb_name: activation.verb_name,
+            this: activation.this.clone(),
+            line,
+            locals: frame.locals(),
+        })
+    }
     pub fn args(&self) -> &List {
         &self.vm_exec_state.top().args
     }
@@ -554,6 +631,7 @@ impl<C> Decode<C> for VmHost {
             max_ticks,
             max_time,
             running: true,
+            single_step: false,
             unsync: Default::default(),
         })
     }
@@ -572,6 +650,7 @@ impl<'de, C> BorrowDecode<'de, C> for VmHost {
             max_ticks,
             max_time,
             running: true,
+            single_step: false,
             unsync: Default::default(),
         })
     }
@@ -14,6 +14,7 @@
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
+    use std::time::Duration;
 
     use moor_common::model::PropFlag;
     use moor_common::model::VerbArgsSpec;
@@ -29,15 +30,20 @@ mod tests {
     use moor_var::SYSTEM_OBJECT;
     use moor_var::*;
 
+    use crate::config::FeaturesConfig;
+    use crate::tasks::task_scheduler_client::TaskSchedulerClient;
     use crate::tasks::vm_test_utils::call_verb;
-    use crate::vm::builtins::BuiltinRegistry;
+    use crate::vm::builtins::{BfCallState, BfErr, BfRet, BuiltinRegistry};
+    use crate::vm::vm_host::VmHost;
+    use crate::vm::{VMHostResponse, VerbCall};
     use moor_common::program::ProgramType;
     use moor_common::program::program::PrgInner;
-    use moor_common::tasks::NoopClientSession;
+    use moor_common::tasks::{AbortLimitReason, NoopClientSession};
     use moor_compiler::Op;
     use moor_compiler::Op::*;
     use moor_compiler::Program;
     use moor_compiler::compile;
+    use moor_compiler::offset_for_builtin;
     use moor_compiler::{CompileOptions, Names};
     use moor_db::{DatabaseConfig, TxDB};
     use moor_var::Symbol;
@@ -56,6 +62,7 @@ mod tests {
             main_vector,
             fork_vectors: vec![],
             line_number_spans: vec![],
+            fork_line_number_spans: vec![],
         }))
     }
 
@@ -1276,6 +1283,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_for_in_map_key_order() {
+        // Maps are kept sorted by key (as in ToastStunt), whatever order the entries were added
+        // in, and iteration follows that order, with numbers ordered before strings.
+        let program = r#"
+        let m = ["c" -> 3, 2 -> "two", "a" -> 1];
+        m["b"] = 2;
+        m[1] = "one";
+        let keys = {};
+        for v, k in (m)
+            keys = {@keys, k};
+        endfor
+        return keys;
+        "#;
+        let mut state = world_with_test_program(program);
+        let session = Arc::new(NoopClientSession::new());
+        let result = call_verb(
+            state.as_mut(),
+            session,
+            BuiltinRegistry::new(),
+            "test",
+            List::mk_list(&[]),
+        );
+        assert_eq!(
+            result.unwrap(),
+            v_list(&[v_int(1), v_int(2), v_str("a"), v_str("b"), v_str("c")])
+        );
+    }
+
     #[test]
     fn test_for_v_k_in_list() {
         let program = r#"
@@ -1339,4 +1375,139 @@ mod tests {
         );
         assert_eq!(result.unwrap(), v_list(&[v_int(666), v_int(321)]));
     }
+
+    #[test]
+    fn test_traceback_line_numbers() {
+        // The fork body's spans used to be mixed in with the main vector's, so an error after a
+        // large enough fork was attributed to a line inside the fork body.
+        let program = r#"x = 1;
+y = 0;
+fork (0)
+  a = {1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16};
+  b = {1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16};
+endfork
+try
+  x = 2;
+  y = x / y;
+except e (E_DIV)
+  return e[4][1][6];
+endtry"#;
+        let mut state = world_with_test_program(program);
+        let session = Arc::new(NoopClientSession::new());
+        let result = call_verb(
+            state.as_mut(),
+            session,
+            BuiltinRegistry::new(),
+            "test",
+            List::mk_list(&[]),
+        );
+        assert_eq!(result, Ok(v_int(9)));
+    }
+
+    /// Stands in for a builtin that is stuck waiting on I/O.
+    fn bf_slow_stub(bf_args: &mut BfCallState<'_>) -> Result<BfRet, BfErr> {
+        bf_args.run_blocking(|| std::thread::sleep(Duration::from_secs(10)))?;
+        Ok(BfRet::RetNil)
+    }
+
+    #[test]
+    fn test_builtin_duration_limit_aborts_task() {
+        assert_eq!(
+            FeaturesConfig::default().builtin_duration_limit(),
+            Some(Duration::from_secs(5))
+        );
+        let no_limit = FeaturesConfig {
+            builtin_duration_limit_ms: 0,
+            ..Default::default()
+        };
+        assert_eq!(no_limit.builtin_duration_limit(), None);
+
+        let mut state = world_with_test_program("server_version(); return 1;");
+        let session = Arc::new(NoopClientSession::new());
+        let mut builtins = BuiltinRegistry::new();
+        Arc::get_mut(&mut builtins.builtins).unwrap()[offset_for_builtin("server_version")] =
+            Box::new(bf_slow_stub);
+        let config = FeaturesConfig {
+            builtin_duration_limit_ms: 50,
+            ..Default::default()
+        };
+        let (scs_tx, _scs_rx) = crossbeam_channel::unbounded();
+        let task_scheduler_client = TaskSchedulerClient::new(0, scs_tx);
+
+        let verb_name = Symbol::mk("test");
+        let (program, verbdef) = state
+            .find_method_verb_on(&SYSTEM_OBJECT, &SYSTEM_OBJECT, verb_name)
+            .unwrap();
+        let mut vm_host = VmHost::new(0, 20, 90_000, Duration::from_secs(5));
+        vm_host.start_call_method_verb(
+            0,
+            &SYSTEM_OBJECT,
+            (program, verbdef),
+            VerbCall {
+                verb_name,
+                location: v_obj(SYSTEM_OBJECT),
+                this: v_obj(SYSTEM_OBJECT),
+                player: SYSTEM_OBJECT,
+                args: List::mk_list(&[]),
+                argstr: "".to_string(),
+                caller: v_obj(SYSTEM_OBJECT),
+            },
+        );
+
+        // The task is aborted at the limit, not when the stuck call eventually returns.
+        let started = std::time::Instant::now();
+        let response = loop {
+            match vm_host.exec_interpreter(
+                0,
+                state.as_mut(),
+                &task_scheduler_client,
+                session.as_ref(),
+                &builtins,
+                &config,
+            ) {
+                VMHostResponse::ContinueOk => continue,
+                response => break response,
+            }
+        };
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let VMHostResponse::AbortLimit(AbortLimitReason::BuiltinDuration(name)) = response else {
+            panic!("Expected the builtin to exceed its duration limit");
+        };
+        assert_eq!(name, Symbol::mk("server_version"));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let test_binary = compile("return 1 + 2;", CompileOptions::default()).unwrap();
+        let disassemble_binary = compile(
+            r#"d = disassemble(#0, "test"); return d[("OPCODES:" in d) + 1..$];"#,
+            CompileOptions::default(),
+        )
+        .unwrap();
+        let mut state = test_db_with_verbs(&[
+            ("test", &test_binary),
+            ("disassemble_test", &disassemble_binary),
+        ])
+        .new_world_state()
+        .unwrap();
+        let session = Arc::new(NoopClientSession::new());
+        let result = call_verb(
+            state.as_mut(),
+            session,
+            BuiltinRegistry::new(),
+            "disassemble_test",
+            List::mk_list(&[]),
+        );
+        assert_eq!(
+            result,
+            Ok(v_list(&[
+                v_str("  0: ImmInt(1)\t\t(line 1)"),
+                v_str("  1: ImmInt(2)"),
+                v_str("  2: Add"),
+                v_str("  3: Return"),
+                v_str("  4: Pop"),
+                v_str("  5: Done"),
+            ]))
+        );
+    }
 }
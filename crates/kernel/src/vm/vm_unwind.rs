@@ -21,7 +21,7 @@ use moor_common::model::Named;
 use moor_common::model::VerbFlag;
 use moor_common::tasks::Exception;
 use moor_compiler::{BUILTINS, Label, Offset, to_literal};
-use moor_var::{Error, NOTHING, v_error, v_string};
+use moor_var::{E_QUOTA, Error, NOTHING, v_error, v_string};
 use moor_var::{Var, v_err, v_int, v_list, v_none, v_obj, v_str};
 
 #[derive(Clone, Eq, PartialEq, Debug, Decode, Encode)]
@@ -178,6 +178,15 @@ impl VMExecState {
         ExecutionResult::More
     }
 
+    /// Cut off the outermost `eval()` which has used up its budget, if any: the code it was
+    /// running is abandoned, and `eval()` itself fails with E_QUOTA, for its caller to deal with.
+    pub(crate) fn cut_off_exhausted_eval(&mut self) -> Option<ExecutionResult> {
+        let (depth, reason) = self.exhausted_eval_budget()?;
+        self.stack.truncate(depth + 1);
+        self.eval_budgets.retain(|b| b.depth < depth);
+        Some(self.push_bf_error(E_QUOTA.msg(reason)))
+    }
+
     /// Unwind the stack with the given reason and return an execution result back to the VM loop
     /// which makes its way back up to the scheduler.
     /// Contains all the logic for handling the various reasons for exiting a verb execution:
@@ -243,7 +252,14 @@ impl VMExecState {
                         }
                     }
                 }
-                Frame::Bf(_) => {
+                Frame::Bf(bf_frame) => {
+                    // The builtin wants to see errors raised out of the verb it dispatched, so
+                    // hand this one back to it rather than unwinding through.
+                    if let (true, FinallyReason::Raise(e)) = (bf_frame.catch_raise, &why) {
+                        bf_frame.catch_raise = false;
+                        bf_frame.raised = Some(e.clone());
+                        return ExecutionResult::More;
+                    }
                     // TODO: unwind builtin function frames here in a way that takes their
                     //   `return_value` (and maybe error state/) and propagates it up the stack.
                     //   This way things like push_bf_err can be removed.
@@ -253,6 +269,7 @@ impl VMExecState {
 
             // No match in the frame, so we pop it.
             self.stack.pop().expect("Stack underflow");
+            self.prune_eval_budgets();
 
             // No more frames to unwind, so break out and handle final exit.
             if self.stack.is_empty() {
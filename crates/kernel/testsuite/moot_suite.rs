@@ -15,25 +15,29 @@
 //!
 //! See example.moot for a full-fledged example
 
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use std::{path::Path, sync::Arc};
 
 use anstream::eprintln;
 use eyre::Context;
 
 use common::{create_db, testsuite_dir};
-use moor_common::tasks::{NoopClientSession, Session};
+use moor_common::model::ObjectRef;
+use moor_common::tasks::{AbortLimitReason, NoopClientSession, SchedulerError, Session};
 use moor_common::tasks::{NoopSystemControl, SessionError, SessionFactory};
 use moor_compiler::to_literal;
 use moor_db::Database;
-use moor_kernel::config::Config;
-use moor_kernel::tasks::NoopTasksDb;
+use moor_kernel::config::{Config, ImportExportConfig};
+use moor_kernel::tasks::debugger::DebugEvent;
+use moor_kernel::tasks::{NoopTasksDb, TaskResult};
 use moor_kernel::{
     SchedulerClient,
     tasks::{scheduler::Scheduler, scheduler_test_utils},
 };
 use moor_moot::stylesheet::MOOT_STYLESHEET;
-use moor_moot::{MootOptions, MootRunner, execute_moot_test};
-use moor_var::{Obj, Var, v_none};
+use moor_moot::{MootOptions, MootRunner, PROGRAMMER, WIZARD, execute_moot_test};
+use moor_var::{List, Obj, SYSTEM_OBJECT, Symbol, Var, v_int, v_none};
 
 mod common;
 
@@ -150,17 +154,21 @@ impl SessionFactory for NoopSessionFactory {
     }
 }
 
-fn test(db: Box<dyn Database>, path: &Path) {
-    if path.is_dir() {
-        return;
-    }
+fn start_scheduler(db: Box<dyn Database>) -> (SchedulerClient, JoinHandle<()>) {
+    start_scheduler_with_config(db, Config::default())
+}
+
+fn start_scheduler_with_config(
+    db: Box<dyn Database>,
+    config: Config,
+) -> (SchedulerClient, JoinHandle<()>) {
     let tasks_db = Box::new(NoopTasksDb {});
     let moot_version = semver::Version::new(0, 1, 0);
     let scheduler = Scheduler::new(
         moot_version,
         db,
         tasks_db,
-        Arc::new(Config::default()),
+        Arc::new(config),
         Arc::new(NoopSystemControl::default()),
         None,
         None,
@@ -171,6 +179,23 @@ fn test(db: Box<dyn Database>, path: &Path) {
         .name("moor-scheduler".to_string())
         .spawn(move || scheduler.run(session_factory.clone()))
         .expect("Failed to spawn scheduler");
+    (scheduler_client, scheduler_loop_jh)
+}
+
+fn stop_scheduler(scheduler_client: SchedulerClient, scheduler_loop_jh: JoinHandle<()>) {
+    scheduler_client
+        .submit_shutdown("Test is done")
+        .expect("Failed to shut down scheduler");
+    scheduler_loop_jh
+        .join()
+        .expect("Failed to join() scheduler");
+}
+
+fn test(db: Box<dyn Database>, path: &Path) {
+    if path.is_dir() {
+        return;
+    }
+    let (scheduler_client, scheduler_loop_jh) = start_scheduler(db);
 
     let options = MootOptions::default();
     execute_moot_test(
@@ -180,12 +205,220 @@ fn test(db: Box<dyn Database>, path: &Path) {
         || Ok(()),
     );
 
-    scheduler_client
-        .submit_shutdown("Test is done")
-        .expect("Failed to shut down scheduler");
-    scheduler_loop_jh
-        .join()
-        .expect("Failed to join() scheduler");
+    stop_scheduler(scheduler_client, scheduler_loop_jh);
+}
+
+/// Run `code` as the wizard in its own task.
+fn wizard_eval(scheduler_client: &SchedulerClient, code: &str) -> Result<Var, SchedulerError> {
+    scheduler_test_utils::call_eval(
+        scheduler_client.clone(),
+        Arc::new(NoopClientSession::new()),
+        &WIZARD,
+        code.to_string(),
+    )
+}
+
+/// A loop which runs out of ticks once $server_options.fg_ticks is 1000.
+const BUSY_LOOP: &str = "for i in [1..2000] endfor return 1;";
+
+/// Changing $server_options has no effect until the options are reloaded (as on SIGHUP), after
+/// which new tasks run under the new limits.
+#[test]
+fn test_reload_server_options() {
+    let (scheduler_client, scheduler_loop_jh) = start_scheduler(create_db());
+    let eval = |code: &str| wizard_eval(&scheduler_client, code);
+
+    eval(
+        r#"o = create($nothing); add_property(o, "fg_ticks", 1000, {player, "r"}); add_property(#0, "server_options", o, {player, "r"});"#,
+    )
+    .unwrap();
+    assert_eq!(eval(BUSY_LOOP).unwrap(), v_int(1));
+
+    scheduler_client.reload_server_options().unwrap();
+    assert!(matches!(
+        eval(BUSY_LOOP),
+        Err(SchedulerError::TaskAbortedLimit(AbortLimitReason::Ticks(_)))
+    ));
+
+    stop_scheduler(scheduler_client, scheduler_loop_jh);
+}
+
+/// load_server_options() applies $server_options as the calling task sees it, ignoring values
+/// that aren't usable, and only once the task commits.
+#[test]
+fn test_load_server_options() {
+    let (scheduler_client, scheduler_loop_jh) = start_scheduler(create_db());
+    let eval = |code: &str| wizard_eval(&scheduler_client, code);
+
+    eval(
+        r#"o = create($nothing); add_property(o, "fg_ticks", "lots", {player, "r"}); add_property(#0, "server_options", o, {player, "r"}); load_server_options();"#,
+    )
+    .unwrap();
+    assert_eq!(eval(BUSY_LOOP).unwrap(), v_int(1));
+
+    // Rolled back, so neither the new value nor the options loaded from it stick.
+    assert!(eval("$server_options.fg_ticks = 1000; load_server_options(); rollback();").is_err());
+    assert_eq!(eval(BUSY_LOOP).unwrap(), v_int(1));
+
+    eval("$server_options.fg_ticks = 1000; load_server_options();").unwrap();
+    assert!(matches!(
+        eval(BUSY_LOOP),
+        Err(SchedulerError::TaskAbortedLimit(AbortLimitReason::Ticks(_)))
+    ));
+
+    stop_scheduler(scheduler_client, scheduler_loop_jh);
+}
+
+/// dump_database() hands a checkpoint off to the scheduler, which writes a textdump into the
+/// configured output directory.
+#[test]
+fn test_dump_database() {
+    let output_dir = tempfile::tempdir().unwrap();
+    let config = Config {
+        import_export_config: ImportExportConfig {
+            output_path: Some(output_dir.path().to_path_buf()),
+            // Only the checkpoint we ask for.
+            checkpoint_interval: None,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (scheduler_client, scheduler_loop_jh) = start_scheduler_with_config(create_db(), config);
+    let session: Arc<dyn Session> = Arc::new(NoopClientSession::new());
+
+    let result = scheduler_test_utils::call_eval(
+        scheduler_client.clone(),
+        session,
+        &WIZARD,
+        "return dump_database();".to_string(),
+    );
+    assert_eq!(result.unwrap(), v_int(1));
+
+    // The dump is written in the background; wait for it to be renamed into place.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let textdump = loop {
+        let finished = std::fs::read_dir(output_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "moo-textdump"));
+        if let Some(textdump) = finished {
+            break textdump;
+        }
+        assert!(Instant::now() < deadline, "No textdump was written");
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    let contents = std::fs::read_to_string(textdump).unwrap();
+    assert!(contents.starts_with("Moor 0.1.0"));
+
+    stop_scheduler(scheduler_client, scheduler_loop_jh);
+}
+
+/// A player can kill the command they're running, as the telnet host does for Ctrl-C, but not
+/// someone else's.
+#[test]
+fn test_kill_running_command() {
+    let (scheduler_client, scheduler_loop_jh) = start_scheduler(create_db());
+    let session: Arc<dyn Session> = Arc::new(NoopClientSession::new());
+
+    scheduler_test_utils::call_eval(
+        scheduler_client.clone(),
+        session.clone(),
+        &WIZARD,
+        r#"add_verb(player, {player, "xd", "dawdle"}, {"none", "none", "none"}); set_verb_code(player, "dawdle", {"suspend(10);", "return 1;"});"#
+            .to_string(),
+    )
+    .unwrap();
+
+    let task_handle = scheduler_client
+        .submit_command_task(&SYSTEM_OBJECT, &WIZARD, "dawdle", session)
+        .unwrap();
+    let task_id = task_handle.task_id();
+    std::thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(
+        scheduler_client.kill_task(task_id, &PROGRAMMER),
+        Err(SchedulerError::TaskNotFound(task_id))
+    );
+    assert_eq!(scheduler_client.kill_task(task_id, &WIZARD), Ok(()));
+
+    // The task is gone without ever producing a result, and can't be killed twice.
+    assert!(
+        task_handle
+            .receiver()
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap_err()
+            .is_disconnected()
+    );
+    assert_eq!(
+        scheduler_client.kill_task(task_id, &WIZARD),
+        Err(SchedulerError::TaskNotFound(task_id))
+    );
+
+    stop_scheduler(scheduler_client, scheduler_loop_jh);
+}
+
+/// A debug verb task submitted through the scheduler pauses before its first statement, and can
+/// then be stepped and continued to completion.
+#[test]
+fn test_debug_verb_task() {
+    let (scheduler_client, scheduler_loop_jh) = start_scheduler(create_db());
+    let session: Arc<dyn Session> = Arc::new(NoopClientSession::new());
+
+    scheduler_test_utils::call_eval(
+        scheduler_client.clone(),
+        session.clone(),
+        &WIZARD,
+        r#"add_verb(player, {player, "xd", "stepper"}, {"this", "none", "this"}); set_verb_code(player, "stepper", {"x = 1;", "x = x + 10;", "return x * 2;"});"#
+            .to_string(),
+    )
+    .unwrap();
+
+    let (task_handle, debug_handle) = scheduler_client
+        .submit_debug_verb_task(
+            &WIZARD,
+            &ObjectRef::Id(WIZARD),
+            Symbol::mk("stepper"),
+            List::mk_list(&[]),
+            "".to_string(),
+            &WIZARD,
+            session,
+        )
+        .unwrap();
+
+    let next_pause = || {
+        let event = debug_handle.next_event(Duration::from_secs(5));
+        let Some(DebugEvent::Paused(frame)) = event else {
+            panic!("Expected a pause, got {:?}", event);
+        };
+        frame
+    };
+
+    let frame = next_pause();
+    assert_eq!(frame.line, 1);
+    assert_eq!(frame.local("x"), None);
+
+    debug_handle.step();
+    let frame = next_pause();
+    assert_eq!(frame.line, 2);
+    assert_eq!(frame.local("x"), Some(&v_int(1)));
+
+    debug_handle.cont();
+    assert_eq!(
+        debug_handle.next_event(Duration::from_secs(5)),
+        Some(DebugEvent::Finished)
+    );
+
+    let (_, result) = task_handle
+        .receiver()
+        .recv_timeout(Duration::from_secs(5))
+        .unwrap();
+    match result {
+        Ok(TaskResult::Result(value)) => assert_eq!(value, v_int(22)),
+        Ok(TaskResult::Replaced(_)) => panic!("Task was unexpectedly replaced"),
+        Err(e) => panic!("Expected a result, got {:?}", e),
+    }
+
+    stop_scheduler(scheduler_client, scheduler_loop_jh);
 }
 
 #[test]
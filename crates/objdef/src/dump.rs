@@ -327,7 +327,8 @@ pub fn dump_object_definitions(object_defs: &[ObjectDefinition], directory_path:
 
             // decompile the verb
             #[allow(irrefutable_let_patterns)]
-            let ProgramType::MooR(program) = &v.program else {
+            let (ProgramType::MooR(program) | ProgramType::MooRWithSource(program, _)) = &v.program
+            else {
                 panic!("Verb program is not Moo");
             };
             let decompiled = program_to_tree(program).expect("Failed to decompile verb binary");
@@ -12,6 +12,7 @@
 //
 
 use crate::ListenersClient;
+use crate::listen_address::{ListenAddressError, parse_listen_addresses};
 use crate::pubsub_client::hosts_events_recv;
 use crate::rpc_client::RpcSendClient;
 use rpc_common::{
@@ -183,45 +184,69 @@ pub async fn process_hosts_events(
                 host_type,
                 port,
                 print_messages: _,
+                tls,
             } => {
                 if host_type == our_host_type {
-                    let listen_addr = format!("{}:{}", listen_address, port);
-                    let sockaddr = listen_addr.parse::<SocketAddr>().unwrap();
-                    info!(
-                        "Starting listener for {} on {}",
-                        host_type.id_str(),
-                        sockaddr
-                    );
-                    let listeners = listeners.clone();
-                    tokio::spawn(async move {
-                        let sockaddr_sockaddr = listen_addr
-                            .parse::<SocketAddr>()
-                            .unwrap_or_else(|_| panic!("Unable to parse address: {}", listen_addr));
-                        if let Err(e) = listeners
-                            .add_listener(&handler_object, sockaddr_sockaddr)
-                            .await
-                        {
-                            error!("Error starting listener: {}", e);
+                    let sockaddrs = match listen_addresses_on_port(&listen_address, port).await {
+                        Ok(sockaddrs) => sockaddrs,
+                        Err(e) => {
+                            error!("Unable to start listener on port {}: {}", port, e);
+                            continue;
                         }
-                    });
+                    };
+                    for sockaddr in sockaddrs {
+                        info!(
+                            "Starting listener for {} on {}",
+                            host_type.id_str(),
+                            sockaddr
+                        );
+                        let listeners = listeners.clone();
+                        let tls = tls.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) =
+                                listeners.add_listener(&handler_object, sockaddr, tls).await
+                            {
+                                error!("Error starting listener: {}", e);
+                            }
+                        });
+                    }
                 }
             }
             HostBroadcastEvent::Unlisten { host_type, port } => {
                 if host_type == our_host_type {
                     // Stop listening on the given port, on `listen_address`.
-                    let listen_addr = format!("{}:{}", listen_address, port);
-                    let sockaddr = listen_addr.parse::<SocketAddr>().unwrap();
-                    info!(
-                        "Stopping listener for {} on {}",
-                        host_type.id_str(),
-                        sockaddr
-                    );
-                    listeners
-                        .remove_listener(sockaddr)
-                        .await
-                        .expect("Unable to stop listener");
+                    let sockaddrs = match listen_addresses_on_port(&listen_address, port).await {
+                        Ok(sockaddrs) => sockaddrs,
+                        Err(e) => {
+                            error!("Unable to stop listener on port {}: {}", port, e);
+                            continue;
+                        }
+                    };
+                    for sockaddr in sockaddrs {
+                        info!(
+                            "Stopping listener for {} on {}",
+                            host_type.id_str(),
+                            sockaddr
+                        );
+                        if let Err(e) = listeners.remove_listener(sockaddr).await {
+                            error!("Unable to stop listener on {}: {}", sockaddr, e);
+                        }
+                    }
                 }
             }
         }
     }
 }
+
+/// Resolve every host in the `listen_address` spec on the given `port`, ignoring any port the spec
+/// itself carries (that belongs to the host's default listener).
+async fn listen_addresses_on_port(
+    listen_address: &str,
+    port: u16,
+) -> Result<Vec<SocketAddr>, ListenAddressError> {
+    let mut sockaddrs = vec![];
+    for entry in parse_listen_addresses(listen_address)? {
+        sockaddrs.extend(entry.with_port(port).resolve(port).await?);
+    }
+    Ok(sockaddrs)
+}
@@ -16,11 +16,15 @@
 pub use host::{
     make_host_token, process_hosts_events, send_host_to_daemon_msg, start_host_session,
 };
+pub use listen_address::{
+    ListenAddress, ListenAddressError, parse_listen_addresses, resolve_listen_addresses,
+};
 pub use listeners::{ListenersClient, ListenersError, ListenersMessage};
 pub use worker::{attach_worker, make_worker_token};
 pub use worker_loop::{WorkerRpcError, worker_loop};
 pub use worker_rpc_client::WorkerRpcSendClient;
 mod host;
+mod listen_address;
 mod listeners;
 pub mod pubsub_client;
 pub mod rpc_client;
@@ -0,0 +1,275 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Parsing of operator-supplied listen addresses.
+//!
+//! A listen address spec is a comma separated list of entries, each of which is one of:
+//!   - an IPv4 address, optionally with a port: `0.0.0.0`, `127.0.0.1:8080`
+//!   - an IPv6 address, bare or bracketed, the latter optionally with a port: `::`, `[::1]:8080`
+//!   - a hostname, optionally with a port, resolved at bind time: `localhost:8080`
+
+use std::net::{IpAddr, SocketAddr};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ListenAddressError {
+    #[error("Empty listen address")]
+    Empty,
+    #[error("Invalid listen address {0:?}: {1}")]
+    Invalid(String, String),
+    #[error("Unable to resolve listen address {0:?}: {1}")]
+    Unresolvable(String, String),
+}
+
+/// One entry from a listen address spec, not yet resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenAddress {
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+impl ListenAddress {
+    /// Parse a single `host[:port]` entry.
+    pub fn parse(entry: &str) -> Result<Self, ListenAddressError> {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return Err(ListenAddressError::Empty);
+        }
+        let invalid = |why: &str| ListenAddressError::Invalid(entry.to_string(), why.to_string());
+
+        // Bracketed IPv6, e.g. `[::1]` or `[::1]:8080`.
+        if let Some(rest) = entry.strip_prefix('[') {
+            let Some((host, after)) = rest.split_once(']') else {
+                return Err(invalid("missing closing ']'"));
+            };
+            if host.parse::<std::net::Ipv6Addr>().is_err() {
+                return Err(invalid("not an IPv6 address"));
+            }
+            let port = match after {
+                "" => None,
+                _ => {
+                    let Some(port) = after.strip_prefix(':') else {
+                        return Err(invalid("unexpected characters after ']'"));
+                    };
+                    Some(port.parse::<u16>().map_err(|_| invalid("bad port"))?)
+                }
+            };
+            return Ok(Self {
+                host: host.to_string(),
+                port,
+            });
+        }
+
+        // A bare IP address (including unbracketed IPv6, which can't carry a port).
+        if let Ok(ip) = entry.parse::<IpAddr>() {
+            return Ok(Self {
+                host: ip.to_string(),
+                port: None,
+            });
+        }
+
+        // IPv4 or hostname with an optional port.
+        let (host, port) = match entry.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                Some(port.parse::<u16>().map_err(|_| invalid("bad port"))?),
+            ),
+            None => (entry, None),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+        if host.contains(':') {
+            return Err(invalid("IPv6 addresses with a port must be bracketed"));
+        }
+        if !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
+        {
+            return Err(invalid("bad hostname"));
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+        })
+    }
+
+    /// The same host, on a specific port.
+    pub fn with_port(&self, port: u16) -> Self {
+        Self {
+            host: self.host.clone(),
+            port: Some(port),
+        }
+    }
+
+    /// Resolve to socket addresses, using `default_port` if the entry didn't specify one.
+    /// IP literals are used as-is; hostnames are looked up.
+    pub async fn resolve(&self, default_port: u16) -> Result<Vec<SocketAddr>, ListenAddressError> {
+        let port = self.port.unwrap_or(default_port);
+        if let Ok(ip) = self.host.parse::<IpAddr>() {
+            return Ok(vec![SocketAddr::new(ip, port)]);
+        }
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((self.host.as_str(), port))
+            .await
+            .map_err(|e| ListenAddressError::Unresolvable(self.host.clone(), e.to_string()))?
+            .collect();
+        if addrs.is_empty() {
+            return Err(ListenAddressError::Unresolvable(
+                self.host.clone(),
+                "no addresses".to_string(),
+            ));
+        }
+        Ok(addrs)
+    }
+}
+
+/// Parse a comma separated listen address spec into its entries.
+pub fn parse_listen_addresses(spec: &str) -> Result<Vec<ListenAddress>, ListenAddressError> {
+    let entries: Vec<_> = spec
+        .split(',')
+        .filter(|e| !e.trim().is_empty())
+        .map(ListenAddress::parse)
+        .collect::<Result<_, _>>()?;
+    if entries.is_empty() {
+        return Err(ListenAddressError::Empty);
+    }
+    Ok(entries)
+}
+
+/// Parse and resolve a listen address spec, using `default_port` for entries without one.
+pub async fn resolve_listen_addresses(
+    spec: &str,
+    default_port: u16,
+) -> Result<Vec<SocketAddr>, ListenAddressError> {
+    let mut result = vec![];
+    for entry in parse_listen_addresses(spec)? {
+        for addr in entry.resolve(default_port).await? {
+            if !result.contains(&addr) {
+                result.push(addr);
+            }
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ipv4() {
+        assert_eq!(
+            ListenAddress::parse("0.0.0.0").unwrap(),
+            ListenAddress {
+                host: "0.0.0.0".to_string(),
+                port: None
+            }
+        );
+        assert_eq!(
+            ListenAddress::parse("127.0.0.1:8080").unwrap(),
+            ListenAddress {
+                host: "127.0.0.1".to_string(),
+                port: Some(8080)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_ipv6() {
+        assert_eq!(
+            ListenAddress::parse("[::1]:8080").unwrap(),
+            ListenAddress {
+                host: "::1".to_string(),
+                port: Some(8080)
+            }
+        );
+        assert_eq!(
+            ListenAddress::parse("[::]").unwrap(),
+            ListenAddress {
+                host: "::".to_string(),
+                port: None
+            }
+        );
+        assert_eq!(
+            ListenAddress::parse("::").unwrap(),
+            ListenAddress {
+                host: "::".to_string(),
+                port: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_hostname() {
+        assert_eq!(
+            ListenAddress::parse("localhost:7777").unwrap(),
+            ListenAddress {
+                host: "localhost".to_string(),
+                port: Some(7777)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(matches!(
+            ListenAddress::parse("[::1"),
+            Err(ListenAddressError::Invalid(_, _))
+        ));
+        assert!(matches!(
+            ListenAddress::parse("127.0.0.1:notaport"),
+            Err(ListenAddressError::Invalid(_, _))
+        ));
+        assert!(matches!(
+            ListenAddress::parse("not a host"),
+            Err(ListenAddressError::Invalid(_, _))
+        ));
+        assert!(matches!(
+            ListenAddress::parse(":8080"),
+            Err(ListenAddressError::Invalid(_, _))
+        ));
+        assert_eq!(ListenAddress::parse(""), Err(ListenAddressError::Empty));
+        assert_eq!(
+            parse_listen_addresses(" , "),
+            Err(ListenAddressError::Empty)
+        );
+    }
+
+    #[test]
+    fn test_parse_multiple() {
+        let entries = parse_listen_addresses("0.0.0.0:7777, [::]:7777").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].host, "::");
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let addrs = resolve_listen_addresses("[::1]:8080,127.0.0.1", 7777)
+            .await
+            .unwrap();
+        assert_eq!(
+            addrs,
+            vec![
+                "[::1]:8080".parse::<SocketAddr>().unwrap(),
+                "127.0.0.1:7777".parse::<SocketAddr>().unwrap()
+            ]
+        );
+
+        let addrs = resolve_listen_addresses("localhost", 7777).await.unwrap();
+        assert!(!addrs.is_empty());
+        assert!(
+            addrs
+                .iter()
+                .all(|a| a.port() == 7777 && a.ip().is_loopback())
+        );
+    }
+}
@@ -11,6 +11,7 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
+use moor_common::tasks::ListenerTls;
 use moor_var::Obj;
 use std::net::SocketAddr;
 
@@ -31,7 +32,9 @@ pub struct ListenersClient {
 }
 
 pub enum ListenersMessage {
-    AddListener(Obj, SocketAddr),
+    /// Listen on the address, handing connections to the object, and terminating TLS on them if
+    /// a certificate and key are given.
+    AddListener(Obj, SocketAddr, Option<ListenerTls>),
     RemoveListener(SocketAddr),
     GetListeners(tokio::sync::oneshot::Sender<Vec<(Obj, SocketAddr)>>),
 }
@@ -45,9 +48,10 @@ impl ListenersClient {
         &self,
         handler: &Obj,
         addr: SocketAddr,
+        tls: Option<ListenerTls>,
     ) -> Result<(), ListenersError> {
         self.listeners_channel
-            .send(ListenersMessage::AddListener(*handler, addr))
+            .send(ListenersMessage::AddListener(*handler, addr, tls))
             .await
             .map_err(|_| ListenersError::AddListenerFailed(*handler, addr))?;
         Ok(())
@@ -29,6 +29,21 @@ pub enum ConnectType {
     Created,
 }
 
+/// What came of a `LoginCommand`, as decided by the core's `do_login_command`.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode)]
+pub enum LoginOutcome {
+    /// Logged in: the auth token, the type of connection event, and the player.
+    Success(AuthToken, ConnectType, Obj),
+    /// Not logged in, but the connection carries on and may try again.
+    Failed,
+    /// Turned away, with a message for the user. The host should show it and then close the
+    /// connection.
+    Rejected(String),
+    /// Handed to another listener's handler object, which the host should send this
+    /// connection's login commands to from now on.
+    Redirect(Obj),
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Encode, Decode)]
 #[repr(u8)]
 pub enum EntityType {
@@ -72,7 +87,7 @@ pub enum HostClientToDaemonMessage {
     /// Anonymously request a sysprop (e.g. $login.welcome_message)
     RequestSysProp(ClientToken, ObjectRef, Symbol),
     /// Login using the words (e.g. "create player bob" or "connect player bob") and return an
-    /// auth token and the object id of the player. None if the login failed.
+    /// auth token and the object id of the player, or why not.
     LoginCommand(ClientToken, Obj, Vec<String>, bool /* attach? */),
     /// Attach to a previously-authenticated user, returning the object id of the player,
     /// and a client token -- or None if the auth token is not valid.
@@ -94,12 +109,22 @@ pub enum HostClientToDaemonMessage {
     RequestedInput(ClientToken, AuthToken, #[bincode(with_serde)] Uuid, String),
     /// Send an "out of band" command to be executed.
     OutOfBand(ClientToken, AuthToken, Obj, String),
+    /// Kill a task the player started, e.g. to interrupt a long-running command.
+    KillTask(ClientToken, AuthToken, usize /* task id */),
     /// Evaluate a MOO expression.
     Eval(ClientToken, AuthToken, String),
     /// Resolve an object reference into a Var
     Resolve(ClientToken, AuthToken, ObjectRef),
     /// Respond to a client ping request.
     ClientPong(ClientToken, SystemTime, Obj, HostType, SocketAddr),
+    /// Report attributes of the client's connection which the host has learned, e.g. its window
+    /// size, to be read back as connection options.
+    SetClientAttributes(ClientToken, Vec<(Symbol, Var)>),
+    /// Request the server's status, for reporting to MUD listing sites over e.g. MSSP.
+    RequestServerStatus(ClientToken),
+    /// Report whether a `ConfirmedNarrative` event with the given delivery id was written out to
+    /// the client.
+    DeliveryReport(ClientToken, #[bincode(with_serde)] Uuid, bool),
     /// We're done with this connection, buh-bye.
     Detach(ClientToken),
 }
@@ -111,8 +136,8 @@ pub enum DaemonToClientReply {
     /// A new non-logged-in connection has been established, tied to the given connection object.
     NewConnection(ClientToken, Obj),
     /// Here's the result of the LoginCommand you sent me. An AuthToken, the type of connection
-    /// event, and the player I've authenticated you against. If any.
-    LoginResult(Option<(AuthToken, ConnectType, Obj)>),
+    /// event, and the player I've authenticated you against. Or, if not, what to do instead.
+    LoginResult(LoginOutcome),
     /// Here's the result of the attachment request you sent me.
     AttachResult(Option<(ClientToken, Obj)>),
     /// Here's a value for the system property you asked for...
@@ -137,6 +162,14 @@ pub enum DaemonToClientReply {
     VerbValue(VerbInfo, Vec<String>),
     /// Response to `Resolve`
     ResolveResult(Var),
+    /// Response to `SetClientAttributes`.
+    ClientAttributesSet,
+    /// Response to `RequestServerStatus`: the server's status, as MSSP variable names and values.
+    ServerStatus(Vec<(String, String)>),
+    /// Response to `DeliveryReport`.
+    DeliveryReported,
+    /// Response to `KillTask`: the task is gone.
+    TaskKilled,
     /// This Client has been disconnected and is not expected to be heard from again.
     Disconnected,
 }
@@ -162,6 +195,10 @@ pub enum ClientEvent {
     /// An event has occurred in the narrative that the connections for the given object are
     /// expected to see.
     Narrative(Obj, NarrativeEvent),
+    /// As `Narrative`, but the sender is waiting to hear whether it got through: the host should
+    /// answer with a `DeliveryReport` RPC carrying the attached delivery id, once it has tried to
+    /// write the event out.
+    ConfirmedNarrative(Obj, NarrativeEvent, #[bincode(with_serde)] Uuid),
     /// The server wants the client to prompt the user for input, and the task this session is
     /// attached to will suspend until the client sends an RPC with a `RequestedInput` message and
     /// the attached request id.
@@ -170,6 +207,9 @@ pub enum ClientEvent {
     SystemMessage(Obj, String),
     /// The system wants to disconnect the given object from all its current active connections.
     Disconnect(),
+    /// The system wants the connections for the given object to change a connection option
+    /// (e.g. "echo"), which the host applies as appropriate to its transport.
+    SetConnectionOption(Obj, Symbol, Var),
     /// Task errors that should be sent to the client.
     TaskError(usize, SchedulerError),
     /// Task return common on success that the client can get.
@@ -12,6 +12,7 @@
 //
 
 use bincode::{Decode, Encode};
+use moor_common::tasks::ListenerTls;
 use moor_var::{Obj, Symbol};
 use std::net::SocketAddr;
 use std::time::SystemTime;
@@ -76,13 +77,14 @@ pub enum DaemonToHostReply {
 #[derive(Debug, Eq, PartialEq, Clone, Decode, Encode)]
 pub enum HostBroadcastEvent {
     /// The system is requesting that all hosts are of the given HostType begin listening on
-    /// the given port.
+    /// the given port, terminating TLS on it if `tls` is given.
     /// Triggered from the `listen` builtin.
     Listen {
         handler_object: Obj,
         host_type: HostType,
         port: u16,
         print_messages: bool,
+        tls: Option<ListenerTls>,
     },
     /// The system is requesting that all hosts of the given HostType stop listening on the given port.
     Unlisten { host_type: HostType, port: u16 },
@@ -17,7 +17,7 @@ use thiserror::Error;
 
 pub use client::{
     ClientEvent, ClientsBroadcastEvent, ConnectType, DaemonToClientReply, EntityType,
-    HostClientToDaemonMessage, PropInfo, VerbInfo, VerbProgramResponse,
+    HostClientToDaemonMessage, LoginOutcome, PropInfo, VerbInfo, VerbProgramResponse,
 };
 pub use host::{DaemonToHostReply, HostBroadcastEvent, HostToDaemonMessage, HostType};
 pub use worker::{DaemonToWorkerMessage, DaemonToWorkerReply, WorkerToDaemonMessage};
@@ -41,6 +41,11 @@ pub const HOST_BROADCAST_TOPIC: &[u8; 5] = b"hosts";
 /// A ZMQ topic for broadcasting to just the workers.
 pub const WORKER_BROADCAST_TOPIC: &[u8; 7] = b"workers";
 
+/// A ZMQ topic for the world's lifecycle events (objects created and recycled, verbs and
+/// properties added and removed), for external services such as search indexers. Only published
+/// to when the daemon is started with `--publish-lifecycle-events`.
+pub const LIFECYCLE_TOPIC: &[u8; 9] = b"lifecycle";
+
 /// Errors at the RPC transport / encoding layer.
 #[derive(Debug, Error)]
 pub enum RpcError {
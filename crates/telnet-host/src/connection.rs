@@ -16,6 +16,7 @@ use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 use std::time::SystemTime;
 
+use base64::{Engine, engine::general_purpose};
 use eyre::Context;
 use eyre::bail;
 use futures_util::SinkExt;
@@ -25,28 +26,39 @@ use moor_common::model::{CompileError, ObjectRef};
 use moor_common::tasks::{AbortLimitReason, CommandError, Event, SchedulerError, VerbProgramError};
 use moor_common::util::parse_into_words;
 use moor_compiler::to_literal;
-use moor_var::{Obj, Symbol, Variant};
+use moor_var::{Obj, Symbol, Var, Variant, v_int};
 use rpc_async_client::pubsub_client::{broadcast_recv, events_recv};
 use rpc_async_client::rpc_client::RpcSendClient;
 use rpc_common::{
-    AuthToken, ClientEvent, ClientToken, ClientsBroadcastEvent, ConnectType, HostType, ReplyResult,
-    RpcMessageError, VerbProgramResponse,
+    AuthToken, ClientEvent, ClientToken, ClientsBroadcastEvent, ConnectType, HostType,
+    LoginOutcome, ReplyResult, RpcMessageError, VerbProgramResponse,
 };
 use rpc_common::{DaemonToClientReply, HostClientToDaemonMessage};
 use termimad::MadSkin;
 use tmq::subscribe::Subscribe;
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::select;
-use tokio_util::codec::{Framed, LinesCodec};
+use tokio_util::codec::Framed;
 use tracing::{debug, error, info, trace, warn};
 use uuid::Uuid;
 
+use crate::mcp::McpMultiline;
+use crate::telnet::{DEFAULT_WINDOW_SIZE, TelnetCodec, TelnetInput, TelnetOutput};
+
 /// Out of band messages are prefixed with this string, e.g. for MCP clients.
 const OUT_OF_BAND_PREFIX: &str = "#$#";
 
+/// A line which kills the running command, for clients that can't send IAC IP.
+const ABORT_COMMAND: &str = "#$#abort";
+
 // TODO: switch to djot
 const CONTENT_TYPE_MARKDOWN: &str = "text/markdown";
 
+/// The transport under a telnet connection: either a plain TCP stream, or TLS over one.
+pub(crate) trait ConnectionStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T> ConnectionStream for T where T: AsyncRead + AsyncWrite + Unpin + Send {}
+pub(crate) type TelnetStream = Box<dyn ConnectionStream>;
+
 pub(crate) struct TelnetConnection {
     pub(crate) peer_addr: SocketAddr,
     /// The "handler" object, who is responsible for this connection, defaults to SYSTEM_OBJECT,
@@ -57,9 +69,12 @@ pub(crate) struct TelnetConnection {
     pub(crate) client_id: Uuid,
     /// Current PASETO token.
     pub(crate) client_token: ClientToken,
-    pub(crate) write: SplitSink<Framed<TcpStream, LinesCodec>, String>,
-    pub(crate) read: SplitStream<Framed<TcpStream, LinesCodec>>,
+    pub(crate) write: SplitSink<Framed<TelnetStream, TelnetCodec>, TelnetOutput>,
+    pub(crate) read: SplitStream<Framed<TelnetStream, TelnetCodec>>,
     pub(crate) kill_switch: Arc<AtomicBool>,
+    /// Whether the client has asked (via the `oob-binary` connection option) for binary content
+    /// to be sent to it out of band, rather than just a notice of it.
+    pub(crate) oob_binary: bool,
 }
 
 /// The input modes the telnet session can be in.
@@ -73,6 +88,21 @@ enum LineMode {
     SpoolingProgram(String, String),
 }
 
+/// The line telling the client about binary content: for clients which asked for it, an
+/// out-of-band line carrying it base64 encoded, e.g.
+/// `#$#moor-binary content-type: "image/png" length: 1234 data: iVBORw0...`, and for others a
+/// notice of what was sent.
+fn binary_output(content_type: Symbol, bytes: &[u8], oob_binary: bool) -> String {
+    if !oob_binary {
+        return format!("[binary content: {content_type}, {} bytes]", bytes.len());
+    }
+    format!(
+        "#$#moor-binary content-type: \"{content_type}\" length: {} data: {}",
+        bytes.len(),
+        general_purpose::STANDARD.encode(bytes)
+    )
+}
+
 fn describe_compile_error(compile_error: CompileError) -> String {
     match compile_error {
         CompileError::StringLexError(_, le) => {
@@ -129,20 +159,14 @@ impl TelnetConnection {
         broadcast_sub: &mut Subscribe,
         rpc_client: &mut RpcSendClient,
     ) -> Result<(), eyre::Error> {
-        // Provoke welcome message, which is a login command with no arguments, and we
-        // don't care about the reply at this point.
-        rpc_client
-            .make_client_rpc_call(
-                self.client_id,
-                HostClientToDaemonMessage::LoginCommand(
-                    self.client_token.clone(),
-                    self.handler_object,
-                    vec![],
-                    false,
-                ),
-            )
-            .await
-            .expect("Unable to send login request to RPC server");
+        self.write.send(TelnetOutput::offer_compression()).await?;
+        self.write.send(TelnetOutput::request_window_size()).await?;
+        self.write.send(TelnetOutput::offer_mssp()).await?;
+        // Until (unless) the client tells us otherwise.
+        let (width, height) = DEFAULT_WINDOW_SIZE;
+        self.report_window_size(rpc_client, width, height).await?;
+
+        self.provoke_welcome(rpc_client).await;
 
         let Ok((auth_token, player, connect_type)) = self
             .authorization_phase(events_sub, broadcast_sub, rpc_client)
@@ -156,7 +180,7 @@ impl TelnetConnection {
             ConnectType::Reconnected => "*** Reconnected ***",
             ConnectType::Created => "*** Created ***",
         };
-        self.write.send(connect_message.to_string()).await?;
+        self.write.send(connect_message.to_string().into()).await?;
 
         debug!(?player, client_id = ?self.client_id, "Entering command dispatch loop");
         if self
@@ -178,13 +202,40 @@ impl TelnetConnection {
         Ok(())
     }
 
+    /// Provoke the welcome message, which is a login command with no arguments, and we don't
+    /// care about the reply at this point.
+    async fn provoke_welcome(&self, rpc_client: &mut RpcSendClient) {
+        rpc_client
+            .make_client_rpc_call(
+                self.client_id,
+                HostClientToDaemonMessage::LoginCommand(
+                    self.client_token.clone(),
+                    self.handler_object,
+                    vec![],
+                    false,
+                ),
+            )
+            .await
+            .expect("Unable to send login request to RPC server");
+    }
+
+    /// Show the user why their login was turned away, and then hang up on them.
+    async fn reject_login(&mut self, message: String) -> Result<(), eyre::Error> {
+        self.write
+            .send(message.into())
+            .await
+            .with_context(|| "Unable to send message to client")?;
+        self.write.close().await?;
+        Ok(())
+    }
+
     async fn output(&mut self, event: Event) -> Result<(), eyre::Error> {
         match event {
             Event::Notify(msg, content_type) => match msg.variant() {
                 Variant::Str(msg_text) => {
                     let formatted = output_format(msg_text.as_str(), content_type);
                     self.write
-                        .send(formatted)
+                        .send(formatted.into())
                         .await
                         .with_context(|| "Unable to send message to client")?;
                 }
@@ -196,32 +247,39 @@ impl TelnetConnection {
                         };
                         let formatted = output_format(line, content_type);
                         self.write
-                            .send(formatted)
+                            .send(formatted.into())
                             .await
                             .with_context(|| "Unable to send message to client")?;
                     }
                 }
                 _ => {
                     self.write
-                        .send(to_literal(&msg))
+                        .send(to_literal(&msg).into())
                         .await
                         .with_context(|| "Unable to send message to client")?;
                 }
             },
+            Event::Binary(content_type, bytes) => {
+                let line = binary_output(content_type, &bytes, self.oob_binary);
+                self.write
+                    .send(line.into())
+                    .await
+                    .with_context(|| "Unable to send message to client")?;
+            }
             Event::Traceback(e) => {
                 for frame in e.backtrace {
                     let Some(s) = frame.as_string() else {
                         continue;
                     };
                     self.write
-                        .send(s.to_string())
+                        .send(s.to_string().into())
                         .await
                         .with_context(|| "Unable to send message to client")?;
                 }
             }
             _ => {
                 self.write
-                    .send(format!("Unsupported event for telnet: {:?}", event))
+                    .send(format!("Unsupported event for telnet: {:?}", event).into())
                     .await
                     .with_context(|| "Unable to send message to client")?;
             }
@@ -252,11 +310,14 @@ impl TelnetConnection {
                     trace!(?event, "narrative_event");
                     match event {
                         ClientEvent::SystemMessage(_author, msg) => {
-                            self.write.send(msg).await.with_context(|| "Unable to send message to client")?;
+                            self.write.send(msg.into()).await.with_context(|| "Unable to send message to client")?;
                         }
                         ClientEvent::Narrative(_author, event) => {
                             self.output(event.event()).await?;
                         }
+                        ClientEvent::ConfirmedNarrative(_author, event, delivery_id) => {
+                            self.output_confirmed(rpc_client, event.event(), delivery_id).await?;
+                        }
                         ClientEvent::RequestInput(_request_id) => {
                             bail!("RequestInput before login");
                         }
@@ -264,6 +325,9 @@ impl TelnetConnection {
                             self.write.close().await?;
                             bail!("Disconnect before login");
                         }
+                        ClientEvent::SetConnectionOption(_author, option, value) => {
+                            self.set_connection_option(option, value).await?;
+                        }
                         ClientEvent::TaskError(_ti, te) => {
                             self.handle_task_error(te).await?;
                         }
@@ -274,18 +338,48 @@ impl TelnetConnection {
                     }
                 }
                 // Auto loop
-                line = self.read.next() => {
-                    let Some(line) = line else {
+                input = self.read.next() => {
+                    let Some(input) = input else {
                         bail!("Connection closed before login");
                     };
-                    let line = line.unwrap();
+                    let line = match input.unwrap() {
+                        TelnetInput::Line(line) => line,
+                        // In character mode, each keypress is a line of its own.
+                        TelnetInput::Char(c) => c.to_string(),
+                        TelnetInput::WindowSize(width, height) => {
+                            self.report_window_size(rpc_client, width, height).await?;
+                            continue;
+                        }
+                        TelnetInput::MsspRequest => {
+                            self.send_server_status(rpc_client).await?;
+                            continue;
+                        }
+                        // Nothing is running on the user's behalf before they've logged in.
+                        TelnetInput::Interrupt => continue,
+                    };
                     let words = parse_into_words(&line);
                     let response = rpc_client.make_client_rpc_call(self.client_id,
                         HostClientToDaemonMessage::LoginCommand(self.client_token.clone(), self.handler_object, words, true)).await.expect("Unable to send login request to RPC server");
-                    if let ReplyResult::ClientSuccess(DaemonToClientReply::LoginResult(Some((auth_token, connect_type, player)))) = response {
-                        info!(?player, client_id = ?self.client_id, "Login successful");
-                        self.connection_oid = player;
-                        return Ok((auth_token, player, connect_type))
+                    let ReplyResult::ClientSuccess(DaemonToClientReply::LoginResult(outcome)) = response else {
+                        continue;
+                    };
+                    match outcome {
+                        LoginOutcome::Success(auth_token, connect_type, player) => {
+                            info!(?player, client_id = ?self.client_id, "Login successful");
+                            self.connection_oid = player;
+                            return Ok((auth_token, player, connect_type))
+                        }
+                        LoginOutcome::Failed => {}
+                        LoginOutcome::Rejected(message) => {
+                            info!(client_id = ?self.client_id, "Login rejected");
+                            self.reject_login(message).await?;
+                            bail!("Login rejected");
+                        }
+                        LoginOutcome::Redirect(handler_object) => {
+                            info!(?handler_object, client_id = ?self.client_id, "Login redirected");
+                            self.handler_object = handler_object;
+                            self.provoke_welcome(rpc_client).await;
+                        }
                     }
                 }
             }
@@ -301,17 +395,40 @@ impl TelnetConnection {
     ) -> Result<(), eyre::Error> {
         let mut line_mode = LineMode::Input;
         let mut program_input = vec![];
+        let mut mcp_multiline = McpMultiline::default();
+        // The task running the last command typed, until we hear that it's finished.
+        let mut command_task = None;
         loop {
             if self.kill_switch.load(std::sync::atomic::Ordering::Relaxed) {
                 return Ok(());
             }
             select! {
-                line = self.read.next() => {
-                    let Some(line) = line else {
+                input = self.read.next() => {
+                    let Some(input) = input else {
                         info!("Connection closed");
                         return Ok(());
                     };
-                    let line = line.unwrap();
+                    let line = match input.unwrap() {
+                        TelnetInput::Line(line) => line,
+                        // In character mode, each keypress is a line of its own.
+                        TelnetInput::Char(c) => c.to_string(),
+                        TelnetInput::WindowSize(width, height) => {
+                            self.report_window_size(rpc_client, width, height).await?;
+                            continue;
+                        }
+                        TelnetInput::MsspRequest => {
+                            self.send_server_status(rpc_client).await?;
+                            continue;
+                        }
+                        TelnetInput::Interrupt => {
+                            // A command waiting on read() goes with it.
+                            if let LineMode::WaitingReply(_) = line_mode {
+                                line_mode = LineMode::Input;
+                            }
+                            self.abort_command(rpc_client, &auth_token, &mut command_task).await?;
+                            continue;
+                        }
+                    };
 
                     let response = match line_mode.clone() {
                         LineMode::Input => {
@@ -322,12 +439,12 @@ impl TelnetConnection {
                                 let words = parse_into_words(&line);
                                 let usage_msg = "Usage: .program <target>:<verb>";
                                 if words.len() != 2 {
-                                    self.write.send(usage_msg.to_string()).await?;
+                                    self.write.send(usage_msg.to_string().into()).await?;
                                     continue
                                 }
                                 let verb_spec = words[1].split(':').collect::<Vec<_>>();
                                 if verb_spec.len() != 2 {
-                                    self.write.send(usage_msg.to_string()).await?;
+                                    self.write.send(usage_msg.to_string().into()).await?;
                                     continue
                                 }
                                 let target = verb_spec[0].to_string();
@@ -335,29 +452,42 @@ impl TelnetConnection {
 
                                 // verb must be a valid identifier
                                 if !verb.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                                    self.write.send("You must specify a verb; use the format object:verb.".to_string()).await?;
+                                    self.write.send("You must specify a verb; use the format object:verb.".to_string().into()).await?;
                                     continue
                                 }
 
                                 // target should be a valid object #number, $objref, ident, or
                                 //  a string inside quotes
                                 if !target.starts_with('$') && !target.starts_with('#') && !target.starts_with('"') && !target.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                                    self.write.send("You must specify a target; use the format object:verb.".to_string()).await?;
+                                    self.write.send("You must specify a target; use the format object:verb.".to_string().into()).await?;
                                     continue
                                 }
 
-                                self.write.send(format!("Now programming {}. Use \".\" to end.", words[1])).await?;
+                                self.write.send(format!("Now programming {}. Use \".\" to end.", words[1]).into()).await?;
 
                                 line_mode = LineMode::SpoolingProgram(target, verb);
                                 continue
                             }
 
+                            if line.trim() == ABORT_COMMAND {
+                                self.abort_command(rpc_client, &auth_token, &mut command_task).await?;
+                                continue
+                            }
+
                             // If the line begins with the out of band prefix, then send it that way,
-                            // instead. And really just fire and forget.
+                            // instead. And really just fire and forget. Multiline MCP messages are
+                            // held back until the whole of them has arrived.
                             if line.starts_with(OUT_OF_BAND_PREFIX) {
+                                let Some(line) = mcp_multiline.feed(line) else {
+                                    continue
+                                };
                                 rpc_client.make_client_rpc_call(self.client_id, HostClientToDaemonMessage::OutOfBand(self.client_token.clone(), auth_token.clone(), self.handler_object, line)).await?
                             } else {
-                                rpc_client.make_client_rpc_call(self.client_id, HostClientToDaemonMessage::Command(self.client_token.clone(), auth_token.clone(), self.handler_object, line)).await?
+                                let reply = rpc_client.make_client_rpc_call(self.client_id, HostClientToDaemonMessage::Command(self.client_token.clone(), auth_token.clone(), self.handler_object, line)).await?;
+                                if let ReplyResult::ClientSuccess(DaemonToClientReply::TaskSubmitted(task_id)) = &reply {
+                                    command_task = Some(*task_id);
+                                }
+                                reply
                             }
                         },
                         // Are we expecting to respond to prompt input? If so, send this through to that, and switch the mode back to input
@@ -402,14 +532,14 @@ impl TelnetConnection {
                         ReplyResult::ClientSuccess(DaemonToClientReply::ProgramResponse(resp)) => {
                             match resp {
                                 VerbProgramResponse::Success(o,verb) => {
-                                    self.write.send(format!("0 error(s).\nVerb {} programmed on object {}", verb, o)).await?;
+                                    self.write.send(format!("0 error(s).\nVerb {} programmed on object {}", verb, o).into()).await?;
                                 }
                                 VerbProgramResponse::Failure(VerbProgramError::CompilationError(e)) => {
                                     let desc = describe_compile_error(e);
-                                    self.write.send(desc).await?;
+                                    self.write.send(desc.into()).await?;
                                 }
                                 VerbProgramResponse::Failure(VerbProgramError::NoVerbToProgram) => {
-                                    self.write.send("That object does not have that verb.".to_string()).await?;
+                                    self.write.send("That object does not have that verb.".to_string().into()).await?;
                                 }
                                 VerbProgramResponse::Failure(e) => {
                                     error!("Unhandled verb program error: {:?}", e);
@@ -435,25 +565,37 @@ impl TelnetConnection {
                 Ok(event) = events_recv(self.client_id, events_sub) => {
                     match event {
                         ClientEvent::SystemMessage(_author, msg) => {
-                            self.write.send(msg).await.with_context(|| "Unable to send message to client")?;
+                            self.write.send(msg.into()).await.with_context(|| "Unable to send message to client")?;
                         }
                         ClientEvent::Narrative(_author, event) => {
                             self.output(event.event()).await?;
                         }
+                        ClientEvent::ConfirmedNarrative(_author, event, delivery_id) => {
+                            self.output_confirmed(rpc_client, event.event(), delivery_id).await?;
+                        }
                         ClientEvent::RequestInput(request_id) => {
                             // Server is requesting that the next line of input get sent through as a response to this request.
                             line_mode = LineMode::WaitingReply(request_id);
                         }
                         ClientEvent::Disconnect() => {
-                            self.write.send("** Disconnected **".to_string()).await.expect("Unable to send disconnect message to client");
+                            self.write.send("** Disconnected **".to_string().into()).await.expect("Unable to send disconnect message to client");
                             self.write.close().await.expect("Unable to close connection");
                             return Ok(())
                         }
-                        ClientEvent::TaskError(_ti, te) => {
+                        ClientEvent::SetConnectionOption(_author, option, value) => {
+                            self.set_connection_option(option, value).await?;
+                        }
+                        ClientEvent::TaskError(task_id, te) => {
+                            if command_task == Some(task_id) {
+                                command_task = None;
+                            }
                             self.handle_task_error(te).await?;
                         }
-                        ClientEvent::TaskSuccess(_ti, _result) => {
+                        ClientEvent::TaskSuccess(task_id, _result) => {
                             // We don't need to do anything with successes.
+                            if command_task == Some(task_id) {
+                                command_task = None;
+                            }
                         }
                     }
                 }
@@ -461,55 +603,243 @@ impl TelnetConnection {
         }
     }
 
+    /// Kill the task running the connection's last command, if it's still going.
+    async fn abort_command(
+        &mut self,
+        rpc_client: &mut RpcSendClient,
+        auth_token: &AuthToken,
+        command_task: &mut Option<usize>,
+    ) -> Result<(), eyre::Error> {
+        let Some(task_id) = command_task.take() else {
+            self.write
+                .send("There is no command to abort.".to_string().into())
+                .await?;
+            return Ok(());
+        };
+        let reply = rpc_client
+            .make_client_rpc_call(
+                self.client_id,
+                HostClientToDaemonMessage::KillTask(
+                    self.client_token.clone(),
+                    auth_token.clone(),
+                    task_id,
+                ),
+            )
+            .await?;
+        match reply {
+            ReplyResult::ClientSuccess(DaemonToClientReply::TaskKilled) => {
+                self.write.send("Aborted.".to_string().into()).await?;
+            }
+            // It finished before we got to it.
+            ReplyResult::Failure(RpcMessageError::TaskError(SchedulerError::TaskNotFound(_))) => {
+                self.write
+                    .send("There is no command to abort.".to_string().into())
+                    .await?;
+            }
+            // Otherwise it may well still be running, so keep hold of it to try again.
+            ReplyResult::Failure(e) => {
+                warn!(?e, task_id, "Unable to kill task");
+                *command_task = Some(task_id);
+                self.write
+                    .send(format!("Unable to abort the command: {e}").into())
+                    .await?;
+            }
+            reply => {
+                error!(?reply, "Unexpected reply to task kill");
+                *command_task = Some(task_id);
+                self.write
+                    .send("Unable to abort the command.".to_string().into())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Output an event whose sender is waiting to hear whether it got through, and tell the
+    /// daemon whether it did.
+    async fn output_confirmed(
+        &mut self,
+        rpc_client: &mut RpcSendClient,
+        event: Event,
+        delivery_id: Uuid,
+    ) -> Result<(), eyre::Error> {
+        let result = self.output(event).await;
+        let response = rpc_client
+            .make_client_rpc_call(
+                self.client_id,
+                HostClientToDaemonMessage::DeliveryReport(
+                    self.client_token.clone(),
+                    delivery_id,
+                    result.is_ok(),
+                ),
+            )
+            .await?;
+        if let ReplyResult::Failure(e) = response {
+            warn!(?e, "Unable to report delivery");
+        }
+        result
+    }
+
+    async fn set_connection_option(
+        &mut self,
+        option: Symbol,
+        value: Var,
+    ) -> Result<(), eyre::Error> {
+        match option.as_str() {
+            "echo" => {
+                self.write
+                    .send(TelnetOutput::echo(value.is_true()))
+                    .await
+                    .with_context(|| "Unable to send echo negotiation to client")?;
+            }
+            "char-mode" => {
+                self.write
+                    .send(TelnetOutput::CharMode(value.is_true()))
+                    .await
+                    .with_context(|| "Unable to send character mode negotiation to client")?;
+            }
+            "oob-binary" => {
+                self.oob_binary = value.is_true();
+            }
+            _ => {
+                warn!(?option, "Unsupported connection option for telnet");
+            }
+        }
+        Ok(())
+    }
+
+    /// Tell the daemon the client's window size, for the `window-width` and `window-height`
+    /// connection options.
+    async fn report_window_size(
+        &mut self,
+        rpc_client: &mut RpcSendClient,
+        width: u16,
+        height: u16,
+    ) -> Result<(), eyre::Error> {
+        let attributes = vec![
+            (Symbol::mk("window-width"), v_int(width as i64)),
+            (Symbol::mk("window-height"), v_int(height as i64)),
+        ];
+        let response = rpc_client
+            .make_client_rpc_call(
+                self.client_id,
+                HostClientToDaemonMessage::SetClientAttributes(
+                    self.client_token.clone(),
+                    attributes,
+                ),
+            )
+            .await?;
+        if let ReplyResult::Failure(e) = response {
+            warn!(?e, "Unable to report window size");
+        }
+        Ok(())
+    }
+
+    /// Answer an MSSP request with the server's status, as reported by the daemon.
+    async fn send_server_status(
+        &mut self,
+        rpc_client: &mut RpcSendClient,
+    ) -> Result<(), eyre::Error> {
+        let response = rpc_client
+            .make_client_rpc_call(
+                self.client_id,
+                HostClientToDaemonMessage::RequestServerStatus(self.client_token.clone()),
+            )
+            .await?;
+        match response {
+            ReplyResult::ClientSuccess(DaemonToClientReply::ServerStatus(variables)) => {
+                self.write
+                    .send(TelnetOutput::Mssp(variables))
+                    .await
+                    .with_context(|| "Unable to send server status to client")?;
+            }
+            _ => {
+                warn!(?response, "Unable to retrieve server status");
+            }
+        }
+        Ok(())
+    }
+
     async fn handle_task_error(&mut self, task_error: SchedulerError) -> Result<(), eyre::Error> {
         match task_error {
             SchedulerError::CommandExecutionError(CommandError::CouldNotParseCommand) => {
                 self.write
-                    .send("I couldn't understand that.".to_string())
+                    .send("I couldn't understand that.".to_string().into())
                     .await?;
             }
             SchedulerError::CommandExecutionError(CommandError::NoObjectMatch) => {
                 self.write
-                    .send("I don't see that here.".to_string())
+                    .send("I don't see that here.".to_string().into())
+                    .await?;
+            }
+            SchedulerError::CommandExecutionError(CommandError::NoObjectMatchSuggestions(
+                object_name,
+                suggestions,
+            )) => {
+                self.write
+                    .send(
+                        format!(
+                            "I don't see \"{object_name}\" here. Did you mean: {}?",
+                            suggestions.join(", ")
+                        )
+                        .into(),
+                    )
                     .await?;
             }
             SchedulerError::CommandExecutionError(CommandError::NoCommandMatch) => {
                 self.write
-                    .send("I couldn't understand that.".to_string())
+                    .send("I couldn't understand that.".to_string().into())
                     .await?;
             }
             SchedulerError::CommandExecutionError(CommandError::PermissionDenied) => {
-                self.write.send("You can't do that.".to_string()).await?;
+                self.write
+                    .send("You can't do that.".to_string().into())
+                    .await?;
             }
             SchedulerError::VerbProgramFailed(VerbProgramError::CompilationError(
                 compile_error,
             )) => {
                 let ce = describe_compile_error(compile_error);
-                self.write.send(ce).await?;
-                self.write.send("Verb not programmed.".to_string()).await?;
+                self.write.send(ce.into()).await?;
+                self.write
+                    .send("Verb not programmed.".to_string().into())
+                    .await?;
             }
             SchedulerError::VerbProgramFailed(VerbProgramError::NoVerbToProgram) => {
                 self.write
-                    .send("That object does not have that verb definition.".to_string())
+                    .send(
+                        "That object does not have that verb definition."
+                            .to_string()
+                            .into(),
+                    )
                     .await?;
             }
             SchedulerError::TaskAbortedLimit(AbortLimitReason::Ticks(_)) => {
-                self.write.send("Task ran out of ticks".to_string()).await?;
+                self.write
+                    .send("Task ran out of ticks".to_string().into())
+                    .await?;
             }
             SchedulerError::TaskAbortedLimit(AbortLimitReason::Time(_)) => {
                 self.write
-                    .send("Task ran out of seconds".to_string())
+                    .send("Task ran out of seconds".to_string().into())
+                    .await?;
+            }
+            SchedulerError::TaskAbortedLimit(AbortLimitReason::BuiltinDuration(name)) => {
+                self.write
+                    .send(format!("Task aborted: {name}() ran too long").into())
                     .await?;
             }
             SchedulerError::TaskAbortedError => {
-                self.write.send("Task aborted".to_string()).await?;
+                self.write.send("Task aborted".to_string().into()).await?;
             }
             SchedulerError::TaskAbortedException(e) => {
                 // This should not really be happening here... but?
-                self.write.send(format!("Task exception: {}", e)).await?;
+                self.write
+                    .send(format!("Task exception: {}", e).into())
+                    .await?;
             }
             SchedulerError::TaskAbortedCancelled => {
-                self.write.send("Task cancelled".to_string()).await?;
+                self.write.send("Task cancelled".to_string().into()).await?;
             }
             _ => {
                 warn!(?task_error, "Unhandled unexpected task error");
@@ -538,3 +868,195 @@ fn output_format(content: &str, content_type: Option<Symbol>) -> String {
         _ => content.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TelnetConnection, TelnetStream, binary_output};
+    use crate::telnet::{IAC, IP, TelnetCodec};
+    use futures_util::{SinkExt, StreamExt};
+    use moor_common::tasks::SchedulerError;
+    use moor_var::{NOTHING, SYSTEM_OBJECT, Symbol};
+    use rpc_async_client::rpc_client::RpcSendClient;
+    use rpc_common::HostClientToDaemonMessage::{Command, KillTask};
+    use rpc_common::{
+        AuthToken, CLIENT_BROADCAST_TOPIC, ClientToken, DaemonToClientReply,
+        HostClientToDaemonMessage, ReplyResult, RpcMessageError,
+    };
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+    use tmq::Multipart;
+    use tokio::io::{AsyncWriteExt, DuplexStream};
+    use tokio::sync::mpsc;
+    use tokio_util::codec::{Framed, LinesCodec};
+    use uuid::Uuid;
+
+    fn test_connection(server: TelnetStream) -> TelnetConnection {
+        let (write, read) = Framed::new(server, TelnetCodec::new()).split();
+        TelnetConnection {
+            peer_addr: "127.0.0.1:7777".parse().unwrap(),
+            handler_object: SYSTEM_OBJECT,
+            connection_oid: NOTHING,
+            client_id: Uuid::new_v4(),
+            client_token: ClientToken("token".to_string()),
+            write,
+            read,
+            kill_switch: Arc::new(AtomicBool::new(false)),
+            oob_binary: false,
+        }
+    }
+
+    /// A login the core turns away shows the user its message, and then the connection closes.
+    #[tokio::test]
+    async fn test_rejected_login_message_then_close() {
+        let (server, client) = tokio::io::duplex(1024);
+        let mut connection = test_connection(Box::new(server));
+
+        connection
+            .reject_login("Sorry, the server is full.".to_string())
+            .await
+            .unwrap();
+
+        let mut client = Framed::new(client, LinesCodec::new());
+        assert_eq!(
+            client.next().await.unwrap().unwrap(),
+            "Sorry, the server is full."
+        );
+        assert!(client.next().await.is_none());
+    }
+
+    /// Stands in for the daemon's RPC endpoint: each command starts a new task, numbered from 1,
+    /// and each kill is reported on `kills` and answered with the next of `kill_replies`.
+    async fn stub_daemon(
+        zmq_ctx: tmq::Context,
+        rpc_address: String,
+        mut kill_replies: VecDeque<ReplyResult>,
+        kills: mpsc::UnboundedSender<usize>,
+    ) {
+        let mut receiver = tmq::reply(&zmq_ctx).bind(&rpc_address).unwrap();
+        let mut next_task_id = 1;
+        loop {
+            let Ok((request, sender)) = receiver.recv().await else {
+                return;
+            };
+            let (message, _): (HostClientToDaemonMessage, _) =
+                bincode::decode_from_slice(&request[1], bincode::config::standard()).unwrap();
+            let reply = match message {
+                Command(..) => {
+                    next_task_id += 1;
+                    ReplyResult::ClientSuccess(DaemonToClientReply::TaskSubmitted(next_task_id - 1))
+                }
+                KillTask(_, _, task_id) => {
+                    kills.send(task_id).unwrap();
+                    kill_replies.pop_front().unwrap()
+                }
+                _ => ReplyResult::Failure(RpcMessageError::InvalidRequest("stub".into())),
+            };
+            let reply = bincode::encode_to_vec(&reply, bincode::config::standard()).unwrap();
+            receiver = sender.send(Multipart::from(vec![reply])).await.unwrap();
+        }
+    }
+
+    async fn expect_line(client: &mut Framed<DuplexStream, LinesCodec>, line: &str) {
+        let received = tokio::time::timeout(Duration::from_secs(5), client.next())
+            .await
+            .expect("No reply from the host")
+            .unwrap()
+            .unwrap();
+        assert_eq!(received, line);
+    }
+
+    /// IAC IP and `#$#abort` kill the task running the last command, through the daemon's
+    /// KillTask, and the user hears how that went.
+    #[tokio::test]
+    async fn test_abort_command() {
+        let id = Uuid::new_v4();
+        let rpc_address = format!("ipc:///tmp/moor-telnet-abort-test-rpc-{id}");
+        let events_address = format!("ipc:///tmp/moor-telnet-abort-test-events-{id}");
+        let zmq_ctx = tmq::Context::new();
+        let (kills_send, mut kills) = mpsc::unbounded_channel();
+        let kill_replies = VecDeque::from([
+            ReplyResult::ClientSuccess(DaemonToClientReply::TaskKilled),
+            ReplyResult::Failure(RpcMessageError::PermissionDenied),
+            ReplyResult::Failure(RpcMessageError::TaskError(SchedulerError::TaskNotFound(2))),
+        ]);
+        tokio::spawn(stub_daemon(
+            zmq_ctx.clone(),
+            rpc_address.clone(),
+            kill_replies,
+            kills_send,
+        ));
+
+        let (server, client) = tokio::io::duplex(1024);
+        let mut connection = test_connection(Box::new(server));
+        let rpc_request_sock = tmq::request(&zmq_ctx)
+            .set_rcvtimeo(1000)
+            .set_sndtimeo(1000)
+            .connect(rpc_address.as_str())
+            .unwrap();
+        let mut rpc_client = RpcSendClient::new(rpc_request_sock);
+        let mut events_sub = tmq::subscribe(&zmq_ctx)
+            .connect(events_address.as_str())
+            .unwrap()
+            .subscribe(&connection.client_id.as_bytes()[..])
+            .unwrap();
+        let mut broadcast_sub = tmq::subscribe(&zmq_ctx)
+            .connect(events_address.as_str())
+            .unwrap()
+            .subscribe(CLIENT_BROADCAST_TOPIC)
+            .unwrap();
+        tokio::spawn(async move {
+            connection
+                .command_loop(
+                    AuthToken("auth".to_string()),
+                    &mut events_sub,
+                    &mut broadcast_sub,
+                    &mut rpc_client,
+                )
+                .await
+        });
+
+        let mut client = Framed::new(client, LinesCodec::new());
+
+        // Interrupting the first command kills its task.
+        client.send("first".to_string()).await.unwrap();
+        client.get_mut().write_all(&[IAC, IP]).await.unwrap();
+        expect_line(&mut client, "Aborted.").await;
+
+        // When the kill fails, the user's told, and the task can be aborted again.
+        client.send("second".to_string()).await.unwrap();
+        client.send("#$#abort".to_string()).await.unwrap();
+        expect_line(
+            &mut client,
+            "Unable to abort the command: Permission denied",
+        )
+        .await;
+        client.send("#$#abort".to_string()).await.unwrap();
+        expect_line(&mut client, "There is no command to abort.").await;
+
+        // With nothing running, there's nothing to ask the daemon to kill.
+        client.send("#$#abort".to_string()).await.unwrap();
+        expect_line(&mut client, "There is no command to abort.").await;
+
+        let mut killed = vec![];
+        while let Ok(task_id) = kills.try_recv() {
+            killed.push(task_id);
+        }
+        assert_eq!(killed, vec![1, 2, 2]);
+    }
+
+    /// Binary content goes out of band only to clients which asked for it.
+    #[test]
+    fn test_binary_output() {
+        let content_type = Symbol::mk("image/png");
+        assert_eq!(
+            binary_output(content_type, b"moo", true),
+            "#$#moor-binary content-type: \"image/png\" length: 3 data: bW9v"
+        );
+        assert_eq!(
+            binary_output(content_type, b"moo", false),
+            "[binary content: image/png, 3 bytes]"
+        );
+    }
+}
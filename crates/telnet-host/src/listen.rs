@@ -11,10 +11,15 @@
 // this program. If not, see <https://www.gnu.org/licenses/>.
 //
 
-use crate::connection::TelnetConnection;
+use crate::connection::{TelnetConnection, TelnetStream};
+use crate::name_lookup;
+use crate::proxy::read_proxy_header;
+use crate::telnet::{TelnetCodec, TelnetOutput};
+use crate::tls::load_tls_acceptor;
 use eyre::bail;
 use futures_util::StreamExt;
 use futures_util::stream::SplitSink;
+use moor_common::tasks::ListenerTls;
 use moor_var::Obj;
 use rpc_async_client::rpc_client::RpcSendClient;
 use rpc_async_client::{ListenersClient, ListenersMessage};
@@ -22,13 +27,17 @@ use rpc_common::HostClientToDaemonMessage::ConnectionEstablish;
 use rpc_common::{CLIENT_BROADCAST_TOPIC, DaemonToClientReply, ReplyResult};
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 use tmq::{request, subscribe};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::select;
-use tokio_util::codec::{Framed, LinesCodec};
-use tracing::{debug, info, warn};
+use tokio::time::timeout;
+use tokio_rustls::TlsAcceptor;
+use tokio_util::codec::Framed;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 pub struct Listeners {
@@ -37,14 +46,25 @@ pub struct Listeners {
     rpc_address: String,
     events_address: String,
     kill_switch: Arc<AtomicBool>,
+    /// If set, resolve peer addresses to hostnames for `connection_name`, waiting at most this
+    /// long before falling back to the raw address.
+    name_lookup_timeout: Option<Duration>,
+    /// If set, connections come through a proxy, and start with a PROXY protocol header giving
+    /// the client's address.
+    trust_proxy: bool,
 }
 
+/// How long to wait for the PROXY header before giving up on the connection.
+const PROXY_HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
 impl Listeners {
     pub fn new(
         zmq_ctx: tmq::Context,
         rpc_address: String,
         events_address: String,
         kill_switch: Arc<AtomicBool>,
+        name_lookup_timeout: Option<Duration>,
+        trust_proxy: bool,
     ) -> (
         Self,
         tokio::sync::mpsc::Receiver<ListenersMessage>,
@@ -57,6 +77,8 @@ impl Listeners {
             rpc_address,
             events_address,
             kill_switch,
+            name_lookup_timeout,
+            trust_proxy,
         };
         let listeners_client = ListenersClient::new(tx);
         (listeners, rx, listeners_client)
@@ -77,7 +99,21 @@ impl Listeners {
             }
 
             match listeners_channel.recv().await {
-                Some(ListenersMessage::AddListener(handler, addr)) => {
+                Some(ListenersMessage::AddListener(handler, addr, tls)) => {
+                    // A listener asked to use TLS which can't doesn't listen at all, rather than
+                    // quietly accepting plaintext.
+                    let tls_acceptor = match tls {
+                        None => None,
+                        Some(ListenerTls { certificate, key }) => {
+                            match load_tls_acceptor(Path::new(&certificate), Path::new(&key)) {
+                                Ok(acceptor) => Some(acceptor),
+                                Err(e) => {
+                                    error!(?addr, "Unable to configure TLS listener: {:#}", e);
+                                    continue;
+                                }
+                            }
+                        }
+                    };
                     let listener = TcpListener::bind(addr)
                         .await
                         .expect("Unable to bind listener");
@@ -90,6 +126,11 @@ impl Listeners {
                     let rpc_address = self.rpc_address.clone();
                     let events_address = self.events_address.clone();
                     let kill_switch = self.kill_switch.clone();
+                    let name_lookup_timeout = self.name_lookup_timeout;
+                    let trust_proxy = self.trust_proxy;
+                    if tls_acceptor.is_some() {
+                        info!("TLS enabled @ {}", addr);
+                    }
 
                     // One task per listener.
                     tokio::spawn(async move {
@@ -120,6 +161,9 @@ impl Listeners {
                                                 listener_port,
                                                 stream,
                                                 addr,
+                                                name_lookup_timeout,
+                                                tls_acceptor.clone(),
+                                                trust_proxy,
                                             ));
                                         }
                                         Err(e) => {
@@ -181,17 +225,52 @@ impl Listener {
         listener_port: u16,
         stream: TcpStream,
         peer_addr: SocketAddr,
+        name_lookup_timeout: Option<Duration>,
+        tls_acceptor: Option<TlsAcceptor>,
+        trust_proxy: bool,
     ) -> Result<(), eyre::Report> {
         let connection_kill_switch = kill_switch.clone();
         let rpc_address = rpc_address.clone();
         let events_address = events_address.clone();
         let zmq_ctx = zmq_ctx.clone();
         tokio::spawn(async move {
+            let mut stream = stream;
+            let mut peer_addr = peer_addr;
+            // The proxy's header comes before anything else, including the TLS handshake.
+            if trust_proxy {
+                match timeout(PROXY_HEADER_TIMEOUT, read_proxy_header(&mut stream)).await {
+                    Ok(Ok(Some(client_addr))) => {
+                        debug!(proxy_addr = ?peer_addr, ?client_addr, "PROXY header received");
+                        peer_addr = client_addr;
+                    }
+                    Ok(Ok(None)) => {}
+                    Ok(Err(e)) => {
+                        warn!(?peer_addr, ?e, "Rejecting connection with bad PROXY header");
+                        return Ok(());
+                    }
+                    Err(_) => {
+                        warn!(?peer_addr, "Rejecting connection with no PROXY header");
+                        return Ok(());
+                    }
+                }
+            }
             let client_id = Uuid::new_v4();
             info!(peer_addr = ?peer_addr, client_id = ?client_id, port = listener_port,
                 "Accepted connection for listener"
             );
 
+            // If this is a TLS listener, complete the handshake before anything else.
+            let stream: TelnetStream = match tls_acceptor {
+                Some(acceptor) => match acceptor.accept(stream).await {
+                    Ok(stream) => Box::new(stream),
+                    Err(e) => {
+                        warn!(?peer_addr, ?e, "TLS handshake failed");
+                        return Ok(());
+                    }
+                },
+                None => Box::new(stream),
+            };
+
             let rpc_request_sock = request(&zmq_ctx)
                 .set_rcvtimeo(100)
                 .set_sndtimeo(100)
@@ -203,8 +282,14 @@ impl Listener {
             debug!(rpc_address, "Contacting RPC server to establish connection");
             let mut rpc_client = RpcSendClient::new(rpc_request_sock);
 
+            let connection_name = name_lookup::connection_name(
+                peer_addr,
+                name_lookup_timeout,
+                name_lookup::reverse_lookup,
+            )
+            .await;
             let (client_token, connection_oid) = match rpc_client
-                .make_client_rpc_call(client_id, ConnectionEstablish(peer_addr.to_string()))
+                .make_client_rpc_call(client_id, ConnectionEstablish(connection_name))
                 .await
             {
                 Ok(ReplyResult::ClientSuccess(DaemonToClientReply::NewConnection(
@@ -247,9 +332,11 @@ impl Listener {
             );
 
             // Re-ify the connection.
-            let framed_stream = Framed::new(stream, LinesCodec::new());
-            let (write, read): (SplitSink<Framed<TcpStream, LinesCodec>, String>, _) =
-                framed_stream.split();
+            let framed_stream = Framed::new(stream, TelnetCodec::new());
+            let (write, read): (
+                SplitSink<Framed<TelnetStream, TelnetCodec>, TelnetOutput>,
+                _,
+            ) = framed_stream.split();
             let mut tcp_connection = TelnetConnection {
                 handler_object,
                 peer_addr,
@@ -259,6 +346,7 @@ impl Listener {
                 write,
                 read,
                 kill_switch: connection_kill_switch,
+                oob_binary: false,
             };
 
             tcp_connection
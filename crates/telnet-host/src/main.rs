@@ -14,18 +14,24 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::listen::Listeners;
+use crate::tls::load_tls_acceptor;
 use clap::Parser;
 use clap_derive::Parser;
 use figment::Figment;
 use figment::providers::{Format, Serialized, Yaml};
+use moor_common::tasks::ListenerTls;
 use moor_var::SYSTEM_OBJECT;
-use rpc_async_client::{make_host_token, process_hosts_events, start_host_session};
+use rpc_async_client::{
+    make_host_token, process_hosts_events, resolve_listen_addresses, start_host_session,
+};
 use rpc_common::client_args::RpcClientArgs;
 use rpc_common::{HostType, load_keypair};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 use tokio::select;
 use tokio::signal::unix::{SignalKind, signal};
 use tracing::{error, info};
@@ -33,6 +39,11 @@ use tracing_subscriber::fmt::format::FmtSpan;
 
 mod connection;
 mod listen;
+mod mcp;
+mod name_lookup;
+mod proxy;
+mod telnet;
+mod tls;
 
 #[derive(Parser, Debug, Serialize, Deserialize)]
 struct Args {
@@ -42,7 +53,7 @@ struct Args {
     #[arg(
         long,
         value_name = "telnet-address",
-        help = "Listen address for the default telnet connections listener",
+        help = "Listen address(es) for the default telnet connections listener; comma separated, IPv4, IPv6 or hostname",
         default_value = "0.0.0.0"
     )]
     telnet_address: String,
@@ -55,6 +66,49 @@ struct Args {
     )]
     telnet_port: u16,
 
+    #[arg(
+        long,
+        value_name = "tls-port",
+        help = "If set, also listen on this port for TLS telnet connections (requires --tls-cert and --tls-key)"
+    )]
+    tls_port: Option<u16>,
+
+    #[arg(
+        long,
+        value_name = "tls-cert",
+        help = "PEM certificate chain file for TLS connections"
+    )]
+    tls_cert: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "tls-key",
+        help = "PEM private key file for TLS connections"
+    )]
+    tls_key: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Resolve connecting addresses to hostnames (reverse DNS) for connection_name()",
+        default_value = "false"
+    )]
+    connection_name_lookup: bool,
+
+    #[arg(
+        long,
+        value_name = "connection-name-lookup-timeout-ms",
+        help = "How long to wait for a reverse DNS lookup before falling back to the address",
+        default_value = "1000"
+    )]
+    connection_name_lookup_timeout_ms: u64,
+
+    #[arg(
+        long,
+        help = "Expect each connection to start with a PROXY protocol (v1 or v2) header giving the real client address, as sent by a load balancer or proxy in front of the host. Connections without one are rejected",
+        default_value = "false"
+    )]
+    trust_proxy: bool,
+
     #[arg(long, help = "Enable debug logging", default_value = "false")]
     debug: bool,
 
@@ -109,18 +163,57 @@ async fn main() -> Result<(), eyre::Error> {
 
     let kill_switch = Arc::new(AtomicBool::new(false));
 
-    // Parse the telnet address and port.
-    let listen_addr = format!("{}:{}", args.telnet_address, args.telnet_port);
-    let telnet_sockaddr = match listen_addr.parse::<SocketAddr>() {
-        Ok(addr) => addr,
-        Err(e) => {
-            error!(
-                "Failed to parse telnet socket address {}: {}",
-                listen_addr, e
-            );
+    // Parse (and resolve) the telnet address(es), using the telnet port for any that don't
+    // specify one.
+    let telnet_sockaddrs =
+        match resolve_listen_addresses(&args.telnet_address, args.telnet_port).await {
+            Ok(addrs) => addrs,
+            Err(e) => {
+                error!(
+                    "Failed to parse telnet listen address {}: {}",
+                    args.telnet_address, e
+                );
+                std::process::exit(1);
+            }
+        };
+
+    // TLS is off unless a port is given, in which case we need a cert and key. (Listeners started
+    // by `listen()` can also use TLS, with the certificate and key from $server_options.)
+    let tls = match (args.tls_port, &args.tls_cert, &args.tls_key) {
+        (None, _, _) => None,
+        (Some(port), Some(cert), Some(key)) => {
+            // Check them now, rather than only when the listener starts.
+            if let Err(e) = load_tls_acceptor(cert, key) {
+                error!("Unable to configure TLS: {:#}", e);
+                std::process::exit(1);
+            }
+            let listener_tls = ListenerTls {
+                certificate: cert.to_string_lossy().to_string(),
+                key: key.to_string_lossy().to_string(),
+            };
+            Some((port, listener_tls))
+        }
+        (Some(_), _, _) => {
+            error!("--tls-port requires both --tls-cert and --tls-key");
             std::process::exit(1);
         }
     };
+    let tls_sockaddrs = match tls.as_ref() {
+        None => vec![],
+        Some((port, _)) => match resolve_listen_addresses(&args.telnet_address, *port).await {
+            Ok(addrs) => addrs
+                .into_iter()
+                .map(|addr| SocketAddr::new(addr.ip(), *port))
+                .collect(),
+            Err(e) => {
+                error!(
+                    "Failed to parse telnet listen address {}: {}",
+                    args.telnet_address, e
+                );
+                std::process::exit(1);
+            }
+        },
+    };
 
     let zmq_ctx = tmq::Context::new();
 
@@ -129,18 +222,33 @@ async fn main() -> Result<(), eyre::Error> {
         args.client_args.rpc_address.clone(),
         args.client_args.events_address.clone(),
         kill_switch.clone(),
+        args.connection_name_lookup
+            .then(|| Duration::from_millis(args.connection_name_lookup_timeout_ms)),
+        args.trust_proxy,
     );
     let listeners_thread = tokio::spawn(async move {
         listeners_server.run(listeners_channel).await;
     });
 
-    listeners
-        .add_listener(&SYSTEM_OBJECT, telnet_sockaddr)
-        .await
-        .unwrap_or_else(|e| {
-            error!("Unable to start default listener: {}", e);
-            std::process::exit(1);
-        });
+    for telnet_sockaddr in telnet_sockaddrs {
+        listeners
+            .add_listener(&SYSTEM_OBJECT, telnet_sockaddr, None)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Unable to start default listener: {}", e);
+                std::process::exit(1);
+            });
+    }
+    for tls_sockaddr in tls_sockaddrs {
+        let listener_tls = tls.as_ref().map(|(_, listener_tls)| listener_tls.clone());
+        listeners
+            .add_listener(&SYSTEM_OBJECT, tls_sockaddr, listener_tls)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Unable to start TLS listener: {}", e);
+                std::process::exit(1);
+            });
+    }
 
     let (private_key, _public_key) =
         match load_keypair(&args.client_args.public_key, &args.client_args.private_key) {
@@ -0,0 +1,291 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Reassembly of MCP 2.1 multiline messages, so the core's out-of-band handler gets each one as
+//! a single line.
+//!
+//! A multiline message starts with a header naming its multiline keys with a trailing `*`, and a
+//! `_data-tag`:
+//!
+//! ```text
+//! #$#dns-org-mud-moo-simpleedit-content 12345 reference: "#1.foo" content*: "" _data-tag: 9
+//! #$#* 9 content: first line
+//! #$#* 9 content: second line
+//! #$#: 9
+//! ```
+//!
+//! which is dispatched, once the `#$#:` ending it arrives, as the header alone with each
+//! multiline key given its lines joined by newlines as a quoted value, and no `_data-tag`.
+
+use std::collections::HashMap;
+
+/// Lines starting a multiline message continuation, e.g. `#$#* 9 content: first line`.
+const CONTINUATION_PREFIX: &str = "#$#* ";
+/// Lines ending a multiline message, e.g. `#$#: 9`.
+const END_PREFIX: &str = "#$#: ";
+const DATA_TAG_KEY: &str = "_data-tag:";
+
+/// How many multiline messages a connection may have open at once, before further ones are
+/// abandoned.
+const MAX_PENDING_MESSAGES: usize = 16;
+/// How many lines a multiline message may grow to before it's abandoned.
+const MAX_MESSAGE_LINES: usize = 10_000;
+
+struct PendingMessage {
+    /// The header's message name and auth key.
+    prefix: Vec<String>,
+    /// The header's keys, with the (raw, possibly quoted) value of each single-line one, and
+    /// the lines received so far for each multiline one.
+    keys: Vec<(String, Value)>,
+    lines: usize,
+}
+
+enum Value {
+    Single(String),
+    Multiline(Vec<String>),
+}
+
+/// The multiline MCP messages a connection has in flight.
+#[derive(Default)]
+pub(crate) struct McpMultiline {
+    pending: HashMap<String, PendingMessage>,
+}
+
+impl McpMultiline {
+    /// Take in an out-of-band line, returning the line to dispatch, if there is one yet: the
+    /// line itself if it isn't part of a multiline message, or the whole message once it ends.
+    pub(crate) fn feed(&mut self, line: String) -> Option<String> {
+        if let Some(rest) = line.strip_prefix(CONTINUATION_PREFIX) {
+            self.continue_message(rest);
+            return None;
+        }
+        if let Some(tag) = line.strip_prefix(END_PREFIX) {
+            return self
+                .pending
+                .remove(tag.trim())
+                .map(|message| message.assemble());
+        }
+        if !line.contains(DATA_TAG_KEY) {
+            return Some(line);
+        }
+        let Some((tag, message)) = parse_header(&line) else {
+            return Some(line);
+        };
+        // Reusing a tag abandons whatever was sent under it before.
+        if !self.pending.contains_key(&tag) && self.pending.len() >= MAX_PENDING_MESSAGES {
+            return None;
+        }
+        self.pending.insert(tag, message);
+        None
+    }
+
+    fn continue_message(&mut self, rest: &str) {
+        let Some((tag, rest)) = rest.split_once(' ') else {
+            return;
+        };
+        let Some((key, value)) = rest.split_once(": ").or_else(|| {
+            // An empty line of data may come without the space after the colon.
+            rest.strip_suffix(':').map(|key| (key, ""))
+        }) else {
+            return;
+        };
+        let Some(message) = self.pending.get_mut(tag) else {
+            return;
+        };
+        message.lines += 1;
+        if message.lines > MAX_MESSAGE_LINES {
+            self.pending.remove(tag);
+            return;
+        }
+        if let Some((_, Value::Multiline(lines))) = message
+            .keys
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        {
+            lines.push(value.to_string());
+        }
+    }
+}
+
+impl PendingMessage {
+    fn assemble(self) -> String {
+        let mut words = self.prefix;
+        for (key, value) in self.keys {
+            words.push(format!("{key}:"));
+            words.push(match value {
+                Value::Single(value) => value,
+                Value::Multiline(lines) => quote(&lines.join("\n")),
+            });
+        }
+        words.join(" ")
+    }
+}
+
+/// Split a multiline message header into its data tag and the message it starts, or None if it
+/// isn't well formed.
+fn parse_header(line: &str) -> Option<(String, PendingMessage)> {
+    let tokens = tokenize(line)?;
+    let mut tokens = tokens.into_iter();
+    let mut prefix = vec![];
+    let mut keys = vec![];
+    let mut tag = None;
+    while let Some(token) = tokens.next() {
+        let Some(key) = token.strip_suffix(':').filter(|_| !token.starts_with('"')) else {
+            if !keys.is_empty() {
+                return None;
+            }
+            prefix.push(token);
+            continue;
+        };
+        let value = tokens.next()?;
+        if token == DATA_TAG_KEY {
+            tag = Some(unquote(&value));
+        } else if let Some(key) = key.strip_suffix('*') {
+            keys.push((key.to_string(), Value::Multiline(vec![])));
+        } else {
+            keys.push((key.to_string(), Value::Single(value)));
+        }
+    }
+    let message = PendingMessage {
+        prefix,
+        keys,
+        lines: 0,
+    };
+    Some((tag?, message))
+}
+
+/// Split an MCP line into its words and quoted strings, keeping each as written.
+fn tokenize(line: &str) -> Option<Vec<String>> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c == ' ' {
+            chars.next();
+            continue;
+        }
+        let mut token = String::new();
+        if c == '"' {
+            token.push(chars.next()?);
+            loop {
+                let c = chars.next()?;
+                token.push(c);
+                match c {
+                    '\\' => token.push(chars.next()?),
+                    '"' => break,
+                    _ => {}
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c == ' ' {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+        }
+        tokens.push(token);
+    }
+    Some(tokens)
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn unquote(value: &str) -> String {
+    let Some(value) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+    let mut unquoted = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            c => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_PENDING_MESSAGES, McpMultiline};
+
+    #[test]
+    fn test_multiline_message_reassembled() {
+        let mut mcp = McpMultiline::default();
+        let lines = [
+            r##"#$#dns-org-mud-moo-simpleedit-content 12345 reference: "#1.foo" content*: "" type: string-list _data-tag: 9"##,
+            r#"#$#* 9 content: first line"#,
+            r#"#$#* 9 content: a "quoted" \ line"#,
+            r#"#$#* 9 content:"#,
+        ];
+        for line in lines {
+            assert_eq!(mcp.feed(line.to_string()), None);
+        }
+        assert_eq!(
+            mcp.feed("#$#: 9".to_string()).unwrap(),
+            r##"#$#dns-org-mud-moo-simpleedit-content 12345 reference: "#1.foo" content: "first line
+a \"quoted\" \\ line
+" type: string-list"##
+        );
+        // The message is finished with.
+        assert_eq!(mcp.feed("#$#: 9".to_string()), None);
+    }
+
+    #[test]
+    fn test_single_line_messages_pass_through() {
+        let mut mcp = McpMultiline::default();
+        let line = r#"#$#mcp-negotiate-can 12345 package: mcp-negotiate min-version: 1.0"#;
+        assert_eq!(mcp.feed(line.to_string()).unwrap(), line);
+    }
+
+    #[test]
+    fn test_interleaved_and_abandoned_messages() {
+        let mut mcp = McpMultiline::default();
+        mcp.feed(r#"#$#msg 1 text*: "" _data-tag: a"#.to_string());
+        mcp.feed(r#"#$#msg 1 text*: "" _data-tag: b"#.to_string());
+        mcp.feed("#$#* a text: one".to_string());
+        mcp.feed("#$#* b text: two".to_string());
+        // Lines for a tag nobody opened are dropped.
+        mcp.feed("#$#* c text: three".to_string());
+        assert_eq!(
+            mcp.feed("#$#: b".to_string()).unwrap(),
+            r#"#$#msg 1 text: "two""#
+        );
+
+        // Reusing a tag abandons what was sent under it.
+        mcp.feed(r#"#$#msg 1 text*: "" _data-tag: a"#.to_string());
+        assert_eq!(
+            mcp.feed("#$#: a".to_string()).unwrap(),
+            r#"#$#msg 1 text: """#
+        );
+
+        // Past the limit, new messages are abandoned outright.
+        for i in 0..=MAX_PENDING_MESSAGES {
+            mcp.feed(format!(r#"#$#msg 1 text*: "" _data-tag: t{i}"#));
+        }
+        assert_eq!(mcp.feed(format!("#$#: t{MAX_PENDING_MESSAGES}")), None);
+        assert!(mcp.feed("#$#: t0".to_string()).is_some());
+    }
+}
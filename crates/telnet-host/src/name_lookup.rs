@@ -0,0 +1,154 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Reverse DNS resolution of peer addresses, used to give `connection_name` a hostname instead
+//! of a raw IP when the operator asks for it.
+
+use std::ffi::CStr;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tracing::debug;
+
+/// Matches `NI_MAXHOST` from `<netdb.h>`.
+const MAX_HOST_LEN: usize = 1025;
+
+/// Produce the name to report for a connection from `peer_addr`.
+///
+/// If `lookup_timeout` is `None`, no lookup is done and the address itself is used. Otherwise
+/// `resolver` is run on a blocking thread, and its answer is used if it produces one within the
+/// timeout. On failure or timeout we fall back to the address.
+pub(crate) async fn connection_name<F>(
+    peer_addr: SocketAddr,
+    lookup_timeout: Option<Duration>,
+    resolver: F,
+) -> String
+where
+    F: FnOnce(IpAddr) -> Option<String> + Send + 'static,
+{
+    let Some(lookup_timeout) = lookup_timeout else {
+        return peer_addr.to_string();
+    };
+    let ip = peer_addr.ip();
+    let lookup = tokio::task::spawn_blocking(move || resolver(ip));
+    match tokio::time::timeout(lookup_timeout, lookup).await {
+        Ok(Ok(Some(hostname))) => hostname,
+        Ok(Ok(None)) | Ok(Err(_)) => {
+            debug!(?peer_addr, "Reverse lookup failed, using address");
+            peer_addr.to_string()
+        }
+        Err(_) => {
+            debug!(
+                ?peer_addr,
+                ?lookup_timeout,
+                "Reverse lookup timed out, using address"
+            );
+            peer_addr.to_string()
+        }
+    }
+}
+
+/// Resolve `ip` to a hostname using the system resolver (`getnameinfo`). Blocking.
+pub(crate) fn reverse_lookup(ip: IpAddr) -> Option<String> {
+    let mut host = [0 as libc::c_char; MAX_HOST_LEN];
+    let rc = match ip {
+        IpAddr::V4(v4) => {
+            // SAFETY: sockaddr_in is plain-old-data, and all-zeroes is a valid value for it.
+            let mut sin: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_addr = libc::in_addr {
+                s_addr: u32::from_ne_bytes(v4.octets()),
+            };
+            // SAFETY: we pass a correctly sized sockaddr and host buffer.
+            unsafe {
+                libc::getnameinfo(
+                    &sin as *const libc::sockaddr_in as *const libc::sockaddr,
+                    size_of::<libc::sockaddr_in>() as libc::socklen_t,
+                    host.as_mut_ptr(),
+                    host.len() as libc::socklen_t,
+                    std::ptr::null_mut(),
+                    0,
+                    libc::NI_NAMEREQD,
+                )
+            }
+        }
+        IpAddr::V6(v6) => {
+            // SAFETY: sockaddr_in6 is plain-old-data, and all-zeroes is a valid value for it.
+            let mut sin6: libc::sockaddr_in6 = unsafe { std::mem::zeroed() };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_addr = libc::in6_addr {
+                s6_addr: v6.octets(),
+            };
+            // SAFETY: we pass a correctly sized sockaddr and host buffer.
+            unsafe {
+                libc::getnameinfo(
+                    &sin6 as *const libc::sockaddr_in6 as *const libc::sockaddr,
+                    size_of::<libc::sockaddr_in6>() as libc::socklen_t,
+                    host.as_mut_ptr(),
+                    host.len() as libc::socklen_t,
+                    std::ptr::null_mut(),
+                    0,
+                    libc::NI_NAMEREQD,
+                )
+            }
+        }
+    };
+    if rc != 0 {
+        return None;
+    }
+    // SAFETY: getnameinfo succeeded, so `host` holds a NUL-terminated string.
+    let hostname = unsafe { CStr::from_ptr(host.as_ptr()) };
+    hostname.to_str().ok().map(|s| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::connection_name;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    fn peer() -> SocketAddr {
+        "192.0.2.7:4321".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_lookup_disabled_uses_address() {
+        let name = connection_name(peer(), None, |_| Some("ignored.example.org".to_string())).await;
+        assert_eq!(name, "192.0.2.7:4321");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_success_uses_hostname() {
+        let name = connection_name(peer(), Some(Duration::from_secs(1)), |ip| {
+            assert_eq!(ip.to_string(), "192.0.2.7");
+            Some("client.example.org".to_string())
+        })
+        .await;
+        assert_eq!(name, "client.example.org");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_failure_falls_back() {
+        let name = connection_name(peer(), Some(Duration::from_secs(1)), |_| None).await;
+        assert_eq!(name, "192.0.2.7:4321");
+    }
+
+    #[tokio::test]
+    async fn test_lookup_timeout_falls_back() {
+        let name = connection_name(peer(), Some(Duration::from_millis(10)), |_| {
+            std::thread::sleep(Duration::from_millis(500));
+            Some("too-slow.example.org".to_string())
+        })
+        .await;
+        assert_eq!(name, "192.0.2.7:4321");
+    }
+}
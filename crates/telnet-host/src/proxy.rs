@@ -0,0 +1,185 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! The PROXY protocol (versions 1 and 2), by which a load balancer or proxy in front of the host
+//! tells us the address of the client it is relaying, ahead of anything the client sends.
+//! See <https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt>.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+/// The longest a version 1 header can be, including the CRLF.
+const V1_MAX_LENGTH: usize = 107;
+
+fn malformed(reason: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("malformed PROXY header: {reason}"),
+    )
+}
+
+/// Read a PROXY protocol header off the front of `stream`, returning the client's address.
+/// None means the header was valid but carries no address (e.g. a proxy health check), so the
+/// connection's own peer address stands. Anything which isn't a valid header is an error.
+/// Nothing past the header is consumed.
+pub(crate) async fn read_proxy_header<S: AsyncRead + Unpin>(
+    stream: &mut S,
+) -> io::Result<Option<SocketAddr>> {
+    // Both versions' headers are longer than the v2 signature.
+    let mut start = [0; 12];
+    stream.read_exact(&mut start).await?;
+    if start == V2_SIGNATURE {
+        return read_v2(stream).await;
+    }
+    if !start.starts_with(b"PROXY ") {
+        return Err(malformed("no PROXY signature"));
+    }
+
+    // Version 1 is a single line, which has to be read a byte at a time so as not to read past
+    // its end into the client's data.
+    let mut line = start.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() == V1_MAX_LENGTH {
+            return Err(malformed("line too long"));
+        }
+        line.push(stream.read_u8().await?);
+    }
+    let line = std::str::from_utf8(&line[..line.len() - 2]).map_err(|_| malformed("not ASCII"))?;
+    parse_v1(line)
+}
+
+fn parse_v1(line: &str) -> io::Result<Option<SocketAddr>> {
+    let fields: Vec<_> = line.split(' ').collect();
+    match fields[..] {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        [
+            "PROXY",
+            family @ ("TCP4" | "TCP6"),
+            source,
+            _destination,
+            source_port,
+            _,
+        ] => {
+            let ip: IpAddr = source.parse().map_err(|_| malformed("bad address"))?;
+            if ip.is_ipv4() != (family == "TCP4") {
+                return Err(malformed("address doesn't match protocol"));
+            }
+            let port: u16 = source_port.parse().map_err(|_| malformed("bad port"))?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(malformed("unrecognized line")),
+    }
+}
+
+async fn read_v2<S: AsyncRead + Unpin>(stream: &mut S) -> io::Result<Option<SocketAddr>> {
+    let mut header = [0; 4];
+    stream.read_exact(&mut header).await?;
+    let [version_command, family, len1, len0] = header;
+    let mut addresses = vec![0; u16::from_be_bytes([len1, len0]) as usize];
+    stream.read_exact(&mut addresses).await?;
+    match version_command {
+        // LOCAL: the proxy's own connection, e.g. a health check.
+        0x20 => return Ok(None),
+        // PROXY
+        0x21 => {}
+        _ => return Err(malformed("unsupported version or command")),
+    }
+    // The high nibble is the address family, the low the transport.
+    match family >> 4 {
+        0x1 => {
+            let Some(a) = addresses.get(..12) else {
+                return Err(malformed("address block too short"));
+            };
+            let ip = Ipv4Addr::new(a[0], a[1], a[2], a[3]);
+            let port = u16::from_be_bytes([a[8], a[9]]);
+            Ok(Some(SocketAddr::new(ip.into(), port)))
+        }
+        0x2 => {
+            let Some(a) = addresses.get(..36) else {
+                return Err(malformed("address block too short"));
+            };
+            let ip: [u8; 16] = a[..16].try_into().unwrap();
+            let port = u16::from_be_bytes([a[32], a[33]]);
+            Ok(Some(SocketAddr::new(Ipv6Addr::from(ip).into(), port)))
+        }
+        // Unspecified, or unix sockets, which have no address for us.
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(input: &[u8]) -> (io::Result<Option<SocketAddr>>, Vec<u8>) {
+        let mut stream = input;
+        let result = read_proxy_header(&mut stream).await;
+        (result, stream.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_v1() {
+        let (result, rest) = read(b"PROXY TCP4 203.0.113.7 192.0.2.1 51234 8888\r\nconnect").await;
+        assert_eq!(result.unwrap(), Some("203.0.113.7:51234".parse().unwrap()));
+        // The client's own data is left for the connection.
+        assert_eq!(rest, b"connect");
+
+        let (result, _) = read(b"PROXY TCP6 2001:db8::7 2001:db8::1 4000 8888\r\n").await;
+        assert_eq!(result.unwrap(), Some("[2001:db8::7]:4000".parse().unwrap()));
+
+        let (result, rest) = read(b"PROXY UNKNOWN\r\nlook").await;
+        assert_eq!(result.unwrap(), None);
+        assert_eq!(rest, b"look");
+    }
+
+    #[tokio::test]
+    async fn test_v1_malformed() {
+        for input in [
+            &b"connect wizard\r\n"[..],
+            b"PROXY TCP4 203.0.113.7 192.0.2.1 51234\r\n",
+            b"PROXY TCP4 2001:db8::7 192.0.2.1 51234 8888\r\n",
+            b"PROXY TCP4 203.0.113.7 192.0.2.1 99999 8888\r\n",
+            b"PROXY TCP4 203.0.113.7 192.0.2.1 51234 8888",
+        ] {
+            let (result, _) = read(input).await;
+            assert!(result.is_err(), "{:?}", String::from_utf8_lossy(input));
+        }
+        let too_long = [&b"PROXY "[..], &[b'x'; 200]].concat();
+        assert!(read(&too_long).await.0.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_v2() {
+        let mut input = V2_SIGNATURE.to_vec();
+        input.extend_from_slice(&[0x21, 0x11, 0, 12]);
+        input.extend_from_slice(&[203, 0, 113, 7, 192, 0, 2, 1]);
+        input.extend_from_slice(&51234u16.to_be_bytes());
+        input.extend_from_slice(&8888u16.to_be_bytes());
+        input.extend_from_slice(b"look");
+        let (result, rest) = read(&input).await;
+        assert_eq!(result.unwrap(), Some("203.0.113.7:51234".parse().unwrap()));
+        assert_eq!(rest, b"look");
+
+        // A health check from the proxy itself.
+        let mut input = V2_SIGNATURE.to_vec();
+        input.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        assert_eq!(read(&input).await.0.unwrap(), None);
+
+        // Claims to be IPv4 but is too short.
+        let mut input = V2_SIGNATURE.to_vec();
+        input.extend_from_slice(&[0x21, 0x11, 0, 4, 203, 0, 113, 7]);
+        assert!(read(&input).await.0.is_err());
+    }
+}
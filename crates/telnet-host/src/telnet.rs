@@ -0,0 +1,732 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Line framing for telnet connections, with enough of the telnet protocol (RFC 854) to send
+//! option negotiation to the client and to keep the client's own negotiation out of the input
+//! lines.
+//! Output is zlib compressed (MCCP2) once the client has agreed to it, and the client's window
+//! size (NAWS) is passed up alongside its input lines whenever it reports one. MUD listing sites
+//! can ask for the server's status over MSSP.
+//! A connection can be switched into character mode, where the client sends each keypress as it's
+//! typed and they're passed up one character at a time, rather than as lines.
+
+use std::io;
+use std::io::Write;
+
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use tokio_util::bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// "Interpret as command"
+pub(crate) const IAC: u8 = 255;
+pub(crate) const DONT: u8 = 254;
+pub(crate) const DO: u8 = 253;
+pub(crate) const WONT: u8 = 252;
+pub(crate) const WILL: u8 = 251;
+/// Subnegotiation begin
+pub(crate) const SB: u8 = 250;
+/// Subnegotiation end
+pub(crate) const SE: u8 = 240;
+/// Interrupt process, which clients send for e.g. Ctrl-C
+pub(crate) const IP: u8 = 244;
+
+/// The ECHO option (RFC 857).
+pub(crate) const OPT_ECHO: u8 = 1;
+/// Suppress Go Ahead (RFC 858).
+pub(crate) const OPT_SGA: u8 = 3;
+/// The LINEMODE option (RFC 1184), in which the client edits lines locally.
+pub(crate) const OPT_LINEMODE: u8 = 34;
+/// Negotiate About Window Size (RFC 1073).
+pub(crate) const OPT_NAWS: u8 = 31;
+/// MSSP, the MUD Server Status Protocol.
+pub(crate) const OPT_MSSP: u8 = 70;
+/// MCCP2, the MUD Client Compression Protocol, version 2.
+pub(crate) const OPT_COMPRESS2: u8 = 86;
+
+/// Marks the start of a variable name in an MSSP subnegotiation.
+pub(crate) const MSSP_VAR: u8 = 1;
+/// Marks the start of a variable's value in an MSSP subnegotiation.
+pub(crate) const MSSP_VAL: u8 = 2;
+
+/// The window size assumed for clients which never report one.
+pub(crate) const DEFAULT_WINDOW_SIZE: (u16, u16) = (80, 24);
+
+/// Something read from a telnet client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TelnetInput {
+    /// A line of input, with the line terminator and any telnet commands removed.
+    Line(String),
+    /// The client's window size, as width and height in characters. Sent when the client first
+    /// agrees to NAWS, and again each time the window is resized.
+    WindowSize(u16, u16),
+    /// The client (usually a MUD listing site's crawler) has asked for the server's status.
+    MsspRequest,
+    /// A single character typed in character mode. Enter arrives as `\n`, however the client
+    /// sends it.
+    Char(char),
+    /// The user wants to interrupt whatever they're running (IAC IP). Anything typed on the
+    /// current line so far is thrown away.
+    Interrupt,
+}
+
+/// Something to be written to a telnet client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TelnetOutput {
+    /// A line of text; the line terminator is added by the codec.
+    Line(String),
+    /// An option negotiation command, e.g. `IAC WILL ECHO`.
+    Negotiate(u8, u8),
+    /// The server's status, as MSSP variable names and values.
+    Mssp(Vec<(String, String)>),
+    /// Switch into or out of character mode: the negotiation to send, and, for the decoder, how
+    /// input is to be read from then on.
+    CharMode(bool),
+}
+
+impl From<String> for TelnetOutput {
+    fn from(line: String) -> Self {
+        TelnetOutput::Line(line)
+    }
+}
+
+impl TelnetOutput {
+    /// The negotiation to send for the `echo` connection option.
+    /// With echo off, the server offers to do the echoing itself (and then doesn't), which is
+    /// how clients are conventionally told to stop echoing typed input, e.g. for passwords.
+    pub(crate) fn echo(enabled: bool) -> Self {
+        if enabled {
+            TelnetOutput::Negotiate(WONT, OPT_ECHO)
+        } else {
+            TelnetOutput::Negotiate(WILL, OPT_ECHO)
+        }
+    }
+
+    /// Offer to compress our output. Nothing changes unless the client answers `DO`.
+    pub(crate) fn offer_compression() -> Self {
+        TelnetOutput::Negotiate(WILL, OPT_COMPRESS2)
+    }
+
+    /// Ask the client to report its window size. Clients which can answer `WILL` and then send
+    /// the size as a subnegotiation.
+    pub(crate) fn request_window_size() -> Self {
+        TelnetOutput::Negotiate(DO, OPT_NAWS)
+    }
+
+    /// Offer to report the server's status. Crawlers which want it answer `DO`.
+    pub(crate) fn offer_mssp() -> Self {
+        TelnetOutput::Negotiate(WILL, OPT_MSSP)
+    }
+}
+
+/// Where the decoder is within the telnet command stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum DecodeState {
+    #[default]
+    Data,
+    /// Saw IAC.
+    Command,
+    /// Saw IAC followed by WILL/WONT/DO/DONT, waiting for the option byte.
+    Negotiation(u8),
+    /// Inside IAC SB ... IAC SE.
+    Subnegotiation,
+    /// Saw IAC inside a subnegotiation.
+    SubnegotiationCommand,
+}
+
+/// Splits client input into lines with telnet commands removed, and writes lines and
+/// negotiation commands to the client.
+#[derive(Debug, Default)]
+pub(crate) struct TelnetCodec {
+    state: DecodeState,
+    line: Vec<u8>,
+    /// The option and data of the subnegotiation being read, with IAC escapes removed.
+    subnegotiation: Vec<u8>,
+    /// The client has agreed to MCCP2, and compression should start with the next output.
+    compression_agreed: bool,
+    /// Input is passed up a character at a time rather than a line at a time.
+    char_mode: bool,
+    /// The `echo` option is off, so the client has been told the server does the echoing.
+    echo_off: bool,
+    /// In character mode, the last character was a CR, so an LF or NUL following it is part of
+    /// the same keypress.
+    after_cr: bool,
+    /// Once compression has started, all output goes through here.
+    compressor: Option<ZlibEncoder<Vec<u8>>>,
+}
+
+impl TelnetCodec {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Decoder for TelnetCodec {
+    type Item = TelnetInput;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        while src.has_remaining() {
+            let byte = src.get_u8();
+            self.state = match (self.state, byte) {
+                (DecodeState::Data, IAC) => DecodeState::Command,
+                (DecodeState::Data, byte) if self.char_mode => {
+                    if let Some(c) = self.char_input(byte) {
+                        return Ok(Some(TelnetInput::Char(c)));
+                    }
+                    DecodeState::Data
+                }
+                (DecodeState::Data, b'\n') => {
+                    if self.line.last() == Some(&b'\r') {
+                        self.line.pop();
+                    }
+                    let line = std::mem::take(&mut self.line);
+                    return Ok(Some(TelnetInput::Line(
+                        String::from_utf8_lossy(&line).into_owned(),
+                    )));
+                }
+                (DecodeState::Data, byte) => {
+                    self.line.push(byte);
+                    DecodeState::Data
+                }
+                // An escaped 0xFF is literal data (though never a character on its own).
+                (DecodeState::Command, IAC) if self.char_mode => {
+                    self.state = DecodeState::Data;
+                    if let Some(c) = self.char_input(IAC) {
+                        return Ok(Some(TelnetInput::Char(c)));
+                    }
+                    DecodeState::Data
+                }
+                (DecodeState::Command, IAC) => {
+                    self.line.push(IAC);
+                    DecodeState::Data
+                }
+                (DecodeState::Command, command @ (WILL | WONT | DO | DONT)) => {
+                    DecodeState::Negotiation(command)
+                }
+                (DecodeState::Command, SB) => {
+                    self.subnegotiation.clear();
+                    DecodeState::Subnegotiation
+                }
+                (DecodeState::Command, IP) => {
+                    self.state = DecodeState::Data;
+                    self.line.clear();
+                    return Ok(Some(TelnetInput::Interrupt));
+                }
+                // Any other command (NOP, GA, etc.) carries no data for us.
+                (DecodeState::Command, _) => DecodeState::Data,
+                // A client that can't do MCCP2 answers DONT (or nothing), and so just never
+                // gets compressed output.
+                (DecodeState::Negotiation(DO), OPT_COMPRESS2) => {
+                    if self.compressor.is_none() {
+                        self.compression_agreed = true;
+                    }
+                    DecodeState::Data
+                }
+                (DecodeState::Negotiation(DO), OPT_MSSP) => {
+                    self.state = DecodeState::Data;
+                    return Ok(Some(TelnetInput::MsspRequest));
+                }
+                // We don't act on the rest of the client's negotiation, so it's just dropped.
+                (DecodeState::Negotiation(_), _) => DecodeState::Data,
+                (DecodeState::Subnegotiation, IAC) => DecodeState::SubnegotiationCommand,
+                (DecodeState::Subnegotiation, byte) => {
+                    self.subnegotiation.push(byte);
+                    DecodeState::Subnegotiation
+                }
+                (DecodeState::SubnegotiationCommand, SE) => {
+                    self.state = DecodeState::Data;
+                    if let Some(window_size) = self.window_size() {
+                        return Ok(Some(window_size));
+                    }
+                    DecodeState::Data
+                }
+                // A 255 in the data (e.g. a width of 255) arrives escaped, like in a line.
+                (DecodeState::SubnegotiationCommand, IAC) => {
+                    self.subnegotiation.push(IAC);
+                    DecodeState::Subnegotiation
+                }
+                (DecodeState::SubnegotiationCommand, _) => DecodeState::Subnegotiation,
+            };
+        }
+        Ok(None)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(line) = self.decode(src)? {
+            return Ok(Some(line));
+        }
+        // In character mode, all that can be left is part of a character.
+        if self.line.is_empty() || self.char_mode {
+            return Ok(None);
+        }
+        let line = std::mem::take(&mut self.line);
+        Ok(Some(TelnetInput::Line(
+            String::from_utf8_lossy(&line).into_owned(),
+        )))
+    }
+}
+
+impl Encoder<TelnetOutput> for TelnetCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: TelnetOutput, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        if self.compression_agreed {
+            // This marker is the last thing sent uncompressed.
+            self.compression_agreed = false;
+            dst.put_slice(&[IAC, SB, OPT_COMPRESS2, IAC, SE]);
+            self.compressor = Some(ZlibEncoder::new(vec![], Compression::default()));
+        }
+        if self.compressor.is_none() {
+            self.encode_plain(item, dst);
+            return Ok(());
+        }
+        let mut plain = BytesMut::new();
+        self.encode_plain(item, &mut plain);
+        let compressor = self.compressor.as_mut().expect("compression has started");
+        compressor.write_all(&plain)?;
+        // A sync flush, so the client can decompress everything sent so far.
+        compressor.flush()?;
+        dst.put_slice(compressor.get_ref());
+        compressor.get_mut().clear();
+        Ok(())
+    }
+}
+
+impl TelnetCodec {
+    /// Take a byte of character mode input, returning the character it completes, if any.
+    fn char_input(&mut self, byte: u8) -> Option<char> {
+        let after_cr = std::mem::take(&mut self.after_cr);
+        match byte {
+            b'\r' => {
+                self.after_cr = true;
+                return Some('\n');
+            }
+            b'\n' | 0 if after_cr => return None,
+            b'\n' => return Some('\n'),
+            _ => {}
+        }
+        self.line.push(byte);
+        match std::str::from_utf8(&self.line) {
+            Ok(text) => {
+                let c = text.chars().next();
+                self.line.clear();
+                c
+            }
+            // The rest of a multi-byte character is still to come.
+            Err(e) if e.error_len().is_none() => None,
+            Err(_) => {
+                self.line.clear();
+                Some(char::REPLACEMENT_CHARACTER)
+            }
+        }
+    }
+
+    /// The window size from the subnegotiation just completed, if it was a NAWS report.
+    /// A size of 0 means the client doesn't know that dimension, so the default is used for it.
+    fn window_size(&self) -> Option<TelnetInput> {
+        let [OPT_NAWS, w1, w0, h1, h0] = self.subnegotiation[..] else {
+            return None;
+        };
+        let width = match u16::from_be_bytes([w1, w0]) {
+            0 => DEFAULT_WINDOW_SIZE.0,
+            width => width,
+        };
+        let height = match u16::from_be_bytes([h1, h0]) {
+            0 => DEFAULT_WINDOW_SIZE.1,
+            height => height,
+        };
+        Some(TelnetInput::WindowSize(width, height))
+    }
+
+    fn encode_plain(&mut self, item: TelnetOutput, dst: &mut BytesMut) {
+        match item {
+            TelnetOutput::Line(line) => {
+                // UTF-8 never contains 0xFF, so text needs no IAC escaping.
+                dst.reserve(line.len() + 1);
+                dst.put_slice(line.as_bytes());
+                dst.put_u8(b'\n');
+            }
+            TelnetOutput::Negotiate(command, option) => {
+                if option == OPT_ECHO {
+                    self.echo_off = command == WILL;
+                }
+                dst.put_slice(&[IAC, command, option]);
+            }
+            TelnetOutput::Mssp(variables) => {
+                dst.put_slice(&[IAC, SB, OPT_MSSP]);
+                for (name, value) in variables {
+                    dst.put_u8(MSSP_VAR);
+                    Self::put_mssp_text(&name, dst);
+                    dst.put_u8(MSSP_VAL);
+                    Self::put_mssp_text(&value, dst);
+                }
+                dst.put_slice(&[IAC, SE]);
+            }
+            // With the server offering to echo and to suppress go-aheads, and the client told not
+            // to do line editing, clients send each character as it's typed. (Nothing is echoed,
+            // so keypresses aren't shown unless the core shows them.)
+            TelnetOutput::CharMode(enabled) => {
+                // Whatever was typed of a line so far isn't going to be finished.
+                self.line.clear();
+                self.after_cr = false;
+                self.char_mode = enabled;
+                if enabled {
+                    dst.put_slice(&[IAC, WILL, OPT_SGA, IAC, WILL, OPT_ECHO]);
+                    dst.put_slice(&[IAC, DONT, OPT_LINEMODE]);
+                } else {
+                    dst.put_slice(&[IAC, WONT, OPT_SGA]);
+                    // Leaving the echo offer in place if the core had turned echo off.
+                    if !self.echo_off {
+                        dst.put_slice(&[IAC, WONT, OPT_ECHO]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// MSSP text can't contain the marker bytes or NUL, so those are dropped. (UTF-8 never
+    /// contains IAC.)
+    fn put_mssp_text(text: &str, dst: &mut BytesMut) {
+        for byte in text.bytes() {
+            if !matches!(byte, 0 | MSSP_VAR | MSSP_VAL) {
+                dst.put_u8(byte);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(items: Vec<TelnetOutput>) -> Vec<u8> {
+        let mut codec = TelnetCodec::new();
+        let mut dst = BytesMut::new();
+        for item in items {
+            codec.encode(item, &mut dst).unwrap();
+        }
+        dst.to_vec()
+    }
+
+    fn decode_all(input: &[u8]) -> Vec<String> {
+        let mut codec = TelnetCodec::new();
+        let mut src = BytesMut::from(input);
+        let mut lines = vec![];
+        while let Some(input) = codec.decode(&mut src).unwrap() {
+            if let TelnetInput::Line(line) = input {
+                lines.push(line);
+            }
+        }
+        lines
+    }
+
+    #[test]
+    fn test_echo_negotiation_bytes() {
+        let bytes = encode(vec![
+            TelnetOutput::echo(false),
+            "Password:".to_string().into(),
+            TelnetOutput::echo(true),
+        ]);
+        assert_eq!(
+            bytes,
+            [
+                &[IAC, WILL, OPT_ECHO][..],
+                b"Password:\n",
+                &[IAC, WONT, OPT_ECHO][..]
+            ]
+            .concat()
+        );
+        assert_eq!(bytes[..3], [255, 251, 1]);
+        assert_eq!(bytes[bytes.len() - 3..], [255, 252, 1]);
+    }
+
+    #[test]
+    fn test_decode_strips_commands() {
+        // Client agrees to us echoing, then sends a line with an escaped 0xFF inside it,
+        // then a subnegotiation, then a CRLF terminated line.
+        let mut input = vec![IAC, DO, OPT_ECHO];
+        input.extend_from_slice(b"sec");
+        input.extend_from_slice(&[IAC, IAC]);
+        input.extend_from_slice(b"ret\n");
+        input.extend_from_slice(&[IAC, SB, 31, 0, 80, 0, 24, IAC, SE]);
+        input.extend_from_slice(b"look\r\n");
+        let lines = decode_all(&input);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], String::from_utf8_lossy(b"sec\xffret"));
+        assert_eq!(lines[1], "look");
+    }
+
+    #[test]
+    fn test_decode_interrupt() {
+        let mut codec = TelnetCodec::new();
+        let mut input = b"half a li".to_vec();
+        input.extend_from_slice(&[IAC, IP]);
+        input.extend_from_slice(b"look\r\n");
+        let mut src = BytesMut::from(&input[..]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::Interrupt)
+        );
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::Line("look".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_compression_negotiation() {
+        use flate2::read::ZlibDecoder;
+        use std::io::Read;
+
+        let mut codec = TelnetCodec::new();
+        let mut dst = BytesMut::new();
+        codec
+            .encode(TelnetOutput::offer_compression(), &mut dst)
+            .unwrap();
+        assert_eq!(dst[..], [IAC, WILL, OPT_COMPRESS2]);
+
+        // Client agrees.
+        let mut src = BytesMut::from(&[IAC, DO, OPT_COMPRESS2][..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        let mut dst = BytesMut::new();
+        codec
+            .encode("Hello, world!".to_string().into(), &mut dst)
+            .unwrap();
+        codec
+            .encode("Hello again!".to_string().into(), &mut dst)
+            .unwrap();
+        let marker = [IAC, SB, OPT_COMPRESS2, IAC, SE];
+        assert_eq!(dst[..marker.len()], marker);
+        let compressed = &dst[marker.len()..];
+        let expected = b"Hello, world!\nHello again!\n";
+        assert_ne!(compressed, &expected[..]);
+
+        // Read only as much as was sent; the stream is flushed but never finished.
+        let mut decompressed = vec![0; 64];
+        let mut decoder = ZlibDecoder::new(compressed);
+        let mut read = 0;
+        while read < expected.len() {
+            let n = decoder.read(&mut decompressed[read..]).unwrap();
+            assert_ne!(n, 0, "compressed output ended early");
+            read += n;
+        }
+        assert_eq!(&decompressed[..read], expected);
+    }
+
+    #[test]
+    fn test_compression_refused() {
+        let mut codec = TelnetCodec::new();
+        let mut src = BytesMut::from(&[IAC, DONT, OPT_COMPRESS2][..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+        let mut dst = BytesMut::new();
+        codec.encode("look".to_string().into(), &mut dst).unwrap();
+        assert_eq!(dst[..], b"look\n"[..]);
+    }
+
+    #[test]
+    fn test_decode_split_command() {
+        let mut codec = TelnetCodec::new();
+        let mut src = BytesMut::from(&[b'h', b'i', IAC][..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+        src.extend_from_slice(&[WONT, OPT_ECHO, b'\n']);
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::Line("hi".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_window_size() {
+        let mut codec = TelnetCodec::new();
+        let mut dst = BytesMut::new();
+        codec
+            .encode(TelnetOutput::request_window_size(), &mut dst)
+            .unwrap();
+        assert_eq!(dst[..], [IAC, DO, OPT_NAWS]);
+
+        // The client agrees and reports 132x50 straight away, then is resized to 255x40, where
+        // the 255 has to be escaped, in the middle of typing a line.
+        let mut src = BytesMut::from(&[IAC, WILL, OPT_NAWS][..]);
+        src.extend_from_slice(&[IAC, SB, OPT_NAWS, 0, 132, 0, 50, IAC, SE]);
+        src.extend_from_slice(b"lo");
+        src.extend_from_slice(&[IAC, SB, OPT_NAWS, 0, IAC, IAC, 0, 40, IAC, SE]);
+        src.extend_from_slice(b"ok\r\n");
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::WindowSize(132, 50))
+        );
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::WindowSize(255, 40))
+        );
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::Line("look".to_string()))
+        );
+
+        // An unknown dimension is reported as 0.
+        let mut src = BytesMut::from(&[IAC, SB, OPT_NAWS, 0, 0, 0, 30, IAC, SE][..]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::WindowSize(DEFAULT_WINDOW_SIZE.0, 30))
+        );
+    }
+    #[test]
+    fn test_char_mode() {
+        let mut codec = TelnetCodec::new();
+        let mut dst = BytesMut::new();
+        codec
+            .encode(TelnetOutput::CharMode(true), &mut dst)
+            .unwrap();
+        assert_eq!(
+            dst[..],
+            [
+                IAC,
+                WILL,
+                OPT_SGA,
+                IAC,
+                WILL,
+                OPT_ECHO,
+                IAC,
+                DONT,
+                OPT_LINEMODE
+            ]
+        );
+
+        // Single keypresses arrive one at a time, with the client's negotiation dropped, a
+        // multi-byte character held until it's complete, and CR LF as one Enter.
+        let mut src = BytesMut::from(&[IAC, DO, OPT_SGA, IAC, DO, OPT_ECHO][..]);
+        src.extend_from_slice(b"y");
+        src.extend_from_slice(&"\u{e9}".as_bytes()[..1]);
+        let mut chars = vec![];
+        while let Some(input) = codec.decode(&mut src).unwrap() {
+            chars.push(input);
+        }
+        assert_eq!(chars, vec![TelnetInput::Char('y')]);
+        src.extend_from_slice(&"\u{e9}".as_bytes()[1..]);
+        src.extend_from_slice(b"\r\nq\r\0");
+        while let Some(input) = codec.decode(&mut src).unwrap() {
+            chars.push(input);
+        }
+        assert_eq!(
+            chars,
+            vec![
+                TelnetInput::Char('y'),
+                TelnetInput::Char('\u{e9}'),
+                TelnetInput::Char('\n'),
+                TelnetInput::Char('q'),
+                TelnetInput::Char('\n'),
+            ]
+        );
+
+        // And back to lines.
+        let mut dst = BytesMut::new();
+        codec
+            .encode(TelnetOutput::CharMode(false), &mut dst)
+            .unwrap();
+        assert_eq!(dst[..], [IAC, WONT, OPT_SGA, IAC, WONT, OPT_ECHO]);
+        let mut src = BytesMut::from(&b"look\r\n"[..]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::Line("look".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_char_mode_keeps_echo_off() {
+        // With echo turned off for a password, leaving character mode doesn't turn it back on.
+        let bytes = encode(vec![
+            TelnetOutput::echo(false),
+            TelnetOutput::CharMode(true),
+            TelnetOutput::CharMode(false),
+        ]);
+        let expected = [
+            &[IAC, WILL, OPT_ECHO][..],
+            &[
+                IAC,
+                WILL,
+                OPT_SGA,
+                IAC,
+                WILL,
+                OPT_ECHO,
+                IAC,
+                DONT,
+                OPT_LINEMODE,
+            ],
+            &[IAC, WONT, OPT_SGA],
+        ]
+        .concat();
+        assert_eq!(bytes, expected);
+
+        // Once echo is back on, leaving character mode stops the echo offer as usual.
+        let bytes = encode(vec![
+            TelnetOutput::echo(false),
+            TelnetOutput::echo(true),
+            TelnetOutput::CharMode(true),
+            TelnetOutput::CharMode(false),
+        ]);
+        assert_eq!(
+            bytes[bytes.len() - 6..],
+            [IAC, WONT, OPT_SGA, IAC, WONT, OPT_ECHO]
+        );
+    }
+
+    #[test]
+    fn test_mssp() {
+        let mut codec = TelnetCodec::new();
+        let mut dst = BytesMut::new();
+        codec.encode(TelnetOutput::offer_mssp(), &mut dst).unwrap();
+        assert_eq!(dst[..], [IAC, WILL, OPT_MSSP]);
+
+        // The crawler asks for the status; a client which refuses gets nothing.
+        let mut src = BytesMut::from(&[IAC, DONT, OPT_MSSP, IAC, DO, OPT_MSSP][..]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(TelnetInput::MsspRequest)
+        );
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        let mut dst = BytesMut::new();
+        codec
+            .encode(
+                TelnetOutput::Mssp(vec![
+                    ("NAME".to_string(), "Test\x01MOO".to_string()),
+                    ("PLAYERS".to_string(), "3".to_string()),
+                    ("CODEBASE".to_string(), "moor".to_string()),
+                ]),
+                &mut dst,
+            )
+            .unwrap();
+        assert_eq!(
+            dst[..],
+            [
+                &[IAC, SB, OPT_MSSP, MSSP_VAR][..],
+                b"NAME",
+                &[MSSP_VAL][..],
+                b"TestMOO",
+                &[MSSP_VAR][..],
+                b"PLAYERS",
+                &[MSSP_VAL][..],
+                b"3",
+                &[MSSP_VAR][..],
+                b"CODEBASE",
+                &[MSSP_VAL][..],
+                b"moor",
+                &[IAC, SE][..],
+            ]
+            .concat()[..]
+        );
+    }
+}
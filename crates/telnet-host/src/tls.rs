@@ -0,0 +1,192 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Optional TLS termination for telnet listeners.
+
+use eyre::{WrapErr, bail, eyre};
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+
+/// Build a TLS acceptor from PEM encoded certificate chain and private key files.
+pub(crate) fn load_tls_acceptor(cert_path: &Path, key_path: &Path) -> eyre::Result<TlsAcceptor> {
+    let cert_pem = std::fs::read(cert_path)
+        .wrap_err_with(|| format!("Unable to read TLS certificate {:?}", cert_path))?;
+    let key_pem = std::fs::read(key_path)
+        .wrap_err_with(|| format!("Unable to read TLS private key {:?}", key_path))?;
+    tls_acceptor_from_pem(&cert_pem, &key_pem)
+}
+
+/// Build a TLS acceptor from PEM encoded certificate chain and private key.
+pub(crate) fn tls_acceptor_from_pem(cert_pem: &[u8], key_pem: &[u8]) -> eyre::Result<TlsAcceptor> {
+    let certs = rustls_pemfile::certs(&mut &cert_pem[..])
+        .collect::<Result<Vec<_>, _>>()
+        .wrap_err("Unable to parse TLS certificate")?;
+    if certs.is_empty() {
+        bail!("No certificates found in TLS certificate file");
+    }
+    let key = rustls_pemfile::private_key(&mut &key_pem[..])
+        .wrap_err("Unable to parse TLS private key")?
+        .ok_or_else(|| eyre!("No private key found in TLS private key file"))?;
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .wrap_err("Invalid TLS certificate/key pair")?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tls_acceptor_from_pem;
+    use crate::listen::Listeners;
+    use moor_common::tasks::ListenerTls;
+    use moor_var::{Obj, SYSTEM_OBJECT};
+    use rpc_common::HostClientToDaemonMessage::{
+        ConnectionEstablish, LoginCommand, SetClientAttributes,
+    };
+    use rpc_common::{
+        AuthToken, ClientToken, ConnectType, DaemonToClientReply, HostClientToDaemonMessage,
+        LoginOutcome, ReplyResult, RpcMessageError,
+    };
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::time::Duration;
+    use tmq::Multipart;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+    use uuid::Uuid;
+
+    const CERT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/tls/cert.pem");
+    const KEY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/tls/key.pem");
+    const CERT_PEM: &[u8] = include_bytes!("../tests/tls/cert.pem");
+    const KEY_PEM: &[u8] = include_bytes!("../tests/tls/key.pem");
+
+    #[test]
+    fn test_bad_pem_rejected() {
+        assert!(tls_acceptor_from_pem(b"", KEY_PEM).is_err());
+        assert!(tls_acceptor_from_pem(CERT_PEM, b"").is_err());
+    }
+
+    /// Stands in for the daemon's RPC endpoint: it establishes connections, and logs in anyone
+    /// who sends a login command with words in it.
+    async fn stub_daemon(zmq_ctx: tmq::Context, rpc_address: String) {
+        let mut receiver = tmq::reply(&zmq_ctx).bind(&rpc_address).unwrap();
+        loop {
+            let Ok((request, sender)) = receiver.recv().await else {
+                return;
+            };
+            let (message, _): (HostClientToDaemonMessage, _) =
+                bincode::decode_from_slice(&request[1], bincode::config::standard()).unwrap();
+            let reply = match message {
+                ConnectionEstablish(_) => {
+                    ReplyResult::ClientSuccess(DaemonToClientReply::NewConnection(
+                        ClientToken("client".into()),
+                        Obj::mk_id(-2),
+                    ))
+                }
+                LoginCommand(_, _, words, true) if !words.is_empty() => ReplyResult::ClientSuccess(
+                    DaemonToClientReply::LoginResult(LoginOutcome::Success(
+                        AuthToken("auth".into()),
+                        ConnectType::Connected,
+                        SYSTEM_OBJECT,
+                    )),
+                ),
+                SetClientAttributes(..) => {
+                    ReplyResult::ClientSuccess(DaemonToClientReply::ClientAttributesSet)
+                }
+                _ => ReplyResult::Failure(RpcMessageError::InvalidRequest("stub".into())),
+            };
+            let reply = bincode::encode_to_vec(&reply, bincode::config::standard()).unwrap();
+            receiver = sender.send(Multipart::from(vec![reply])).await.unwrap();
+        }
+    }
+
+    /// Start a TLS listener through the host's listener set, as `listen()` or `--tls-port` would,
+    /// and log in over it.
+    #[tokio::test]
+    async fn test_tls_listener_login() {
+        let id = Uuid::new_v4();
+        let rpc_address = format!("ipc:///tmp/moor-telnet-tls-test-rpc-{id}");
+        let events_address = format!("ipc:///tmp/moor-telnet-tls-test-events-{id}");
+        let zmq_ctx = tmq::Context::new();
+        tokio::spawn(stub_daemon(zmq_ctx.clone(), rpc_address.clone()));
+
+        let kill_switch = Arc::new(AtomicBool::new(false));
+        let (mut listeners_server, listeners_channel, listeners) = Listeners::new(
+            zmq_ctx,
+            rpc_address,
+            events_address,
+            kill_switch,
+            None,
+            false,
+        );
+        tokio::spawn(async move { listeners_server.run(listeners_channel).await });
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let listener_tls = ListenerTls {
+            certificate: CERT_PATH.to_string(),
+            key: KEY_PATH.to_string(),
+        };
+        listeners
+            .add_listener(&SYSTEM_OBJECT, addr, Some(listener_tls))
+            .await
+            .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut &CERT_PEM[..]) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = TlsConnector::from(Arc::new(config));
+
+        // The listener comes up asynchronously.
+        let mut attempts = 0;
+        let stream = loop {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => break stream,
+                Err(_) if attempts < 50 => {
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                Err(e) => panic!("Listener never came up: {e}"),
+            }
+        };
+        let mut stream = connector
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+        stream.write_all(b"connect wizard\r\n").await.unwrap();
+
+        // Telnet negotiation comes first; read until we see the login confirmed.
+        let mut received = vec![];
+        let mut buf = [0u8; 1024];
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !String::from_utf8_lossy(&received).contains("*** Connected ***") {
+                let n = stream.read(&mut buf).await.unwrap();
+                assert_ne!(n, 0, "Connection closed before login");
+                received.extend_from_slice(&buf[..n]);
+            }
+        })
+        .await
+        .expect("Never logged in");
+    }
+}
@@ -23,7 +23,8 @@ use rpc_async_client::{ListenersClient, ListenersMessage};
 use rpc_common::HostClientToDaemonMessage::ConnectionEstablish;
 use rpc_common::{
     AuthToken, CLIENT_BROADCAST_TOPIC, ClientEvent, ClientToken, ClientsBroadcastEvent,
-    DaemonToClientReply, HostClientToDaemonMessage, HostType, ReplyResult, VerbProgramResponse,
+    DaemonToClientReply, HostClientToDaemonMessage, HostType, LoginOutcome, ReplyResult,
+    VerbProgramResponse,
 };
 use std::collections::HashMap;
 use std::net::{Ipv4Addr, SocketAddr};
@@ -42,7 +43,7 @@ pub async fn noop_listeners_loop() -> (ListenersClient, JoinHandle<()>) {
     let t = tokio::spawn(async move {
         while let Some(msg) = rx.recv().await {
             match msg {
-                ListenersMessage::AddListener(_, _) => {}
+                ListenersMessage::AddListener(_, _, _) => {}
                 ListenersMessage::RemoveListener(_) => {}
                 ListenersMessage::GetListeners(r) => {
                     let _ = r.send(vec![]);
@@ -177,7 +178,7 @@ pub async fn create_user_session(
         .await
         .expect("Unable to send login request to RPC server");
     let (connection_oid, auth_token) = if let ReplyResult::ClientSuccess(
-        DaemonToClientReply::LoginResult(Some((auth_token, _connect_type, player))),
+        DaemonToClientReply::LoginResult(LoginOutcome::Success(auth_token, _connect_type, player)),
     ) = response
     {
         (player, auth_token.clone())
@@ -121,8 +121,8 @@ impl MootClient {
     }
 
     fn read_line(&self) -> eyre::Result<Option<String>> {
-        let mut buf = String::new();
-        match BufReader::new(&self.stream).read_line(&mut buf) {
+        let mut buf = vec![];
+        match BufReader::new(&self.stream).read_until(b'\n', &mut buf) {
             Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                 let port = self.port();
                 eprintln!(
@@ -139,6 +139,7 @@ impl MootClient {
             }
             Ok(0) => Ok(None),
             Ok(_) => {
+                let buf = String::from_utf8_lossy(&strip_telnet_commands(&buf)).into_owned();
                 let line = buf.trim_end_matches(['\r', '\n']).to_string();
                 let port = self.port();
                 eprintln!(
@@ -156,6 +157,40 @@ impl MootClient {
     }
 }
 
+/// Drop the server's telnet option negotiation (e.g. its offer of compression) from a line.
+/// We never agree to anything, so there's nothing else to handle.
+fn strip_telnet_commands(bytes: &[u8]) -> Vec<u8> {
+    const IAC: u8 = 255;
+    const SB: u8 = 250;
+    const SE: u8 = 240;
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != IAC {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        match bytes.get(i + 1) {
+            Some(&IAC) => {
+                out.push(IAC);
+                i += 2;
+            }
+            // WILL, WONT, DO, DONT
+            Some(251..=254) => i += 3,
+            Some(&SB) => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == IAC && bytes.get(i + 1) == Some(&SE)) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            _ => i += 2,
+        }
+    }
+    out
+}
+
 pub struct TelnetMootRunner {
     port: u16,
     clients: HashMap<Obj, MootClient>,
@@ -168,7 +168,8 @@ pub fn make_textdump(tx: &dyn LoaderInterface, version: String) -> Textdump {
                 .expect("Failed to get verb binary");
 
             #[allow(irrefutable_let_patterns)]
-            let ProgramType::MooR(program) = program else {
+            let (ProgramType::MooR(program) | ProgramType::MooRWithSource(program, _)) = program
+            else {
                 panic!("Expected Moo program, got {:?}", program);
             };
             let prgstr = if !program.main_vector().is_empty() {
@@ -23,6 +23,7 @@ mod test {
     use std::path::PathBuf;
     use std::sync::Arc;
 
+    use moor_common::model::PropFlag;
     use moor_common::model::VerbArgsSpec;
     use moor_common::model::VerbFlag;
     use moor_common::model::WorldStateSource;
@@ -39,6 +40,7 @@ mod test {
     use moor_var::SYSTEM_OBJECT;
     use moor_var::Symbol;
     use moor_var::{NOTHING, Obj};
+    use moor_var::{v_int, v_list, v_obj, v_str};
 
     fn get_minimal_db() -> File {
         let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -76,6 +78,148 @@ mod test {
         String::from_utf8(output).expect("Failed to convert output to string")
     }
 
+    /// Read a textdump held in a string into a fresh db.
+    fn reload_textdump(textdump: &str) -> Arc<TxDB> {
+        let (db, _) = TxDB::open(None, DatabaseConfig::default());
+        let db = Arc::new(db);
+        let mut lc = db.clone().loader_client().unwrap();
+        read_textdump(
+            lc.as_mut(),
+            BufReader::new(textdump.as_bytes()),
+            Version::new(0, 1, 0),
+            CompileOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(lc.commit().unwrap(), CommitResult::Success);
+        db
+    }
+
+    /// Verify that the two dbs hold the same objects, with the same properties and verbs on each.
+    fn assert_same_world(db1: Arc<TxDB>, db2: Arc<TxDB>) {
+        let tx1 = db1.loader_client().unwrap();
+        let tx2 = db2.loader_client().unwrap();
+        let objects1 = tx1.get_objects().unwrap();
+        let objects2 = tx2.get_objects().unwrap();
+        let objects1 = objects1.iter().collect::<BTreeSet<_>>();
+        let objects2 = objects2.iter().collect::<BTreeSet<_>>();
+        assert_eq!(objects1, objects2);
+
+        for o in objects1 {
+            // set of properties should be the same
+            let o1_props = tx1.get_object_properties(&o).unwrap();
+            let o2_props = tx2.get_object_properties(&o).unwrap();
+            let mut o1_props = o1_props.iter().collect::<Vec<_>>();
+            let mut o2_props = o2_props.iter().collect::<Vec<_>>();
+
+            o1_props.sort_by(|a, b| a.name().cmp(b.name()));
+            o2_props.sort_by(|a, b| a.name().cmp(b.name()));
+
+            // We want to do equality testing, but ignore the UUID which can be different
+            // between textdump loads...
+            assert_eq!(o1_props.len(), o2_props.len());
+            let zipped = o1_props.iter().zip(o2_props.iter());
+            for (i, prop) in zipped.enumerate() {
+                let (p1, p2) = prop;
+
+                assert_eq!(
+                    p1.name(),
+                    p2.name(),
+                    "{}.{}, name mismatch",
+                    o.clone(),
+                    p1.name(),
+                );
+
+                assert_eq!(
+                    p1.definer(),
+                    p2.definer(),
+                    "{}.{}, definer mismatch ({} != {})",
+                    o,
+                    p1.name(),
+                    p1.definer(),
+                    p2.definer()
+                );
+                // location
+                assert_eq!(
+                    p1.location(),
+                    p2.location(),
+                    "{}.{}, location mismatch",
+                    o,
+                    p1.name()
+                );
+
+                let (value1, perms1) = tx1.get_property_value(&o, p1.uuid()).unwrap();
+                let (value2, perms2) = tx2.get_property_value(&o, p2.uuid()).unwrap();
+
+                assert_eq!(
+                    perms1.flags(),
+                    perms2.flags(),
+                    "{}.{}, flags mismatch",
+                    o,
+                    p1.name(),
+                );
+                assert_eq!(
+                    perms1.owner(),
+                    perms2.owner(),
+                    "{}.{}, owner mismatch",
+                    o,
+                    p1.name(),
+                );
+
+                assert_eq!(
+                    value1,
+                    value2,
+                    "{}.{}, value mismatch ({}th value checked)",
+                    o,
+                    p1.name(),
+                    i
+                );
+            }
+
+            // Now compare verbdefs
+            let o1_verbs = tx1.get_object_verbs(&o).unwrap();
+            let o2_verbs = tx2.get_object_verbs(&o).unwrap();
+            let o1_verbs = o1_verbs.iter().collect::<Vec<_>>();
+            let o2_verbs = o2_verbs.iter().collect::<Vec<_>>();
+
+            assert_eq!(o1_verbs.len(), o2_verbs.len());
+            for (v1, v2) in o1_verbs.iter().zip(o2_verbs.iter()) {
+                let v1_name = v1.names().join(" ").to_string();
+                assert_eq!(v1.names(), v2.names(), "{}:{}, name mismatch", o, v1_name);
+
+                assert_eq!(v1.owner(), v2.owner(), "{}:{}, owner mismatch", o, v1_name);
+                assert_eq!(v1.flags(), v2.flags(), "{}:{}, flags mismatch", o, v1_name);
+                assert_eq!(v1.args(), v2.args(), "{}:{}, args mismatch", o, v1_name);
+
+                // We want to actually decode and compare the opcode streams rather than
+                // the binary, so that we can make meaningful error reports.
+                let prg1 = tx1.get_verb_program(&o, v1.uuid()).unwrap();
+                let prg2 = tx2.get_verb_program(&o, v2.uuid()).unwrap();
+
+                #[allow(irrefutable_let_patterns)]
+                let ProgramType::MooR(program1) = &prg1 else {
+                    panic!("ProgramType::Moo expected");
+                };
+                #[allow(irrefutable_let_patterns)]
+                let ProgramType::MooR(program2) = &prg2 else {
+                    panic!("ProgramType::Moo expected");
+                };
+                let program1 = moor_compiler::program_to_tree(program1).unwrap();
+                let program2 = moor_compiler::program_to_tree(program2).unwrap();
+
+                assert_eq!(
+                    program1.variables, program2.variables,
+                    "{}:{}, variable names mismatch",
+                    o, v1_name
+                );
+                assert_eq!(
+                    program1.stmts, program2.stmts,
+                    "{}:{}, statements mismatch",
+                    o, v1_name
+                );
+            }
+        }
+    }
+
     /// Load Minimal.db with the textdump reader and confirm that it has the expected contents.
     #[test]
     fn load_minimal() {
@@ -302,142 +446,69 @@ mod test {
         let textdump = write_textdump(db1.clone(), "** LambdaMOO Database, Format Version 4 **");
 
         // Now load that same core back in to a new DB, and hope we don't blow up anywhere.
-        let (db2, _) = TxDB::open(None, DatabaseConfig::default());
-        let db2 = Arc::new(db2);
-        let buffered_string_reader = std::io::BufReader::new(textdump.as_bytes());
-        let mut lc = db2.clone().loader_client().unwrap();
-        read_textdump(
-            lc.as_mut(),
-            buffered_string_reader,
-            Version::new(0, 1, 0),
-            CompileOptions::default(),
-        )
-        .unwrap();
-        assert_eq!(lc.commit().unwrap(), CommitResult::Success);
-
-        // Now go through the properties and verbs of all the objects on db1, and verify that
-        // they're the same on db2.
-        let tx1 = db1.loader_client().unwrap();
-        let tx2 = db2.loader_client().unwrap();
-        let objects1 = tx1.get_objects().unwrap();
-        let objects2 = tx2.get_objects().unwrap();
-        let objects1 = objects1.iter().collect::<BTreeSet<_>>();
-        let objects2 = objects2.iter().collect::<BTreeSet<_>>();
-        assert_eq!(objects1, objects2);
-
-        for o in objects1 {
-            // set of properties should be the same
-            let o1_props = tx1.get_object_properties(&o).unwrap();
-            let o2_props = tx2.get_object_properties(&o).unwrap();
-            let mut o1_props = o1_props.iter().collect::<Vec<_>>();
-            let mut o2_props = o2_props.iter().collect::<Vec<_>>();
-
-            o1_props.sort_by(|a, b| a.name().cmp(b.name()));
-            o2_props.sort_by(|a, b| a.name().cmp(b.name()));
-
-            // We want to do equality testing, but ignore the UUID which can be different
-            // between textdump loads...
-            assert_eq!(o1_props.len(), o2_props.len());
-            let zipped = o1_props.iter().zip(o2_props.iter());
-            for (i, prop) in zipped.enumerate() {
-                let (p1, p2) = prop;
-
-                assert_eq!(
-                    p1.name(),
-                    p2.name(),
-                    "{}.{}, name mismatch",
-                    o.clone(),
-                    p1.name(),
-                );
-
-                assert_eq!(
-                    p1.definer(),
-                    p2.definer(),
-                    "{}.{}, definer mismatch ({} != {})",
-                    o,
-                    p1.name(),
-                    p1.definer(),
-                    p2.definer()
-                );
-                // location
-                assert_eq!(
-                    p1.location(),
-                    p2.location(),
-                    "{}.{}, location mismatch",
-                    o,
-                    p1.name()
-                );
-
-                let (value1, perms1) = tx1.get_property_value(&o, p1.uuid()).unwrap();
-                let (value2, perms2) = tx2.get_property_value(&o, p2.uuid()).unwrap();
-
-                assert_eq!(
-                    perms1.flags(),
-                    perms2.flags(),
-                    "{}.{}, flags mismatch",
-                    o,
-                    p1.name(),
-                );
-                assert_eq!(
-                    perms1.owner(),
-                    perms2.owner(),
-                    "{}.{}, owner mismatch",
-                    o,
-                    p1.name(),
-                );
-
-                assert_eq!(
-                    value1,
-                    value2,
-                    "{}.{}, value mismatch ({}th value checked)",
-                    o,
-                    p1.name(),
-                    i
-                );
-            }
-
-            // Now compare verbdefs
-            let o1_verbs = tx1.get_object_verbs(&o).unwrap();
-            let o2_verbs = tx2.get_object_verbs(&o).unwrap();
-            let o1_verbs = o1_verbs.iter().collect::<Vec<_>>();
-            let o2_verbs = o2_verbs.iter().collect::<Vec<_>>();
-
-            assert_eq!(o1_verbs.len(), o2_verbs.len());
-            for (v1, v2) in o1_verbs.iter().zip(o2_verbs.iter()) {
-                let v1_name = v1.names().join(" ").to_string();
-                assert_eq!(v1.names(), v2.names(), "{}:{}, name mismatch", o, v1_name);
+        let db2 = reload_textdump(&textdump);
+        assert_same_world(db1, db2);
+    }
 
-                assert_eq!(v1.owner(), v2.owner(), "{}:{}, owner mismatch", o, v1_name);
-                assert_eq!(v1.flags(), v2.flags(), "{}:{}, flags mismatch", o, v1_name);
-                assert_eq!(v1.args(), v2.args(), "{}:{}, args mismatch", o, v1_name);
+    /// Load Minimal.db, add a property and a verb to it, write it out, then read that back into a
+    /// fresh db, and verify nothing was lost on the way.
+    #[test]
+    fn load_write_reload_minimal() {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let minimal_db = manifest_dir.join("tests/Minimal.db");
 
-                // We want to actually decode and compare the opcode streams rather than
-                // the binary, so that we can make meaningful error reports.
-                let prg1 = tx1.get_verb_program(&o, v1.uuid()).unwrap();
-                let prg2 = tx2.get_verb_program(&o, v2.uuid()).unwrap();
+        let (db1, _) = TxDB::open(None, DatabaseConfig::default());
+        let db1 = Arc::new(db1);
+        load_textdump_file(
+            db1.clone().loader_client().unwrap(),
+            minimal_db.to_str().unwrap(),
+        );
 
-                #[allow(irrefutable_let_patterns)]
-                let ProgramType::MooR(program1) = &prg1 else {
-                    panic!("ProgramType::Moo expected");
-                };
-                #[allow(irrefutable_let_patterns)]
-                let ProgramType::MooR(program2) = &prg2 else {
-                    panic!("ProgramType::Moo expected");
-                };
-                let program1 = moor_compiler::program_to_tree(program1).unwrap();
-                let program2 = moor_compiler::program_to_tree(program2).unwrap();
+        // Minimal.db has no properties, and its only verb is empty, so give the round trip
+        // something to carry.
+        let wizard = Obj::mk_id(3);
+        let mut tx = db1.clone().loader_client().unwrap();
+        tx.define_property(
+            &Obj::mk_id(1),
+            &Obj::mk_id(1),
+            "greeting",
+            &wizard,
+            PropFlag::r(),
+            Some(v_list(&[v_int(1), v_str("two"), v_obj(wizard)])),
+        )
+        .unwrap();
+        tx.set_property(&Obj::mk_id(2), "greeting", None, None, Some(v_str("hello")))
+            .unwrap();
+        let program = moor_compiler::compile(
+            "if (args) return {1, \"two\"}; endif return this.greeting;",
+            CompileOptions::default(),
+        )
+        .unwrap();
+        tx.add_verb(
+            &SYSTEM_OBJECT,
+            vec!["test_verb", "tv*"],
+            &wizard,
+            VerbFlag::rxd(),
+            VerbArgsSpec::this_none_this(),
+            ProgramType::MooR(program),
+        )
+        .unwrap();
+        assert_eq!(tx.commit().unwrap(), CommitResult::Success);
 
-                assert_eq!(
-                    program1.variables, program2.variables,
-                    "{}:{}, variable names mismatch",
-                    o, v1_name
-                );
-                assert_eq!(
-                    program1.stmts, program2.stmts,
-                    "{}:{}, statements mismatch",
-                    o, v1_name
-                );
-            }
-        }
+        let textdump = write_textdump(db1.clone(), "** LambdaMOO Database, Format Version 4 **");
+        let db2 = reload_textdump(&textdump);
+
+        // The comparison below only looks at properties where they're defined, so check the
+        // value overridden on the child came through too.
+        let tx2 = db2.clone().loader_client().unwrap();
+        let room_props = tx2.get_all_property_values(&Obj::mk_id(2)).unwrap();
+        let (_, (greeting, _)) = room_props
+            .iter()
+            .find(|(pd, _)| pd.name() == "greeting")
+            .expect("greeting property missing after reload");
+        assert_eq!(greeting, &Some(v_str("hello")));
+        assert_eq!(tx2.commit().unwrap(), CommitResult::Success);
+
+        assert_same_world(db1, db2);
     }
 }
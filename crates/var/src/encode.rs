@@ -41,6 +41,8 @@ pub enum DecodingError {
     InvalidBinaryTypeValue(u8),
     #[error("Invalid Error value: {0}")]
     InvalidErrorValue(u8),
+    #[error("Unsupported program format version {0} (this server reads version {1})")]
+    UnsupportedProgramVersion(u16, u16),
 }
 
 /// A trait for all common that can be stored in the database. (e.g. all of them).
@@ -46,6 +46,16 @@ impl Str {
         let v = Variant::Str(s);
         Var::from_variant(v)
     }
+
+    /// The byte offset of the character at `index`, or the end of the string if it's past it.
+    /// Strings are indexed by character, not byte, so that non-ASCII text behaves.
+    fn byte_offset(&self, index: usize) -> usize {
+        let s = self.as_str();
+        s.char_indices()
+            .nth(index)
+            .map(|(b, _)| b)
+            .unwrap_or(s.len())
+    }
 }
 
 impl Sequence for Str {
@@ -54,7 +64,7 @@ impl Sequence for Str {
     }
 
     fn len(&self) -> usize {
-        self.as_str().len()
+        self.as_str().chars().count()
     }
 
     fn index_in(&self, value: &Var, case_sensitive: bool) -> Result<Option<usize>, Error> {
@@ -72,14 +82,16 @@ impl Sequence for Str {
 
         let s = self.as_str();
         let value = value.as_str();
-        let contains = if case_sensitive {
+        let position = if case_sensitive {
             // Get the index of the substring in the string.
-            s.find(value)
+            s.find(value).map(|pos| s[..pos].chars().count())
         } else {
-            s.to_lowercase().find(&value.to_lowercase())
+            let s = s.to_lowercase();
+            s.find(&value.to_lowercase())
+                .map(|pos| s[..pos].chars().count())
         };
 
-        Ok(contains)
+        Ok(position)
     }
 
     fn contains(&self, value: &Var, case_sensitive: bool) -> Result<bool, Error> {
@@ -107,7 +119,7 @@ impl Sequence for Str {
     }
 
     fn index(&self, index: usize) -> Result<Var, Error> {
-        if index >= self.as_str().len() {
+        if index >= self.len() {
             return Err(E_RANGE.with_msg(|| {
                 format!(
                     "Index {} out of range for string of length {}",
@@ -122,7 +134,7 @@ impl Sequence for Str {
     }
 
     fn index_set(&self, index: usize, value: &Var) -> Result<Var, Error> {
-        if index >= self.as_str().len() {
+        if index >= self.len() {
             return Err(E_RANGE.with_msg(|| {
                 format!(
                     "Index {} out of range for string of length {}",
@@ -152,8 +164,10 @@ impl Sequence for Str {
             return Err(E_INVARG.msg("String index set value must be a single character"));
         }
 
+        let start = self.byte_offset(index);
+        let end = self.byte_offset(index + 1);
         let mut s = self.as_str().to_string();
-        s.replace_range(index..=index, value.as_str());
+        s.replace_range(start..end, value.as_str());
         Ok(Var::from_variant(Variant::Str(Str(Arc::new(s)))))
     }
 
@@ -187,7 +201,7 @@ impl Sequence for Str {
         };
 
         let mut new_copy = self.as_str().to_string();
-        new_copy.insert_str(index, value.as_str());
+        new_copy.insert_str(self.byte_offset(index), value.as_str());
         Ok(Var::from_variant(Variant::Str(Str(Arc::new(new_copy)))))
     }
 
@@ -195,20 +209,18 @@ impl Sequence for Str {
         if to < from {
             return Ok(Var::mk_str(""));
         }
-        let s = self.as_str();
+        let len = self.len();
         let start = max(from, 0) as usize;
         let to = to as usize;
-        if start >= s.len() || to >= s.len() {
+        if start >= len || to >= len {
             return Err(E_RANGE.with_msg(|| {
                 format!(
                     "Range {}..{} out of bounds for string of length {}",
-                    from,
-                    to,
-                    s.len()
+                    from, to, len
                 )
             }));
         }
-        let s = s.get(start..=to).unwrap();
+        let s = &self.as_str()[self.byte_offset(start)..self.byte_offset(to + 1)];
         Ok(Var::mk_str(s))
     }
 
@@ -226,7 +238,7 @@ impl Sequence for Str {
         };
 
         let base_str = self.as_str();
-        let from = max(from, 0) as usize;
+        let from = self.byte_offset(max(from, 0) as usize);
 
         let mut result_str = if from > 0 {
             base_str[..from].to_string()
@@ -237,7 +249,7 @@ impl Sequence for Str {
 
         match to.to_usize() {
             Some(to) => {
-                result_str.push_str(&base_str[to + 1..]);
+                result_str.push_str(&base_str[self.byte_offset(to + 1)..]);
             }
             None => {
                 result_str.push_str(base_str);
@@ -263,7 +275,7 @@ impl Sequence for Str {
     }
 
     fn remove_at(&self, index: usize) -> Result<Var, Error> {
-        if index >= self.as_str().len() {
+        if index >= self.len() {
             return Err(E_RANGE.with_msg(|| {
                 format!(
                     "Index {} out of range for string of length {}",
@@ -274,7 +286,7 @@ impl Sequence for Str {
         }
 
         let mut new_copy = self.as_str().to_string();
-        new_copy.remove(index);
+        new_copy.remove(self.byte_offset(index));
         Ok(Var::from_variant(Variant::Str(Str(Arc::new(new_copy)))))
     }
 }
@@ -406,6 +418,40 @@ mod tests {
         assert_eq!(fail_bad_index.unwrap_err(), E_RANGE);
     }
 
+    #[test]
+    fn test_string_unicode_indexing() {
+        // Lengths and indices count characters, not bytes.
+        let s = v_str("ünïcödé");
+        assert_eq!(s.len().unwrap(), 7);
+        assert_eq!(
+            s.index(&v_int(2), IndexMode::ZeroBased).unwrap(),
+            v_str("ï")
+        );
+        assert_eq!(
+            s.range(&v_int(2), &v_int(4), IndexMode::OneBased).unwrap(),
+            v_str("nïc")
+        );
+        assert_eq!(
+            s.index_set(&v_int(6), &v_str("e"), IndexMode::ZeroBased)
+                .unwrap(),
+            v_str("ünïcöde")
+        );
+        assert_eq!(
+            s.range_set(&v_int(2), &v_int(3), &v_str("NI"), IndexMode::OneBased)
+                .unwrap(),
+            v_str("üNIcödé")
+        );
+        assert_eq!(
+            s.index(&v_int(7), IndexMode::ZeroBased).unwrap_err(),
+            E_RANGE
+        );
+        assert_eq!(
+            s.index_in(&v_str("cö"), false, IndexMode::OneBased)
+                .unwrap(),
+            v_int(4)
+        );
+    }
+
     #[test]
     fn test_one_index_slice() {
         let s = v_str("hello world");
@@ -16,8 +16,15 @@ use bincode::enc::Encoder;
 use bincode::error::{DecodeError, EncodeError};
 use bincode::{BorrowDecode, Decode, Encode};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::fmt::{Debug, Display};
-use ustr::Ustr;
+use ustr::{Ustr, UstrMap};
+
+thread_local! {
+    /// The case-folded form of each symbol folded on this thread, so that a name is only ever
+    /// lowercased once per thread rather than on every comparison.
+    static FOLDED: RefCell<UstrMap<Ustr>> = RefCell::new(UstrMap::default());
+}
 
 /// An interned string used for things like verb names and property names.
 /// Not currently a permissible value in Var, but is used throughout the system.
@@ -51,6 +58,23 @@ impl Symbol {
     pub fn mk_case_insensitive(s: &str) -> Self {
         Symbol(Ustr::from(&s.to_lowercase()))
     }
+
+    /// The lowercased form of this symbol, itself interned, so that two names can be compared
+    /// case-insensitively by identity instead of by string.
+    pub fn fold(&self) -> Symbol {
+        FOLDED.with(|folded| {
+            let folded = *folded
+                .borrow_mut()
+                .entry(self.0)
+                .or_insert_with(|| Ustr::from(&self.0.as_str().to_lowercase()));
+            Symbol(folded)
+        })
+    }
+
+    /// Whether this symbol and `other` are the same name, ignoring case.
+    pub fn eq_case_insensitive(&self, other: &Symbol) -> bool {
+        self == other || self.fold() == other.fold()
+    }
 }
 
 impl From<&str> for Symbol {
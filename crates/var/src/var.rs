@@ -687,7 +687,7 @@ impl Var {
         match self.type_class() {
             TypeClass::Sequence(s) => Ok(s.len()),
             TypeClass::Associative(a) => Ok(a.len()),
-            TypeClass::Scalar => Err(E_INVARG.with_msg(|| {
+            TypeClass::Scalar => Err(E_TYPE.with_msg(|| {
                 format!(
                     "Cannot get length of scalar value {}",
                     self.type_code().to_literal()
@@ -15,7 +15,7 @@
 import { Context, Player } from "./model";
 
 import van, { State } from "vanjs-core";
-import { displayDjot, handleEvent } from "./narrative";
+import { displayDjot, handleBinary, handleEvent } from "./narrative";
 
 const { button, div, input, select, option, br, label } = van.tags;
 
@@ -62,7 +62,9 @@ async function connect(context: Context, player: State<Player>, mode, username,
         setTimeout(() => document.getElementById("input_area").focus(), 0.5);
     };
     ws.onmessage = (e) => {
-        if (e.data) {
+        if (e.data instanceof Blob) {
+            handleBinary(context, e.data);
+        } else if (e.data) {
             handleEvent(context, e.data);
         }
     };
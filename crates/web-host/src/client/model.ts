@@ -80,6 +80,8 @@ export class Context {
     player: Player;
     spool: Spool | null;
     presentations: State<Presentations>;
+    // The content type of the binary frame announced to follow next, if any.
+    pendingBinary: string | null;
 
     constructor() {
         this.ws = null;
@@ -90,6 +92,7 @@ export class Context {
         this.player = new Player("", "", false);
         this.spool = null;
         this.presentations = van.state(new Presentations({}));
+        this.pendingBinary = null;
     }
 }
 
@@ -33,7 +33,7 @@ import { launchVerbEditor, showVerbEditor } from "./verb_edit";
 // import sanitize html
 import DOMPurify from "dompurify";
 
-const { div, span, textarea } = van.tags;
+const { a, div, img, span, textarea } = van.tags;
 
 // Utility function to build DOM elements from HTML.
 function generateElements(html) {
@@ -375,6 +375,23 @@ function handleTraceback(context: Context, traceback: Traceback) {
     narrativeAppend(content_node);
 }
 
+// Show binary content sent with send_binary(): images inline, anything else as a download link.
+export function handleBinary(context: Context, data: Blob) {
+    let content_type = context.pendingBinary || "application/octet-stream";
+    context.pendingBinary = null;
+    let url = URL.createObjectURL(new Blob([data], { type: content_type }));
+    let content_node;
+    if (content_type.startsWith("image/")) {
+        content_node = div({ class: "binary_narrative" }, img({ src: url }));
+    } else {
+        content_node = div(
+            { class: "binary_narrative" },
+            a({ href: url, download: "" }, `Download (${content_type}, ${data.size} bytes)`),
+        );
+    }
+    narrativeAppend(content_node);
+}
+
 // Process an inbound (JSON) event from the websocket connection to the server.
 export function handleEvent(context: Context, msg) {
     let event = JSON.parse(msg);
@@ -391,6 +408,9 @@ export function handleEvent(context: Context, msg) {
         handleUnpresent(context, event["unpresent"]);
     } else if (event["traceback"]) {
         handleTraceback(context, event["traceback"]);
+    } else if (event["binary"] !== undefined) {
+        // The content itself follows in the next (binary) frame.
+        context.pendingBinary = event["content_type"];
     } else {
         console.log("Unknown event type: " + event);
     }
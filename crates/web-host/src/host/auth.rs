@@ -15,11 +15,14 @@ use crate::host::WebHost;
 use crate::host::web_host::{LoginType, WsHostError};
 use axum::Form;
 use axum::extract::{ConnectInfo, State};
-use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
+use eyre::eyre;
+use moor_var::Obj;
 use rpc_async_client::rpc_client::RpcSendClient;
 use rpc_common::{
-    AuthToken, ClientToken, DaemonToClientReply, HostClientToDaemonMessage, ReplyResult,
+    AuthToken, ClientToken, DaemonToClientReply, HostClientToDaemonMessage, LoginOutcome,
+    ReplyResult,
 };
 use serde_derive::Deserialize;
 use std::net::SocketAddr;
@@ -48,41 +51,37 @@ pub async fn create_auth_handler(
     auth_handler(LoginType::Create, addr, ws_host, player, password).await
 }
 
-/// Stand-alone HTTP POST authentication handler which connects and then gets a valid authentication token
-/// which can then be used in the headers/query-string for subsequent websocket request.
-async fn auth_handler(
+/// A player connection which has logged in (or been created) over a fresh RPC connection.
+pub(crate) struct LoggedIn {
+    pub(crate) client_id: Uuid,
+    pub(crate) rpc_client: RpcSendClient,
+    pub(crate) client_token: ClientToken,
+    pub(crate) auth_token: AuthToken,
+    pub(crate) player: Obj,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum LoginError {
+    #[error("Unable to establish connection: {0}")]
+    Connection(WsHostError),
+    #[error("Login rejected")]
+    Rejected,
+}
+
+/// Open a connection to the daemon and run the `connect` or `create` login command through it.
+pub(crate) async fn login(
     login_type: LoginType,
     addr: SocketAddr,
-    host: WebHost,
+    host: &WebHost,
     player: String,
     password: String,
-) -> impl IntoResponse {
-    debug!("Authenticating player: {}", player);
-    let (client_id, mut rpc_client, client_token) =
-        match host.establish_client_connection(addr).await {
-            Ok((client_id, rpc_client, client_token)) => (client_id, rpc_client, client_token),
-            Err(WsHostError::AuthenticationFailed) => {
-                warn!("Authentication failed for {}", player);
-                return Response::builder()
-                    .status(StatusCode::FORBIDDEN)
-                    .body("".to_string())
-                    .unwrap();
-            }
-            Err(e) => {
-                error!("Unable to establish connection: {}", e);
-                return Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body("".to_string())
-                    .unwrap();
-            }
-        };
-
-    let auth_verb = match login_type {
-        LoginType::Connect => "connect",
-        LoginType::Create => "create",
-    };
+) -> Result<LoggedIn, LoginError> {
+    let (client_id, mut rpc_client, client_token) = host
+        .establish_client_connection(addr)
+        .await
+        .map_err(LoginError::Connection)?;
 
-    let words = vec![auth_verb.to_string(), player, password];
+    let words = vec![login_type.auth_verb().to_string(), player, password];
     let response = rpc_client
         .make_client_rpc_call(
             client_id,
@@ -94,29 +93,65 @@ async fn auth_handler(
             ),
         )
         .await
-        .expect("Unable to send login request to RPC server");
-    let ReplyResult::ClientSuccess(DaemonToClientReply::LoginResult(Some((
+        .map_err(|e| LoginError::Connection(WsHostError::RpcError(eyre!(e))))?;
+    let ReplyResult::ClientSuccess(DaemonToClientReply::LoginResult(LoginOutcome::Success(
         auth_token,
         _connect_type,
         player,
-    )))) = response
+    ))) = response
     else {
         error!(?response, "Login failed");
-
-        return Response::builder()
-            .status(StatusCode::UNAUTHORIZED)
-            .body("".to_string())
-            .unwrap();
+        return Err(LoginError::Rejected);
     };
 
-    // We now have a valid auth token for the player, so we return it in the response headers.
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        "X-Moor-Auth-Token",
-        HeaderValue::from_str(&auth_token.0).expect("Invalid token"),
-    );
+    Ok(LoggedIn {
+        client_id,
+        rpc_client,
+        client_token,
+        auth_token,
+        player,
+    })
+}
 
-    // We now need to wait for the login message completion.
+/// Stand-alone HTTP POST authentication handler which connects and then gets a valid authentication token
+/// which can then be used in the headers/query-string for subsequent websocket request.
+async fn auth_handler(
+    login_type: LoginType,
+    addr: SocketAddr,
+    host: WebHost,
+    player: String,
+    password: String,
+) -> impl IntoResponse {
+    debug!("Authenticating player: {}", player);
+    let LoggedIn {
+        client_id,
+        mut rpc_client,
+        client_token,
+        auth_token,
+        player,
+    } = match login(login_type, addr, &host, player.clone(), password).await {
+        Ok(logged_in) => logged_in,
+        Err(LoginError::Connection(WsHostError::AuthenticationFailed)) => {
+            warn!("Authentication failed for {}", player);
+            return Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body("".to_string())
+                .unwrap();
+        }
+        Err(LoginError::Connection(e)) => {
+            error!("Unable to establish connection: {}", e);
+            return Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body("".to_string())
+                .unwrap();
+        }
+        Err(LoginError::Rejected) => {
+            return Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body("".to_string())
+                .unwrap();
+        }
+    };
 
     // We're done with this RPC connection, so we detach it.
     let _ = rpc_client
@@ -130,7 +165,7 @@ async fn auth_handler(
     Response::builder()
         .status(StatusCode::OK)
         .header("X-Moor-Auth-Token", auth_token.0)
-        .body(format!("{} {}", player, auth_verb))
+        .body(format!("{} {}", player, login_type.auth_verb()))
         .unwrap()
 }
 
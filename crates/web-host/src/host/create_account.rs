@@ -0,0 +1,326 @@
+// Copyright (C) 2025 Ryan Daum <ryan.daum@gmail.com> This program is free
+// software: you can redistribute it and/or modify it under the terms of the GNU
+// General Public License as published by the Free Software Foundation, version
+// 3.
+//
+// This program is distributed in the hope that it will be useful, but WITHOUT
+// ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or FITNESS
+// FOR A PARTICULAR PURPOSE. See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with
+// this program. If not, see <https://www.gnu.org/licenses/>.
+//
+
+//! Account creation over a websocket.
+//! The client opens `/ws/create` and sends one JSON frame with the desired name and password,
+//! e.g. `{"player": "Fred", "password": "secret"}`. The host runs the core's `create` login
+//! command and replies with a JSON frame, either
+//! `{"result": "created", "player": {"oid": 123}, "auth_token": "..."}`, after which the socket
+//! carries on as a normal connection for the new player, or
+//! `{"result": "error", "error": "rejected", "message": "..."}`, after which it is closed.
+
+use crate::host::WebHost;
+use crate::host::auth::{LoggedIn, LoginError, login};
+use crate::host::serialize_var;
+use crate::host::web_host::LoginType;
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{ConnectInfo, State, WebSocketUpgrade};
+use axum::response::IntoResponse;
+use moor_var::{Var, v_obj};
+use rpc_common::ConnectType;
+use serde_derive::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tracing::{debug, error, info};
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CreateAccountRequest {
+    pub player: String,
+    pub password: String,
+}
+
+impl CreateAccountRequest {
+    pub fn decode(frame: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(frame)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreateAccountError {
+    /// The request frame was missing or couldn't be parsed.
+    InvalidRequest,
+    /// The host couldn't talk to the daemon.
+    ConnectionFailed,
+    /// The core refused to create the player, e.g. because the name is taken.
+    Rejected,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum CreateAccountReply {
+    Created {
+        #[serde(serialize_with = "serialize_var")]
+        player: Var,
+        auth_token: String,
+    },
+    Error {
+        error: CreateAccountError,
+        message: String,
+    },
+}
+
+impl CreateAccountReply {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).expect("Unable to serialize create account reply")
+    }
+
+    fn error(error: CreateAccountError, message: impl ToString) -> Self {
+        CreateAccountReply::Error {
+            error,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Websocket upgrade handler for creating a new player, see the module docs for the protocol.
+pub async fn ws_create_account_handler(
+    ws: WebSocketUpgrade,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    State(host): State<WebHost>,
+) -> impl IntoResponse {
+    info!("Account creation connection from {}", addr);
+
+    ws.on_upgrade(move |socket| async move { create_account(host, addr, socket).await })
+}
+
+async fn create_account(host: WebHost, addr: SocketAddr, mut socket: WebSocket) {
+    let request = match socket.recv().await {
+        Some(Ok(Message::Text(frame))) => CreateAccountRequest::decode(frame.as_str()),
+        other => {
+            debug!(?other, "No account creation request received");
+            let reply = CreateAccountReply::error(
+                CreateAccountError::InvalidRequest,
+                "expected a JSON text frame",
+            );
+            let _ = socket.send(Message::Text(reply.encode().into())).await;
+            return;
+        }
+    };
+    let request = match request {
+        Ok(request) => request,
+        Err(e) => {
+            let reply = CreateAccountReply::error(CreateAccountError::InvalidRequest, e);
+            let _ = socket.send(Message::Text(reply.encode().into())).await;
+            return;
+        }
+    };
+
+    let LoggedIn {
+        client_id,
+        rpc_client,
+        client_token,
+        auth_token,
+        player,
+    } = match login(
+        LoginType::Create,
+        addr,
+        &host,
+        request.player,
+        request.password,
+    )
+    .await
+    {
+        Ok(logged_in) => logged_in,
+        Err(e) => {
+            let error = match e {
+                LoginError::Connection(_) => CreateAccountError::ConnectionFailed,
+                LoginError::Rejected => CreateAccountError::Rejected,
+            };
+            let reply = CreateAccountReply::error(error, e);
+            let _ = socket.send(Message::Text(reply.encode().into())).await;
+            return;
+        }
+    };
+
+    let reply = CreateAccountReply::Created {
+        player: v_obj(player),
+        auth_token: auth_token.0.clone(),
+    };
+    if socket
+        .send(Message::Text(reply.encode().into()))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    let mut connection = match host
+        .start_ws_connection(
+            &host.handler_object,
+            &player,
+            client_id,
+            client_token,
+            auth_token,
+            rpc_client,
+            addr,
+        )
+        .await
+    {
+        Ok(connection) => connection,
+        Err(e) => {
+            error!("Unable to start connection for new player: {}", e);
+            return;
+        }
+    };
+    connection.handle(ConnectType::Created, socket).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::routing::get;
+    use futures_util::{SinkExt, StreamExt};
+    use moor_var::{Obj, SYSTEM_OBJECT};
+    use rpc_common::HostClientToDaemonMessage::{ConnectionEstablish, LoginCommand};
+    use rpc_common::{
+        AuthToken, ClientToken, DaemonToClientReply, HostClientToDaemonMessage, LoginOutcome,
+        ReplyResult, RpcMessageError,
+    };
+    use serde_json::json;
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use tmq::Multipart;
+    use tokio_tungstenite::connect_async;
+    use tokio_tungstenite::tungstenite::Message as ClientMessage;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_decode_request() {
+        let request =
+            CreateAccountRequest::decode(r#"{"player": "Fred", "password": "secret"}"#).unwrap();
+        assert_eq!(
+            request,
+            CreateAccountRequest {
+                player: "Fred".to_string(),
+                password: "secret".to_string(),
+            }
+        );
+        assert!(CreateAccountRequest::decode(r#"{"player": "Fred"}"#).is_err());
+        assert!(CreateAccountRequest::decode("create Fred secret").is_err());
+    }
+
+    #[test]
+    fn test_encode_replies() {
+        let created = CreateAccountReply::Created {
+            player: v_obj(Obj::mk_id(123)),
+            auth_token: "token".to_string(),
+        };
+        let created: serde_json::Value = serde_json::from_str(&created.encode()).unwrap();
+        assert_eq!(
+            created,
+            json!({"result": "created", "player": {"oid": 123}, "auth_token": "token"})
+        );
+
+        let rejected = CreateAccountReply::error(CreateAccountError::Rejected, "Login rejected");
+        let rejected: serde_json::Value = serde_json::from_str(&rejected.encode()).unwrap();
+        assert_eq!(
+            rejected,
+            json!({"result": "error", "error": "rejected", "message": "Login rejected"})
+        );
+    }
+
+    /// Stands in for the daemon's RPC endpoint: it establishes connections, and creates a player
+    /// #123 for any `create` login command, except for the name "Taken".
+    async fn stub_daemon(zmq_ctx: tmq::Context, rpc_address: String) {
+        let mut receiver = tmq::reply(&zmq_ctx).bind(&rpc_address).unwrap();
+        loop {
+            let Ok((request, sender)) = receiver.recv().await else {
+                return;
+            };
+            let (message, _): (HostClientToDaemonMessage, _) =
+                bincode::decode_from_slice(&request[1], bincode::config::standard()).unwrap();
+            let reply = match message {
+                ConnectionEstablish(_) => {
+                    ReplyResult::ClientSuccess(DaemonToClientReply::NewConnection(
+                        ClientToken("client".into()),
+                        Obj::mk_id(-2),
+                    ))
+                }
+                LoginCommand(_, _, words, false) if words[0] == "create" => {
+                    let outcome = match words[1].as_str() {
+                        "Taken" => LoginOutcome::Failed,
+                        _ => LoginOutcome::Success(
+                            AuthToken("auth".into()),
+                            ConnectType::Created,
+                            Obj::mk_id(123),
+                        ),
+                    };
+                    ReplyResult::ClientSuccess(DaemonToClientReply::LoginResult(outcome))
+                }
+                _ => ReplyResult::Failure(RpcMessageError::InvalidRequest("stub".into())),
+            };
+            let reply = bincode::encode_to_vec(&reply, bincode::config::standard()).unwrap();
+            receiver = sender.send(Multipart::from(vec![reply])).await.unwrap();
+        }
+    }
+
+    /// Send `frame` to a fresh `/ws/create` connection, and return the host's first reply.
+    async fn create_over_ws(addr: SocketAddr, frame: &str) -> serde_json::Value {
+        let (mut socket, _) = connect_async(format!("ws://{addr}/ws/create"))
+            .await
+            .unwrap();
+        socket
+            .send(ClientMessage::Text(frame.into()))
+            .await
+            .unwrap();
+        let reply = tokio::time::timeout(Duration::from_secs(5), socket.next())
+            .await
+            .expect("No reply to account creation")
+            .unwrap()
+            .unwrap();
+        serde_json::from_str(reply.to_text().unwrap()).unwrap()
+    }
+
+    /// Drive account creation through the `/ws/create` route, as the web client would.
+    #[tokio::test]
+    async fn test_ws_create_account() {
+        let id = Uuid::new_v4();
+        let rpc_address = format!("ipc:///tmp/moor-web-create-test-rpc-{id}");
+        let events_address = format!("ipc:///tmp/moor-web-create-test-events-{id}");
+        tokio::spawn(stub_daemon(tmq::Context::new(), rpc_address.clone()));
+
+        let host = WebHost::new(
+            PathBuf::from("."),
+            rpc_address,
+            events_address,
+            SYSTEM_OBJECT,
+        );
+        let router = Router::new()
+            .route("/ws/create", get(ws_create_account_handler))
+            .with_state(host);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+        });
+
+        let created = create_over_ws(addr, r#"{"player": "Fred", "password": "secret"}"#).await;
+        assert_eq!(
+            created,
+            json!({"result": "created", "player": {"oid": 123}, "auth_token": "auth"})
+        );
+
+        let taken = create_over_ws(addr, r#"{"player": "Taken", "password": "secret"}"#).await;
+        assert_eq!(taken["result"], "error");
+        assert_eq!(taken["error"], "rejected");
+
+        let garbled = create_over_ws(addr, "create Fred secret").await;
+        assert_eq!(garbled["result"], "error");
+        assert_eq!(garbled["error"], "invalid_request");
+    }
+}
@@ -12,6 +12,7 @@
 //
 
 mod auth;
+mod create_account;
 mod props;
 mod verbs;
 pub mod web_host;
@@ -19,6 +20,7 @@ mod ws_connection;
 
 pub use auth::connect_auth_handler;
 pub use auth::create_auth_handler;
+pub use create_account::ws_create_account_handler;
 use moor_var::{Var, Variant, v_err, v_float, v_int, v_list, v_map, v_none, v_objid, v_str};
 pub use props::properties_handler;
 pub use props::property_retrieval_handler;
@@ -45,6 +45,16 @@ pub enum LoginType {
     Create,
 }
 
+impl LoginType {
+    /// The login command word which the core's `do_login_command` is sent for this login.
+    pub(crate) fn auth_verb(&self) -> &'static str {
+        match self {
+            LoginType::Connect => "connect",
+            LoginType::Create => "create",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct WebHost {
     zmq_context: tmq::Context,
@@ -16,7 +16,7 @@ use axum::extract::ws::{Message, WebSocket};
 use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
 use moor_common::tasks::{
-    AbortLimitReason, CommandError, Event, Exception, Presentation, SchedulerError,
+    AbortLimitReason, CommandError, Event, Exception, NarrativeEvent, Presentation, SchedulerError,
     VerbProgramError,
 };
 use moor_var::{Obj, Var, v_obj};
@@ -78,6 +78,9 @@ pub struct NarrativeOutput {
     /// If this is a traceback 'splosion, it's here.
     #[serde(skip_serializing_if = "Option::is_none")]
     traceback: Option<Traceback>,
+    /// If binary content (of `content_type`) follows in the next frame, its length in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    binary: Option<usize>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -223,27 +226,28 @@ impl WebSocketConnection {
                 .await;
             }
             ClientEvent::Narrative(_author, event) => {
-                let msg = event.event();
-                match &msg {
-                    Event::Notify(msg, content_type) => {
-                        let content_type = content_type.map(|s| s.to_string());
-                        Self::emit_narrative_msg(
-                            ws_sender,
-                            event.author(),
-                            content_type,
-                            msg.clone(),
-                        )
-                        .await;
-                    }
-                    Event::Traceback(exception) => {
-                        Self::emit_traceback(ws_sender, event.author(), exception).await;
-                    }
-                    Event::Present(p) => {
-                        Self::emit_present(ws_sender, event.author(), p.clone()).await;
-                    }
-                    Event::Unpresent(id) => {
-                        Self::emit_unpresent(ws_sender, event.author(), id.clone()).await;
-                    }
+                Self::emit_event(ws_sender, &event).await;
+            }
+            ClientEvent::ConfirmedNarrative(_author, event, delivery_id) => {
+                let delivered = Self::emit_event(ws_sender, &event).await;
+                let response = self
+                    .rpc_client
+                    .make_client_rpc_call(
+                        self.client_id,
+                        HostClientToDaemonMessage::DeliveryReport(
+                            self.client_token.clone(),
+                            delivery_id,
+                            delivered,
+                        ),
+                    )
+                    .await;
+                if !matches!(
+                    response,
+                    Ok(ReplyResult::ClientSuccess(
+                        DaemonToClientReply::DeliveryReported
+                    ))
+                ) {
+                    warn!(?response, "Unable to report delivery");
                 }
             }
             ClientEvent::RequestInput(request_id) => {
@@ -260,6 +264,10 @@ impl WebSocketConnection {
                 .await;
                 ws_sender.close().await.expect("Unable to close connection");
             }
+            ClientEvent::SetConnectionOption(_, option, _) => {
+                // Options like "echo" are line-protocol concerns the browser client manages itself.
+                trace!(?option, "Ignoring connection option for websocket client");
+            }
             ClientEvent::TaskError(ti, te) => {
                 if let Some(pending_event) = self.pending_task.take() {
                     if pending_event.task_id != ti {
@@ -418,6 +426,23 @@ impl WebSocketConnection {
                 )
                 .await
             }
+            SchedulerError::CommandExecutionError(CommandError::NoObjectMatchSuggestions(
+                object_name,
+                suggestions,
+            )) => {
+                Self::emit_error(
+                    ws_sender,
+                    ErrorOutput {
+                        message: format!(
+                            "I don't see \"{object_name}\" here. Did you mean: {}?",
+                            suggestions.join(", ")
+                        ),
+                        description: None,
+                        server_time: SystemTime::now(),
+                    },
+                )
+                .await
+            }
             SchedulerError::CommandExecutionError(CommandError::NoCommandMatch) => {
                 Self::emit_error(
                     ws_sender,
@@ -484,6 +509,17 @@ impl WebSocketConnection {
                 )
                 .await
             }
+            SchedulerError::TaskAbortedLimit(AbortLimitReason::BuiltinDuration(name)) => {
+                Self::emit_error(
+                    ws_sender,
+                    ErrorOutput {
+                        message: format!("Task aborted: {name}() ran too long"),
+                        description: None,
+                        server_time: SystemTime::now(),
+                    },
+                )
+                .await
+            }
             SchedulerError::TaskAbortedError => {
                 Self::emit_error(
                     ws_sender,
@@ -516,11 +552,48 @@ impl WebSocketConnection {
         Ok(())
     }
 
+    /// Send a narrative event on to the client, returning whether it could be written.
+    async fn emit_event(
+        ws_sender: &mut SplitSink<WebSocket, Message>,
+        event: &NarrativeEvent,
+    ) -> bool {
+        match event.event() {
+            Event::Notify(msg, content_type) => {
+                let content_type = content_type.map(|s| s.to_string());
+                Self::emit_narrative_msg(ws_sender, event.author(), content_type, msg).await
+            }
+            Event::Traceback(exception) => {
+                Self::emit_traceback(ws_sender, event.author(), &exception).await
+            }
+            Event::Present(p) => Self::emit_present(ws_sender, event.author(), p).await,
+            Event::Unpresent(id) => Self::emit_unpresent(ws_sender, event.author(), id).await,
+            Event::Binary(content_type, bytes) => {
+                Self::emit_binary(ws_sender, event.author(), content_type.to_string(), bytes).await
+            }
+        }
+    }
+
+    /// Binary content goes as a narrative header saying what it is, followed by a binary frame
+    /// with the bytes themselves.
+    async fn emit_binary(
+        ws_sender: &mut SplitSink<WebSocket, Message>,
+        author: &Var,
+        content_type: String,
+        bytes: Vec<u8>,
+    ) -> bool {
+        for msg in binary_messages(author, content_type, bytes) {
+            if ws_sender.send(msg).await.is_err() {
+                return false;
+            }
+        }
+        true
+    }
+
     async fn emit_present(
         ws_sender: &mut SplitSink<WebSocket, Message>,
         author: &Var,
         present: Presentation,
-    ) {
+    ) -> bool {
         Self::emit_narrative(
             ws_sender,
             NarrativeOutput {
@@ -532,16 +605,17 @@ impl WebSocketConnection {
                 present: Some(present),
                 unpresent: None,
                 traceback: None,
+                binary: None,
             },
         )
-        .await;
+        .await
     }
 
     async fn emit_unpresent(
         ws_sender: &mut SplitSink<WebSocket, Message>,
         author: &Var,
         id: String,
-    ) {
+    ) -> bool {
         Self::emit_narrative(
             ws_sender,
             NarrativeOutput {
@@ -553,6 +627,7 @@ impl WebSocketConnection {
                 present: None,
                 unpresent: Some(id),
                 traceback: None,
+                binary: None,
             },
         )
         .await
@@ -563,7 +638,7 @@ impl WebSocketConnection {
         author: &Var,
         content_type: Option<String>,
         msg: Var,
-    ) {
+    ) -> bool {
         Self::emit_narrative(
             ws_sender,
             NarrativeOutput {
@@ -575,9 +650,10 @@ impl WebSocketConnection {
                 present: None,
                 unpresent: None,
                 traceback: None,
+                binary: None,
             },
         )
-        .await;
+        .await
     }
 
     async fn emit_narrative_sys_msg(
@@ -597,6 +673,7 @@ impl WebSocketConnection {
                 present: None,
                 unpresent: None,
                 traceback: None,
+                binary: None,
             },
         )
         .await;
@@ -606,7 +683,7 @@ impl WebSocketConnection {
         ws_sender: &mut SplitSink<WebSocket, Message>,
         author: &Var,
         exception: &Exception,
-    ) {
+    ) -> bool {
         let mut traceback = vec![];
         for frame in &exception.backtrace {
             let Some(s) = frame.as_string() else {
@@ -628,16 +705,20 @@ impl WebSocketConnection {
                     error: format!("{}", exception),
                     traceback,
                 }),
+                binary: None,
             },
         )
-        .await;
+        .await
     }
 
-    async fn emit_narrative(ws_sender: &mut SplitSink<WebSocket, Message>, msg: NarrativeOutput) {
+    async fn emit_narrative(
+        ws_sender: &mut SplitSink<WebSocket, Message>,
+        msg: NarrativeOutput,
+    ) -> bool {
         // Serialize to JSON.
         let msg = serde_json::to_string(&msg).unwrap();
         let msg = Message::Text(msg.into());
-        ws_sender.send(msg).await.ok();
+        ws_sender.send(msg).await.is_ok()
     }
 
     async fn emit_error(ws_sender: &mut SplitSink<WebSocket, Message>, msg: ErrorOutput) {
@@ -654,3 +735,48 @@ impl WebSocketConnection {
         ws_sender.send(msg).await.ok();
     }
 }
+
+fn binary_messages(author: &Var, content_type: String, bytes: Vec<u8>) -> [Message; 2] {
+    let header = NarrativeOutput {
+        author: var_as_json(author),
+        system_message: None,
+        message: None,
+        content_type: Some(content_type),
+        server_time: SystemTime::now(),
+        present: None,
+        unpresent: None,
+        traceback: None,
+        binary: Some(bytes.len()),
+    };
+    let header = serde_json::to_string(&header).unwrap();
+    [Message::Text(header.into()), Message::Binary(bytes.into())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NarrativeOutput, binary_messages};
+    use axum::extract::ws::Message;
+    use moor_var::{SYSTEM_OBJECT, v_obj};
+
+    #[test]
+    fn test_binary_is_header_then_binary_frame() {
+        let bytes = vec![0x89, b'P', b'N', b'G', 0, 255];
+        let [header, frame] = binary_messages(
+            &v_obj(SYSTEM_OBJECT),
+            "image/png".to_string(),
+            bytes.clone(),
+        );
+
+        let Message::Text(header) = header else {
+            panic!("Expected a text header, got {:?}", header);
+        };
+        let header: NarrativeOutput = serde_json::from_str(header.as_str()).unwrap();
+        assert_eq!(header.content_type.as_deref(), Some("image/png"));
+        assert_eq!(header.binary, Some(bytes.len()));
+
+        let Message::Binary(frame) = frame else {
+            panic!("Expected a binary frame, got {:?}", frame);
+        };
+        assert_eq!(frame.as_ref(), bytes.as_slice());
+    }
+}
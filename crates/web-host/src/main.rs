@@ -18,6 +18,7 @@ use std::collections::HashMap;
 
 use axum::Router;
 use axum::routing::{get, post};
+use axum_server::tls_rustls::RustlsConfig;
 use clap::Parser;
 use clap_derive::Parser;
 
@@ -28,13 +29,15 @@ use axum::response::IntoResponse;
 use figment::Figment;
 use figment::providers::{Format, Serialized, Yaml};
 use futures_util::future::OptionFuture;
+use moor_common::tasks::ListenerTls;
 use moor_var::{Obj, SYSTEM_OBJECT};
 use rolldown::{
     Bundler, BundlerOptions, ExperimentalOptions, InputItem, OutputFormat, Platform,
     RawMinifyOptions, SourceMapType, Watcher,
 };
 use rpc_async_client::{
-    ListenersClient, ListenersMessage, make_host_token, process_hosts_events, start_host_session,
+    ListenersClient, ListenersMessage, make_host_token, process_hosts_events,
+    resolve_listen_addresses, start_host_session,
 };
 use rpc_common::client_args::RpcClientArgs;
 use rpc_common::{HostType, load_keypair};
@@ -51,6 +54,9 @@ use tower_http::services::ServeDir;
 use tracing::{error, info, warn};
 use tracing_subscriber::fmt::format::FmtSpan;
 
+/// Port used for listen addresses which don't specify one.
+const DEFAULT_HTTP_PORT: u16 = 8080;
+
 #[derive(Parser, Debug, Serialize, Deserialize)]
 struct Args {
     #[command(flatten)]
@@ -59,7 +65,7 @@ struct Args {
     #[arg(
         long,
         value_name = "listen-address",
-        help = "HTTP listen address",
+        help = "HTTP listen address(es); comma separated, IPv4, IPv6 or hostname, with optional port",
         default_value = "0.0.0.0:8080"
     )]
     listen_address: String,
@@ -89,6 +95,16 @@ struct Args {
     )]
     client_sources: PathBuf,
 
+    #[arg(
+        long,
+        value_name = "tls-cert",
+        help = "PEM certificate chain file; if set (with --tls-key), serve HTTPS/WSS instead of HTTP/WS"
+    )]
+    tls_cert: Option<PathBuf>,
+
+    #[arg(long, value_name = "tls-key", help = "PEM private key file for TLS")]
+    tls_key: Option<PathBuf>,
+
     #[arg(long, help = "Enable debug logging", default_value = "false")]
     pub debug: bool,
 
@@ -104,6 +120,8 @@ struct Listeners {
     kill_switch: Arc<AtomicBool>,
     src_dir: PathBuf,
     dist_dir: PathBuf,
+    /// If set, all listeners terminate TLS with this configuration.
+    tls_config: Option<RustlsConfig>,
 }
 
 impl Listeners {
@@ -114,6 +132,7 @@ impl Listeners {
         kill_switch: Arc<AtomicBool>,
         src_dir: PathBuf,
         dist_dir: PathBuf,
+        tls_config: Option<RustlsConfig>,
     ) -> (
         Self,
         tokio::sync::mpsc::Receiver<ListenersMessage>,
@@ -128,6 +147,7 @@ impl Listeners {
             kill_switch,
             src_dir,
             dist_dir,
+            tls_config,
         };
         let listeners_client = ListenersClient::new(tx);
         (listeners, rx, listeners_client)
@@ -148,7 +168,21 @@ impl Listeners {
             }
 
             match listeners_channel.recv().await {
-                Some(ListenersMessage::AddListener(handler, addr)) => {
+                Some(ListenersMessage::AddListener(handler, addr, tls)) => {
+                    // A certificate and key given for this listener take the place of the ones
+                    // the host was started with.
+                    let tls_config = match tls {
+                        None => self.tls_config.clone(),
+                        Some(ListenerTls { certificate, key }) => {
+                            match RustlsConfig::from_pem_file(certificate, key).await {
+                                Ok(config) => Some(config),
+                                Err(e) => {
+                                    error!(?addr, "Unable to configure TLS listener: {}", e);
+                                    continue;
+                                }
+                            }
+                        }
+                    };
                     let ws_host = WebHost::new(
                         self.src_dir.clone(),
                         self.rpc_address.clone(),
@@ -181,7 +215,7 @@ impl Listeners {
                             _ = term_receive.changed() => {
                                 info!("Listener terminated, stopping...");
                             }
-                            _ = Listener::serve(listener, main_router) => {
+                            _ = Listener::serve(listener, main_router, tls_config) => {
                                 info!("Listener exited, restarting...");
                             }
                         }
@@ -226,14 +260,28 @@ impl Listener {
         }
     }
 
-    pub async fn serve(listener: TcpListener, main_router: Router) -> eyre::Result<()> {
+    pub async fn serve(
+        listener: TcpListener,
+        main_router: Router,
+        tls_config: Option<RustlsConfig>,
+    ) -> eyre::Result<()> {
         let addr = listener.local_addr()?;
-        info!("Listening on {:?}", addr);
-        axum::serve(
-            listener,
-            main_router.into_make_service_with_connect_info::<SocketAddr>(),
-        )
-        .await?;
+        match tls_config {
+            None => {
+                info!("Listening on {:?}", addr);
+                axum::serve(
+                    listener,
+                    main_router.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .await?;
+            }
+            Some(tls_config) => {
+                info!("Listening (TLS) on {:?}", addr);
+                axum_server::from_tcp_rustls(listener.into_std()?, tls_config)
+                    .serve(main_router.into_make_service_with_connect_info::<SocketAddr>())
+                    .await?;
+            }
+        }
         info!("Done listening on {:?}", addr);
         Ok(())
     }
@@ -292,6 +340,7 @@ fn mk_routes(web_host: WebHost, dist_dir: &Path) -> eyre::Result<Router> {
             "/ws/attach/create/{token}",
             get(host::ws_create_attach_handler),
         )
+        .route("/ws/create", get(host::ws_create_account_handler))
         .route("/auth/connect", post(host::connect_auth_handler))
         .route("/auth/create", post(host::create_auth_handler))
         .route("/welcome", get(host::welcome_message_handler))
@@ -409,6 +458,22 @@ async fn main() -> Result<(), eyre::Error> {
 
     let zmq_ctx = tmq::Context::new();
 
+    // Plaintext unless both a certificate and key are given.
+    let tls_config = match (&args.tls_cert, &args.tls_key) {
+        (None, None) => None,
+        (Some(cert), Some(key)) => match RustlsConfig::from_pem_file(cert, key).await {
+            Ok(config) => Some(config),
+            Err(e) => {
+                error!("Unable to configure TLS: {}", e);
+                std::process::exit(1);
+            }
+        },
+        _ => {
+            error!("TLS requires both --tls-cert and --tls-key");
+            std::process::exit(1);
+        }
+    };
+
     let (mut listeners_server, listeners_channel, listeners) = Listeners::new(
         zmq_ctx.clone(),
         args.client_args.rpc_address.clone(),
@@ -416,6 +481,7 @@ async fn main() -> Result<(), eyre::Error> {
         kill_switch.clone(),
         args.client_sources.to_owned(),
         args.dist_directory.to_owned(),
+        tls_config,
     );
     let listeners_thread = tokio::spawn(async move {
         listeners_server.run(listeners_channel).await;
@@ -438,25 +504,26 @@ async fn main() -> Result<(), eyre::Error> {
         }
     };
 
-    listeners
-        .add_listener(
-            &SYSTEM_OBJECT,
-            match args.listen_address.parse() {
-                Ok(addr) => addr,
-                Err(e) => {
-                    error!(
-                        "Unable to parse listen address {}: {}",
-                        args.listen_address, e
-                    );
-                    std::process::exit(1);
-                }
-            },
-        )
-        .await
-        .unwrap_or_else(|e| {
-            error!("Unable to start default listener: {}", e);
-            std::process::exit(1);
-        });
+    let listen_sockaddrs =
+        match resolve_listen_addresses(&args.listen_address, DEFAULT_HTTP_PORT).await {
+            Ok(addrs) => addrs,
+            Err(e) => {
+                error!(
+                    "Unable to parse listen address {}: {}",
+                    args.listen_address, e
+                );
+                std::process::exit(1);
+            }
+        };
+    for listen_sockaddr in listen_sockaddrs {
+        listeners
+            .add_listener(&SYSTEM_OBJECT, listen_sockaddr, None)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Unable to start default listener: {}", e);
+                std::process::exit(1);
+            });
+    }
 
     let host_listen_loop = process_hosts_events(
         rpc_client,